@@ -1,10 +1,26 @@
+use crate::compose_config::update_service_environment;
 use crate::compose_discovery::{refresh_compose_scan, scan_compose_files, ComposeDiscoveryCache};
+use crate::firewall_dump::{
+    detect_backend, parse_iptables_rules, parse_nft_ruleset, IPTABLES_TABLES,
+};
+use crate::logrotate_config::{global_directives, parse_logrotate_config};
+use crate::nginx_config::{
+    diff_lines, insert_location, parse_proxy_target, parse_structure, unified_diff,
+    upsert_directive, vhost_probe,
+};
+use crate::prometheus::render_metrics;
+use crate::redact::Redactor;
+use crate::response_cache::ResponseCache;
 use crate::ssh::SshClient;
+use crate::sshd_config::{glob_matches, is_accumulating, parse_sshd_directives};
+use crate::stream_registry::{stream_id, StreamRegistry};
 use crate::types::*;
+use crate::ufw_parse::parse_ufw_rules;
+use crate::units::{format_bytes, parse_bytes};
 use serde_json::Value as JsonValue;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
-use tauri::State;
+use tauri::{Emitter, Manager, State};
 use tauri_plugin_store::StoreExt;
 use tokio::sync::Mutex;
 
@@ -36,35 +52,139 @@ fn profiles_to_json(profiles: &HashMap<String, SavedServerProfile>) -> JsonValue
 }
 
 pub struct AppState {
-    pub ssh_client: Mutex<Option<Arc<SshClient>>>,
+    pub connections: Mutex<Connections>,
     pub server_profiles: Mutex<HashMap<String, ServerProfile>>,
-    pub cpu_history: Mutex<VecDeque<f64>>,
-    pub memory_history: Mutex<VecDeque<f64>>,
-    pub network_history: Mutex<VecDeque<NetworkHistoryPoint>>,
-    pub last_network_stats: Mutex<Option<NetworkStats>>,
+    /// Metrics history per connected server, keyed by profile id
+    pub metrics_history: Mutex<HashMap<String, MetricsHistory>>,
+    /// Usage history per server, then per container id
+    pub container_history: Mutex<HashMap<String, HashMap<String, ContainerHistory>>>,
+    pub metrics_config: Mutex<MetricsConfig>,
     pub compose_cache: Arc<ComposeDiscoveryCache>,
+    /// How each connected server's Docker daemon is reached, keyed by profile id
+    pub docker_contexts: Mutex<HashMap<String, DockerContextInfo>>,
+    /// Result of the last `check_sudo_access` per connected server, keyed by profile id
+    pub sudo_access: Mutex<HashMap<String, SudoAccess>>,
+    /// Stop flags of running background streams, stopped with their server on disconnect
+    pub streams: Arc<StreamRegistry>,
+    /// Recent results of slow read commands (users, groups, images, compose)
+    pub cache: ResponseCache,
+    /// Outcomes of the connections `autoconnect_on_startup` made at launch
+    pub startup_results: Mutex<Vec<StartupConnectResult>>,
+    /// Health probe task of each connected server, see `monitor_connection`
+    pub monitors: Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>>,
+    /// Decides which environment values are hidden unless secrets are requested
+    pub redactor: Mutex<Redactor>,
 }
 
 impl Default for AppState {
     fn default() -> Self {
         AppState {
-            ssh_client: Mutex::new(None),
+            connections: Mutex::new(Connections::default()),
             server_profiles: Mutex::new(HashMap::new()),
-            cpu_history: Mutex::new(VecDeque::with_capacity(MAX_HISTORY_POINTS)),
-            memory_history: Mutex::new(VecDeque::with_capacity(MAX_HISTORY_POINTS)),
-            network_history: Mutex::new(VecDeque::with_capacity(MAX_HISTORY_POINTS)),
-            last_network_stats: Mutex::new(None),
+            metrics_history: Mutex::new(HashMap::new()),
+            container_history: Mutex::new(HashMap::new()),
+            metrics_config: Mutex::new(MetricsConfig::default()),
             compose_cache: Arc::new(ComposeDiscoveryCache::new()),
+            docker_contexts: Mutex::new(HashMap::new()),
+            sudo_access: Mutex::new(HashMap::new()),
+            streams: Arc::new(StreamRegistry::default()),
+            cache: ResponseCache::default(),
+            startup_results: Mutex::new(Vec::new()),
+            monitors: Mutex::new(HashMap::new()),
+            redactor: Mutex::new(Redactor::default()),
         }
     }
 }
 
+impl AppState {
+    /// Resolve `server_id`, or the active server when `None`, to its profile id and client
+    pub async fn connection(
+        &self,
+        server_id: Option<&str>,
+    ) -> Result<(String, Arc<SshClient>), String> {
+        let connections = self.connections.lock().await;
+        let id = match server_id {
+            Some(id) => id.to_string(),
+            None => connections.active.clone().ok_or("Not connected")?,
+        };
+        let client = connections
+            .clients
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| format!("Not connected to server '{}'", id))?;
+        Ok((id, client))
+    }
+
+    pub async fn client(&self, server_id: Option<&str>) -> Result<Arc<SshClient>, String> {
+        self.connection(server_id).await.map(|(_, client)| client)
+    }
+
+    /// Like `connection`, for handlers that change server state: refused while the
+    /// connection is in read-only mode
+    pub async fn writable_connection(
+        &self,
+        server_id: Option<&str>,
+    ) -> Result<(String, Arc<SshClient>), String> {
+        let (id, client) = self.connection(server_id).await?;
+        let mode = self.connections.lock().await.permission_mode(&id);
+        if mode == PermissionMode::ReadOnly {
+            return Err(format!(
+                "Server '{}' is in read-only mode; this action is not allowed",
+                id
+            ));
+        }
+        Ok((id, client))
+    }
+
+    pub async fn writable_client(&self, server_id: Option<&str>) -> Result<Arc<SshClient>, String> {
+        self.writable_connection(server_id)
+            .await
+            .map(|(_, client)| client)
+    }
+}
+
+/// Open SSH connections, keyed by server profile id
+#[derive(Default)]
+pub struct Connections {
+    pub clients: HashMap<String, Arc<SshClient>>,
+    /// Server used by commands that don't pass a `server_id`
+    pub active: Option<String>,
+    /// Connections switched away from the default `Full` mode
+    pub permission_modes: HashMap<String, PermissionMode>,
+}
+
+impl Connections {
+    pub fn permission_mode(&self, server_id: &str) -> PermissionMode {
+        self.permission_modes
+            .get(server_id)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Default)]
+pub struct MetricsHistory {
+    cpu: VecDeque<f64>,
+    memory: VecDeque<f64>,
+    network: VecDeque<NetworkHistoryPoint>,
+    last_network_stats: Option<NetworkStats>,
+    /// Latest metrics and when they were read, reused by polls that come too soon
+    last_snapshot: Option<(std::time::Instant, SystemMetrics)>,
+}
+
+#[derive(Default)]
+pub struct ContainerHistory {
+    cpu: VecDeque<f64>,
+    memory: VecDeque<f64>,
+}
+
 #[tauri::command]
 pub fn test_connection(
     host: String,
     port: u16,
     username: String,
     auth_method: AuthMethod,
+    connect_timeout_secs: Option<u64>,
 ) -> Result<ConnectionResult, String> {
     let profile = ServerProfile {
         id: "test".to_string(),
@@ -73,6 +193,11 @@ pub fn test_connection(
         port,
         username,
         auth_method,
+        keepalive_secs: None,
+        connect_timeout_secs,
+        retry_attempts: None,
+        probe_interval_secs: None,
+        max_output_bytes: None,
     };
 
     let client = SshClient::new(profile);
@@ -82,20 +207,37 @@ pub fn test_connection(
             Ok(ConnectionResult {
                 success: true,
                 message: "Connection successful".to_string(),
+                error_code: None,
             })
         }
         Err(e) => Ok(ConnectionResult {
             success: false,
             message: e.message,
+            error_code: Some(e.code),
         }),
     }
 }
 
+/// Like `test_connection`, but reports the outcome and timing of each connect stage
+#[tauri::command]
+pub fn diagnose_connection(profile: ServerProfile) -> Result<ConnectionDiagnosis, String> {
+    Ok(SshClient::new(profile).diagnose())
+}
+
 #[tauri::command]
 pub async fn connect_to_server(
     profile: ServerProfile,
     state: State<'_, AppState>,
     app: tauri::AppHandle,
+) -> Result<ConnectionResult, String> {
+    connect_profile(&state, &app, profile).await
+}
+
+/// Connect, remember the profile, and make it the active server
+async fn connect_profile(
+    state: &AppState,
+    app: &tauri::AppHandle,
+    profile: ServerProfile,
 ) -> Result<ConnectionResult, String> {
     let client = Arc::new(SshClient::new(profile.clone()));
     match client.connect() {
@@ -133,41 +275,470 @@ pub async fn connect_to_server(
                     .map_err(|e| format!("Failed to save profile: {}", e))?;
             }
 
-            let mut ssh_client = state.ssh_client.lock().await;
-            *ssh_client = Some(client);
+            // Best effort: a server without Docker still connects fine
+            if let Ok(context) = detect_docker_context(&client) {
+                state
+                    .docker_contexts
+                    .lock()
+                    .await
+                    .insert(profile.id.clone(), context);
+            }
+
+            // The newest connection becomes the default target, as with a single connection
+            let mut connections = state.connections.lock().await;
+            if let Some(previous) = connections
+                .clients
+                .insert(profile.id.clone(), Arc::clone(&client))
+            {
+                previous.disconnect();
+            }
+            connections.active = Some(profile.id.clone());
+            drop(connections);
+
+            let interval = profile
+                .probe_interval_secs
+                .unwrap_or(DEFAULT_PROBE_INTERVAL_SECS);
+            let monitor = (interval > 0).then(|| {
+                tauri::async_runtime::spawn(monitor_connection(
+                    app.clone(),
+                    profile.id.clone(),
+                    client,
+                    std::time::Duration::from_secs(interval),
+                ))
+            });
+            let mut monitors = state.monitors.lock().await;
+            if let Some(previous) = monitors.remove(&profile.id) {
+                previous.abort();
+            }
+            if let Some(monitor) = monitor {
+                monitors.insert(profile.id.clone(), monitor);
+            }
             Ok(ConnectionResult {
                 success: true,
                 message: "Connected successfully".to_string(),
+                error_code: None,
             })
         }
         Err(e) => Ok(ConnectionResult {
             success: false,
             message: e.message,
+            error_code: Some(e.code),
         }),
     }
 }
 
+/// Probe interval used when the profile doesn't set `probe_interval_secs`
+const DEFAULT_PROBE_INTERVAL_SECS: u64 = 15;
+/// A probe that hasn't answered by then counts as failed, so a half-open TCP connection
+/// is noticed without waiting for the OS to time it out
+const PROBE_TIMEOUT_SECS: u64 = 10;
+
+/// Run a trivial command on `client` every `interval`, skipping rounds where another
+/// command holds the session. When the probe fails or times out, the client is taken out
+/// of `AppState`, its per-server state is cleared as on `disconnect_server` and
+/// `connection-lost` is emitted. Reconnecting uses a fresh client with the same docker
+/// preamble, since the old one may still be stuck on the dead socket; if it succeeds the
+/// new client is put in with the previous permission mode and `connection-restored`
+/// follows. Ends when reconnecting gives up, when the server is connected again from
+/// elsewhere, or when `disconnect_server` aborts it.
+async fn monitor_connection(
+    app: tauri::AppHandle,
+    server_id: String,
+    mut client: Arc<SshClient>,
+    interval: std::time::Duration,
+) {
+    let timeout = std::time::Duration::from_secs(PROBE_TIMEOUT_SECS);
+    loop {
+        tokio::time::sleep(interval).await;
+        let probe = Arc::clone(&client);
+        let reason = match tauri::async_runtime::spawn_blocking(move || probe.probe(timeout)).await
+        {
+            Ok(Ok(_)) => continue,
+            Ok(Err(e)) => e.message,
+            Err(e) => e.to_string(),
+        };
+
+        let state = app.state::<AppState>();
+        let (was_active, mode) = {
+            let mut connections = state.connections.lock().await;
+            let current = connections.clients.get(&server_id);
+            // Disconnected or replaced while the probe ran
+            if !current.is_some_and(|c| Arc::ptr_eq(c, &client)) {
+                return;
+            }
+            connections.clients.remove(&server_id);
+            let was_active = connections.active.as_deref() == Some(server_id.as_str());
+            if was_active {
+                connections.active = None;
+            }
+            (was_active, connections.permission_mode(&server_id))
+        };
+        clear_server_state(&state, &server_id).await;
+        log::warn!("Connection to {} lost: {}", server_id, reason);
+        let _ = app.emit(
+            "connection-lost",
+            ConnectionEvent {
+                server_id: server_id.clone(),
+                message: reason,
+            },
+        );
+        // Closing may wait on a command stuck on the old socket, so it isn't awaited
+        let lost = Arc::clone(&client);
+        drop(tauri::async_runtime::spawn_blocking(move || {
+            lost.disconnect()
+        }));
+
+        let attempts = client.retry_attempts().max(1);
+        let mut delay = std::time::Duration::from_millis(RETRY_BACKOFF_MS);
+        let mut restored = None;
+        for attempt in 1..=attempts {
+            tokio::time::sleep(delay).await;
+            delay = delay.saturating_mul(2);
+            let fresh = Arc::new(client.unconnected_copy());
+            let connecting = Arc::clone(&fresh);
+            match tauri::async_runtime::spawn_blocking(move || connecting.connect()).await {
+                Ok(Ok(())) => {
+                    restored = Some((attempt, fresh));
+                    break;
+                }
+                Ok(Err(e)) => log::warn!("Reconnecting to {} failed: {}", server_id, e.message),
+                Err(e) => log::warn!("Reconnecting to {} failed: {}", server_id, e),
+            }
+        }
+        let Some((attempt, fresh)) = restored else {
+            return;
+        };
+
+        let mut connections = state.connections.lock().await;
+        if connections.clients.contains_key(&server_id) {
+            // Connected again by hand in the meantime; that connection has its own monitor
+            drop(connections);
+            drop(tauri::async_runtime::spawn_blocking(move || {
+                fresh.disconnect()
+            }));
+            return;
+        }
+        connections
+            .clients
+            .insert(server_id.clone(), Arc::clone(&fresh));
+        if was_active && connections.active.is_none() {
+            connections.active = Some(server_id.clone());
+        }
+        if mode != PermissionMode::default() {
+            connections.permission_modes.insert(server_id.clone(), mode);
+        }
+        drop(connections);
+        client = fresh;
+        let _ = app.emit(
+            "connection-restored",
+            ConnectionEvent {
+                server_id: server_id.clone(),
+                message: format!("Reconnected after {} attempt(s)", attempt),
+            },
+        );
+    }
+}
+
 #[tauri::command]
-pub async fn disconnect_server(state: State<'_, AppState>) -> Result<(), String> {
-    let mut ssh_client = state.ssh_client.lock().await;
-    if let Some(client) = ssh_client.take() {
+pub async fn disconnect_server(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let mut connections = state.connections.lock().await;
+    let Some(id) = server_id.or_else(|| connections.active.clone()) else {
+        return Ok(());
+    };
+    if let Some(client) = connections.clients.remove(&id) {
         client.disconnect();
     }
+    if connections.active.as_deref() == Some(id.as_str()) {
+        connections.active = None;
+    }
+    drop(connections);
+
+    if let Some(monitor) = state.monitors.lock().await.remove(&id) {
+        monitor.abort();
+    }
+    clear_server_state(&state, &id).await;
+    Ok(())
+}
+
+/// Forget everything kept for a server besides its client: permission mode, streams,
+/// history and cached lookups. Shared by `disconnect_server` and a lost connection.
+async fn clear_server_state(state: &AppState, server_id: &str) {
+    state
+        .connections
+        .lock()
+        .await
+        .permission_modes
+        .remove(server_id);
+    state.streams.stop_server_streams(server_id);
+
+    state.metrics_history.lock().await.remove(server_id);
+    state.container_history.lock().await.remove(server_id);
+    state.docker_contexts.lock().await.remove(server_id);
+    state.sudo_access.lock().await.remove(server_id);
+    state.cache.invalidate_server(server_id).await;
+}
+
+#[tauri::command]
+pub async fn list_active_connections(
+    state: State<'_, AppState>,
+) -> Result<Vec<ActiveConnection>, String> {
+    let connections = state.connections.lock().await;
+    let profiles = state.server_profiles.lock().await;
+
+    let mut active: Vec<ActiveConnection> = connections
+        .clients
+        .iter()
+        .map(|(id, client)| {
+            let profile = profiles.get(id);
+            ActiveConnection {
+                server_id: id.clone(),
+                name: profile
+                    .map(|p| p.name.clone())
+                    .unwrap_or_else(|| id.clone()),
+                host: client.get_host(),
+                username: profile.map(|p| p.username.clone()).unwrap_or_default(),
+                connected: client.is_connected(),
+                is_default: connections.active.as_deref() == Some(id.as_str()),
+                permission_mode: connections.permission_mode(id),
+            }
+        })
+        .collect();
+    active.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(active)
+}
+
+/// Switch a connection between read-only and full access. The mode lasts until the
+/// server is disconnected.
+#[tauri::command]
+pub async fn set_permission_mode(
+    mode: PermissionMode,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let (id, _) = state.connection(server_id.as_deref()).await?;
+    let mut connections = state.connections.lock().await;
+    match mode {
+        PermissionMode::Full => connections.permission_modes.remove(&id),
+        PermissionMode::ReadOnly => connections.permission_modes.insert(id, mode),
+    };
     Ok(())
 }
 
+const SUDO_ACCESS_COMMAND: &str = "echo '===ID==='; id -u; id -Gn; \
+     if command -v sudo >/dev/null 2>&1; then echo '===SUDO==='; LC_ALL=C sudo -n -l 2>&1; fi";
+
+fn parse_sudo_access(sections: &HashMap<String, String>) -> SudoAccess {
+    let mut id_lines = sections.get("ID").map(|s| s.lines()).into_iter().flatten();
+    let is_root = id_lines.next().map(str::trim) == Some("0");
+    let in_admin_group = id_lines
+        .next()
+        .unwrap_or_default()
+        .split_whitespace()
+        .any(|group| matches!(group, "sudo" | "wheel" | "admin"));
+
+    let listing = sections.get("SUDO");
+    let installed = listing.is_some();
+    let listing = listing.map(String::as_str).unwrap_or_default();
+
+    // Rules are the indented lines after "User x may run the following commands on host:"
+    let allowed_commands: Vec<String> = listing
+        .lines()
+        .skip_while(|line| !line.contains("may run the following commands"))
+        .skip(1)
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect();
+    let passwordless = is_root
+        || allowed_commands.iter().any(|rule| {
+            rule.contains("NOPASSWD:") && rule.rsplit(':').next().map(str::trim) == Some("ALL")
+        });
+    let not_permitted = listing.contains("may not run sudo");
+    let needs_password = listing.contains("password is required");
+    let can_sudo = is_root || !allowed_commands.is_empty() || (needs_password && in_admin_group);
+
+    let warning = if passwordless {
+        None
+    } else if !installed {
+        Some("sudo is not installed; actions that need root will fail".to_string())
+    } else if not_permitted || !can_sudo {
+        Some(
+            "This account is not allowed to use sudo; actions that need root will fail".to_string(),
+        )
+    } else if needs_password {
+        Some("This account needs a sudo password; actions that need root will fail".to_string())
+    } else {
+        Some("sudo only allows some commands without a password; other actions that need root will fail".to_string())
+    };
+
+    SudoAccess {
+        installed,
+        is_root,
+        can_sudo,
+        passwordless,
+        allowed_commands,
+        warning,
+    }
+}
+
+/// What the connected account may do through sudo. The result is kept per connection;
+/// pass `refresh` after changing sudoers to check again.
+#[tauri::command]
+pub async fn check_sudo_access(
+    refresh: Option<bool>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<SudoAccess, String> {
+    let (server_id, ssh_client) = state.connection(server_id.as_deref()).await?;
+    if !refresh.unwrap_or(false) {
+        if let Some(access) = state.sudo_access.lock().await.get(&server_id) {
+            return Ok(access.clone());
+        }
+    }
+
+    let output = ssh_client
+        .execute_command(SUDO_ACCESS_COMMAND)
+        .map_err(|e| e.message)?;
+    let access = parse_sudo_access(&split_sections(&output));
+    state
+        .sudo_access
+        .lock()
+        .await
+        .insert(server_id, access.clone());
+    Ok(access)
+}
+
+/// Fingerprints of the host key the connected server presented, for checking it by hand
+#[tauri::command]
+pub async fn get_host_key_fingerprints(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<HostKeyFingerprint>, String> {
+    let client = state.client(server_id.as_deref()).await?;
+    client.host_key_fingerprints().map_err(|e| e.message)
+}
+
 #[tauri::command]
-pub async fn get_system_metrics(state: State<'_, AppState>) -> Result<SystemMetrics, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+pub async fn get_connection_health(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ConnectionHealth, String> {
+    let client = match state.client(server_id.as_deref()).await {
+        Ok(client) => client,
+        Err(_) => {
+            return Ok(ConnectionHealth {
+                connected: false,
+                authenticated: false,
+                latency_ms: None,
+                cipher: None,
+                kex: None,
+                reconnect_count: 0,
+                error: None,
+            })
+        }
+    };
 
-    // OPTIMIZATION: Execute all metrics in a SINGLE SSH command to reduce overhead
-    // This reduces 8+ SSH channel setups to just 1, dramatically improving performance
-    let combined_command = r#"
+    // Time a trivial round-trip; a reply proves the session is alive and authenticated
+    let started = std::time::Instant::now();
+    let probe = client.execute_command("echo ok");
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let (authenticated, latency_ms, error) = match probe {
+        Ok(output) if output.trim() == "ok" => (true, Some(latency_ms), None),
+        Ok(output) => (
+            false,
+            None,
+            Some(format!("Unexpected probe output: {}", output.trim())),
+        ),
+        Err(e) => (false, None, Some(e.message)),
+    };
+
+    Ok(ConnectionHealth {
+        connected: true,
+        authenticated,
+        latency_ms,
+        cipher: client.negotiated_method(ssh2::MethodType::CryptCs),
+        kex: client.negotiated_method(ssh2::MethodType::Kex),
+        reconnect_count: client.reconnect_count(),
+        error,
+    })
+}
+
+#[tauri::command]
+pub async fn get_system_metrics(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<SystemMetrics, String> {
+    let (server_id, ssh_client) = state.connection(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    if let Some(metrics) = recent_metrics(&state, &server_id).await {
+        return Ok(metrics);
+    }
+    let output = execute_read_only(client, METRICS_COMMAND).map_err(|e| e.message)?;
+    let mut metrics = parse_system_metrics(&split_sections(&output));
+    record_metrics_history(&state, server_id, &mut metrics).await;
+    Ok(metrics)
+}
+
+/// The current metrics in Prometheus text format, for scraping or pushing to a gateway
+#[tauri::command]
+pub async fn export_metrics_prometheus(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let metrics = get_system_metrics(server_id, state).await?;
+    Ok(render_metrics(&metrics))
+}
+
+/// Slack for timer jitter, so a UI polling at exactly the configured interval still
+/// gets fresh numbers every time
+const METRICS_POLL_JITTER: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// The last snapshot of `server_id`, marked as cached, if it's younger than the poll
+/// interval. Keeps overlapping pollers from piling metric scripts onto small servers.
+async fn recent_metrics(state: &AppState, server_id: &str) -> Option<SystemMetrics> {
+    let interval =
+        std::time::Duration::from_secs(state.metrics_config.lock().await.poll_interval_secs);
+    let history = state.metrics_history.lock().await;
+    let (taken_at, metrics) = history.get(server_id)?.last_snapshot.as_ref()?;
+    if taken_at.elapsed() + METRICS_POLL_JITTER >= interval {
+        return None;
+    }
+    let mut metrics = metrics.clone();
+    metrics.cached = true;
+    Some(metrics)
+}
+
+#[tauri::command]
+pub async fn get_metrics_config(state: State<'_, AppState>) -> Result<MetricsConfig, String> {
+    Ok(state.metrics_config.lock().await.clone())
+}
+
+#[tauri::command]
+pub async fn set_metrics_config(
+    config: MetricsConfig,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    if !(1..=3600).contains(&config.poll_interval_secs) {
+        return Err("The poll interval must be between 1 and 3600 seconds".to_string());
+    }
+    *state.metrics_config.lock().await = config;
+    Ok(())
+}
+
+/// OPTIMIZATION: all metrics in a SINGLE SSH command to reduce overhead.
+/// This reduces 8+ SSH channel setups to just 1, dramatically improving performance
+const METRICS_COMMAND: &str = r#"
         echo "===CPU===";
         top -bn1 | grep 'Cpu(s)' | awk '{print $2}' | cut -d'%' -f1;
         echo "===MEM===";
         free -b | grep Mem | awk '{print $3,$2}';
+        echo "===SWAP===";
+        free -b | grep Swap | awk '{print $3,$2}';
         echo "===DISK===";
         df -B1 | tail -n +2 | awk '{print $6,$3,$2,$5}' | grep -E '^/';
         echo "===LOAD===";
@@ -181,35 +752,10 @@ pub async fn get_system_metrics(state: State<'_, AppState>) -> Result<SystemMetr
         echo "===IFACE===";
         ip route | grep default | awk '{print $5}' | head -n 1;
         echo "===END===";
-    "#;
-
-    let output = client
-        .execute_command(combined_command)
-        .map_err(|e| e.message)?;
-
-    // Parse the combined output
-    let mut sections: HashMap<String, String> = HashMap::new();
-    let mut current_section: Option<String> = None;
-    let mut section_lines: Vec<String> = Vec::new();
-
-    for line in output.lines() {
-        if line.starts_with("===") {
-            // Save previous section
-            if let Some(section_name) = current_section.take() {
-                sections.insert(section_name, section_lines.join("\n"));
-            }
-            // Start new section
-            current_section = Some(line.trim_matches('=').trim().to_string());
-            section_lines.clear();
-        } else {
-            section_lines.push(line.to_string());
-        }
-    }
-    // Save last section
-    if let Some(section_name) = current_section {
-        sections.insert(section_name, section_lines.join("\n"));
-    }
+"#;
 
+/// Metrics from the sections of `METRICS_COMMAND`, with empty history
+fn parse_system_metrics(sections: &HashMap<String, String>) -> SystemMetrics {
     // Parse CPU
     let cpu_percent: f64 = sections
         .get("CPU")
@@ -222,6 +768,12 @@ pub async fn get_system_metrics(state: State<'_, AppState>) -> Result<SystemMetr
     let memory_used: u64 = mem_parts.get(0).and_then(|s| s.parse().ok()).unwrap_or(0);
     let memory_total: u64 = mem_parts.get(1).and_then(|s| s.parse().ok()).unwrap_or(0);
 
+    // Parse Swap
+    let swap_str = sections.get("SWAP").map(|s| s.as_str()).unwrap_or("");
+    let swap_parts: Vec<&str> = swap_str.split_whitespace().collect();
+    let swap_used: u64 = swap_parts.first().and_then(|s| s.parse().ok()).unwrap_or(0);
+    let swap_total: u64 = swap_parts.get(1).and_then(|s| s.parse().ok()).unwrap_or(0);
+
     // Parse Disk
     let mut disk_usage = Vec::new();
     let disk_str = sections.get("DISK").map(|s| s.as_str()).unwrap_or("");
@@ -288,1743 +840,12332 @@ pub async fn get_system_metrics(state: State<'_, AppState>) -> Result<SystemMetr
         interface,
     };
 
+    SystemMetrics {
+        cpu_percent,
+        memory_used,
+        memory_total,
+        swap_used,
+        swap_total,
+        disk_usage,
+        load_avg,
+        uptime,
+        process_count,
+        network,
+        cpu_history: Vec::new(),
+        memory_history: Vec::new(),
+        network_history: Vec::new(),
+        collected_at: 0,
+        cached: false,
+    }
+}
+
+/// Add a sample to the server's metrics history and copy the history into `metrics`
+async fn record_metrics_history(state: &AppState, server_id: String, metrics: &mut SystemMetrics) {
     // Update history - use single lock scope for efficiency
     let timestamp = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
         .as_millis() as u64;
 
+    let mut all_history = state.metrics_history.lock().await;
+    let history = all_history.entry(server_id).or_default();
+
     // CPU History - O(1) operation with VecDeque
-    {
-        let mut cpu_hist = state.cpu_history.lock().await;
-        cpu_hist.push_back(cpu_percent);
-        if cpu_hist.len() > MAX_HISTORY_POINTS {
-            cpu_hist.pop_front(); // O(1) instead of remove(0) which is O(n)
-        }
+    history.cpu.push_back(metrics.cpu_percent);
+    if history.cpu.len() > MAX_HISTORY_POINTS {
+        history.cpu.pop_front(); // O(1) instead of remove(0) which is O(n)
     }
 
     // Memory History
-    {
-        let mem_percent = if memory_total > 0 {
-            (memory_used as f64 / memory_total as f64) * 100.0
-        } else {
-            0.0
-        };
-        let mut mem_hist = state.memory_history.lock().await;
-        mem_hist.push_back(mem_percent);
-        if mem_hist.len() > MAX_HISTORY_POINTS {
-            mem_hist.pop_front();
-        }
+    let mem_percent = if metrics.memory_total > 0 {
+        (metrics.memory_used as f64 / metrics.memory_total as f64) * 100.0
+    } else {
+        0.0
+    };
+    history.memory.push_back(mem_percent);
+    if history.memory.len() > MAX_HISTORY_POINTS {
+        history.memory.pop_front();
     }
 
     // Network History - calculate delta from last reading
-    let network_history_point = {
-        let mut last_net = state.last_network_stats.lock().await;
-        let point = if let Some(ref last) = *last_net {
-            NetworkHistoryPoint {
-                timestamp,
-                bytes_sent: bytes_sent.saturating_sub(last.bytes_sent),
-                bytes_recv: bytes_recv.saturating_sub(last.bytes_recv),
-            }
-        } else {
-            NetworkHistoryPoint {
-                timestamp,
-                bytes_sent: 0,
-                bytes_recv: 0,
-            }
-        };
-        *last_net = Some(network.clone());
-        point
+    let network_history_point = if let Some(ref last) = history.last_network_stats {
+        NetworkHistoryPoint {
+            timestamp,
+            bytes_sent: metrics.network.bytes_sent.saturating_sub(last.bytes_sent),
+            bytes_recv: metrics.network.bytes_recv.saturating_sub(last.bytes_recv),
+        }
+    } else {
+        NetworkHistoryPoint {
+            timestamp,
+            bytes_sent: 0,
+            bytes_recv: 0,
+        }
     };
+    history.last_network_stats = Some(metrics.network.clone());
 
-    {
-        let mut net_hist = state.network_history.lock().await;
-        net_hist.push_back(network_history_point);
-        if net_hist.len() > MAX_HISTORY_POINTS {
-            net_hist.pop_front();
-        }
+    history.network.push_back(network_history_point);
+    if history.network.len() > MAX_HISTORY_POINTS {
+        history.network.pop_front();
     }
 
     // Get history snapshots - convert VecDeque to Vec
-    let cpu_history: Vec<f64> = state.cpu_history.lock().await.clone().into();
-    let memory_history: Vec<f64> = state.memory_history.lock().await.clone().into();
-    let network_history: Vec<NetworkHistoryPoint> =
-        state.network_history.lock().await.clone().into();
+    metrics.cpu_history = history.cpu.iter().copied().collect();
+    metrics.memory_history = history.memory.iter().copied().collect();
+    metrics.network_history = history.network.iter().cloned().collect();
+    metrics.collected_at = timestamp;
+    history.last_snapshot = Some((std::time::Instant::now(), metrics.clone()));
+}
 
-    Ok(SystemMetrics {
-        cpu_percent,
-        memory_used,
-        memory_total,
-        disk_usage,
-        load_avg,
-        uptime,
-        process_count,
-        network,
-        cpu_history,
-        memory_history,
-        network_history,
-    })
+/// Add a sample per running container to the server's container history and copy each
+/// container's history into it. Containers that are gone lose their history.
+async fn record_container_history(
+    state: &AppState,
+    server_id: String,
+    containers: &mut [DockerContainer],
+) {
+    let mut all_history = state.container_history.lock().await;
+    let history = all_history.entry(server_id).or_default();
+    history.retain(|id, _| containers.iter().any(|c| &c.id == id));
+
+    for container in containers.iter_mut() {
+        let entry = history.entry(container.id.clone()).or_default();
+        if container.state == "running" {
+            let mem_percent = if container.memory_limit > 0 {
+                (container.memory_usage as f64 / container.memory_limit as f64) * 100.0
+            } else {
+                0.0
+            };
+            for (samples, value) in [
+                (&mut entry.cpu, container.cpu_percent),
+                (&mut entry.memory, mem_percent),
+            ] {
+                samples.push_back(value);
+                if samples.len() > MAX_HISTORY_POINTS {
+                    samples.pop_front();
+                }
+            }
+        }
+        container.cpu_history = entry.cpu.iter().copied().collect();
+        container.mem_history = entry.memory.iter().copied().collect();
+    }
 }
 
+/// Sections `dashboard_snapshot` can include
+const DASHBOARD_SECTIONS: [&str; 4] = ["metrics", "containers", "services", "ufw"];
+
+/// Gather several dashboard sections with a single SSH command, instead of one invoke
+/// (and one remote command or more) per section.
 #[tauri::command]
-pub async fn get_docker_containers(
+pub async fn dashboard_snapshot(
+    include: Vec<String>,
+    server_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<Vec<DockerContainer>, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+) -> Result<DashboardSnapshot, String> {
+    let (server_id, ssh_client) = state.connection(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
 
-    // Execute commands sequentially - more efficient than thread spawning with mutex contention
-    let ps_output = client.execute_command(
-        "docker ps -a --format '{{.ID}}|{{.Names}}|{{.Image}}|{{.Status}}|{{.State}}' --no-trunc",
-    ).map_err(|e| e.message)?;
+    if include.is_empty() {
+        return Err("No dashboard sections requested".to_string());
+    }
+    if let Some(unknown) = include
+        .iter()
+        .find(|name| !DASHBOARD_SECTIONS.contains(&name.as_str()))
+    {
+        return Err(format!(
+            "Unknown dashboard section '{}' (expected one of: {})",
+            unknown,
+            DASHBOARD_SECTIONS.join(", ")
+        ));
+    }
+    let wants = |name: &str| include.iter().any(|s| s == name);
+    let cached_metrics = match wants("metrics") {
+        true => recent_metrics(&state, &server_id).await,
+        false => None,
+    };
 
-    let mut containers = Vec::new();
-    for line in ps_output.lines() {
-        let parts: Vec<&str> = line.split('|').collect();
-        if parts.len() >= 5 {
-            containers.push(DockerContainer {
-                id: parts[0].to_string(),
-                name: parts[1].to_string(),
-                image: parts[2].to_string(),
-                status: parts[3].to_string(),
-                state: parts[4].to_string(),
-                cpu_percent: 0.0,
-                memory_usage: 0,
-                memory_limit: 0,
-                ports: Vec::new(),
-            });
+    let mut script = String::new();
+    if wants("metrics") && cached_metrics.is_none() {
+        script.push_str(METRICS_COMMAND);
+    }
+    if wants("containers") {
+        script.push_str(&format!(
+            r#"
+        echo "===DOCKER_PS==="
+        if ps_out=$({ps} 2>&1); then
+            printf '%s\n' "$ps_out"
+            echo "===DOCKER_HEALTH==="
+            ids=$(printf '%s\n' "$ps_out" | cut -d'|' -f1)
+            [ -n "$ids" ] && docker inspect {health} $ids 2>/dev/null
+            echo "===DOCKER_STATS==="
+            {stats} 2>/dev/null
+        else
+            echo "===DOCKER_FAILED==="
+            printf '%s\n' "$ps_out"
+        fi
+"#,
+            ps = DOCKER_PS_COMMAND,
+            health = DOCKER_HEALTH_FORMAT,
+            stats = DOCKER_STATS_COMMAND
+        ));
+    }
+    if wants("services") {
+        script.push_str(&format!(
+            "\necho \"===SERVICES===\"\n{}\n",
+            SERVICES_COMMAND
+        ));
+    }
+    if wants("ufw") {
+        for (name, command) in [
+            ("UFW_STATUS", UFW_STATUS_COMMAND),
+            ("UFW_LISTENING", LISTENING_PORTS_COMMAND),
+            ("UFW_DEFAULTS", UFW_DEFAULTS_COMMAND),
+            ("UFW_PUBLISHED", PUBLISHED_PORTS_COMMAND),
+            ("UFW_DOCKER_USER", DOCKER_USER_CHAIN_COMMAND),
+        ] {
+            script.push_str(&format!("\necho \"==={}===\"\n{}\n", name, command));
         }
     }
 
-    // Fetch stats and merge with container list
-    let stats_output = client
-        .execute_command("docker stats --no-stream --format '{{.Name}}|{{.CPUPerc}}|{{.MemUsage}}'")
-        .map_err(|e| e.message)?;
+    let output = execute_read_only(client, &script).map_err(|e| e.message)?;
+    let sections = split_sections(&output);
+    let section = |name: &str| sections.get(name).map(|s| s.as_str()).unwrap_or("");
+
+    let mut snapshot = DashboardSnapshot {
+        server_id: server_id.clone(),
+        metrics: None,
+        containers: None,
+        services: None,
+        ufw: None,
+        errors: HashMap::new(),
+    };
 
-    for line in stats_output.lines() {
-        let parts: Vec<&str> = line.split('|').collect();
-        if parts.len() >= 3 {
-            if let Some(container) = containers.iter_mut().find(|c| c.name == parts[0]) {
-                container.cpu_percent = parts[1].trim_end_matches('%').parse().unwrap_or(0.0);
-                let mem_parts: Vec<&str> = parts[2].split('/').collect();
-                if mem_parts.len() >= 2 {
-                    container.memory_usage = parse_memory(mem_parts[0]);
-                    container.memory_limit = parse_memory(mem_parts[1]);
-                }
+    if let Some(metrics) = cached_metrics {
+        snapshot.metrics = Some(metrics);
+    } else if wants("metrics") {
+        let mut metrics = parse_system_metrics(&sections);
+        record_metrics_history(&state, server_id.clone(), &mut metrics).await;
+        snapshot.metrics = Some(metrics);
+    }
+    if wants("containers") {
+        match sections.get("DOCKER_FAILED") {
+            Some(error) => {
+                snapshot.errors.insert(
+                    "containers".to_string(),
+                    format!("docker ps failed: {}", error.trim()),
+                );
+            }
+            None => {
+                let mut containers = parse_docker_containers(
+                    section("DOCKER_PS"),
+                    section("DOCKER_HEALTH"),
+                    section("DOCKER_STATS"),
+                );
+                record_container_history(&state, server_id, &mut containers).await;
+                snapshot.containers = Some(containers);
             }
         }
     }
+    if wants("services") {
+        snapshot.services = Some(parse_services(section("SERVICES")));
+    }
+    if wants("ufw") {
+        let status = section("UFW_STATUS");
+        let docker_exposures = build_docker_exposures(
+            section("UFW_PUBLISHED"),
+            status,
+            ufw_is_active(status),
+            section("UFW_DOCKER_USER"),
+        );
+        snapshot.ufw = Some(build_ufw_overview(
+            status,
+            section("UFW_LISTENING"),
+            section("UFW_DEFAULTS"),
+            docker_exposures,
+        ));
+    }
 
-    Ok(containers)
+    Ok(snapshot)
 }
 
-fn parse_memory(mem_str: &str) -> u64 {
-    let mem_str = mem_str.trim().to_uppercase();
-
-    // Handle various memory formats: "1.5GiB", "1.5GB", "100MiB", "100MB", "100 MiB", "100 MB"
-    if mem_str.contains("GIB") || mem_str.contains("GB") {
-        let value_str = mem_str.replace("GIB", " ").replace("GB", " ");
-        let value: f64 = value_str.trim().parse().unwrap_or(0.0);
-        (value * 1024.0 * 1024.0 * 1024.0) as u64
-    } else if mem_str.contains("MIB") || mem_str.contains("MB") {
-        let value_str = mem_str.replace("MIB", " ").replace("MB", " ");
-        let value: f64 = value_str.trim().parse().unwrap_or(0.0);
-        (value * 1024.0 * 1024.0) as u64
-    } else if mem_str.contains("KIB") || mem_str.contains("KB") {
-        let value_str = mem_str.replace("KIB", " ").replace("KB", " ");
-        let value: f64 = value_str.trim().parse().unwrap_or(0.0);
-        (value * 1024.0) as u64
-    } else {
-        // Assume bytes if no unit
-        let value_str = mem_str.replace("B", " ");
-        let value: f64 = value_str.trim().parse().unwrap_or(0.0);
-        value as u64
-    }
+/// Back-off before the first retry of a read-only command; doubles on each further retry
+const RETRY_BACKOFF_MS: u64 = 250;
+
+/// Run a read-only command with the connection's retry policy, so a transient failure
+/// doesn't blank the dashboard. Never use this for state-changing commands such as
+/// `ufw delete`, which must not run twice.
+fn execute_read_only(client: &SshClient, command: &str) -> Result<String, CommandError> {
+    client
+        .execute_with_retry(
+            command,
+            client.retry_attempts(),
+            std::time::Duration::from_millis(RETRY_BACKOFF_MS),
+        )
+        .map(|(output, _)| output)
 }
 
-#[tauri::command]
-pub async fn docker_container_action(
-    action: String,
-    container_name: String,
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+/// Split the output of a combined command into its `===NAME===` sections
+pub(crate) fn split_sections(output: &str) -> HashMap<String, String> {
+    let mut sections: HashMap<String, String> = HashMap::new();
+    let mut current_section: Option<String> = None;
+    let mut section_lines: Vec<String> = Vec::new();
 
-    let command = format!("docker {} {}", action, container_name);
-    client.execute_command(&command).map_err(|e| e.message)
+    for line in output.lines() {
+        if line.starts_with("===") {
+            // Save previous section
+            if let Some(section_name) = current_section.take() {
+                sections.insert(section_name, section_lines.join("\n"));
+            }
+            // Start new section
+            current_section = Some(line.trim_matches('=').trim().to_string());
+            section_lines.clear();
+        } else {
+            section_lines.push(line.to_string());
+        }
+    }
+    // Save last section
+    if let Some(section_name) = current_section {
+        sections.insert(section_name, section_lines.join("\n"));
+    }
+    sections
 }
 
 #[tauri::command]
-pub async fn get_container_logs(
-    container_name: String,
-    lines: Option<u32>,
+pub async fn get_advanced_storage(
+    server_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
-
-    let lines = lines.unwrap_or(100);
-    let command = format!("docker logs --tail {} {}", lines, container_name);
-    client.execute_command(&command).map_err(|e| e.message)
-}
+) -> Result<AdvancedStorage, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
 
-#[tauri::command]
-pub async fn get_services(state: State<'_, AppState>) -> Result<Vec<ServiceInfo>, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+    // LVM needs root to read device metadata; without passwordless sudo it's reported as absent
+    let combined_command = r#"
+        echo "===ZPOOL===";
+        command -v zpool >/dev/null 2>&1 && zpool list -Hp -o name,size,alloc,free,frag,cap,health 2>/dev/null;
+        echo "===ZFS===";
+        command -v zfs >/dev/null 2>&1 && zfs list -Hp -t filesystem,volume -o name,used,avail,refer,mountpoint 2>/dev/null;
+        echo "===ZFSSNAP===";
+        command -v zfs >/dev/null 2>&1 && zfs list -Hp -t snapshot -o name,used,refer,creation 2>/dev/null;
+        echo "===BTRFS===";
+        command -v btrfs >/dev/null 2>&1 && findmnt -rn -t btrfs -o SOURCE,TARGET 2>/dev/null | while read -r src target; do
+            echo "@@ $src $target"; btrfs filesystem df -b "$target" 2>/dev/null;
+        done;
+        echo "===VGS===";
+        command -v vgs >/dev/null 2>&1 && sudo -n vgs --noheadings --units b --nosuffix --separator '|' -o vg_name,vg_size,vg_free,lv_count,snap_count 2>/dev/null;
+        echo "===LVS===";
+        command -v lvs >/dev/null 2>&1 && sudo -n lvs --noheadings --units b --nosuffix --separator '|' -o lv_name,vg_name,lv_size,data_percent,lv_attr,origin,pool_lv 2>/dev/null;
+        echo "===END===";
+    "#;
 
     let output = client
-        .execute_command("systemctl list-units --type=service --all --no-pager --no-legend --plain")
+        .execute_command(combined_command)
         .map_err(|e| e.message)?;
+    let sections = split_sections(&output);
+    let section = |name: &str| sections.get(name).map(String::as_str).unwrap_or("");
 
-    let mut services = Vec::new();
-    for line in output.lines() {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 4 {
-            services.push(ServiceInfo {
-                name: parts[0].to_string(),
-                state: parts
-                    .get(1)
-                    .map(|s: &&str| s.to_string())
-                    .unwrap_or_default(),
-                sub_state: parts
-                    .get(2)
-                    .map(|s: &&str| s.to_string())
-                    .unwrap_or_default(),
-                description: parts[3..].join(" "),
-            });
-        }
+    Ok(AdvancedStorage {
+        zfs: parse_zfs_storage(section("ZPOOL"), section("ZFS"), section("ZFSSNAP")),
+        btrfs: parse_btrfs_filesystems(section("BTRFS")),
+        lvm: parse_lvm_storage(section("VGS"), section("LVS")),
+    })
+}
+
+/// Parse `zpool list -Hp` / `zfs list -Hp` output (tab separated, exact byte values)
+fn parse_zfs_storage(zpool: &str, datasets: &str, snapshots: &str) -> Option<ZfsStorage> {
+    let rows = |output: &str| -> Vec<Vec<String>> {
+        output
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.split('\t').map(String::from).collect())
+            .collect()
+    };
+    let number = |value: &str| value.trim().trim_end_matches('%').parse::<u64>().ok();
+
+    let pools: Vec<ZfsPool> = rows(zpool)
+        .into_iter()
+        .filter(|r| r.len() >= 7)
+        .map(|r| ZfsPool {
+            name: r[0].clone(),
+            size: number(&r[1]).unwrap_or(0),
+            allocated: number(&r[2]).unwrap_or(0),
+            free: number(&r[3]).unwrap_or(0),
+            fragmentation_percent: number(&r[4]).map(|v| v as u32),
+            capacity_percent: number(&r[5]).unwrap_or(0) as u32,
+            health: r[6].clone(),
+        })
+        .collect();
+    if pools.is_empty() {
+        return None;
     }
 
-    Ok(services)
-}
+    let datasets = rows(datasets)
+        .into_iter()
+        .filter(|r| r.len() >= 5)
+        .map(|r| ZfsDataset {
+            name: r[0].clone(),
+            used: number(&r[1]).unwrap_or(0),
+            available: number(&r[2]).unwrap_or(0),
+            referenced: number(&r[3]).unwrap_or(0),
+            mountpoint: match r[4].as_str() {
+                "-" | "none" | "legacy" => None,
+                path => Some(path.to_string()),
+            },
+        })
+        .collect();
 
-#[tauri::command]
-pub async fn service_action(
-    action: String,
-    service_name: String,
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+    let snapshots = rows(snapshots)
+        .into_iter()
+        .filter(|r| r.len() >= 4)
+        .map(|r| ZfsSnapshot {
+            name: r[0].clone(),
+            used: number(&r[1]).unwrap_or(0),
+            referenced: number(&r[2]).unwrap_or(0),
+            created: r[3].trim().parse().unwrap_or(0),
+        })
+        .collect();
 
-    let command = format!("sudo systemctl {} {}", action, service_name);
-    client.execute_command(&command).map_err(|e| e.message)
+    Some(ZfsStorage {
+        pools,
+        datasets,
+        snapshots,
+    })
 }
 
-#[tauri::command]
-pub async fn get_service_logs(
-    service_name: String,
-    lines: Option<u32>,
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
-
-    let lines = lines.unwrap_or(100);
+/// Parse `@@ <source> <mount>` headers each followed by `btrfs filesystem df -b` output.
+/// Subvolume mounts of the same device (`/dev/sda2[/@home]`) are reported once.
+fn parse_btrfs_filesystems(output: &str) -> Option<Vec<BtrfsFilesystem>> {
+    let mut filesystems: Vec<BtrfsFilesystem> = Vec::new();
+    let mut skipping = false;
 
-    // Try journalctl first (for systemd services with journald)
-    let journalctl_cmd = format!(
-        "journalctl -u {} -n {} --no-pager 2>&1",
-        service_name, lines
-    );
-    let journalctl_result = client.execute_command(&journalctl_cmd);
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix("@@ ") {
+            let (source, mount_point) = header.split_once(' ').unwrap_or((header, ""));
+            let device = source.split('[').next().unwrap_or(source).to_string();
+            skipping = filesystems.iter().any(|fs| fs.device == device);
+            if !skipping {
+                filesystems.push(BtrfsFilesystem {
+                    device,
+                    mount_point: mount_point.to_string(),
+                    allocations: Vec::new(),
+                });
+            }
+            continue;
+        }
+        if skipping {
+            continue;
+        }
 
-    // If journalctl succeeds and returns content, use it
-    if let Ok(output) = journalctl_result {
-        if !output.is_empty() && !output.contains("No entries") && !output.contains("cannot open") {
-            return Ok(output);
+        // e.g. Metadata, DUP: total=1073741824, used=268435456
+        let Some((label, sizes)) = line.split_once(": ") else {
+            continue;
+        };
+        let (kind, profile) = label.split_once(", ").unwrap_or((label, ""));
+        let size = |key: &str| {
+            sizes
+                .split(", ")
+                .find_map(|part| part.strip_prefix(key))
+                .and_then(|v| v.parse::<u64>().ok())
+        };
+        if let (Some(fs), Some(total), Some(used)) =
+            (filesystems.last_mut(), size("total="), size("used="))
+        {
+            fs.allocations.push(BtrfsAllocation {
+                kind: kind.to_string(),
+                profile: profile.to_string(),
+                total,
+                used,
+            });
         }
     }
 
-    // Fallback: Try common log file locations
-    let log_paths = vec![
-        format!("/var/log/{}.log", service_name),
-        format!("/var/log/{}.log", service_name.replace('-', "")),
-        format!("/var/log/{}/{}.log", service_name, service_name),
-        format!("/var/log/syslog"),
-        format!("/var/log/messages"),
-    ];
-
-    for log_path in log_paths {
-        let tail_cmd = format!(
-            "test -f {} && tail -n {} {} 2>&1",
-            log_path, lines, log_path
-        );
-        if let Ok(output) = client.execute_command(&tail_cmd) {
-            if !output.is_empty() && !output.contains("No such file") {
-                return Ok(format!("(From file: {})\n{}", log_path, output));
-            }
-        }
+    if filesystems.is_empty() {
+        None
+    } else {
+        Some(filesystems)
     }
+}
 
-    // Try to find service-specific log directory
-    let find_cmd = format!(
-        "find /var/log -name '*{}*' -type f 2>/dev/null | head -5",
-        service_name
-    );
-    if let Ok(found_files) = client.execute_command(&find_cmd) {
-        for file in found_files.lines() {
-            if !file.is_empty() {
-                let tail_cmd = format!("tail -n {} {} 2>&1", lines, file);
-                if let Ok(output) = client.execute_command(&tail_cmd) {
-                    if !output.is_empty() {
-                        return Ok(format!("(From file: {})\n{}", file, output));
-                    }
-                }
-            }
+/// Parse `vgs`/`lvs --noheadings --units b --nosuffix --separator '|'` output
+fn parse_lvm_storage(vgs: &str, lvs: &str) -> Option<LvmStorage> {
+    let rows = |output: &str| -> Vec<Vec<String>> {
+        output
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.trim().split('|').map(|f| f.trim().to_string()).collect())
+            .collect()
+    };
+    let optional = |value: &str| {
+        if value.is_empty() {
+            None
+        } else {
+            Some(value.to_string())
         }
+    };
+
+    let volume_groups: Vec<LvmVolumeGroup> = rows(vgs)
+        .into_iter()
+        .filter(|r| r.len() >= 5)
+        .map(|r| LvmVolumeGroup {
+            name: r[0].clone(),
+            size: r[1].parse().unwrap_or(0),
+            free: r[2].parse().unwrap_or(0),
+            lv_count: r[3].parse().unwrap_or(0),
+            snapshot_count: r[4].parse().unwrap_or(0),
+        })
+        .collect();
+    if volume_groups.is_empty() {
+        return None;
     }
 
-    Err(format!(
-        "No logs found for service '{}'. Service may not log to journal or standard log locations.",
-        service_name
-    ))
+    let logical_volumes = rows(lvs)
+        .into_iter()
+        .filter(|r| r.len() >= 7)
+        .map(|r| LvmLogicalVolume {
+            name: r[0].clone(),
+            vg_name: r[1].clone(),
+            size: r[2].parse().unwrap_or(0),
+            data_percent: r[3].parse().ok(),
+            attributes: r[4].clone(),
+            origin: optional(&r[5]),
+            pool: optional(&r[6]),
+        })
+        .collect();
+
+    Some(LvmStorage {
+        volume_groups,
+        logical_volumes,
+    })
 }
 
+/// Entries returned by `get_directory_usage` when no limit is given
+const DEFAULT_DIR_USAGE_LIMIT: usize = 50;
+
 #[tauri::command]
-pub async fn execute_command(
-    command: String,
+pub async fn get_directory_usage(
+    path: String,
+    depth: u32,
+    limit: Option<u32>,
+    one_filesystem: Option<bool>,
+    server_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
-    client.execute_command(&command).map_err(|e| e.message)
+) -> Result<Vec<DirUsage>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let quoted_path = shell_quote(&path);
+    let (_, exists) = client
+        .execute_with_status(&format!("test -d {}", quoted_path))
+        .map_err(|e| e.message)?;
+    if exists != 0 {
+        return Err(format!("Directory not found: {}", path));
+    }
+
+    // du exits non-zero and warns on stderr for unreadable subdirectories; keep what it could size
+    let output = client
+        .execute_command(&format!(
+            "du {}--max-depth={} -B1 {} 2>/dev/null",
+            if one_filesystem.unwrap_or(true) {
+                "-x "
+            } else {
+                ""
+            },
+            depth,
+            quoted_path
+        ))
+        .map_err(|e| e.message)?;
+
+    let limit = limit.map_or(DEFAULT_DIR_USAGE_LIMIT, |l| l as usize);
+    Ok(parse_du_output(&output, limit))
+}
+
+/// Parse `du -B1` output (`<bytes>\t<path>`), largest first
+fn parse_du_output(output: &str, limit: usize) -> Vec<DirUsage> {
+    let mut usage: Vec<DirUsage> = output
+        .lines()
+        .filter_map(|line| {
+            let (bytes, path) = line.split_once('\t')?;
+            Some(DirUsage {
+                path: path.to_string(),
+                bytes: bytes.trim().parse().ok()?,
+            })
+        })
+        .collect();
+    usage.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.path.cmp(&b.path)));
+    usage.truncate(limit);
+    usage
 }
 
+/// Files returned by `find_large_files` when no limit is given
+const DEFAULT_LARGE_FILES_LIMIT: usize = 50;
+
+/// The biggest files under `path` on its own filesystem, largest first. Directories that
+/// can't be read are skipped rather than failing the search.
 #[tauri::command]
-pub async fn save_server_profile(
-    profile: ServerProfile,
+pub async fn find_large_files(
+    path: String,
+    min_size_mb: u64,
+    limit: Option<u32>,
+    server_id: Option<String>,
     state: State<'_, AppState>,
-    app: tauri::AppHandle,
-) -> Result<(), String> {
-    // Save to in-memory state
-    let mut profiles = state.server_profiles.lock().await;
-    profiles.insert(profile.id.clone(), profile.clone());
+) -> Result<Vec<LargeFile>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
 
-    // Save to persistent storage
-    let mut saved_profile: SavedServerProfile = SavedServerProfile::from(profile);
-    if let Ok(store) = app.store(STORE_FILENAME) {
-        let mut profiles_map = profiles_from_json(store.get(PROFILES_KEY));
+    let quoted_path = shell_quote(&path);
+    let (_, exists) = client
+        .execute_with_status(&format!("test -d {}", quoted_path))
+        .map_err(|e| e.message)?;
+    if exists != 0 {
+        return Err(format!("Directory not found: {}", path));
+    }
 
-        // Preserve existing metadata if profile already exists
-        if let Some(existing) = profiles_map.get(&saved_profile.id) {
-            let mut updated = saved_profile;
-            updated.created_at = existing.created_at;
-            updated.last_connected = existing.last_connected;
-            updated.connect_on_startup = existing.connect_on_startup;
-            saved_profile = updated;
-        }
+    let limit = limit.map_or(DEFAULT_LARGE_FILES_LIMIT, |l| l as usize);
+    // -size +NM rounds up to whole MiB, so compare in KiB to honour the exact threshold
+    let output = client
+        .execute_command(&format!(
+            "sudo find {} -xdev -type f -size +{}k -printf '%s %T@ %p\\n' 2>/dev/null \
+             | sort -rn | head -n {}",
+            quoted_path,
+            min_size_mb.saturating_mul(1024),
+            limit
+        ))
+        .map_err(|e| e.message)?;
+    Ok(parse_large_files(&output, limit))
+}
 
-        profiles_map.insert(saved_profile.id.clone(), saved_profile);
-        store.set(PROFILES_KEY, profiles_to_json(&profiles_map));
-        store
-            .save()
-            .map_err(|e| format!("Failed to save profile: {}", e))?;
-    }
-    Ok(())
+/// Parse `find -printf '%s %T@ %p\n'` lines, largest first
+fn parse_large_files(output: &str, limit: usize) -> Vec<LargeFile> {
+    let mut files: Vec<LargeFile> = output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, ' ');
+            let size = fields.next()?.parse().ok()?;
+            let modified = fields.next()?.split('.').next()?.parse().ok()?;
+            let path = fields.next().filter(|p| !p.is_empty())?;
+            Some(LargeFile {
+                path: path.to_string(),
+                size,
+                modified,
+            })
+        })
+        .collect();
+    files.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    files.truncate(limit);
+    files
 }
 
+/// `case` patterns for the files `truncate_file` may empty: anything under /var/log and
+/// the json-file logs of Docker containers
+const TRUNCATABLE_LOG_PATTERNS: &[&str] =
+    &["/var/log/*", "/var/lib/docker/containers/*/*-json.log*"];
+
+/// Empty a log file in place, for logs a running process still holds open where deleting
+/// wouldn't free the space. Only log locations are accepted, checked after resolving the
+/// path on the server so symlinks and `..` can't lead elsewhere. The caller is expected
+/// to have confirmed with the user.
 #[tauri::command]
-pub async fn get_server_profiles(
+pub async fn truncate_file(
+    path: String,
+    server_id: Option<String>,
     state: State<'_, AppState>,
-    app: tauri::AppHandle,
-) -> Result<Vec<SavedServerProfile>, String> {
-    // Try to load from persistent storage first
-    if let Ok(store) = app.store(STORE_FILENAME) {
-        let profiles_map = profiles_from_json(store.get(PROFILES_KEY));
-        if !profiles_map.is_empty() {
-            let profiles: Vec<SavedServerProfile> = profiles_map.values().cloned().collect();
-            return Ok(profiles);
+) -> Result<ActionResult, String> {
+    if !path.starts_with('/') {
+        return Err(format!("Expected an absolute path, got '{}'", path));
+    }
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let script = format!(
+        "r=$(sudo realpath -e -- {p}) || {{ echo 'No such file or directory'; exit 3; }}; \
+         case \"$r\" in {patterns}) ;; \
+         *) echo \"$r is not a log file under /var/log or a container log\"; exit 3;; esac; \
+         sudo test -f \"$r\" || {{ echo \"$r is not a regular file\"; exit 3; }}; \
+         sudo stat -c %s -- \"$r\" && sudo truncate -s 0 -- \"$r\"",
+        p = shell_quote(&path),
+        patterns = TRUNCATABLE_LOG_PATTERNS.join("|")
+    );
+    let mut result = run_action(
+        client,
+        &script,
+        format!("Truncated {}", path),
+        &format!("Failed to truncate {}", path),
+    )?;
+    if result.success {
+        if let Some(freed) = result
+            .raw_output
+            .lines()
+            .find_map(|l| l.trim().parse::<u64>().ok())
+        {
+            result.message = format!("Truncated {}, freeing {}", path, format_bytes(freed));
         }
     }
-
-    // Fallback to in-memory profiles
-    let profiles = state.server_profiles.lock().await;
-    Ok(profiles
-        .values()
-        .map(|p| SavedServerProfile::from(p.clone()))
-        .collect())
+    Ok(result)
 }
 
 #[tauri::command]
-pub async fn delete_server_profile(
-    profile_id: String,
+pub async fn get_file_permissions(
+    path: String,
+    server_id: Option<String>,
     state: State<'_, AppState>,
-    app: tauri::AppHandle,
-) -> Result<(), String> {
-    // Remove from in-memory state
-    let mut profiles = state.server_profiles.lock().await;
-    profiles.remove(&profile_id);
-
-    // Remove from persistent storage
-    if let Ok(store) = app.store(STORE_FILENAME) {
-        let mut profiles_map = profiles_from_json(store.get(PROFILES_KEY));
-        if profiles_map.remove(&profile_id).is_some() {
-            store.set(PROFILES_KEY, profiles_to_json(&profiles_map));
-            store
-                .save()
-                .map_err(|e| format!("Failed to delete profile: {}", e))?;
-        }
+) -> Result<FilePermissions, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let (output, status) = client
+        .execute_with_status(&format!(
+            "sudo stat -c '%a|%A|%U|%G' -- {} 2>&1",
+            shell_quote(&path)
+        ))
+        .map_err(|e| e.message)?;
+    if status != 0 {
+        return Err(format!("Can't read {}: {}", path, output.trim()));
     }
-    Ok(())
+    parse_stat_permissions(&output)
+        .ok_or_else(|| format!("Unexpected output from stat: {}", output.trim()))
 }
 
+/// `set_file_permissions` won't recurse from these, compared after resolving the path
+const PROTECTED_RECURSIVE_PATHS: &[&str] = &[
+    "/", "/bin", "/boot", "/dev", "/etc", "/home", "/lib", "/lib32", "/lib64", "/opt", "/proc",
+    "/root", "/run", "/sbin", "/srv", "/sys", "/usr", "/var",
+];
+
+/// chmod and/or chown `path`, checking first that the owner and group exist so a typo
+/// doesn't leave the change half applied
 #[tauri::command]
-pub async fn update_server_profile_metadata(
-    profile_id: String,
-    connect_on_startup: Option<bool>,
-    app: tauri::AppHandle,
-) -> Result<(), String> {
-    if let Ok(store) = app.store(STORE_FILENAME) {
-        let mut profiles_map = profiles_from_json(store.get(PROFILES_KEY));
-        if let Some(profile) = profiles_map.get_mut(&profile_id) {
-            if let Some(value) = connect_on_startup {
-                profile.connect_on_startup = value;
-            }
-            store.set(PROFILES_KEY, profiles_to_json(&profiles_map));
-            store
-                .save()
-                .map_err(|e| format!("Failed to update profile: {}", e))?;
-            return Ok(());
+pub async fn set_file_permissions(
+    path: String,
+    mode: Option<String>,
+    owner: Option<String>,
+    group: Option<String>,
+    recursive: bool,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    if !path.starts_with('/') {
+        return Err(format!("Expected an absolute path, got '{}'", path));
+    }
+    if recursive && path.trim_end_matches('/').is_empty() {
+        return Err("Refusing to change permissions recursively from /".to_string());
+    }
+    let non_empty = |value: Option<String>| {
+        value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let (mode, owner, group) = (non_empty(mode), non_empty(owner), non_empty(group));
+    if mode.is_none() && owner.is_none() && group.is_none() {
+        return Err("Nothing to change: give a mode, owner or group".to_string());
+    }
+    if let Some(mode) = &mode {
+        if !is_valid_octal_mode(mode) {
+            return Err(format!(
+                "Invalid mode '{}', expected octal like 644 or 2775",
+                mode
+            ));
         }
     }
-    Err("Profile not found".to_string())
+    for name in owner.iter().chain(group.iter()) {
+        if !is_valid_account_name(name) {
+            return Err(format!("Invalid user or group name '{}'", name));
+        }
+    }
+
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let quoted = shell_quote(&path);
+    let flag = if recursive { "-R " } else { "" };
+    // A recursive change applies to the path that was checked
+    let target = if recursive {
+        "\"$resolved\""
+    } else {
+        quoted.as_str()
+    };
+    let mut script = if recursive {
+        // `/.`, `/etc/../usr` and symlinks only show what they are once resolved
+        vec![
+            format!(
+                "resolved=$(sudo realpath -e -- {}) || {{ echo 'No such file or directory'; exit 3; }}",
+                quoted
+            ),
+            format!(
+                "case \"$resolved\" in {}) echo \"Refusing to change permissions recursively from $resolved\"; exit 3;; esac",
+                PROTECTED_RECURSIVE_PATHS.join("|")
+            ),
+        ]
+    } else {
+        vec![format!(
+            "sudo test -e {} || {{ echo 'No such file or directory'; exit 3; }}",
+            quoted
+        )]
+    };
+    if let Some(owner) = &owner {
+        script.push(format!(
+            "getent passwd {0} > /dev/null || {{ echo {1}; exit 3; }}",
+            shell_quote(owner),
+            shell_quote(&format!("No user named {}", owner))
+        ));
+    }
+    if let Some(group) = &group {
+        script.push(format!(
+            "getent group {0} > /dev/null || {{ echo {1}; exit 3; }}",
+            shell_quote(group),
+            shell_quote(&format!("No group named {}", group))
+        ));
+    }
+    let ownership = match (&owner, &group) {
+        (Some(owner), Some(group)) => Some(format!("{}:{}", owner, group)),
+        (Some(owner), None) => Some(owner.clone()),
+        (None, Some(group)) => Some(format!(":{}", group)),
+        (None, None) => None,
+    };
+    let mut changes = Vec::new();
+    if let Some(ownership) = &ownership {
+        script.push(format!(
+            "sudo chown {}{} -- {}",
+            flag,
+            shell_quote(ownership),
+            target
+        ));
+        changes.push(format!("owner {}", ownership.trim_start_matches(':')));
+    }
+    if let Some(mode) = &mode {
+        script.push(format!("sudo chmod {}{} -- {}", flag, mode, target));
+        changes.push(format!("mode {}", mode));
+    }
+
+    run_action(
+        client,
+        &script.join(" && "),
+        format!(
+            "Set {} on {}{}",
+            changes.join(" and "),
+            path,
+            if recursive {
+                " and everything below it"
+            } else {
+                ""
+            }
+        ),
+        &format!("Failed to change permissions of {}", path),
+    )
 }
 
-#[tauri::command]
-pub async fn get_ufw_status(state: State<'_, AppState>) -> Result<UfwStatus, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+/// Three or four octal digits, as `chmod` takes them
+fn is_valid_octal_mode(mode: &str) -> bool {
+    (3..=4).contains(&mode.len()) && mode.chars().all(|c| ('0'..='7').contains(&c))
+}
 
-    // Get UFW status verbose
-    let status_output = client
-        .execute_command("sudo ufw status verbose 2>&1")
-        .map_err(|e| e.message)?;
+/// A user or group name, or a numeric id. `$` is only allowed at the end, as on Samba
+/// machine accounts.
+fn is_valid_account_name(name: &str) -> bool {
+    let base = name.strip_suffix('$').unwrap_or(name);
+    name.len() <= 32
+        && !base.is_empty()
+        && !name.starts_with('-')
+        && base
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
 
-    let mut active = false;
-    let mut logging = "off".to_string();
-    let mut default = "deny (incoming), allow (outgoing)".to_string();
-    let mut rules: Vec<UfwRule> = Vec::new();
+/// `stat -c '%a|%A|%U|%G'` output
+fn parse_stat_permissions(output: &str) -> Option<FilePermissions> {
+    let mut fields = output.trim().split('|');
+    let mode = fields.next()?;
+    let permissions = FilePermissions {
+        mode_octal: format!("{:0>4}", mode),
+        symbolic: fields.next()?.to_string(),
+        owner: fields.next()?.to_string(),
+        group: fields.next()?.to_string(),
+    };
+    is_valid_octal_mode(&permissions.mode_octal).then_some(permissions)
+}
 
-    let lines: Vec<&str> = status_output.lines().collect();
-    let mut in_rules = false;
+/// Supported digests: the name the UI passes, the coreutils tool, and its hex length
+const CHECKSUM_ALGORITHMS: &[(&str, &str, usize)] = &[
+    ("sha256", "sha256sum", 64),
+    ("sha1", "sha1sum", 40),
+    ("md5", "md5sum", 32),
+];
 
-    for line in lines {
-        let line = line.trim();
+/// Printed by the checksum scripts instead of a digest when the file doesn't exist
+const CHECKSUM_MISSING_MARKER: &str = "__MISSING__";
 
-        if line.starts_with("Status:") {
-            active = line.contains("active");
-        } else if line.starts_with("Logging:") {
-            logging = line.replace("Logging:", "").trim().to_string();
-        } else if line.starts_with("Default:") {
-            default = line.replace("Default:", "").trim().to_string();
-        } else if line.starts_with("---") {
-            in_rules = true;
-        } else if in_rules && !line.is_empty() {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 3 {
-                // Parse rule line: "22/tcp                     ALLOW       Anywhere"
-                let rule_str = parts[0].to_string();
-                let action = if parts.len() > 1 {
-                    parts[1].to_string()
-                } else {
-                    "".to_string()
-                };
-                let from = if parts.len() > 2 {
-                    parts[2..].join(" ")
-                } else {
-                    "Anywhere".to_string()
-                };
+fn checksum_tool(algorithm: &str) -> Option<&'static str> {
+    CHECKSUM_ALGORITHMS
+        .iter()
+        .find(|(name, _, _)| name.eq_ignore_ascii_case(algorithm.trim()))
+        .map(|(_, tool, _)| *tool)
+}
 
-                // Extract port if present
-                let port = rule_str.split('/').nth(1).map(|s| s.to_string());
+/// The algorithm that produces digests shaped like `hash`
+fn checksum_algorithm_for(hash: &str) -> Option<&'static str> {
+    if !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    CHECKSUM_ALGORITHMS
+        .iter()
+        .find(|(_, _, len)| *len == hash.len())
+        .map(|(name, _, _)| *name)
+}
 
-                rules.push(UfwRule {
-                    rule: rule_str,
-                    to: "Anywhere".to_string(),
-                    action,
-                    from: from.replace(" (v6)", ""),
-                    port,
-                });
-            }
+/// The digest from `sha256sum`-style output; escaped file names prefix it with `\`
+fn parse_checksum_output(output: &str, tool: &str) -> Option<String> {
+    let hash = output.split_whitespace().next()?.trim_start_matches('\\');
+    let len = CHECKSUM_ALGORITHMS
+        .iter()
+        .find(|(_, name, _)| *name == tool)
+        .map(|(_, _, len)| *len)?;
+    (hash.len() == len && hash.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| hash.to_ascii_lowercase())
+}
+
+/// Shell snippet printing the digest of `path`, or the missing marker. Files the user
+/// can read are hashed without sudo; only the rest go through `sudo -n`, which fails
+/// instead of waiting for a password. An existing file sudo can't reach is reported as
+/// a permission error rather than as missing.
+fn checksum_script(path: &str, tool: &str) -> String {
+    let quoted = shell_quote(path);
+    format!(
+        "if [ -r {path} ]; then {tool} -- {path} 2>&1; \
+         elif ! sudo -n true 2>/dev/null; then \
+         if [ -e {path} ]; then echo 'Permission denied: sudo needs a password'; \
+         else echo {missing}; fi; \
+         elif sudo -n test -e {path}; then sudo -n {tool} -- {path} 2>&1; else echo {missing}; fi",
+        path = quoted,
+        tool = tool,
+        missing = CHECKSUM_MISSING_MARKER
+    )
+}
+
+#[tauri::command]
+pub async fn checksum_file(
+    path: String,
+    algorithm: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let tool = checksum_tool(&algorithm)
+        .ok_or_else(|| format!("Unsupported checksum algorithm: {}", algorithm))?;
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let output = execute_read_only(ssh_client.as_ref(), &checksum_script(&path, tool))
+        .map_err(|e| e.message)?;
+    if output.trim() == CHECKSUM_MISSING_MARKER {
+        return Err(format!("File not found: {}", path));
+    }
+    parse_checksum_output(&output, tool)
+        .ok_or_else(|| format!("Failed to checksum {}: {}", path, output.trim()))
+}
+
+/// Check a manifest of `(path, expected hash)` pairs in one round trip. The algorithm
+/// for each entry follows from the length of its hash, so manifests can mix digests.
+#[tauri::command]
+pub async fn verify_checksums(
+    expected: Vec<(String, String)>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ChecksumResult>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let mut script = Vec::new();
+    for (i, (path, hash)) in expected.iter().enumerate() {
+        if let Some(tool) = checksum_algorithm_for(hash.trim()).and_then(checksum_tool) {
+            script.push(format!("echo '==={}==='", i));
+            script.push(checksum_script(path, tool));
         }
     }
+    let sections = if script.is_empty() {
+        HashMap::new()
+    } else {
+        split_sections(&execute_read_only(client, &script.join("\n")).map_err(|e| e.message)?)
+    };
 
-    Ok(UfwStatus {
-        active,
-        logging,
-        default,
-        rules,
-    })
+    Ok(expected
+        .into_iter()
+        .enumerate()
+        .map(|(i, (path, hash))| {
+            let expected = hash.trim().to_ascii_lowercase();
+            let algorithm = checksum_algorithm_for(&expected);
+            let output = sections.get(&i.to_string()).map(|s| s.trim());
+            let actual = algorithm
+                .and_then(checksum_tool)
+                .zip(output)
+                .and_then(|(tool, output)| parse_checksum_output(output, tool));
+            let (status, message) = match (algorithm, output, &actual) {
+                (None, _, _) => (
+                    ChecksumStatus::Error,
+                    Some("Expected hash is not a sha256, sha1 or md5 digest".to_string()),
+                ),
+                (_, Some(CHECKSUM_MISSING_MARKER), _) => (ChecksumStatus::Missing, None),
+                (_, _, Some(actual)) if *actual == expected => (ChecksumStatus::Match, None),
+                (_, _, Some(_)) => (ChecksumStatus::Mismatch, None),
+                (_, output, None) => (
+                    ChecksumStatus::Error,
+                    Some(output.unwrap_or("No output").to_string()),
+                ),
+            };
+            ChecksumResult {
+                path,
+                algorithm: algorithm.map(str::to_string),
+                expected,
+                actual,
+                status,
+                message,
+            }
+        })
+        .collect())
 }
 
+const DISK_WARN_PERCENT: u32 = 90;
+const DISK_CRITICAL_PERCENT: u32 = 95;
+
 #[tauri::command]
-pub async fn get_ufw_stats(state: State<'_, AppState>) -> Result<UfwStats, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+pub async fn get_system_health(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<HealthReport, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
 
-    let status_output = client
-        .execute_command("sudo ufw status numbered 2>&1")
+    let combined_command = r#"
+        echo "===NGINX===";
+        if command -v nginx >/dev/null 2>&1; then
+            if systemctl is-active --quiet nginx 2>/dev/null || pgrep -x nginx >/dev/null; then echo active; else echo inactive; fi;
+        else echo absent; fi;
+        echo "===FAILED===";
+        systemctl list-units --state=failed --no-legend --plain 2>/dev/null | awk '{print $1}';
+        echo "===CONTAINERS===";
+        ids=$(docker ps -aq 2>/dev/null);
+        [ -n "$ids" ] && docker inspect --format '{{.Name}}|{{.State.Status}}|{{if .State.Health}}{{.State.Health.Status}}{{end}}' $ids 2>/dev/null;
+        echo "===DISK===";
+        df -P -x tmpfs -x devtmpfs -x squashfs -x overlay 2>/dev/null | tail -n +2 | awk '{print $6,$5}';
+        echo "===MEM===";
+        grep -E '^(MemTotal|MemAvailable):' /proc/meminfo | awk '{print $2}';
+        echo "===PSI===";
+        cat /proc/pressure/memory 2>/dev/null | head -n 1;
+        echo "===END===";
+    "#;
+
+    let output = client
+        .execute_command(combined_command)
         .map_err(|e| e.message)?;
 
-    let mut total_rules = 0u32;
-    let mut allow_rules = 0u32;
-    let mut deny_rules = 0u32;
-    let mut limit_rules = 0u32;
+    Ok(build_health_report(&split_sections(&output)))
+}
 
-    for line in status_output.lines() {
-        let line = line.trim();
-        if line.contains("ALLOW") {
-            total_rules += 1;
-            allow_rules += 1;
-        } else if line.contains("DENY") {
-            total_rules += 1;
-            deny_rules += 1;
-        } else if line.contains("LIMIT") {
-            total_rules += 1;
-            limit_rules += 1;
+fn build_health_report(sections: &HashMap<String, String>) -> HealthReport {
+    let section = |name: &str| sections.get(name).map(String::as_str).unwrap_or("");
+    let mut checks = Vec::new();
+
+    // Nginx
+    let nginx = section("NGINX").trim();
+    checks.push(match nginx {
+        "active" => health_check("Nginx", HealthStatus::Ok, "Running"),
+        "absent" | "" => health_check("Nginx", HealthStatus::Ok, "Not installed"),
+        _ => health_check("Nginx", HealthStatus::Critical, "Installed but not running"),
+    });
+
+    // Failed systemd units
+    let failed: Vec<&str> = section("FAILED")
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    checks.push(if failed.is_empty() {
+        health_check("Services", HealthStatus::Ok, "No failed services")
+    } else {
+        health_check(
+            "Services",
+            HealthStatus::Critical,
+            &format!("{} failed: {}", failed.len(), failed.join(", ")),
+        )
+    });
+
+    // Containers: unhealthy ones are critical, stopped ones worth a look
+    let mut unhealthy = Vec::new();
+    let mut stopped = Vec::new();
+    for line in section("CONTAINERS").lines() {
+        let parts: Vec<&str> = line.trim().split('|').collect();
+        if parts.len() < 3 {
+            continue;
+        }
+        let name = parts[0].trim_start_matches('/');
+        if parts[2] == "unhealthy" {
+            unhealthy.push(name);
+        } else if matches!(parts[1], "exited" | "dead" | "restarting") {
+            stopped.push(name);
         }
     }
+    checks.push(match (unhealthy.is_empty(), stopped.is_empty()) {
+        (true, true) => health_check("Containers", HealthStatus::Ok, "All containers healthy"),
+        (false, _) => health_check(
+            "Containers",
+            HealthStatus::Critical,
+            &format!("{} unhealthy: {}", unhealthy.len(), unhealthy.join(", ")),
+        ),
+        (true, false) => health_check(
+            "Containers",
+            HealthStatus::Warn,
+            &format!("{} stopped: {}", stopped.len(), stopped.join(", ")),
+        ),
+    });
+
+    // Disk partitions
+    let mut full: Vec<(String, u32)> = section("DISK")
+        .lines()
+        .filter_map(|line| {
+            let (mount, used) = line.trim().split_once(' ')?;
+            let used: u32 = used.trim().trim_end_matches('%').parse().ok()?;
+            (used > DISK_WARN_PERCENT).then(|| (mount.to_string(), used))
+        })
+        .collect();
+    full.sort_by_key(|(_, used)| std::cmp::Reverse(*used));
+    checks.push(match full.first() {
+        None => health_check(
+            "Disk",
+            HealthStatus::Ok,
+            &format!("All partitions below {}%", DISK_WARN_PERCENT),
+        ),
+        Some((_, worst)) => health_check(
+            "Disk",
+            if *worst >= DISK_CRITICAL_PERCENT {
+                HealthStatus::Critical
+            } else {
+                HealthStatus::Warn
+            },
+            &full
+                .iter()
+                .map(|(mount, used)| format!("{} at {}%", mount, used))
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+    });
+
+    // Memory: available share, plus kernel pressure stall info when present
+    let mem: Vec<u64> = section("MEM")
+        .lines()
+        .filter_map(|l| l.trim().parse().ok())
+        .collect();
+    let psi_avg10: Option<f64> = section("PSI")
+        .split_whitespace()
+        .find_map(|field| field.strip_prefix("avg10="))
+        .and_then(|v| v.parse().ok());
+    checks.push(match mem.as_slice() {
+        [total, available] if *total > 0 => {
+            let available_percent = *available as f64 / *total as f64 * 100.0;
+            let stalled = psi_avg10.unwrap_or(0.0);
+            let status = if available_percent < 5.0 || stalled > 25.0 {
+                HealthStatus::Critical
+            } else if available_percent < 10.0 || stalled > 10.0 {
+                HealthStatus::Warn
+            } else {
+                HealthStatus::Ok
+            };
+            let mut detail = format!("{:.0}% available", available_percent);
+            if let Some(avg10) = psi_avg10 {
+                detail.push_str(&format!(", {:.1}% stalled (10s)", avg10));
+            }
+            health_check("Memory", status, &detail)
+        }
+        _ => health_check("Memory", HealthStatus::Warn, "Memory usage unavailable"),
+    });
 
-    Ok(UfwStats {
-        total_rules,
-        allow_rules,
-        deny_rules,
-        limit_rules,
-    })
+    let status = checks
+        .iter()
+        .map(|c| c.status)
+        .max()
+        .unwrap_or(HealthStatus::Ok);
+    HealthReport { status, checks }
+}
+
+fn health_check(name: &str, status: HealthStatus, detail: &str) -> HealthCheck {
+    HealthCheck {
+        name: name.to_string(),
+        status,
+        detail: detail.to_string(),
+    }
 }
 
 #[tauri::command]
-pub async fn ufw_action(action: String, state: State<'_, AppState>) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+pub async fn get_gpu_metrics(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<GpuInfo>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    // Hosts without an NVIDIA driver simply report no GPUs
+    let command = r#"
+        command -v nvidia-smi >/dev/null 2>&1 || exit 0;
+        nvidia-smi --query-gpu=index,name,utilization.gpu,memory.used,memory.total,temperature.gpu,uuid --format=csv,noheader,nounits;
+        echo "===APPS===";
+        nvidia-smi --query-compute-apps=gpu_uuid,pid,process_name,used_memory --format=csv,noheader,nounits 2>/dev/null;
+    "#;
 
-    let command = match action.as_str() {
-        "enable" => "echo 'y' | sudo ufw enable",
-        "disable" => "sudo ufw disable",
-        "reset" => "echo 'y' | sudo ufw reset",
-        "reload" => "sudo ufw reload",
-        _ => return Err(format!("Unknown action: {}", action)),
+    let output = client.execute_command(command).map_err(|e| e.message)?;
+    Ok(parse_gpu_metrics(&output))
+}
+
+/// Parse the combined `--query-gpu` / `--query-compute-apps` CSV output
+fn parse_gpu_metrics(output: &str) -> Vec<GpuInfo> {
+    let (gpu_section, apps_section) = match output.split_once("===APPS===") {
+        Some((gpus, apps)) => (gpus, apps),
+        None => (output, ""),
     };
 
-    client.execute_command(command).map_err(|e| e.message)
+    // nvidia-smi prints "[N/A]" or "[Not Supported]" for unavailable fields
+    let number = |field: &str| field.trim().parse::<f64>().unwrap_or(0.0);
+
+    let mut processes: HashMap<String, Vec<GpuProcess>> = HashMap::new();
+    for line in apps_section.lines() {
+        let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+        if fields.len() < 4 {
+            continue;
+        }
+        let Ok(pid) = fields[1].parse::<u32>() else {
+            continue;
+        };
+        processes
+            .entry(fields[0].to_string())
+            .or_default()
+            .push(GpuProcess {
+                pid,
+                process_name: fields[2].to_string(),
+                used_memory_mb: number(fields[3]) as u64,
+            });
+    }
+
+    let mut gpus = Vec::new();
+    for line in gpu_section.lines() {
+        let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+        if fields.len() < 6 {
+            continue;
+        }
+        let Ok(index) = fields[0].parse::<u32>() else {
+            continue;
+        };
+        let gpu_processes = fields
+            .get(6)
+            .and_then(|uuid| processes.remove(*uuid))
+            .unwrap_or_default();
+
+        gpus.push(GpuInfo {
+            index,
+            name: fields[1].to_string(),
+            util_percent: number(fields[2]),
+            mem_used_mb: number(fields[3]) as u64,
+            mem_total_mb: number(fields[4]) as u64,
+            temp_c: number(fields[5]),
+            processes: gpu_processes,
+        });
+    }
+
+    gpus
 }
 
 #[tauri::command]
-pub async fn ufw_add_rule(
-    rule_type: String,
-    port: Option<String>,
-    from_ip: Option<String>,
-    to_ip: Option<String>,
-    protocol: Option<String>,
+pub async fn get_docker_containers(
+    server_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+) -> Result<Vec<DockerContainer>, String> {
+    let (server_id, ssh_client) = state.connection(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
 
-    let mut command = String::from("sudo ufw");
+    // Execute commands sequentially - more efficient than thread spawning with mutex contention
+    let ps_output = execute_read_only(client, DOCKER_PS_COMMAND).map_err(|e| e.message)?;
 
-    // Allow/Deny
-    command.push_str(&format!(" {}", rule_type));
+    // Healthcheck and OOM status aren't part of `docker ps`, so inspect every container in one call
+    let ids: Vec<&str> = ps_output
+        .lines()
+        .filter_map(|line| line.split('|').next())
+        .filter(|id| !id.is_empty())
+        .collect();
+    let health_output = if ids.is_empty() {
+        String::new()
+    } else {
+        let health_command = format!(
+            "docker inspect {} {} 2>/dev/null",
+            DOCKER_HEALTH_FORMAT,
+            ids.join(" ")
+        );
+        execute_read_only(client, &health_command).unwrap_or_default()
+    };
 
-    // Protocol
-    if let Some(proto) = protocol {
-        if !proto.is_empty() {
-            command.push_str(&format!(" proto {}", proto));
-        }
-    }
+    // Fetch stats and merge with container list
+    let stats_output = execute_read_only(client, DOCKER_STATS_COMMAND).map_err(|e| e.message)?;
+
+    let mut containers = parse_docker_containers(&ps_output, &health_output, &stats_output);
+    record_container_history(&state, server_id, &mut containers).await;
+    Ok(containers)
+}
 
-    // Port
-    if let Some(p) = port {
-        if !p.is_empty() {
-            command.push_str(&format!(" port {}", p));
+const DOCKER_PS_COMMAND: &str =
+    "docker ps -a --format '{{.ID}}|{{.Names}}|{{.Image}}|{{.Status}}|{{.State}}|{{.Ports}}' --no-trunc";
+pub(crate) const DOCKER_STATS_COMMAND: &str =
+    "docker stats --no-stream --format '{{.Name}}|{{.CPUPerc}}|{{.MemUsage}}'";
+/// `docker inspect` format producing the `id|health|oom_killed|exit_code` lines read by
+/// `parse_container_health` and `parse_container_oom`
+const DOCKER_HEALTH_FORMAT: &str =
+    "--format '{{.Id}}|{{if .State.Health}}{{.State.Health.Status}}{{end}}|{{.State.OOMKilled}}|{{.State.ExitCode}}'";
+
+/// Containers from `DOCKER_PS_COMMAND`, merged with health and `DOCKER_STATS_COMMAND` output
+fn parse_docker_containers(
+    ps_output: &str,
+    health_output: &str,
+    stats_output: &str,
+) -> Vec<DockerContainer> {
+    let mut containers = Vec::new();
+    for line in ps_output.lines() {
+        let parts: Vec<&str> = line.split('|').collect();
+        if parts.len() >= 5 {
+            containers.push(DockerContainer {
+                id: parts[0].to_string(),
+                name: parts[1].to_string(),
+                image: parts[2].to_string(),
+                status: parts[3].to_string(),
+                state: parts[4].to_string(),
+                cpu_percent: 0.0,
+                memory_usage: 0,
+                memory_limit: 0,
+                ports: parts
+                    .get(5)
+                    .map(|p| parse_docker_ports(p))
+                    .unwrap_or_default(),
+                health: None,
+                oom_killed: false,
+                cpu_history: Vec::new(),
+                mem_history: Vec::new(),
+            });
         }
     }
 
-    // From IP
-    if let Some(from) = from_ip {
-        if !from.is_empty() && from != "any" {
-            command.push_str(&format!(" from {}", from));
-        }
+    let health = parse_container_health(health_output);
+    let oom_killed = parse_container_oom(health_output);
+    for container in &mut containers {
+        container.health = health.get(&container.id).cloned();
+        container.oom_killed = oom_killed.contains(&container.id);
     }
 
-    // To IP
-    if let Some(to) = to_ip {
-        if !to.is_empty() && to != "any" {
-            command.push_str(&format!(" to {}", to));
+    apply_container_stats(&mut containers, stats_output);
+    containers
+}
+
+/// Fill in CPU and memory usage from `DOCKER_STATS_COMMAND` output, matched by name
+pub(crate) fn apply_container_stats(containers: &mut [DockerContainer], stats_output: &str) {
+    for line in stats_output.lines() {
+        let parts: Vec<&str> = line.split('|').collect();
+        if parts.len() >= 3 {
+            if let Some(container) = containers.iter_mut().find(|c| c.name == parts[0]) {
+                container.cpu_percent = parts[1].trim_end_matches('%').parse().unwrap_or(0.0);
+                let mem_parts: Vec<&str> = parts[2].split('/').collect();
+                if mem_parts.len() >= 2 {
+                    container.memory_usage = parse_bytes(mem_parts[0]).unwrap_or(0);
+                    container.memory_limit = parse_bytes(mem_parts[1]).unwrap_or(0);
+                }
+            }
         }
     }
-
-    client.execute_command(&command).map_err(|e| e.message)
 }
 
-#[tauri::command]
-pub async fn ufw_delete_rule(
-    rule_number: u32,
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+/// Parse `docker inspect` lines of `id|health`; containers without a healthcheck are left out
+fn parse_container_health(output: &str) -> HashMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.trim().split('|');
+            let (id, health) = (fields.next()?, fields.next()?);
+            (!health.is_empty()).then(|| (id.to_string(), health.to_string()))
+        })
+        .collect()
+}
 
-    let command = format!("echo 'y' | sudo ufw delete {}", rule_number);
-    client.execute_command(&command).map_err(|e| e.message)
+/// Ids of containers that docker flags as OOM-killed or that exited with 137. The flag
+/// alone misses kills of a child process under cgroup v2, hence the exit code too.
+fn parse_container_oom(output: &str) -> HashSet<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.trim().split('|').collect();
+            let (id, flag, exit_code) = (fields.first()?, fields.get(2)?, fields.get(3)?);
+            (*flag == "true" || *exit_code == "137").then(|| id.to_string())
+        })
+        .collect()
 }
 
+/// Re-detect how to reach the Docker daemon and return the result
 #[tauri::command]
-pub async fn ufw_set_default(
-    direction: String,
-    policy: String,
+pub async fn get_docker_context(
+    server_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+) -> Result<DockerContextInfo, String> {
+    let (server_id, ssh_client) = state.connection(server_id.as_deref()).await?;
+
+    let context = detect_docker_context(&ssh_client)?;
+    state
+        .docker_contexts
+        .lock()
+        .await
+        .insert(server_id, context.clone());
+    Ok(context)
+}
 
-    let command = format!("sudo ufw default {} {}", policy, direction);
-    client.execute_command(&command).map_err(|e| e.message)
+/// Find a working way to run `docker`: as is, with the login shell's `DOCKER_HOST`, against
+/// the rootless socket, or through passwordless sudo. The winner is installed as the
+/// client's docker preamble so every later docker command uses it.
+fn detect_docker_context(client: &SshClient) -> Result<DockerContextInfo, String> {
+    let script = r#"
+        FMT='{{.ServerVersion}}|{{json .SecurityOptions}}'
+        mode=none; host=""; out=""
+        if out=$(docker info --format "$FMT" 2>/dev/null); then
+            mode=direct; host="$DOCKER_HOST"
+        else
+            host=$(bash -lc 'printf %s "$DOCKER_HOST"' 2>/dev/null)
+            if [ -n "$host" ] && out=$(DOCKER_HOST="$host" docker info --format "$FMT" 2>/dev/null); then
+                mode=env
+            else
+                host="unix://${XDG_RUNTIME_DIR:-/run/user/$(id -u)}/docker.sock"
+                if [ -S "${host#unix://}" ] && out=$(DOCKER_HOST="$host" docker info --format "$FMT" 2>/dev/null); then
+                    mode=env
+                elif out=$(sudo -n docker info --format "$FMT" 2>/dev/null); then
+                    mode=sudo; host=""
+                else
+                    host=""; out=""
+                fi
+            fi
+        fi
+        d() {
+            case "$mode" in
+                env) DOCKER_HOST="$host" docker "$@" ;;
+                sudo) sudo -n docker "$@" ;;
+                *) docker "$@" ;;
+            esac
+        }
+        echo "===MODE==="; echo "$mode"
+        echo "===HOST==="; echo "$host"
+        echo "===INFO==="; echo "$out"
+        echo "===CONTEXT==="
+        if [ "$mode" != none ]; then
+            d context show 2>/dev/null
+            d context inspect --format '{{.Endpoints.docker.Host}}' 2>/dev/null
+        fi
+        echo "===END==="
+    "#;
+
+    // Probe without any previous preamble so the result reflects the server as it is now
+    client.set_docker_preamble(String::new());
+    let output = client.execute_command(script).map_err(|e| e.message)?;
+    let (context, preamble) = parse_docker_context(&split_sections(&output));
+    client.set_docker_preamble(preamble);
+    Ok(context)
 }
 
-#[tauri::command]
-pub async fn ufw_set_logging(level: String, state: State<'_, AppState>) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+/// Turn the detection script's sections into the context info and the preamble to install
+fn parse_docker_context(sections: &HashMap<String, String>) -> (DockerContextInfo, String) {
+    let section = |name: &str| sections.get(name).map(|s| s.trim()).unwrap_or("");
+    let mode = section("MODE");
+    let host = section("HOST");
+    let (version, security_options) = section("INFO").split_once('|').unwrap_or(("", ""));
+    let mut context_lines = section("CONTEXT").lines().map(str::trim);
+    let context_name = context_lines.next().filter(|l| !l.is_empty());
+    let endpoint = context_lines.next().filter(|l| !l.is_empty());
+
+    let (invocation, preamble) = match mode {
+        "direct" => ("direct", String::new()),
+        "env" => ("env", format!("export DOCKER_HOST={}; ", shell_quote(host))),
+        "sudo" => ("sudo", "docker() { sudo -n docker \"$@\"; }; ".to_string()),
+        _ => ("unavailable", String::new()),
+    };
+    let available = invocation != "unavailable";
 
-    let command = format!("sudo ufw logging {}", level);
-    client.execute_command(&command).map_err(|e| e.message)
+    let context = DockerContextInfo {
+        available,
+        invocation: invocation.to_string(),
+        socket: if !host.is_empty() {
+            Some(host.to_string())
+        } else {
+            endpoint.map(String::from)
+        },
+        rootless: security_options.contains("name=rootless"),
+        version: Some(version.trim())
+            .filter(|v| available && !v.is_empty())
+            .map(String::from),
+        context_name: context_name.map(String::from),
+    };
+    (context, preamble)
 }
 
 #[tauri::command]
-pub async fn get_container_details(
-    container_name: String,
+pub async fn docker_container_action(
+    action: String,
+    container_name: String,
+    server_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<ContainerDetails, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
-
-    // Get container inspect data
-    let inspect_output = client
-        .execute_command(&format!("docker inspect {}", container_name))
-        .map_err(|e| e.message)?;
-
-    // Parse JSON manually (simplified parsing)
-    let inspect_value: serde_json::Value = serde_json::from_str(&inspect_output)
-        .map_err(|e| format!("Failed to parse inspect JSON: {}", e))?;
-
-    let container_data = inspect_value
-        .as_array()
-        .and_then(|arr| arr.first())
-        .ok_or("No container data found")?;
+) -> Result<ActionResult, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
 
-    let config = container_data.get("Config").ok_or("No config")?;
-    let host_config = container_data.get("HostConfig").ok_or("No host config")?;
-    let network_settings = container_data
-        .get("NetworkSettings")
-        .ok_or("No network settings")?;
-    let state_data = container_data.get("State").ok_or("No state")?;
+    let command = format!("docker {} {}", action, container_name);
+    run_action(
+        client,
+        &command,
+        format!(
+            "Container '{}' {}",
+            container_name,
+            action_past_tense(&action)
+        ),
+        &format!("Failed to {} container '{}'", action, container_name),
+    )
+}
 
-    // Extract environment variables (filter out sensitive ones)
-    let env_vars: Vec<String> = config
-        .get("Env")
-        .and_then(|v| v.as_array())
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|v| v.as_str())
-                .filter(|e| {
-                    !e.contains("PASSWORD")
-                        && !e.contains("SECRET")
-                        && !e.contains("KEY")
-                        && !e.contains("TOKEN")
-                })
-                .map(String::from)
-                .collect()
+#[tauri::command]
+pub async fn get_container_logs(
+    container_name: String,
+    lines: Option<u32>,
+    timestamps: Option<bool>,
+    since: Option<String>,
+    until: Option<String>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ContainerLogs, String> {
+    if !is_valid_container_name(&container_name) {
+        return Err(format!("Invalid container name '{}'", container_name));
+    }
+    let flags = container_log_flags(
+        lines.unwrap_or(100),
+        timestamps.unwrap_or(false),
+        since.as_deref(),
+        until.as_deref(),
+    )?;
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let args: Vec<String> = flags
+        .iter()
+        .map(|(flag, value)| match value {
+            Some(value) => format!("{} {}", flag, shell_quote(value)),
+            None => flag.to_string(),
         })
-        .unwrap_or_default();
+        .collect();
+    let command = format!("docker logs {} {}", args.join(" "), container_name);
+    let logs = client.execute_command(&command).map_err(|e| e.message)?;
+    Ok(ContainerLogs {
+        logs,
+        flags: flags
+            .into_iter()
+            .map(|(flag, value)| match value {
+                Some(value) => format!("{}={}", flag, value),
+                None => flag.to_string(),
+            })
+            .collect(),
+    })
+}
 
-    // Extract ports
-    let mut ports: Vec<PortMapping> = Vec::new();
-    if let Some(port_bindings) = host_config.get("PortBindings").and_then(|v| v.as_object()) {
-        for (container_port, bindings) in port_bindings {
-            if let Some(binding_arr) = bindings.as_array() {
-                for binding in binding_arr {
-                    if let Some(obj) = binding.as_object() {
-                        ports.push(PortMapping {
-                            host_ip: obj
-                                .get("HostIp")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("0.0.0.0")
-                                .to_string(),
-                            host_port: obj
-                                .get("HostPort")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("")
-                                .to_string(),
-                            container_port: container_port
-                                .split('/')
-                                .next()
-                                .unwrap_or("")
-                                .to_string(),
-                            protocol: container_port
-                                .split('/')
-                                .nth(1)
-                                .unwrap_or("tcp")
-                                .to_string(),
-                        });
-                    }
-                }
-            }
+/// `docker logs` flags with their values. Blank `since`/`until` are left out; anything
+/// else is passed on for docker to interpret (RFC 3339 times, Unix timestamps, or
+/// durations such as `30m`).
+fn container_log_flags(
+    lines: u32,
+    timestamps: bool,
+    since: Option<&str>,
+    until: Option<&str>,
+) -> Result<Vec<(&'static str, Option<String>)>, String> {
+    let mut flags = vec![("--tail", Some(lines.to_string()))];
+    if timestamps {
+        flags.push(("--timestamps", None));
+    }
+    for (flag, value) in [("--since", since), ("--until", until)] {
+        let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+            continue;
+        };
+        if value.starts_with('-') || value.contains(char::is_whitespace) {
+            return Err(format!("Invalid time '{}' for {}", value, flag));
         }
+        flags.push((flag, Some(value.to_string())));
     }
+    Ok(flags)
+}
 
-    // Extract networks
-    let networks: Vec<String> = network_settings
-        .get("Networks")
-        .and_then(|v| v.as_object())
-        .map(|obj| obj.keys().cloned().collect())
-        .unwrap_or_default();
+/// Write a container's logs (stdout and stderr) straight to `local_path` on this machine,
+/// for logs too large to pass through the UI. All lines unless `lines` is given; `since`
+/// takes anything `docker logs --since` does. Returns the number of bytes written.
+/// The logs go to a temporary file next to `local_path` that replaces it only once the
+/// export succeeded, so a failed export leaves an existing file as it was.
+#[tauri::command]
+pub async fn export_container_logs(
+    container_name: String,
+    lines: Option<u32>,
+    since: Option<String>,
+    local_path: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<u64, String> {
+    if !is_valid_container_name(&container_name) {
+        return Err(format!("Invalid container name '{}'", container_name));
+    }
+    let path = match local_path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .ok_or("Home directory not found")?
+            .join(rest),
+        None => std::path::PathBuf::from(&local_path),
+    };
+    let ssh_client = state.client(server_id.as_deref()).await?;
 
-    // Extract volumes
-    let mut volumes: Vec<VolumeMount> = Vec::new();
-    if let Some(mounts) = container_data.get("Mounts").and_then(|v| v.as_array()) {
-        for mount in mounts {
-            volumes.push(VolumeMount {
-                source: mount
-                    .get("Source")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string(),
-                destination: mount
-                    .get("Destination")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string(),
-                mode: mount
-                    .get("Mode")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string(),
-            });
+    let mut command = format!(
+        "docker logs --tail {}",
+        lines.map_or("all".to_string(), |n| n.to_string())
+    );
+    if let Some(since) = since.as_deref().filter(|s| !s.trim().is_empty()) {
+        command.push_str(&format!(" --since {}", shell_quote(since.trim())));
+    }
+    command.push_str(&format!(" {} 2>&1", container_name));
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("{} isn't a file path", local_path))?;
+    let partial = path.with_file_name(format!(".{}.part", file_name.to_string_lossy()));
+    let file = std::fs::File::create(&partial)
+        .map_err(|e| format!("Failed to create {}: {}", partial.display(), e))?;
+    let mut writer = std::io::BufWriter::new(file);
+    let outcome = ssh_client
+        .execute_to_writer(&command, &mut writer)
+        .map_err(|e| e.message)
+        .and_then(|result| {
+            std::io::Write::flush(&mut writer)
+                .map(|_| result)
+                .map_err(|e| format!("Failed to write {}: {}", local_path, e))
+        });
+    drop(writer);
+
+    let result = match outcome {
+        Ok((written, 0)) => std::fs::rename(&partial, &path)
+            .map(|_| written)
+            .map_err(|e| format!("Failed to write {}: {}", local_path, e)),
+        Ok((written, status)) => {
+            // A short file holds docker's error message rather than logs
+            let error = match written {
+                0..=4096 => std::fs::read_to_string(&partial).unwrap_or_default(),
+                _ => format!("docker logs exited with status {}", status),
+            };
+            Err(format!(
+                "Failed to export logs for {}: {}",
+                container_name,
+                error.trim()
+            ))
         }
+        Err(e) => Err(e),
+    };
+    if result.is_err() {
+        let _ = std::fs::remove_file(&partial);
     }
+    result
+}
 
-    // Extract labels
-    let mut labels: Vec<Label> = Vec::new();
-    if let Some(labels_obj) = config.get("Labels").and_then(|v| v.as_object()) {
-        for (key, value) in labels_obj {
-            labels.push(Label {
-                key: key.clone(),
-                value: value.as_str().unwrap_or("").to_string(),
-            });
-        }
+/// Grep the last `lines` log lines of a container. `pattern` is a literal string unless
+/// `is_regex` is set, in which case it's an extended regular expression.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn search_container_logs(
+    container_name: String,
+    pattern: String,
+    lines: Option<u32>,
+    case_insensitive: Option<bool>,
+    context: Option<u32>,
+    is_regex: Option<bool>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<LogMatch>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    if !is_valid_container_name(&container_name) {
+        return Err(format!("Invalid container name '{}'", container_name));
+    }
+    if pattern.is_empty() || pattern.contains('\n') {
+        return Err("Search pattern must be a single, non-empty line".to_string());
+    }
+    let context = context.unwrap_or(2).min(20);
+    let mut flags = format!("-n -C {}", context);
+    flags.push_str(if is_regex.unwrap_or(false) {
+        " -E"
+    } else {
+        " -F"
+    });
+    if case_insensitive.unwrap_or(false) {
+        flags.push_str(" -i");
     }
 
-    let started_at = state_data
-        .get("StartedAt")
-        .and_then(|v| v.as_str())
-        .map(String::from);
+    // The pipeline's status is grep's, so a missing container is caught up front rather
+    // than reading as "no matches"
+    let (output, exit_status) = client
+        .execute_with_status(&format!(
+            "e=$(docker inspect --type container --format . {0} 2>&1) || {{ echo \"$e\"; exit 3; }}; \
+             docker logs --tail {1} {0} 2>&1 | grep {2} -e {3}",
+            container_name,
+            lines.unwrap_or(1000),
+            flags,
+            shell_quote(&pattern)
+        ))
+        .map_err(|e| e.message)?;
+    // grep exits 1 when nothing matched and 2 on errors such as an invalid regex
+    match exit_status {
+        0 => Ok(parse_grep_context(&output, context as usize)),
+        1 => Ok(Vec::new()),
+        3 => Err(output.trim().to_string()),
+        _ => Err(format!("Log search failed: {}", output.trim())),
+    }
+}
 
-    Ok(ContainerDetails {
-        id: container_data
-            .get("Id")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string()[..12]
-            .to_string(),
-        name: container_data
-            .get("Name")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .trim_start_matches('/')
-            .to_string(),
-        image: container_data
-            .get("Image")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string(),
-        state: state_data
-            .get("Status")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string(),
-        status: container_data
-            .get("State")
-            .and_then(|v| v.get("Status"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string(),
-        created: container_data
-            .get("Created")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string(),
-        started_at,
-        env_vars,
-        ports,
-        networks,
-        volumes,
-        labels,
-        command: config
-            .get("Cmd")
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.as_str())
+/// Parse `grep -n -C` output: `12:text` for matches, `11-text` for context lines and `--`
+/// between groups whose context doesn't touch
+fn parse_grep_context(output: &str, context: usize) -> Vec<LogMatch> {
+    let mut groups: Vec<Vec<(u32, bool, &str)>> = vec![Vec::new()];
+    for line in output.lines() {
+        if line == "--" {
+            groups.push(Vec::new());
+            continue;
+        }
+        let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let (number, rest) = line.split_at(digits);
+        let (Ok(number), Some(separator)) = (number.parse::<u32>(), rest.chars().next()) else {
+            continue;
+        };
+        if let Some(group) = groups.last_mut() {
+            group.push((number, separator == ':', &rest[1..]));
+        }
+    }
+
+    let mut matches = Vec::new();
+    for group in &groups {
+        for (idx, &(line_number, is_match, text)) in group.iter().enumerate() {
+            if !is_match {
+                continue;
+            }
+            let texts = |range: std::ops::Range<usize>| {
+                group[range]
+                    .iter()
+                    .map(|(_, _, text)| text.to_string())
                     .collect::<Vec<_>>()
-                    .join(" ")
-            })
-            .unwrap_or_default(),
-        working_dir: config
-            .get("WorkingDir")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string(),
-        user: config
-            .get("User")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string(),
-        restart_policy: host_config
-            .get("RestartPolicy")
-            .and_then(|v| v.get("Name"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("no")
-            .to_string(),
-        memory_limit: host_config
-            .get("Memory")
-            .and_then(|v| v.as_u64())
-            .map(|m| {
-                if m > 0 {
-                    format!("{:.2} GB", m as f64 / 1024.0 / 1024.0 / 1024.0)
-                } else {
-                    "Unlimited".to_string()
-                }
-            })
-            .unwrap_or("Unlimited".to_string()),
-        cpu_limit: host_config
-            .get("NanoCpus")
-            .and_then(|v| v.as_u64())
-            .map(|c| {
-                if c > 0 {
-                    format!("{:.2} CPUs", c as f64 / 1_000_000_000.0)
-                } else {
-                    "Unlimited".to_string()
-                }
-            })
-            .unwrap_or("Unlimited".to_string()),
-    })
+            };
+            matches.push(LogMatch {
+                line_number,
+                line: text.to_string(),
+                context_before: texts(idx.saturating_sub(context)..idx),
+                context_after: texts(idx + 1..(idx + 1 + context).min(group.len())),
+            });
+        }
+    }
+    matches
 }
 
 #[tauri::command]
-pub async fn get_docker_volumes(state: State<'_, AppState>) -> Result<Vec<DockerVolume>, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+pub async fn get_services(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ServiceInfo>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
 
     let output = client
-        .execute_command(
-            "docker volume ls --format '{{.Name}}|{{.Driver}}|{{.Mountpoint}}|{{.Scope}}'",
+        .execute_command(SERVICES_COMMAND)
+        .map_err(|e| e.message)?;
+
+    Ok(parse_services(&output))
+}
+
+const SERVICES_COMMAND: &str =
+    "systemctl list-units --type=service --all --no-pager --no-legend --plain";
+
+fn parse_services(output: &str) -> Vec<ServiceInfo> {
+    let mut services = Vec::new();
+    for line in output.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() >= 4 {
+            services.push(ServiceInfo {
+                name: parts[0].to_string(),
+                state: parts
+                    .get(1)
+                    .map(|s: &&str| s.to_string())
+                    .unwrap_or_default(),
+                sub_state: parts
+                    .get(2)
+                    .map(|s: &&str| s.to_string())
+                    .unwrap_or_default(),
+                description: parts[3..].join(" "),
+            });
+        }
+    }
+
+    services
+}
+
+#[tauri::command]
+pub async fn service_action(
+    action: String,
+    service_name: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let command = format!("sudo systemctl {} {}", action, service_name);
+    run_action(
+        client,
+        &command,
+        format!("Service '{}' {}", service_name, action_past_tense(&action)),
+        &format!("Failed to {} service '{}'", action, service_name),
+    )
+}
+
+/// Services `reload_services` knows how to check, with the command validating their
+/// configuration. The service name is also the systemd unit that gets reloaded.
+const RELOAD_VALIDATORS: &[(&str, &str)] = &[
+    ("nginx", "sudo nginx -t"),
+    ("apache2", "sudo apachectl configtest"),
+    ("httpd", "sudo apachectl configtest"),
+    ("ssh", "sudo sshd -t"),
+    ("sshd", "sudo sshd -t"),
+    ("haproxy", "sudo haproxy -c -f /etc/haproxy/haproxy.cfg"),
+    ("caddy", "sudo caddy validate --config /etc/caddy/Caddyfile"),
+    ("postfix", "sudo postfix check"),
+    ("named", "sudo named-checkconf"),
+    ("bind9", "sudo named-checkconf"),
+    ("squid", "sudo squid -k parse"),
+    ("dnsmasq", "sudo dnsmasq --test"),
+];
+
+/// Check each target's configuration and reload only the ones that pass, like the
+/// nginx test-then-reload of `enable_vhost`. Services without a known check are refused.
+#[tauri::command]
+pub async fn reload_services(
+    targets: Vec<String>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ReloadResult>, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let mut results: Vec<ReloadResult> = Vec::new();
+    for service in targets {
+        let service = service.trim().trim_end_matches(".service").to_string();
+        if results.iter().any(|r| r.service == service) {
+            continue;
+        }
+        let Some((_, validator)) = RELOAD_VALIDATORS.iter().find(|(name, _)| *name == service)
+        else {
+            results.push(ReloadResult {
+                message: format!("No config check is known for '{}'; not reloaded", service),
+                service,
+                validated: false,
+                reloaded: false,
+                output: String::new(),
+            });
+            continue;
+        };
+
+        let (output, status) = client
+            .execute_with_status(&format!("{} 2>&1", validator))
+            .map_err(|e| e.message)?;
+        if status != 0 {
+            results.push(ReloadResult {
+                message: format!("{} config check failed; not reloaded", service),
+                service,
+                validated: false,
+                reloaded: false,
+                output: output.trim().to_string(),
+            });
+            continue;
+        }
+
+        let (reload_output, reload_status) = client
+            .execute_with_status(&format!("sudo systemctl reload {} 2>&1", service))
+            .map_err(|e| e.message)?;
+        let reloaded = reload_status == 0;
+        let output = if reloaded { output } else { reload_output };
+        results.push(ReloadResult {
+            message: if reloaded {
+                format!("{} config is valid and the service was reloaded", service)
+            } else {
+                format!("{} config is valid but the reload failed", service)
+            },
+            validated: true,
+            reloaded,
+            output: output.trim().to_string(),
+            service,
+        });
+    }
+    Ok(results)
+}
+
+/// What `service_name` needs, or with `reverse` what needs it, as a tree. Each unit's
+/// state comes from `systemctl show` rather than the tree's status glyphs.
+#[tauri::command]
+pub async fn get_unit_dependencies(
+    service_name: String,
+    reverse: bool,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<UnitDepTree, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let (output, status) = client
+        .execute_with_status(&format!(
+            "SYSTEMD_COLORS=0 systemctl list-dependencies --no-pager{} -- {} 2>&1",
+            if reverse { " --reverse" } else { "" },
+            shell_quote(&service_name)
+        ))
+        .map_err(|e| e.message)?;
+    if status != 0 {
+        return Err(format!(
+            "Failed to list dependencies of {}: {}",
+            service_name,
+            output.trim()
+        ));
+    }
+    let nodes = parse_dependency_lines(&output);
+    if nodes.is_empty() {
+        return Err(format!("No dependencies reported for {}", service_name));
+    }
+
+    let mut units: Vec<&str> = nodes.iter().map(|(_, unit)| unit.as_str()).collect();
+    units.sort_unstable();
+    units.dedup();
+    let quoted: Vec<String> = units.iter().map(|unit| shell_quote(unit)).collect();
+    let states = execute_read_only(
+        client,
+        &format!(
+            "systemctl show --no-pager --property=LoadState,ActiveState,SubState -- {}",
+            quoted.join(" ")
+        ),
+    )
+    .map_err(|e| e.message)?;
+    // `show` prints one block per unit, in the order they were given
+    let states: HashMap<&str, HashMap<&str, &str>> = units
+        .iter()
+        .copied()
+        .zip(states.split("\n\n").map(|block| {
+            block
+                .lines()
+                .filter_map(|line| line.split_once('='))
+                .collect()
+        }))
+        .collect();
+
+    let mut pos = 0;
+    let mut trees = build_dependency_tree(&nodes, &mut pos, None, &states);
+    Ok(trees.remove(0))
+}
+
+/// `(depth, unit)` for each line of a `list-dependencies` tree. Handles the Unicode and
+/// ASCII tree drawings, leading status glyphs and stray colour codes.
+fn parse_dependency_lines(output: &str) -> Vec<(usize, String)> {
+    const BRANCHES: &[&str] = &["├─", "└─", "│ ", "|-", "`-", "| ", "  "];
+    let mut nodes = Vec::new();
+    for line in output.lines() {
+        let line = strip_ansi(line);
+        let mut rest = line.trim_end();
+        for glyph in ["● ", "○ ", "× ", "* "] {
+            if let Some(stripped) = rest.strip_prefix(glyph) {
+                rest = stripped;
+                break;
+            }
+        }
+        let mut depth = 0;
+        while let Some(stripped) = BRANCHES.iter().find_map(|b| rest.strip_prefix(b)) {
+            rest = stripped;
+            depth += 1;
+        }
+        if rest.is_empty() || rest.contains(char::is_whitespace) {
+            continue;
+        }
+        nodes.push((depth, rest.to_string()));
+    }
+    nodes
+}
+
+/// Nodes from `pos` deeper than `parent_depth`, nested by depth
+fn build_dependency_tree(
+    nodes: &[(usize, String)],
+    pos: &mut usize,
+    parent_depth: Option<usize>,
+    states: &HashMap<&str, HashMap<&str, &str>>,
+) -> Vec<UnitDepTree> {
+    let mut trees = Vec::new();
+    while let Some((depth, unit)) = nodes.get(*pos) {
+        if parent_depth.is_some_and(|parent| *depth <= parent) {
+            break;
+        }
+        *pos += 1;
+        let state = |key: &str| {
+            states
+                .get(unit.as_str())
+                .and_then(|props| props.get(key))
+                .map(|value| value.to_string())
+                .unwrap_or_default()
+        };
+        trees.push(UnitDepTree {
+            unit: unit.clone(),
+            load_state: state("LoadState"),
+            active_state: state("ActiveState"),
+            sub_state: state("SubState"),
+            children: build_dependency_tree(nodes, pos, Some(*depth), states),
+        });
+    }
+    trees
+}
+
+/// Drop ANSI escape sequences (`ESC [ ... letter`)
+fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            out.push(c);
+            continue;
+        }
+        if chars.next() == Some('[') {
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    out
+}
+
+#[tauri::command]
+pub async fn get_service_logs(
+    service_name: String,
+    lines: Option<u32>,
+    since: Option<String>,
+    priority: Option<String>,
+    rotation: Option<u32>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ServiceLogResult, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let lines = lines.unwrap_or(100);
+    if let Some(priority) = &priority {
+        if !is_valid_journal_priority(priority) {
+            return Err(format!("Invalid log priority '{}'", priority));
+        }
+    }
+
+    // Try journalctl first (for systemd services with journald)
+    let mut filters = format!("-u {}", shell_quote(&service_name));
+    let since = since.as_deref().filter(|s| !s.trim().is_empty());
+    let filtered = since.is_some() || priority.is_some();
+    if let Some(since) = since {
+        filters.push_str(&format!(" --since {}", shell_quote(since.trim())));
+    }
+    if let Some(priority) = &priority {
+        filters.push_str(&format!(" -p {}", priority));
+    }
+    let journalctl_cmd = format!("journalctl {} -n {} --no-pager 2>&1", filters, lines);
+    let journalctl_result = client.execute_with_status(&journalctl_cmd);
+
+    // If journalctl succeeds and returns content, use it
+    if let Ok((output, exit_status)) = journalctl_result {
+        if exit_status == 0
+            && !output.is_empty()
+            && !output.contains("No entries")
+            && !output.contains("cannot open")
+        {
+            let boots_cmd = format!(
+                "journalctl {} -n {} --no-pager -o json --output-fields=_BOOT_ID 2>/dev/null | sed -n '1p;$p'",
+                filters, lines
+            );
+            let boot_range = client
+                .execute_command(&boots_cmd)
+                .ok()
+                .and_then(|out| parse_boot_range(&out));
+            return Ok(ServiceLogResult {
+                source: ServiceLogSource::Journal,
+                lines: split_log_lines(&output),
+                boot_range,
+            });
+        }
+        // A bad --since is the caller's mistake, not a reason to fall back to files
+        if exit_status != 0 && output.contains("Failed to parse") {
+            return Err(output.trim().to_string());
+        }
+    }
+
+    // Files can't be filtered by time or priority, so a filtered query that the journal
+    // had nothing for is empty rather than an unfiltered file passed off as the answer
+    if filtered {
+        return Ok(ServiceLogResult {
+            source: ServiceLogSource::Journal,
+            lines: Vec::new(),
+            boot_range: None,
+        });
+    }
+
+    // Fallback: Try common log file locations. The journal already spans rotations;
+    // `rotation` pulls in that many rotated files here.
+    let log_paths = vec![
+        format!("/var/log/{}.log", service_name),
+        format!("/var/log/{}.log", service_name.replace('-', "")),
+        format!("/var/log/{}/{}.log", service_name, service_name),
+        format!("/var/log/syslog"),
+        format!("/var/log/messages"),
+    ];
+
+    for log_path in log_paths {
+        if !is_remote_file(client, &log_path) {
+            continue;
+        }
+        let tail_cmd = log_tail_command(&log_path, lines, rotation);
+        if let Ok(output) = client.execute_command(&tail_cmd) {
+            if !output.is_empty() {
+                return Ok(ServiceLogResult {
+                    source: ServiceLogSource::File(log_path),
+                    lines: split_log_lines(&output),
+                    boot_range: None,
+                });
+            }
+        }
+    }
+
+    // Try to find service-specific log directory
+    let find_cmd = format!(
+        "find /var/log -name '*{}*' -type f 2>/dev/null | head -5",
+        service_name
+    );
+    if let Ok(found_files) = client.execute_command(&find_cmd) {
+        for file in found_files.lines() {
+            if !file.is_empty() {
+                let tail_cmd = log_tail_command(file, lines, rotation);
+                if let Ok(output) = client.execute_command(&tail_cmd) {
+                    if !output.is_empty() {
+                        return Ok(ServiceLogResult {
+                            source: ServiceLogSource::File(file.to_string()),
+                            lines: split_log_lines(&output),
+                            boot_range: None,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(ServiceLogResult {
+        source: ServiceLogSource::NotFound,
+        lines: Vec::new(),
+        boot_range: None,
+    })
+}
+
+/// Most rotated generations a log read will go back through
+const MAX_LOG_ROTATIONS: u32 = 30;
+
+/// Shell command printing the last `lines` lines of `path`. With `rotation`, up to that
+/// many logrotate generations (`path.1`, `path.2.gz`, ...) are read first, oldest to
+/// newest, so the output stays in chronological order across the rotation boundary.
+fn log_tail_command(path: &str, lines: u32, rotation: Option<u32>) -> String {
+    let path = shell_quote(path);
+    let rotation = rotation.unwrap_or(0).min(MAX_LOG_ROTATIONS);
+    if rotation == 0 {
+        return format!("tail -n {} {} 2>&1", lines, path);
+    }
+    format!(
+        "{{ for n in $(seq {} -1 1); do f={}.$n; \
+           if [ -f \"$f\" ]; then cat \"$f\"; elif [ -f \"$f.gz\" ]; then zcat \"$f.gz\"; fi; \
+         done; cat {}; }} 2>/dev/null | tail -n {}",
+        rotation, path, path, lines
+    )
+}
+
+#[tauri::command]
+pub async fn remote_stat(
+    path: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Option<RemoteStat>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    stat_remote(ssh_client.as_ref(), &path)
+}
+
+/// Metadata for `path` (following symlinks), `None` when nothing exists there. The
+/// existence check is a shell test and not stat's error text, so it holds in any locale.
+fn stat_remote(client: &SshClient, path: &str) -> Result<Option<RemoteStat>, String> {
+    let quoted = shell_quote(path);
+    let command = format!(
+        "[ -e {0} ] || exit 3; LC_ALL=C stat -L --format='%s %a %u %g %Y %F' -- {0} 2>&1",
+        quoted
+    );
+    let (output, status) = client
+        .execute_with_status(&command)
+        .map_err(|e| e.message)?;
+    match status {
+        0 => parse_remote_stat(&output)
+            .map(Some)
+            .ok_or_else(|| format!("Unexpected stat output: {}", output.trim())),
+        3 => Ok(None),
+        _ => Err(output.trim().to_string()),
+    }
+}
+
+/// Whether `path` exists and is a regular file; errors count as no
+fn is_remote_file(client: &SshClient, path: &str) -> bool {
+    matches!(stat_remote(client, path), Ok(Some(stat)) if !stat.is_dir)
+}
+
+/// One line of `stat --format='%s %a %u %g %Y %F'`; the file type is last because it
+/// can contain spaces ("regular empty file")
+fn parse_remote_stat(output: &str) -> Option<RemoteStat> {
+    let mut fields = output.trim().splitn(6, ' ');
+    let size = fields.next()?.parse().ok()?;
+    let mode = fields.next()?.to_string();
+    let uid = fields.next()?.parse().ok()?;
+    let gid = fields.next()?.parse().ok()?;
+    let modified = fields.next()?.parse().ok()?;
+    let file_type = fields.next()?;
+    Some(RemoteStat {
+        size,
+        mode,
+        uid,
+        gid,
+        is_dir: file_type == "directory",
+        modified,
+    })
+}
+
+fn split_log_lines(output: &str) -> Vec<String> {
+    output.lines().map(String::from).collect()
+}
+
+/// journalctl `-p` accepts a level name or number, or a `from..to` range of them
+fn is_valid_journal_priority(priority: &str) -> bool {
+    const LEVELS: &[&str] = &[
+        "emerg", "alert", "crit", "err", "warning", "notice", "info", "debug",
+    ];
+    let is_level = |level: &str| {
+        LEVELS.contains(&level) || (level.len() == 1 && matches!(level.as_bytes()[0], b'0'..=b'7'))
+    };
+    match priority.split_once("..") {
+        Some((from, to)) => is_level(from) && is_level(to),
+        None => is_level(priority),
+    }
+}
+
+/// Boot ids of the first and last entry, from `journalctl -o json` output trimmed to
+/// those two lines
+fn parse_boot_range(output: &str) -> Option<JournalBootRange> {
+    let boot_ids: Vec<String> = output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<JsonValue>(line).ok())
+        .filter_map(|entry| entry.get("_BOOT_ID")?.as_str().map(String::from))
+        .collect();
+
+    Some(JournalBootRange {
+        first_boot_id: boot_ids.first()?.clone(),
+        last_boot_id: boot_ids.last()?.clone(),
+    })
+}
+
+/// journalctl prints sizes like `1.2G` or `56.0M` with 1024-based units
+fn parse_journal_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    match size.strip_suffix(['K', 'M', 'G', 'T', 'P']) {
+        Some(number) => parse_bytes(&format!("{}{}iB", number, &size[number.len()..])),
+        None => parse_bytes(size),
+    }
+}
+
+const JOURNAL_USAGE_COMMAND: &str = "echo '===USAGE==='; sudo journalctl --disk-usage 2>&1; \
+     echo '===OLDEST==='; sudo journalctl -q --no-pager -o short-iso 2>/dev/null | head -n 1; \
+     echo '===VERIFY==='; sudo journalctl --verify -q 2>&1";
+
+fn parse_journal_disk_usage(
+    sections: &HashMap<String, String>,
+) -> Result<JournalDiskUsage, String> {
+    let usage = sections.get("USAGE").map(|s| s.trim()).unwrap_or_default();
+    // "Archived and active journals take up 1.2G in the file system."
+    let bytes = usage
+        .split_once(" take up ")
+        .and_then(|(_, rest)| rest.split_whitespace().next())
+        .and_then(parse_journal_size)
+        .ok_or_else(|| format!("Failed to read journal disk usage: {}", usage))?;
+
+    let oldest_entry = sections
+        .get("OLDEST")
+        .and_then(|s| s.split_whitespace().next())
+        .map(String::from);
+
+    // Failures look like "File corruption detected at /var/log/journal/.../system.journal:..."
+    // followed by "FAIL: /var/log/journal/.../system.journal (Bad message)"
+    let corrupt_files = sections
+        .get("VERIFY")
+        .map(|s| {
+            s.lines()
+                .filter_map(|line| line.trim().strip_prefix("FAIL: "))
+                .map(|rest| rest.split(" (").next().unwrap_or(rest).to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(JournalDiskUsage {
+        bytes,
+        oldest_entry,
+        corrupt_files,
+    })
+}
+
+/// Sum of "Vacuuming done, freed 8.0M of archived journals from ..." lines (one per
+/// journal directory) and the files named in "Deleted archived journal ..." lines
+fn parse_journal_vacuum(output: &str) -> JournalVacuumResult {
+    let mut freed_bytes = 0;
+    let mut deleted_files = Vec::new();
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("Vacuuming done, freed ") {
+            freed_bytes += rest
+                .split_whitespace()
+                .next()
+                .and_then(parse_journal_size)
+                .unwrap_or(0);
+        } else if let Some(rest) = line.strip_prefix("Deleted archived journal ") {
+            deleted_files.push(rest.split(" (").next().unwrap_or(rest).to_string());
+        }
+    }
+    JournalVacuumResult {
+        freed_bytes,
+        deleted_files,
+        output: output.to_string(),
+    }
+}
+
+/// Disk space taken by the systemd journal, how far back it goes and whether
+/// any of its files are corrupt
+#[tauri::command]
+pub async fn get_journal_disk_usage(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<JournalDiskUsage, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let output = client
+        .execute_command(JOURNAL_USAGE_COMMAND)
+        .map_err(|e| e.message)?;
+    parse_journal_disk_usage(&split_sections(&output))
+}
+
+/// Delete archived journal files until `keep` is satisfied. The active journal
+/// files are never removed, so usage may stay above the requested size.
+#[tauri::command]
+pub async fn vacuum_journal(
+    keep: VacuumSpec,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<JournalVacuumResult, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let flag = match keep {
+        VacuumSpec::Size { max_bytes } => format!("--vacuum-size={}", max_bytes),
+        VacuumSpec::Time { max_age_days: 0 } => {
+            return Err("Keep at least one day of journal entries".to_string())
+        }
+        VacuumSpec::Time { max_age_days } => format!("--vacuum-time={}d", max_age_days),
+    };
+    let (output, exit_status) = client
+        .execute_with_status(&format!("sudo journalctl {} 2>&1", flag))
+        .map_err(|e| e.message)?;
+    if exit_status != 0 {
+        return Err(format!("Failed to vacuum journal: {}", output.trim()));
+    }
+    Ok(parse_journal_vacuum(&output))
+}
+
+#[tauri::command]
+pub async fn execute_command(
+    command: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+    let result = client.execute_bounded(&command).map_err(|e| e.message)?;
+    if !result.truncated {
+        return Ok(result.output);
+    }
+    Ok(format!(
+        "{}\n[output truncated after {}]",
+        result.output,
+        format_bytes(client.max_output_bytes())
+    ))
+}
+
+/// Run `command` on several servers at once. A server that isn't connected or fails gets
+/// an error entry instead of failing the whole batch.
+#[tauri::command]
+pub async fn execute_command_multi(
+    server_ids: Vec<String>,
+    command: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<MultiResult>, String> {
+    let mut tasks = Vec::new();
+    for server_id in server_ids {
+        if tasks.iter().any(|(id, _)| *id == server_id) {
+            continue;
+        }
+        let client = state.writable_client(Some(&server_id)).await;
+        let command = command.clone();
+        let task = tokio::task::spawn_blocking(move || {
+            client.and_then(|client| client.execute_bounded(&command).map_err(|e| e.message))
+        });
+        tasks.push((server_id, task));
+    }
+
+    let mut results = Vec::with_capacity(tasks.len());
+    for (server_id, task) in tasks {
+        let result = task
+            .await
+            .unwrap_or_else(|e| Err(format!("Command task failed: {}", e)));
+        results.push(multi_result(server_id, result));
+    }
+    Ok(results)
+}
+
+fn multi_result(server_id: String, result: Result<CommandOutput, String>) -> MultiResult {
+    match result {
+        Ok(result) => MultiResult {
+            server_id,
+            output: Some(result.output),
+            exit_status: Some(result.exit_status),
+            truncated: result.truncated,
+            error: None,
+        },
+        Err(error) => MultiResult {
+            server_id,
+            output: None,
+            exit_status: None,
+            truncated: false,
+            error: Some(error),
+        },
+    }
+}
+
+#[tauri::command]
+pub async fn save_server_profile(
+    profile: ServerProfile,
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    // Save to in-memory state
+    let mut profiles = state.server_profiles.lock().await;
+    profiles.insert(profile.id.clone(), profile.clone());
+
+    // Save to persistent storage
+    let mut saved_profile: SavedServerProfile = SavedServerProfile::from(profile);
+    if let Ok(store) = app.store(STORE_FILENAME) {
+        let mut profiles_map = profiles_from_json(store.get(PROFILES_KEY));
+
+        // Preserve existing metadata if profile already exists
+        if let Some(existing) = profiles_map.get(&saved_profile.id) {
+            let mut updated = saved_profile;
+            updated.created_at = existing.created_at;
+            updated.last_connected = existing.last_connected;
+            updated.connect_on_startup = existing.connect_on_startup;
+            saved_profile = updated;
+        }
+
+        profiles_map.insert(saved_profile.id.clone(), saved_profile);
+        store.set(PROFILES_KEY, profiles_to_json(&profiles_map));
+        store
+            .save()
+            .map_err(|e| format!("Failed to save profile: {}", e))?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_server_profiles(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<Vec<SavedServerProfile>, String> {
+    // Try to load from persistent storage first
+    if let Ok(store) = app.store(STORE_FILENAME) {
+        let profiles_map = profiles_from_json(store.get(PROFILES_KEY));
+        if !profiles_map.is_empty() {
+            let profiles: Vec<SavedServerProfile> = profiles_map.values().cloned().collect();
+            return Ok(profiles);
+        }
+    }
+
+    // Fallback to in-memory profiles
+    let profiles = state.server_profiles.lock().await;
+    Ok(profiles
+        .values()
+        .map(|p| SavedServerProfile::from(p.clone()))
+        .collect())
+}
+
+#[tauri::command]
+pub async fn delete_server_profile(
+    profile_id: String,
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    // Remove from in-memory state
+    let mut profiles = state.server_profiles.lock().await;
+    profiles.remove(&profile_id);
+
+    // Remove from persistent storage
+    if let Ok(store) = app.store(STORE_FILENAME) {
+        let mut profiles_map = profiles_from_json(store.get(PROFILES_KEY));
+        if profiles_map.remove(&profile_id).is_some() {
+            store.set(PROFILES_KEY, profiles_to_json(&profiles_map));
+            store
+                .save()
+                .map_err(|e| format!("Failed to delete profile: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Store key that turns off connecting `connect_on_startup` profiles at launch
+const SKIP_AUTOCONNECT_KEY: &str = "skip_autoconnect";
+
+/// Connect every profile marked `connect_on_startup`, least recently used first so the
+/// most recent one ends up active. Each outcome is emitted as `startup-connect-result`
+/// and kept for `get_startup_connect_results`, since the UI may not be listening yet.
+/// Setting `DPANEL_SKIP_AUTOCONNECT`, or `set_skip_autoconnect(true)`, turns this off.
+pub async fn autoconnect_on_startup(app: tauri::AppHandle) {
+    if std::env::var_os("DPANEL_SKIP_AUTOCONNECT").is_some() {
+        log::info!("Autoconnect skipped: DPANEL_SKIP_AUTOCONNECT is set");
+        return;
+    }
+    let Ok(store) = app.store(STORE_FILENAME) else {
+        return;
+    };
+    if store.get(SKIP_AUTOCONNECT_KEY).and_then(|v| v.as_bool()) == Some(true) {
+        log::info!("Autoconnect skipped: disabled in settings");
+        return;
+    }
+    let mut profiles: Vec<SavedServerProfile> = profiles_from_json(store.get(PROFILES_KEY))
+        .into_values()
+        .filter(|p| p.connect_on_startup)
+        .collect();
+    profiles.sort_by_key(|p| p.last_connected.unwrap_or(0));
+
+    let state = app.state::<AppState>();
+    for profile in profiles {
+        let (success, message) = match connect_profile(&state, &app, profile.clone().into()).await {
+            Ok(result) => (result.success, result.message),
+            Err(error) => (false, error),
+        };
+        if !success {
+            log::warn!("Autoconnect to '{}' failed: {}", profile.name, message);
+        }
+        let result = StartupConnectResult {
+            profile,
+            success,
+            message,
+        };
+        state.startup_results.lock().await.push(result.clone());
+        let _ = app.emit("startup-connect-result", result);
+    }
+}
+
+#[tauri::command]
+pub async fn get_startup_connect_results(
+    state: State<'_, AppState>,
+) -> Result<Vec<StartupConnectResult>, String> {
+    Ok(state.startup_results.lock().await.clone())
+}
+
+#[tauri::command]
+pub async fn get_skip_autoconnect(app: tauri::AppHandle) -> Result<bool, String> {
+    let store = app.store(STORE_FILENAME).map_err(|e| e.to_string())?;
+    Ok(store
+        .get(SKIP_AUTOCONNECT_KEY)
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false))
+}
+
+#[tauri::command]
+pub async fn set_skip_autoconnect(skip: bool, app: tauri::AppHandle) -> Result<(), String> {
+    let store = app.store(STORE_FILENAME).map_err(|e| e.to_string())?;
+    store.set(SKIP_AUTOCONNECT_KEY, JsonValue::Bool(skip));
+    store
+        .save()
+        .map_err(|e| format!("Failed to save settings: {}", e))
+}
+
+#[tauri::command]
+pub async fn update_server_profile_metadata(
+    profile_id: String,
+    connect_on_startup: Option<bool>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    if let Ok(store) = app.store(STORE_FILENAME) {
+        let mut profiles_map = profiles_from_json(store.get(PROFILES_KEY));
+        if let Some(profile) = profiles_map.get_mut(&profile_id) {
+            if let Some(value) = connect_on_startup {
+                profile.connect_on_startup = value;
+            }
+            store.set(PROFILES_KEY, profiles_to_json(&profiles_map));
+            store
+                .save()
+                .map_err(|e| format!("Failed to update profile: {}", e))?;
+            return Ok(());
+        }
+    }
+    Err("Profile not found".to_string())
+}
+
+#[tauri::command]
+pub async fn get_ufw_status(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<UfwStatus, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    // Get UFW status verbose
+    let status_output = client
+        .execute_command("sudo ufw status verbose 2>&1")
+        .map_err(|e| e.message)?;
+
+    let mut active = false;
+    let mut logging = "off".to_string();
+    let mut default = "deny (incoming), allow (outgoing)".to_string();
+
+    for line in status_output.lines() {
+        let line = line.trim();
+
+        if line.starts_with("Status:") {
+            active = line.contains("active");
+        } else if line.starts_with("Logging:") {
+            logging = line.replace("Logging:", "").trim().to_string();
+        } else if line.starts_with("Default:") {
+            default = line.replace("Default:", "").trim().to_string();
+        }
+    }
+    let rules = parse_ufw_rules(&status_output);
+
+    Ok(UfwStatus {
+        active,
+        logging,
+        default,
+        rules,
+    })
+}
+
+#[tauri::command]
+pub async fn get_ufw_stats(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<UfwStats, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let status_output = client
+        .execute_command("sudo ufw status numbered 2>&1")
+        .map_err(|e| e.message)?;
+
+    let mut total_rules = 0u32;
+    let mut allow_rules = 0u32;
+    let mut deny_rules = 0u32;
+    let mut limit_rules = 0u32;
+
+    for line in status_output.lines() {
+        let line = line.trim();
+        if line.contains("ALLOW") {
+            total_rules += 1;
+            allow_rules += 1;
+        } else if line.contains("DENY") {
+            total_rules += 1;
+            deny_rules += 1;
+        } else if line.contains("LIMIT") {
+            total_rules += 1;
+            limit_rules += 1;
+        }
+    }
+
+    Ok(UfwStats {
+        total_rules,
+        allow_rules,
+        deny_rules,
+        limit_rules,
+    })
+}
+
+#[tauri::command]
+pub async fn ufw_action(
+    action: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let command = match action.as_str() {
+        "enable" => "echo 'y' | sudo ufw enable",
+        "disable" => "sudo ufw disable",
+        "reset" => "echo 'y' | sudo ufw reset",
+        "reload" => "sudo ufw reload",
+        _ => return Err(format!("Unknown action: {}", action)),
+    };
+
+    run_action(
+        client,
+        command,
+        format!("Firewall {}", action_past_tense(&action)),
+        &format!("Failed to {} the firewall", action),
+    )
+}
+
+#[tauri::command]
+pub async fn ufw_add_rule(
+    rule_type: String,
+    port: Option<String>,
+    from_ip: Option<String>,
+    to_ip: Option<String>,
+    protocol: Option<String>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    let rule = build_ufw_rule(&rule_type, port, from_ip, to_ip, protocol)?;
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    run_action(
+        client,
+        &rule.command,
+        format!("Rule added: {}", rule.description),
+        "Failed to add the rule",
+    )
+}
+
+/// The command `ufw_add_rule` would run for these arguments, and what it means,
+/// without touching the server
+#[tauri::command]
+pub async fn preview_ufw_rule(
+    rule_type: String,
+    port: Option<String>,
+    from_ip: Option<String>,
+    to_ip: Option<String>,
+    protocol: Option<String>,
+) -> Result<UfwRulePreview, String> {
+    build_ufw_rule(&rule_type, port, from_ip, to_ip, protocol)
+}
+
+/// Validates rule arguments and builds the ufw command in its extended
+/// `<action> [proto P] from A to B [port N]` form
+fn build_ufw_rule(
+    rule_type: &str,
+    port: Option<String>,
+    from_ip: Option<String>,
+    to_ip: Option<String>,
+    protocol: Option<String>,
+) -> Result<UfwRulePreview, String> {
+    let action = match rule_type {
+        "allow" => "Allow",
+        "deny" => "Deny",
+        "reject" => "Reject",
+        "limit" => "Rate-limit",
+        _ => return Err(format!("Unknown rule type '{}'", rule_type)),
+    };
+    let non_empty = |value: Option<String>| {
+        value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let protocol = non_empty(protocol).filter(|p| p != "any");
+    if let Some(proto) = &protocol {
+        if proto != "tcp" && proto != "udp" {
+            return Err(format!("Invalid protocol '{}', expected tcp or udp", proto));
+        }
+    }
+    let port = non_empty(port);
+    if let Some(port) = &port {
+        if !is_valid_ufw_port(port) {
+            return Err(format!(
+                "Invalid port '{}', expected a port (1-65535), a range like 8000:8010 or a comma-separated list",
+                port
+            ));
+        }
+        // ufw only accepts ranges and lists for a single protocol
+        if protocol.is_none() && (port.contains(':') || port.contains(',')) {
+            return Err("Port ranges and lists need a protocol (tcp or udp)".to_string());
+        }
+    }
+    let address = |value: Option<String>, side: &str| -> Result<Option<String>, String> {
+        match non_empty(value).filter(|v| v != "any") {
+            Some(v) if !is_valid_cidr(&v) => Err(format!("Invalid {} address '{}'", side, v)),
+            other => Ok(other),
+        }
+    };
+    let from = address(from_ip, "source")?;
+    let to = address(to_ip, "destination")?;
+
+    let mut command = format!("sudo ufw {}", rule_type);
+    if let Some(proto) = &protocol {
+        command.push_str(&format!(" proto {}", proto));
+    }
+    command.push_str(&format!(" from {}", from.as_deref().unwrap_or("any")));
+    command.push_str(&format!(" to {}", to.as_deref().unwrap_or("any")));
+    if let Some(port) = &port {
+        command.push_str(&format!(" port {}", port));
+    }
+
+    let mut description = action.to_string();
+    if let Some(proto) = &protocol {
+        description.push_str(&format!(" {}", proto.to_uppercase()));
+    }
+    match &port {
+        Some(port) if port.contains(',') || port.contains(':') => description.push_str(&format!(
+            " ports {}",
+            port.replace(':', "-").replace(',', ", ")
+        )),
+        Some(port) => description.push_str(&format!(" port {}", port)),
+        None if protocol.is_some() => description.push_str(" traffic"),
+        None => description.push_str(" all traffic"),
+    }
+    match &from {
+        Some(from) => description.push_str(&format!(" from {}", from)),
+        None => description.push_str(" from anywhere"),
+    }
+    if let Some(to) = &to {
+        description.push_str(&format!(" to {}", to));
+    }
+
+    Ok(UfwRulePreview {
+        command,
+        description,
+    })
+}
+
+/// A port, a `low:high` range, or a comma-separated list of those (ufw allows 15)
+fn is_valid_ufw_port(port: &str) -> bool {
+    let is_port = |p: &str| {
+        !p.is_empty()
+            && p.chars().all(|c| c.is_ascii_digit())
+            && p.parse::<u16>().is_ok_and(|n| n > 0)
+    };
+    let entries: Vec<&str> = port.split(',').collect();
+    entries.len() <= 15
+        && entries.iter().all(|entry| match entry.split_once(':') {
+            Some((low, high)) => {
+                is_port(low)
+                    && is_port(high)
+                    && low.parse::<u16>().unwrap() < high.parse::<u16>().unwrap()
+            }
+            None => is_port(entry),
+        })
+}
+
+/// An IPv4 or IPv6 address with an optional prefix length
+fn is_valid_cidr(value: &str) -> bool {
+    let (address, prefix) = match value.split_once('/') {
+        Some((address, prefix)) => (address, Some(prefix)),
+        None => (value, None),
+    };
+    let max_prefix = match address.parse::<std::net::IpAddr>() {
+        Ok(std::net::IpAddr::V4(_)) => 32,
+        Ok(std::net::IpAddr::V6(_)) => 128,
+        Err(_) => return false,
+    };
+    match prefix {
+        Some(prefix) => {
+            prefix.chars().all(|c| c.is_ascii_digit())
+                && prefix.parse::<u8>().is_ok_and(|p| p <= max_prefix)
+        }
+        None => true,
+    }
+}
+
+/// Whether `address` falls inside `cidr`, a plain address counting as a full-length prefix.
+/// IPv4-mapped IPv6 addresses are compared as IPv4.
+fn cidr_contains(cidr: &str, address: &str) -> bool {
+    use std::net::IpAddr;
+    let (network, prefix) = match cidr.split_once('/') {
+        Some((network, prefix)) => (network, Some(prefix)),
+        None => (cidr, None),
+    };
+    let unmap = |ip: IpAddr| match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(IpAddr::V6(v6), IpAddr::V4),
+        v4 => v4,
+    };
+    let (Ok(network), Ok(address)) = (network.parse::<IpAddr>(), address.parse::<IpAddr>()) else {
+        return false;
+    };
+    let network = if prefix.is_none() {
+        unmap(network)
+    } else {
+        network
+    };
+    let (network, address, bits) = match (network, unmap(address)) {
+        (IpAddr::V4(n), IpAddr::V4(a)) => (u32::from(n) as u128, u32::from(a) as u128, 32),
+        (IpAddr::V6(n), IpAddr::V6(a)) => (u128::from(n), u128::from(a), 128),
+        _ => return false,
+    };
+    let prefix = match prefix.map(str::parse::<u32>) {
+        None => bits,
+        Some(Ok(prefix)) if prefix <= bits => prefix,
+        Some(_) => return false,
+    };
+    let mask = u128::MAX.checked_shl(bits - prefix).unwrap_or(0);
+    (network ^ address) & mask == 0
+}
+
+#[tauri::command]
+pub async fn ufw_delete_rule(
+    rule_number: u32,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let command = format!("echo 'y' | sudo ufw delete {}", rule_number);
+    run_action(
+        client,
+        &command,
+        format!("Rule {} deleted", rule_number),
+        &format!("Failed to delete rule {}", rule_number),
+    )
+}
+
+#[tauri::command]
+pub async fn ufw_set_default(
+    direction: String,
+    policy: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let command = format!("sudo ufw default {} {}", policy, direction);
+    client.execute_command(&command).map_err(|e| e.message)
+}
+
+#[tauri::command]
+pub async fn ufw_set_logging(
+    level: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let command = format!("sudo ufw logging {}", level);
+    client.execute_command(&command).map_err(|e| e.message)
+}
+
+#[tauri::command]
+pub async fn get_container_details(
+    container_name: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ContainerDetails, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    // Get container inspect data
+    let inspect_output = client
+        .execute_command(&format!("docker inspect {}", container_name))
+        .map_err(|e| e.message)?;
+
+    // Parse JSON manually (simplified parsing)
+    let inspect_value: serde_json::Value = serde_json::from_str(&inspect_output)
+        .map_err(|e| format!("Failed to parse inspect JSON: {}", e))?;
+
+    let container_data = inspect_value
+        .as_array()
+        .and_then(|arr| arr.first())
+        .ok_or("No container data found")?;
+
+    let config = container_data.get("Config").ok_or("No config")?;
+    let host_config = container_data.get("HostConfig").ok_or("No host config")?;
+    let network_settings = container_data
+        .get("NetworkSettings")
+        .ok_or("No network settings")?;
+    let state_data = container_data.get("State").ok_or("No state")?;
+
+    // Extract environment variables, with secret values redacted
+    let redactor = state.redactor.lock().await.clone();
+    let env_vars: Vec<String> = config
+        .get("Env")
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str())
+                .map(|e| redactor.redact_env(e))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Extract ports
+    let mut ports: Vec<PortMapping> = Vec::new();
+    if let Some(port_bindings) = host_config.get("PortBindings").and_then(|v| v.as_object()) {
+        for (container_port, bindings) in port_bindings {
+            if let Some(binding_arr) = bindings.as_array() {
+                for binding in binding_arr {
+                    if let Some(obj) = binding.as_object() {
+                        ports.push(PortMapping {
+                            host_ip: obj
+                                .get("HostIp")
+                                .and_then(|v| v.as_str())
+                                .unwrap_or("0.0.0.0")
+                                .to_string(),
+                            host_port: obj
+                                .get("HostPort")
+                                .and_then(|v| v.as_str())
+                                .unwrap_or("")
+                                .to_string(),
+                            container_port: container_port
+                                .split('/')
+                                .next()
+                                .unwrap_or("")
+                                .to_string(),
+                            protocol: container_port
+                                .split('/')
+                                .nth(1)
+                                .unwrap_or("tcp")
+                                .to_string(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    // Extract networks
+    let networks: Vec<String> = network_settings
+        .get("Networks")
+        .and_then(|v| v.as_object())
+        .map(|obj| obj.keys().cloned().collect())
+        .unwrap_or_default();
+
+    // Extract volumes
+    let mut volumes: Vec<VolumeMount> = Vec::new();
+    if let Some(mounts) = container_data.get("Mounts").and_then(|v| v.as_array()) {
+        for mount in mounts {
+            volumes.push(VolumeMount {
+                source: mount
+                    .get("Source")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                destination: mount
+                    .get("Destination")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                mode: mount
+                    .get("Mode")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+            });
+        }
+    }
+
+    // Extract labels
+    let mut labels: Vec<Label> = Vec::new();
+    if let Some(labels_obj) = config.get("Labels").and_then(|v| v.as_object()) {
+        for (key, value) in labels_obj {
+            labels.push(Label {
+                key: key.clone(),
+                value: value.as_str().unwrap_or("").to_string(),
+            });
+        }
+    }
+
+    let started_at = state_data
+        .get("StartedAt")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+
+    Ok(ContainerDetails {
+        id: container_data
+            .get("Id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string()[..12]
+            .to_string(),
+        name: container_data
+            .get("Name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .trim_start_matches('/')
+            .to_string(),
+        image: container_data
+            .get("Image")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        state: state_data
+            .get("Status")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        status: container_data
+            .get("State")
+            .and_then(|v| v.get("Status"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        created: container_data
+            .get("Created")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        started_at,
+        env_vars,
+        ports,
+        networks,
+        volumes,
+        labels,
+        command: config
+            .get("Cmd")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .unwrap_or_default(),
+        working_dir: config
+            .get("WorkingDir")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        user: config
+            .get("User")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        restart_policy: host_config
+            .get("RestartPolicy")
+            .and_then(|v| v.get("Name"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("no")
+            .to_string(),
+        memory_limit: host_config
+            .get("Memory")
+            .and_then(|v| v.as_u64())
+            .map(|m| {
+                if m > 0 {
+                    format_bytes(m)
+                } else {
+                    "Unlimited".to_string()
+                }
+            })
+            .unwrap_or("Unlimited".to_string()),
+        cpu_limit: host_config
+            .get("NanoCpus")
+            .and_then(|v| v.as_u64())
+            .map(|c| {
+                if c > 0 {
+                    format!("{:.2} CPUs", c as f64 / 1_000_000_000.0)
+                } else {
+                    "Unlimited".to_string()
+                }
+            })
+            .unwrap_or("Unlimited".to_string()),
+    })
+}
+
+#[tauri::command]
+pub async fn get_docker_volumes(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<DockerVolume>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let output = client
+        .execute_command(
+            "docker volume ls --format '{{.Name}}|{{.Driver}}|{{.Mountpoint}}|{{.Scope}}'",
+        )
+        .map_err(|e| e.message)?;
+
+    let mut volumes: Vec<DockerVolume> = Vec::new();
+    for line in output.lines() {
+        let parts: Vec<&str> = line.split('|').collect();
+        if parts.len() >= 4 {
+            volumes.push(DockerVolume {
+                name: parts[0].to_string(),
+                driver: parts[1].to_string(),
+                mountpoint: parts[2].to_string(),
+                scope: parts[3].to_string(),
+                labels: Vec::new(),
+            });
+        }
+    }
+    Ok(volumes)
+}
+
+#[tauri::command]
+pub async fn get_docker_networks(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<DockerNetwork>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let output = client
+        .execute_command("docker network ls --format '{{.ID}}|{{.Name}}|{{.Driver}}|{{.Scope}}'")
+        .map_err(|e| e.message)?;
+
+    let mut networks: Vec<DockerNetwork> = Vec::new();
+    for line in output.lines() {
+        let parts: Vec<&str> = line.split('|').collect();
+        if parts.len() >= 4 {
+            networks.push(DockerNetwork {
+                id: parts[0].to_string(),
+                name: parts[1].to_string(),
+                driver: parts[2].to_string(),
+                scope: parts[3].to_string(),
+                subnet: None,
+                gateway: None,
+                containers: Vec::new(),
+            });
+        }
+    }
+    Ok(networks)
+}
+
+/// Printed by `docker secret|config` commands on hosts that aren't Swarm managers
+const NOT_SWARM_MANAGER: &str = "not a swarm manager";
+
+/// The id, name and creation time of a Swarm secret or config
+type SwarmObjectRow = (String, String, String);
+
+/// Rows of `docker secret ls` or `docker config ls`, `None` when the host isn't a Swarm
+/// manager
+fn list_swarm_objects(
+    client: &SshClient,
+    kind: &str,
+) -> Result<Option<Vec<SwarmObjectRow>>, String> {
+    let (output, status) = client
+        .execute_with_status(&format!(
+            "docker {} ls --format '{{{{.ID}}}}|{{{{.Name}}}}|{{{{.CreatedAt}}}}' 2>&1",
+            kind
+        ))
+        .map_err(|e| e.message)?;
+    if status != 0 {
+        if output.contains(NOT_SWARM_MANAGER) {
+            return Ok(None);
+        }
+        return Err(format!(
+            "Failed to list Docker {}s: {}",
+            kind,
+            output.trim()
+        ));
+    }
+    Ok(Some(parse_swarm_objects(&output)))
+}
+
+fn parse_swarm_objects(output: &str) -> Vec<SwarmObjectRow> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '|');
+            Some((
+                parts.next()?.to_string(),
+                parts.next()?.to_string(),
+                parts.next()?.trim().to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Swarm secrets, or `None` when the host isn't a Swarm manager
+#[tauri::command]
+pub async fn get_docker_secrets(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Option<Vec<DockerSecret>>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    Ok(
+        list_swarm_objects(ssh_client.as_ref(), "secret")?.map(|rows| {
+            rows.into_iter()
+                .map(|(id, name, created)| DockerSecret { id, name, created })
+                .collect()
+        }),
+    )
+}
+
+/// Swarm configs, or `None` when the host isn't a Swarm manager
+#[tauri::command]
+pub async fn get_docker_configs(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Option<Vec<DockerConfig>>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    Ok(
+        list_swarm_objects(ssh_client.as_ref(), "config")?.map(|rows| {
+            rows.into_iter()
+                .map(|(id, name, created)| DockerConfig { id, name, created })
+                .collect()
+        }),
+    )
+}
+
+fn validate_swarm_object_name(kind: &str, name: &str) -> Result<(), String> {
+    if name.len() > 64 || !is_valid_container_name(name) {
+        return Err(format!("Invalid {} name '{}'", kind, name));
+    }
+    Ok(())
+}
+
+/// Create a Swarm secret or config from `data`. It's sent on stdin so it never shows up
+/// in a process listing, which would include the command line of the remote shell.
+fn create_swarm_object(
+    client: &SshClient,
+    kind: &str,
+    name: &str,
+    data: &str,
+) -> Result<ActionResult, String> {
+    validate_swarm_object_name(kind, name)?;
+    if data.is_empty() {
+        return Err(format!("The {} can't be empty", kind));
+    }
+    let (output, status) = client
+        .execute_with_input(
+            &format!("docker {} create {} - 2>&1", kind, shell_quote(name)),
+            data,
+        )
+        .map_err(|e| e.message)?;
+    Ok(action_result(
+        status,
+        output,
+        format!("Created {} '{}'", kind, name),
+        &format!("Failed to create {} '{}'", kind, name),
+    ))
+}
+
+fn remove_swarm_object(client: &SshClient, kind: &str, name: &str) -> Result<ActionResult, String> {
+    validate_swarm_object_name(kind, name)?;
+    run_action(
+        client,
+        &format!("docker {} rm {}", kind, shell_quote(name)),
+        format!("Removed {} '{}'", kind, name),
+        &format!("Failed to remove {} '{}'", kind, name),
+    )
+}
+
+#[tauri::command]
+pub async fn create_docker_secret(
+    name: String,
+    value: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    create_swarm_object(ssh_client.as_ref(), "secret", &name, &value)
+}
+
+#[tauri::command]
+pub async fn remove_docker_secret(
+    name: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    remove_swarm_object(ssh_client.as_ref(), "secret", &name)
+}
+
+#[tauri::command]
+pub async fn create_docker_config(
+    name: String,
+    content: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    create_swarm_object(ssh_client.as_ref(), "config", &name, &content)
+}
+
+#[tauri::command]
+pub async fn remove_docker_config(
+    name: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    remove_swarm_object(ssh_client.as_ref(), "config", &name)
+}
+
+#[tauri::command]
+pub async fn get_docker_images(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<DockerImage>, String> {
+    let (id, ssh_client) = state.connection(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+    if let Some(images) = state.cache.get::<Vec<DockerImage>>(&id, "images").await {
+        return Ok(images);
+    }
+
+    let output = client
+        .execute_command("docker images --format '{{.ID}}|{{.Repository}}|{{.Tag}}|{{.Size}}|{{.CreatedAt}}' --no-trunc")
+        .map_err(|e| e.message)?;
+
+    let mut images: Vec<DockerImage> = Vec::new();
+    for line in output.lines() {
+        let parts: Vec<&str> = line.split('|').collect();
+        if parts.len() >= 5 {
+            images.push(DockerImage {
+                id: parts[0].to_string(),
+                repository: parts[1].to_string(),
+                tag: parts[2].to_string(),
+                size: parse_bytes(parts[3]).unwrap_or(0),
+                created: parts[4].to_string(),
+                architecture: "amd64".to_string(),
+            });
+        }
+    }
+    state.cache.put(&id, "images", images.clone()).await;
+    Ok(images)
+}
+
+#[tauri::command]
+pub async fn get_container_env(
+    container_name: String,
+    show_secrets: bool,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let output = client
+        .execute_command(&format!(
+            "docker inspect --format '{{{{json .Config.Env}}}}' {}",
+            container_name
+        ))
+        .map_err(|e| e.message)?;
+
+    let env_vars: Vec<String> = serde_json::from_str(&output).unwrap_or_default();
+
+    if show_secrets {
+        Ok(env_vars)
+    } else {
+        let redactor = state.redactor.lock().await;
+        Ok(env_vars.iter().map(|e| redactor.redact_env(e)).collect())
+    }
+}
+
+/// Name patterns that mark environment variables as secret; values that look like
+/// credentials (URLs with passwords, JWTs, random tokens) are redacted regardless
+#[tauri::command]
+pub async fn get_redaction_patterns(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    Ok(state.redactor.lock().await.patterns().to_vec())
+}
+
+/// Set the name patterns redacted on top of the defaults and keep them for later
+/// sessions. An empty list leaves just the defaults.
+#[tauri::command]
+pub async fn set_redaction_patterns(
+    patterns: Vec<String>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let redactor = Redactor::new(patterns);
+    let store = app.store(STORE_FILENAME).map_err(|e| e.to_string())?;
+    store.set(
+        REDACTION_PATTERNS_KEY,
+        serde_json::json!(redactor.patterns()),
+    );
+    store
+        .save()
+        .map_err(|e| format!("Failed to save settings: {}", e))?;
+    let patterns = redactor.patterns().to_vec();
+    *state.redactor.lock().await = redactor;
+    Ok(patterns)
+}
+
+const REDACTION_PATTERNS_KEY: &str = "redaction_patterns";
+
+/// Load the patterns saved by `set_redaction_patterns`, if any
+pub async fn restore_redaction_patterns(app: tauri::AppHandle) {
+    let Ok(store) = app.store(STORE_FILENAME) else {
+        return;
+    };
+    let Some(patterns) = store
+        .get(REDACTION_PATTERNS_KEY)
+        .and_then(|v| serde_json::from_value::<Vec<String>>(v).ok())
+    else {
+        return;
+    };
+    *app.state::<AppState>().redactor.lock().await = Redactor::new(patterns);
+}
+
+/// `KEY=value` entries of a NUL-separated `/proc/<pid>/environ`
+fn parse_environ(raw: &str, redactor: Option<&Redactor>) -> Vec<String> {
+    raw.split('\0')
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match redactor {
+            Some(redactor) => redactor.redact_env(entry),
+            None => entry.to_string(),
+        })
+        .collect()
+}
+
+/// Environment a process was started with, e.g. to see what a systemd service picked up.
+/// Secrets are redacted like in `get_container_env` unless `show_secrets` is set.
+#[tauri::command]
+pub async fn get_process_environ(
+    pid: u32,
+    show_secrets: bool,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    if pid == 0 {
+        return Err("Invalid pid 0".to_string());
+    }
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let (output, exit_status) = ssh_client
+        .execute_with_status(&proc_read_command(
+            pid,
+            &format!("cat /proc/{}/environ", pid),
+        ))
+        .map_err(|e| e.message)?;
+    match exit_status {
+        0 => {
+            let redactor = state.redactor.lock().await;
+            Ok(parse_environ(
+                &output,
+                (!show_secrets).then_some(&*redactor),
+            ))
+        }
+        status => Err(proc_read_error(pid, status, "environment")),
+    }
+}
+
+/// Run `command` (which reads from /proc/`pid`) as the connecting user, then through
+/// `sudo -n` if that fails. Exits 3 when there's no such process and 4 when it belongs to
+/// another user and sudo needs a password.
+fn proc_read_command(pid: u32, command: &str) -> String {
+    format!(
+        "[ -d /proc/{0} ] || exit 3; \
+         sh -c {1} 2>/dev/null && exit 0; \
+         sudo -n sh -c {1} 2>/dev/null && exit 0; exit 4",
+        pid,
+        shell_quote(command)
+    )
+}
+
+fn proc_read_error(pid: u32, exit_status: i32, what: &str) -> String {
+    match exit_status {
+        3 => format!("No process with pid {}", pid),
+        4 => format!(
+            "Permission denied reading the {} of process {}; \
+             it belongs to another user and passwordless sudo isn't available",
+            what, pid
+        ),
+        _ => format!("Failed to read the {} of process {}", what, pid),
+    }
+}
+
+/// Resource limits the process runs with, e.g. to compare "Max open files" against
+/// `get_process_open_files`
+#[tauri::command]
+pub async fn get_process_limits(
+    pid: u32,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ResourceLimit>, String> {
+    if pid == 0 {
+        return Err("Invalid pid 0".to_string());
+    }
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let (output, exit_status) = ssh_client
+        .execute_with_status(&proc_read_command(
+            pid,
+            &format!("cat /proc/{}/limits", pid),
+        ))
+        .map_err(|e| e.message)?;
+    match exit_status {
+        0 => Ok(parse_proc_limits(&output)),
+        status => Err(proc_read_error(pid, status, "limits")),
+    }
+}
+
+/// Descriptors shown by `get_process_open_files`
+const OPEN_FILES_SAMPLE: usize = 50;
+
+#[tauri::command]
+pub async fn get_process_open_files(
+    pid: u32,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ProcessOpenFiles, String> {
+    if pid == 0 {
+        return Err("Invalid pid 0".to_string());
+    }
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    // Listing the directory fails for another user's process, so the count can't come
+    // back as a misleading 0
+    let script = format!(
+        "ls -U /proc/{0}/fd > /dev/null && ls -U /proc/{0}/fd | wc -l && \
+         ls -l /proc/{0}/fd | head -n {1}",
+        pid,
+        OPEN_FILES_SAMPLE + 1
+    );
+    let (output, exit_status) = ssh_client
+        .execute_with_status(&proc_read_command(pid, &script))
+        .map_err(|e| e.message)?;
+    match exit_status {
+        0 => {
+            parse_open_files(&output).ok_or_else(|| format!("Unexpected output: {}", output.trim()))
+        }
+        status => Err(proc_read_error(pid, status, "open files")),
+    }
+}
+
+/// /proc/<pid>/limits is a fixed-width table; the header gives the column offsets since
+/// limit names contain spaces
+fn parse_proc_limits(content: &str) -> Vec<ResourceLimit> {
+    let mut lines = content.lines();
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let (Some(soft_at), Some(hard_at), Some(units_at)) = (
+        header.find("Soft Limit"),
+        header.find("Hard Limit"),
+        header.find("Units"),
+    ) else {
+        return Vec::new();
+    };
+    let column = |line: &str, from: usize, to: usize| -> String {
+        line.get(from.min(line.len())..to.min(line.len()))
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    };
+    lines
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let units = column(line, units_at, line.len());
+            ResourceLimit {
+                name: column(line, 0, soft_at),
+                soft: column(line, soft_at, hard_at),
+                hard: column(line, hard_at, units_at),
+                units: (!units.is_empty()).then_some(units),
+            }
+        })
+        .collect()
+}
+
+/// The descriptor count followed by `ls -l` of the fd directory
+fn parse_open_files(output: &str) -> Option<ProcessOpenFiles> {
+    let mut lines = output.lines();
+    let count = lines.next()?.trim().parse().ok()?;
+    let sample = lines
+        .filter_map(|line| {
+            let (before, target) = line.split_once(" -> ")?;
+            let fd = before.split_whitespace().last()?;
+            Some(format!("{} -> {}", fd, target))
+        })
+        .take(OPEN_FILES_SAMPLE)
+        .collect();
+    Some(ProcessOpenFiles { count, sample })
+}
+
+/// Processes that hint at something stuck: zombies, uninterruptible (D state) sleepers,
+/// and anything holding more than `fd_threshold` (default 1000) open files. Counting
+/// other users' file descriptors needs root, so without passwordless sudo only the
+/// account's own processes are counted and `fd_counts_complete` is false.
+#[tauri::command]
+pub async fn get_problem_processes(
+    fd_threshold: Option<u32>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ProblemProcesses, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let threshold = fd_threshold.unwrap_or(1000);
+    let fd_loop = format!(
+        "for d in /proc/[0-9]*; do n=$(ls -U \"$d/fd\" 2>/dev/null | wc -l); \
+         [ \"$n\" -gt {} ] && echo \"${{d#/proc/}} $n\"; done; true",
+        threshold
+    );
+    let command = format!(
+        "echo '===PS==='; ps axo stat=,ppid=,pid=,comm= 2>/dev/null; \
+         if sudo -n true 2>/dev/null; then echo '===FDS_ALL==='; sudo -n sh -c {0}; \
+         else echo '===FDS_OWN==='; sh -c {0}; fi",
+        shell_quote(&fd_loop)
+    );
+    let output = execute_read_only(client, &command).map_err(|e| e.message)?;
+    let sections = split_sections(&output);
+    let section = |name: &str| sections.get(name).map(String::as_str);
+
+    Ok(parse_problem_processes(
+        section("PS").unwrap_or_default(),
+        section("FDS_ALL")
+            .or(section("FDS_OWN"))
+            .unwrap_or_default(),
+        threshold,
+        sections.contains_key("FDS_ALL"),
+    ))
+}
+
+/// `ps axo stat=,ppid=,pid=,comm=` rows plus `pid count` lines for processes over the
+/// open-file threshold
+fn parse_problem_processes(
+    ps_output: &str,
+    fd_output: &str,
+    fd_threshold: u32,
+    fd_counts_complete: bool,
+) -> ProblemProcesses {
+    struct Row<'a> {
+        stat: &'a str,
+        ppid: u32,
+        pid: u32,
+        command: &'a str,
+    }
+    let rows: Vec<Row> = ps_output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let (stat, rest) = line.split_once(char::is_whitespace)?;
+            let (ppid, rest) = rest.trim_start().split_once(char::is_whitespace)?;
+            let (pid, command) = rest.trim_start().split_once(char::is_whitespace)?;
+            Some(Row {
+                stat,
+                ppid: ppid.parse().ok()?,
+                pid: pid.parse().ok()?,
+                command: command.trim(),
+            })
+        })
+        .collect();
+    let command_of = |pid: u32| {
+        rows.iter()
+            .find(|row| row.pid == pid)
+            .map(|row| row.command.to_string())
+    };
+    let process = |row: &Row, open_files: Option<u32>| ProblemProcess {
+        pid: row.pid,
+        ppid: row.ppid,
+        command: row.command.to_string(),
+        parent_command: command_of(row.ppid),
+        state: row.stat.to_string(),
+        open_files,
+    };
+
+    let mut result = ProblemProcesses {
+        zombies: Vec::new(),
+        uninterruptible: Vec::new(),
+        many_open_files: Vec::new(),
+        fd_threshold,
+        fd_counts_complete,
+    };
+    for row in &rows {
+        match row.stat.chars().next() {
+            Some('Z') => result.zombies.push(process(row, None)),
+            Some('D') => result.uninterruptible.push(process(row, None)),
+            _ => {}
+        }
+    }
+    for line in fd_output.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(pid), Some(count)) = (
+            fields.next().and_then(|p| p.parse::<u32>().ok()),
+            fields.next().and_then(|c| c.parse::<u32>().ok()),
+        ) else {
+            continue;
+        };
+        // The process may have exited between ps and the fd count
+        if let Some(row) = rows.iter().find(|row| row.pid == pid) {
+            result.many_open_files.push(process(row, Some(count)));
+        }
+    }
+    result
+        .many_open_files
+        .sort_by_key(|p| std::cmp::Reverse(p.open_files));
+    result
+}
+
+const DMESG_LEVELS: &[&str] = &[
+    "emerg", "alert", "crit", "err", "warn", "notice", "info", "debug",
+];
+
+/// The last `lines` kernel messages, optionally only those at `level` (a dmesg level list
+/// such as `err,warn`). Falls back to sudo when `kernel.dmesg_restrict` hides the buffer,
+/// and to boot-relative timestamps on util-linux without `--time-format`.
+#[tauri::command]
+pub async fn get_dmesg(
+    lines: u32,
+    level: Option<String>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<DmesgEntry>, String> {
+    let level = level
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty());
+    if let Some(level) = &level {
+        let valid = level
+            .split(',')
+            .all(|l| DMESG_LEVELS.contains(&l.trim_matches('+')));
+        if !valid {
+            return Err(format!(
+                "Invalid level '{}', expected one or more of {}",
+                level,
+                DMESG_LEVELS.join(", ")
+            ));
+        }
+    }
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let args = match &level {
+        Some(level) => format!("-x -l {}", level),
+        None => "-x".to_string(),
+    };
+    let (output, status) = client
+        .execute_with_status(&format!(
+            "out=$(dmesg {0} --time-format=iso 2>/dev/null \
+                || sudo -n dmesg {0} --time-format=iso 2>/dev/null \
+                || dmesg {0} 2>/dev/null || sudo -n dmesg {0} 2>/dev/null) || exit 4; \
+             printf '%s\\n' \"$out\" | tail -n {1}",
+            args,
+            lines.clamp(1, 10_000)
+        ))
+        .map_err(|e| e.message)?;
+    match status {
+        0 => Ok(parse_dmesg(&output)),
+        4 => Err(
+            "Can't read the kernel log: kernel.dmesg_restrict is set and sudo needs a password"
+                .to_string(),
+        ),
+        _ => Err(format!("Failed to read the kernel log: {}", output.trim())),
+    }
+}
+
+/// `dmesg -x` lines: `kern  :err   : <time> message`, where the time is ISO 8601 or
+/// `[  12.345678]`. Lines without the prefix continue the previous message.
+fn parse_dmesg(output: &str) -> Vec<DmesgEntry> {
+    let mut entries: Vec<DmesgEntry> = Vec::new();
+    for line in output.lines().filter(|l| !l.trim().is_empty()) {
+        let parsed = line.split_once(':').and_then(|(_, rest)| {
+            let (level, rest) = rest.split_once(':')?;
+            let level = level.trim();
+            DMESG_LEVELS
+                .contains(&level)
+                .then_some((level, rest.trim_start()))
+        });
+        let Some((level, rest)) = parsed else {
+            if let Some(last) = entries.last_mut() {
+                last.message.push('\n');
+                last.message.push_str(line.trim());
+            }
+            continue;
+        };
+        let (timestamp, message) = match rest.strip_prefix('[') {
+            Some(relative) => relative
+                .split_once(']')
+                .map(|(time, message)| (time.trim(), message))
+                .unwrap_or(("", rest)),
+            None => rest.split_once(' ').unwrap_or((rest, "")),
+        };
+        entries.push(DmesgEntry {
+            timestamp: timestamp.to_string(),
+            level: level.to_string(),
+            message: message.trim().to_string(),
+        });
+    }
+    entries
+}
+
+/// Time sync daemons `timedatectl set-ntp` can switch (chrony's unit name varies by distro)
+const TIMEDATECTL_NTP_SERVICES: &[&str] = &["systemd-timesyncd", "chrony", "chronyd"];
+
+const TIME_CONFIG_COMMAND: &str = "echo '===SHOW==='; timedatectl show 2>/dev/null; \
+     echo '===LOCAL==='; date '+%Y-%m-%d %H:%M:%S %Z'; \
+     echo '===UTC==='; date -u '+%Y-%m-%d %H:%M:%S UTC'; \
+     echo '===NTP==='; for s in systemd-timesyncd chrony chronyd ntp ntpd ntpsec; do \
+         [ \"$(systemctl is-active \"$s\" 2>/dev/null)\" = active ] && echo \"$s\"; done; true";
+
+#[tauri::command]
+pub async fn get_time_config(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<TimeConfig, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    read_time_config(ssh_client.as_ref())
+}
+
+/// Set the timezone, which has to be one `timedatectl list-timezones` knows
+#[tauri::command]
+pub async fn set_timezone(
+    tz: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<TimeConfig, String> {
+    let valid_chars = tz
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '_' | '-' | '+'));
+    if tz.is_empty() || !valid_chars || tz.starts_with('-') {
+        return Err(format!("Invalid timezone '{}'", tz));
+    }
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let (output, status) = client
+        .execute_with_status(&format!(
+            "timedatectl list-timezones 2>/dev/null | grep -qxF {0} || exit 3; \
+             sudo timedatectl set-timezone {0} 2>&1",
+            shell_quote(&tz)
+        ))
+        .map_err(|e| e.message)?;
+    match status {
+        0 => read_time_config(client),
+        3 => Err(format!("Unknown timezone '{}'", tz)),
+        _ => Err(format!("Failed to set the timezone: {}", output.trim())),
+    }
+}
+
+/// Turn network time sync on or off through `timedatectl set-ntp`, which only works when
+/// systemd-timesyncd or chrony is the time source
+#[tauri::command]
+pub async fn set_ntp(
+    enabled: bool,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<TimeConfig, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let current = read_time_config(client)?;
+    if let Some(service) = current
+        .ntp_service
+        .as_deref()
+        .filter(|s| !TIMEDATECTL_NTP_SERVICES.contains(s))
+    {
+        return Err(format!(
+            "{} is the active time source and timedatectl can't switch it; \
+             use `systemctl {} {}` instead",
+            service,
+            if enabled { "start" } else { "stop" },
+            service
+        ));
+    }
+
+    let (output, status) = client
+        .execute_with_status(&format!("sudo timedatectl set-ntp {} 2>&1", enabled))
+        .map_err(|e| e.message)?;
+    if status != 0 {
+        let output = output.trim();
+        return Err(if output.contains("NTP not supported") {
+            "Neither systemd-timesyncd nor chrony is installed, so timedatectl can't manage NTP"
+                .to_string()
+        } else {
+            format!("Failed to set NTP: {}", output)
+        });
+    }
+    read_time_config(client)
+}
+
+fn read_time_config(client: &SshClient) -> Result<TimeConfig, String> {
+    let output = execute_read_only(client, TIME_CONFIG_COMMAND).map_err(|e| e.message)?;
+    parse_time_config(&split_sections(&output))
+}
+
+fn parse_time_config(sections: &HashMap<String, String>) -> Result<TimeConfig, String> {
+    let show = sections.get("SHOW").map(String::as_str).unwrap_or_default();
+    let property = |key: &str| {
+        show.lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+            .map(str::trim)
+    };
+    let Some(timezone) = property("Timezone") else {
+        return Err("timedatectl isn't available on this server".to_string());
+    };
+    let section = |name: &str| {
+        sections
+            .get(name)
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    };
+    Ok(TimeConfig {
+        timezone: timezone.to_string(),
+        local_time: section("LOCAL"),
+        utc_time: section("UTC"),
+        ntp_enabled: property("NTP") == Some("yes"),
+        ntp_synced: property("NTPSynchronized") == Some("yes"),
+        ntp_service: sections
+            .get("NTP")
+            .and_then(|s| s.lines().map(str::trim).find(|l| !l.is_empty()))
+            .map(String::from),
+    })
+}
+
+/// Debian/Ubuntu write reboot-required after kernel and libc updates; RHEL-likes answer
+/// through `needs-restarting -r`, which exits 1 when a reboot is due
+const REBOOT_STATUS_COMMAND: &str = "echo '===RUNNING==='; uname -r; \
+     echo '===REQUIRED==='; [ -f /var/run/reboot-required ] && echo reboot-required; \
+     command -v needs-restarting >/dev/null 2>&1 && { needs-restarting -r >/dev/null 2>&1; \
+     [ $? -eq 1 ] && echo needs-restarting; }; \
+     echo '===PACKAGES==='; cat /var/run/reboot-required.pkgs 2>/dev/null; \
+     echo '===KERNELS==='; ls -1 /boot 2>/dev/null | sed -n 's/^vmlinuz-//p'; true";
+
+/// Whether the server should be rebooted to finish applying updates
+#[tauri::command]
+pub async fn get_reboot_status(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<RebootStatus, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let output = ssh_client
+        .execute_command(REBOOT_STATUS_COMMAND)
+        .map_err(|e| e.message)?;
+    Ok(parse_reboot_status(&split_sections(&output)))
+}
+
+fn parse_reboot_status(sections: &HashMap<String, String>) -> RebootStatus {
+    let section = |name: &str| sections.get(name).map(String::as_str).unwrap_or_default();
+    let running_kernel = section("RUNNING").trim().to_string();
+
+    let mut packages: Vec<String> = Vec::new();
+    for package in section("PACKAGES").lines().map(str::trim) {
+        if !package.is_empty() && !packages.iter().any(|p| p == package) {
+            packages.push(package.to_string());
+        }
+    }
+
+    // Only names that start with a version can be compared; Arch's `vmlinuz-linux` can't
+    let installed_kernel = section("KERNELS")
+        .lines()
+        .map(str::trim)
+        .filter(|k| k.starts_with(|c: char| c.is_ascii_digit()))
+        .max_by(|a, b| compare_versions(a, b))
+        .map(String::from);
+    let kernel_outdated = installed_kernel
+        .as_deref()
+        .is_some_and(|newest| compare_versions(newest, &running_kernel).is_gt());
+
+    RebootStatus {
+        required: !section("REQUIRED").trim().is_empty() || kernel_outdated,
+        packages,
+        running_kernel,
+        installed_kernel,
+    }
+}
+
+/// Compare version strings piecewise, numbers by value: `5.15.0-105` is newer than `5.15.0-97`
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    fn pieces(version: &str) -> Vec<&str> {
+        let mut pieces = Vec::new();
+        let mut start = 0;
+        for (i, c) in version.char_indices().skip(1) {
+            let prev = version[..i].chars().next_back().unwrap_or(c);
+            if prev.is_ascii_digit() != c.is_ascii_digit() {
+                pieces.push(&version[start..i]);
+                start = i;
+            }
+        }
+        pieces.push(&version[start..]);
+        pieces
+    }
+    for (x, y) in pieces(a).into_iter().zip(pieces(b)) {
+        let order = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            _ => x.cmp(y),
+        };
+        if order.is_ne() {
+            return order;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+/// Probes for both security modules; the profile and module lists need root
+const SECURITY_MODULE_COMMAND: &str = "echo '===SELINUX==='; \
+     command -v getenforce >/dev/null 2>&1 && getenforce 2>/dev/null; \
+     echo '===SELINUX_MODULES==='; \
+     [ -d /sys/fs/selinux ] && sudo -n semodule -l 2>/dev/null | wc -l; \
+     echo '===APPARMOR==='; cat /sys/module/apparmor/parameters/enabled 2>/dev/null; \
+     echo '===APPARMOR_PROFILES==='; \
+     sudo -n cat /sys/kernel/security/apparmor/profiles 2>/dev/null";
+
+/// Which mandatory access control module is active, if any. Services that "can't access
+/// files" despite correct permissions are often being denied by one of these.
+#[tauri::command]
+pub async fn get_security_module_status(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<SecurityModule, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let output = ssh_client
+        .execute_command(SECURITY_MODULE_COMMAND)
+        .map_err(|e| e.message)?;
+    Ok(parse_security_module(&split_sections(&output)))
+}
+
+/// Switch SELinux between enforcing and permissive until the next boot. The boot-time
+/// mode stays whatever SELINUX= in /etc/selinux/config says.
+#[tauri::command]
+pub async fn set_enforcement_mode(
+    mode: EnforcementMode,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    let value = match mode {
+        EnforcementMode::Enforcing => 1,
+        EnforcementMode::Permissive => 0,
+        _ => return Err("SELinux can only be switched to enforcing or permissive".to_string()),
+    };
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let (output, status) = client
+        .execute_with_status(&format!(
+            "command -v setenforce >/dev/null 2>&1 || exit 127; sudo setenforce {} 2>&1",
+            value
+        ))
+        .map_err(|e| e.message)?;
+    let mode = if value == 1 {
+        "enforcing"
+    } else {
+        "permissive"
+    };
+    Ok(match status {
+        127 => ActionResult {
+            success: false,
+            message: "SELinux tools aren't installed on this server".to_string(),
+            raw_output: output,
+        },
+        status => action_result(
+            status,
+            output,
+            format!(
+                "SELinux is now {} (until reboot; set SELINUX= in /etc/selinux/config to keep it)",
+                mode
+            ),
+            "Failed to change the SELinux mode",
+        ),
+    })
+}
+
+/// Put one AppArmor profile in enforce or complain mode with `aa-enforce`/`aa-complain`.
+/// `profile` is a program path or a file under /etc/apparmor.d; profiles loaded by other
+/// tools (docker-default, snaps) have no such file and can't be switched this way.
+#[tauri::command]
+pub async fn set_apparmor_profile_mode(
+    profile: String,
+    mode: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    let tool = match mode.trim() {
+        "enforce" => "aa-enforce",
+        "complain" => "aa-complain",
+        other => return Err(format!("Unknown AppArmor mode '{}'", other)),
+    };
+    if profile.trim().is_empty() {
+        return Err("Profile name is empty".to_string());
+    }
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let (output, status) = client
+        .execute_with_status(&format!(
+            "command -v {0} >/dev/null 2>&1 || exit 127; sudo {0} -- {1} 2>&1",
+            tool,
+            shell_quote(profile.trim())
+        ))
+        .map_err(|e| e.message)?;
+    Ok(match status {
+        127 => ActionResult {
+            success: false,
+            message: format!("{} isn't installed (it comes with apparmor-utils)", tool),
+            raw_output: output,
+        },
+        status => action_result(
+            status,
+            output,
+            format!("{} is now in {} mode", profile.trim(), mode.trim()),
+            &format!(
+                "Failed to switch {} to {} mode",
+                profile.trim(),
+                mode.trim()
+            ),
+        ),
+    })
+}
+
+/// SELinux wins when it's enforcing or permissive; otherwise AppArmor if the kernel has it
+/// on, then SELinux reported as disabled when its tools are present, then `None`
+fn parse_security_module(sections: &HashMap<String, String>) -> SecurityModule {
+    let section = |name: &str| sections.get(name).map(|s| s.trim()).unwrap_or_default();
+    let selinux = section("SELINUX");
+    let selinux_mode = match selinux.to_ascii_lowercase().as_str() {
+        "enforcing" => Some(EnforcementMode::Enforcing),
+        "permissive" => Some(EnforcementMode::Permissive),
+        _ => None,
+    };
+    if let Some(mode) = selinux_mode {
+        return SecurityModule {
+            kind: SecurityModuleKind::SELinux,
+            mode,
+            profiles_loaded: section("SELINUX_MODULES").parse().unwrap_or(0),
+            profiles: Vec::new(),
+        };
+    }
+
+    if section("APPARMOR") == "Y" {
+        let profiles = parse_apparmor_profiles(section("APPARMOR_PROFILES"));
+        let all_complain = !profiles.is_empty() && profiles.iter().all(|p| p.mode == "complain");
+        return SecurityModule {
+            kind: SecurityModuleKind::AppArmor,
+            mode: if all_complain {
+                EnforcementMode::Complaining
+            } else {
+                EnforcementMode::Enforcing
+            },
+            profiles_loaded: profiles.len() as u32,
+            profiles,
+        };
+    }
+
+    SecurityModule {
+        kind: if selinux.is_empty() {
+            SecurityModuleKind::None
+        } else {
+            SecurityModuleKind::SELinux
+        },
+        mode: EnforcementMode::Disabled,
+        profiles_loaded: 0,
+        profiles: Vec::new(),
+    }
+}
+
+/// Lines of /sys/kernel/security/apparmor/profiles: `/usr/sbin/cupsd (enforce)`
+fn parse_apparmor_profiles(content: &str) -> Vec<AppArmorProfile> {
+    let mut profiles: Vec<AppArmorProfile> = content
+        .lines()
+        .filter_map(|line| {
+            let (name, mode) = line.trim().rsplit_once(" (")?;
+            Some(AppArmorProfile {
+                name: name.to_string(),
+                mode: mode.strip_suffix(')')?.to_string(),
+            })
+        })
+        .collect();
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    profiles
+}
+
+const ENVIRONMENT_PATH: &str = "/etc/environment";
+
+/// System-wide variables from /etc/environment, in file order
+#[tauri::command]
+pub async fn get_system_environment(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<(String, String)>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let (content, _) = ssh_client
+        .execute_with_status(&format!("cat {} 2>/dev/null", ENVIRONMENT_PATH))
+        .map_err(|e| e.message)?;
+    Ok(parse_environment_file(&content))
+}
+
+/// Set `key` in /etc/environment, replacing an existing assignment in place
+#[tauri::command]
+pub async fn set_system_environment_var(
+    key: String,
+    value: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    if !is_shell_identifier(&key) {
+        return Err(format!("'{}' isn't a valid variable name", key));
+    }
+    if value.contains(['"', '\n', '\r']) {
+        return Err("Values can't contain double quotes or line breaks".to_string());
+    }
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    // Only a missing file counts as empty; one that can't be read would be overwritten
+    let (output, status) = client
+        .execute_with_status(&format!(
+            "sudo true 2>&1 || exit 2; sudo test -e {} || exit 3",
+            ENVIRONMENT_PATH
+        ))
+        .map_err(|e| e.message)?;
+    let current = match status {
+        0 => read_remote_file(client, ENVIRONMENT_PATH)?,
+        3 => String::new(),
+        _ => {
+            return Err(format!(
+                "Can't check {}: {}",
+                ENVIRONMENT_PATH,
+                output.trim()
+            ))
+        }
+    };
+    let content = set_environment_line(&current, &key, &value);
+    write_environment_file(client, &content, format!("Set {}", key))
+}
+
+#[tauri::command]
+pub async fn unset_system_environment_var(
+    key: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let current = read_remote_file(client, ENVIRONMENT_PATH)?;
+    let content = remove_environment_lines(&current, &key);
+    if content == current {
+        return Err(format!("{} isn't set in {}", key, ENVIRONMENT_PATH));
+    }
+    write_environment_file(client, &content, format!("Removed {}", key))
+}
+
+/// Back up /etc/environment to `.bak` and replace it with `content`
+fn write_environment_file(
+    client: &SshClient,
+    content: &str,
+    done: String,
+) -> Result<ActionResult, String> {
+    run_action(
+        client,
+        &format!(
+            "if [ -f {0} ]; then sudo cp -p {0} {0}.bak; fi && printf '%s' {1} | sudo tee {0} > /dev/null",
+            ENVIRONMENT_PATH,
+            shell_quote(content)
+        ),
+        format!(
+            "{} in {} (previous version in {}.bak). It applies to new login sessions; \
+             running shells and services keep their old environment until restarted.",
+            done, ENVIRONMENT_PATH, ENVIRONMENT_PATH
+        ),
+        &format!("Failed to write {}", ENVIRONMENT_PATH),
+    )
+}
+
+fn is_shell_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The name of the variable a /etc/environment line assigns, if any. pam_env ignores an
+/// `export ` prefix, so it's accepted here too.
+fn environment_line_key(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+    let (key, _) = line.split_once('=')?;
+    is_shell_identifier(key).then_some(key)
+}
+
+/// `KEY=value` pairs with the surrounding quotes removed, skipping comments
+fn parse_environment_file(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| {
+            let key = environment_line_key(line)?;
+            let (_, value) = line.split_once('=')?;
+            let value = value.trim();
+            let unquoted = ['"', '\'']
+                .iter()
+                .find_map(|&q| value.strip_prefix(q)?.strip_suffix(q))
+                .unwrap_or(value);
+            Some((key.to_string(), unquoted.to_string()))
+        })
+        .collect()
+}
+
+/// `content` with `key` set to `value`: the first assignment is replaced in place and any
+/// later ones dropped, or a new line is appended. Everything else is kept as is.
+fn set_environment_line(content: &str, key: &str, value: &str) -> String {
+    let assignment = format!("{}=\"{}\"", key, value);
+    let mut lines: Vec<String> = Vec::new();
+    let mut replaced = false;
+    for line in content.lines() {
+        if environment_line_key(line) == Some(key) {
+            if !replaced {
+                lines.push(assignment.clone());
+                replaced = true;
+            }
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+    if !replaced {
+        lines.push(assignment);
+    }
+    lines.join("\n") + "\n"
+}
+
+fn remove_environment_lines(content: &str, key: &str) -> String {
+    let kept: Vec<&str> = content
+        .lines()
+        .filter(|line| environment_line_key(line) != Some(key))
+        .collect();
+    if kept.len() == content.lines().count() {
+        return content.to_string();
+    }
+    if kept.is_empty() {
+        return String::new();
+    }
+    kept.join("\n") + "\n"
+}
+
+/// Kernel tunables set with `persist` are kept here so they survive a reboot
+const SYSCTL_PERSIST_FILE: &str = "/etc/sysctl.d/99-dpanel.conf";
+
+/// One kernel tunable, or every readable one when `key` is `None`
+#[tauri::command]
+pub async fn get_sysctl(
+    key: Option<String>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SysctlEntry>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    match key {
+        Some(key) => Ok(vec![read_sysctl(client, &key)?]),
+        None => {
+            // Keys only root may read print errors on stderr; they're left out
+            let output =
+                execute_read_only(client, "sysctl -a 2>/dev/null").map_err(|e| e.message)?;
+            Ok(parse_sysctl_all(&output))
+        }
+    }
+}
+
+/// Set a tunable now, and with `persist` also in `SYSCTL_PERSIST_FILE`. Returns the value
+/// the kernel reports afterwards, which may be normalised (e.g. tabs between fields).
+#[tauri::command]
+pub async fn set_sysctl(
+    key: String,
+    value: String,
+    persist: bool,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<SysctlEntry, String> {
+    if !is_valid_sysctl_key(&key) {
+        return Err(format!("Invalid sysctl key '{}'", key));
+    }
+    let value = value.trim();
+    if value.is_empty() || value.chars().any(|c| c.is_control() && c != '\t') {
+        return Err("Invalid sysctl value".to_string());
+    }
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let (output, status) = client
+        .execute_with_status(&format!(
+            "sudo sysctl -w {} 2>&1",
+            shell_quote(&format!("{}={}", key, value))
+        ))
+        .map_err(|e| e.message)?;
+    if status != 0 {
+        return Err(format!("Failed to set {}: {}", key, output.trim()));
+    }
+
+    if persist {
+        let (current, _) = client
+            .execute_with_status(&format!("sudo cat {} 2>/dev/null", SYSCTL_PERSIST_FILE))
+            .map_err(|e| e.message)?;
+        let content = upsert_sysctl_conf(&current, &key, value);
+        let (output, status) = client
+            .execute_with_status(&format!(
+                "printf '%s' {} | sudo tee {} > /dev/null",
+                shell_quote(&content),
+                SYSCTL_PERSIST_FILE
+            ))
+            .map_err(|e| e.message)?;
+        if status != 0 {
+            return Err(format!(
+                "{} was set but saving it to {} failed: {}",
+                key,
+                SYSCTL_PERSIST_FILE,
+                output.trim()
+            ));
+        }
+    }
+
+    read_sysctl(client, &key)
+}
+
+fn read_sysctl(client: &SshClient, key: &str) -> Result<SysctlEntry, String> {
+    if !is_valid_sysctl_key(key) {
+        return Err(format!("Invalid sysctl key '{}'", key));
+    }
+    let (output, status) = client
+        .execute_with_status(&format!("sysctl -n {} 2>&1", key))
+        .map_err(|e| e.message)?;
+    if status != 0 {
+        return Err(format!("Failed to read {}: {}", key, output.trim()));
+    }
+    Ok(SysctlEntry {
+        key: key.to_string(),
+        value: output.trim_end_matches('\n').to_string(),
+    })
+}
+
+/// `^[a-z0-9._]+$`, which also keeps keys from being read as options or paths
+fn is_valid_sysctl_key(key: &str) -> bool {
+    !key.is_empty()
+        && !key.starts_with('.')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '_')
+}
+
+/// `sysctl -a` lines of `key = value`
+fn parse_sysctl_all(output: &str) -> Vec<SysctlEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(" = ").or_else(|| {
+                // Empty values print as "key = " and lose the trailing space to trimming
+                line.strip_suffix(" =").map(|key| (key, ""))
+            })?;
+            Some(SysctlEntry {
+                key: key.trim().to_string(),
+                value: value.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Replace `key`'s line in a sysctl.d file, or append one. Comments and other keys are
+/// kept; the first line for `key` is replaced in place and any later ones are dropped, so
+/// the file has a single setting for it.
+fn upsert_sysctl_conf(content: &str, key: &str, value: &str) -> String {
+    let line_key = |line: &str| {
+        let line = line.trim();
+        if line.starts_with('#') || line.starts_with(';') {
+            return None;
+        }
+        line.split_once('=')
+            .map(|(k, _)| k.trim().trim_start_matches('-').to_string())
+    };
+    let mut lines: Vec<String> = Vec::new();
+    let mut replaced = false;
+    for line in content.lines() {
+        if line_key(line).as_deref() == Some(key) {
+            if !replaced {
+                lines.push(format!("{} = {}", key, value));
+                replaced = true;
+            }
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+    if !replaced {
+        if lines.is_empty() {
+            lines.push("# Managed by DPanel".to_string());
+        }
+        lines.push(format!("{} = {}", key, value));
+    }
+    format!("{}\n", lines.join("\n"))
+}
+
+const FSTAB_PATH: &str = "/etc/fstab";
+/// `mount -a` gets this long before it's killed, so an unreachable network share can't hang
+const MOUNT_ALL_TIMEOUT_SECS: u32 = 30;
+
+#[tauri::command]
+pub async fn get_fstab(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<FstabEntry>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let output = execute_read_only(ssh_client.as_ref(), &format!("cat {}", FSTAB_PATH))
+        .map_err(|e| e.message)?;
+    Ok(parse_fstab(&output))
+}
+
+/// Append a mount line; `mount` also runs `mount -a` once the devices check out
+#[tauri::command]
+pub async fn add_fstab_entry(
+    entry: FstabEntry,
+    mount: Option<bool>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<FstabSaveResult, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+    let mut entries = parse_fstab(&read_remote_file(client, FSTAB_PATH)?);
+    if !is_swap_entry(&entry) && entries.iter().any(|e| e.mountpoint == entry.mountpoint) {
+        return Err(format!("{} is already in {}", entry.mountpoint, FSTAB_PATH));
+    }
+    entries.push(entry);
+    write_fstab(client, &entries, mount.unwrap_or(false))
+}
+
+/// Drop every line mounting at `mountpoint`. Nothing is unmounted.
+#[tauri::command]
+pub async fn remove_fstab_entry(
+    mountpoint: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<FstabSaveResult, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+    let mut entries = parse_fstab(&read_remote_file(client, FSTAB_PATH)?);
+    let before = entries.len();
+    entries.retain(|e| e.mountpoint != mountpoint);
+    if entries.len() == before {
+        return Err(format!("No {} entry mounts at {}", FSTAB_PATH, mountpoint));
+    }
+    write_fstab(client, &entries, false)
+}
+
+/// Replace the mount lines with `entries`. Comments and blank lines stay where they were.
+#[tauri::command]
+pub async fn save_fstab(
+    entries: Vec<FstabEntry>,
+    mount: Option<bool>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<FstabSaveResult, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    write_fstab(ssh_client.as_ref(), &entries, mount.unwrap_or(false))
+}
+
+/// Back up fstab to `.bak` and write `entries`. With `mount`, the devices and mount
+/// points are checked before anything is written and a failing check leaves fstab
+/// untouched; `mount -a` then runs on the new file and the backup is restored if it fails.
+/// The `/` line can't be dropped.
+fn write_fstab(
+    client: &SshClient,
+    entries: &[FstabEntry],
+    mount: bool,
+) -> Result<FstabSaveResult, String> {
+    for entry in entries {
+        validate_fstab_entry(entry)?;
+    }
+    let current = read_remote_file(client, FSTAB_PATH)?;
+    if drops_root_entry(&parse_fstab(&current), entries) {
+        return Err(format!(
+            "Refusing to remove the / entry from {}",
+            FSTAB_PATH
+        ));
+    }
+    let content = render_fstab(&current, entries);
+    let backup = format!("{}.bak", FSTAB_PATH);
+
+    let mut result = FstabSaveResult {
+        message: String::new(),
+        entries: parse_fstab(&content),
+        problems: Vec::new(),
+        mounted: None,
+        mount_output: None,
+        rolled_back: false,
+    };
+    if mount {
+        let (output, _) = client
+            .execute_with_status(&fstab_check_script(entries))
+            .map_err(|e| e.message)?;
+        result.problems = output.lines().map(str::to_string).collect();
+        if !result.problems.is_empty() {
+            result.entries = parse_fstab(&current);
+            result.message = format!(
+                "{} was not changed: {} problem(s) found",
+                FSTAB_PATH,
+                result.problems.len()
+            );
+            return Ok(result);
+        }
+    }
+
+    let (output, status) = client
+        .execute_with_status(&format!(
+            "sudo cp -p {0} {1} && printf '%s' {2} | sudo tee {0} > /dev/null",
+            FSTAB_PATH,
+            backup,
+            shell_quote(&content)
+        ))
+        .map_err(|e| e.message)?;
+    if status != 0 {
+        return Err(format!("Failed to write {}: {}", FSTAB_PATH, output.trim()));
+    }
+    result.message = format!("Saved {} (previous version in {})", FSTAB_PATH, backup);
+    if !mount {
+        return Ok(result);
+    }
+
+    let (output, status) = client
+        .execute_with_status(&format!(
+            "sudo timeout {} mount -a 2>&1",
+            MOUNT_ALL_TIMEOUT_SECS
+        ))
+        .map_err(|e| e.message)?;
+    result.mounted = Some(status == 0);
+    result.mount_output = Some(output.trim().to_string());
+    if status == 0 {
+        result.message = format!("Saved {} and mounted all entries", FSTAB_PATH);
+        return Ok(result);
+    }
+
+    client
+        .execute_command(&format!("sudo cp -p {} {}", backup, FSTAB_PATH))
+        .map_err(|e| e.message)?;
+    result.rolled_back = true;
+    result.entries = parse_fstab(&current);
+    result.message = if status == 124 {
+        format!(
+            "mount -a timed out after {}s; the previous {} was restored",
+            MOUNT_ALL_TIMEOUT_SECS, FSTAB_PATH
+        )
+    } else {
+        format!("mount -a failed; the previous {} was restored", FSTAB_PATH)
+    };
+    Ok(result)
+}
+
+/// Whether `entries` no longer mount anything at `/` although `current` did
+fn drops_root_entry(current: &[FstabEntry], entries: &[FstabEntry]) -> bool {
+    current.iter().any(|e| e.mountpoint == "/") && !entries.iter().any(|e| e.mountpoint == "/")
+}
+
+fn parse_fstab_line(line: &str) -> Option<FstabEntry> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let [device, mountpoint, fstype, options, rest @ ..] = fields.as_slice() else {
+        return None;
+    };
+    Some(FstabEntry {
+        device: device.to_string(),
+        mountpoint: mountpoint.to_string(),
+        fstype: fstype.to_string(),
+        options: options.to_string(),
+        dump: rest.first().and_then(|v| v.parse().ok()).unwrap_or(0),
+        pass: rest.get(1).and_then(|v| v.parse().ok()).unwrap_or(0),
+    })
+}
+
+fn parse_fstab(content: &str) -> Vec<FstabEntry> {
+    content.lines().filter_map(parse_fstab_line).collect()
+}
+
+fn format_fstab_entry(entry: &FstabEntry) -> String {
+    format!(
+        "{}\t{}\t{}\t{}\t{}\t{}",
+        entry.device, entry.mountpoint, entry.fstype, entry.options, entry.dump, entry.pass
+    )
+}
+
+/// Rewrite `current` to hold `entries`. Comments and blank lines are kept in place; each
+/// existing mount line is replaced by the next new entry with the same mount point (kept
+/// as written when unchanged) or dropped, and entries left over are appended.
+fn render_fstab(current: &str, entries: &[FstabEntry]) -> String {
+    let mut used = vec![false; entries.len()];
+    let mut lines: Vec<String> = Vec::new();
+    for line in current.lines() {
+        let Some(existing) = parse_fstab_line(line) else {
+            lines.push(line.to_string());
+            continue;
+        };
+        let replacement =
+            (0..entries.len()).find(|&i| !used[i] && entries[i].mountpoint == existing.mountpoint);
+        if let Some(i) = replacement {
+            used[i] = true;
+            if entries[i] == existing {
+                lines.push(line.to_string());
+            } else {
+                lines.push(format_fstab_entry(&entries[i]));
+            }
+        }
+    }
+    for (entry, _) in entries.iter().zip(&used).filter(|(_, used)| !**used) {
+        lines.push(format_fstab_entry(entry));
+    }
+    format!("{}\n", lines.join("\n"))
+}
+
+fn is_swap_entry(entry: &FstabEntry) -> bool {
+    entry.fstype == "swap" || entry.mountpoint == "none" || entry.mountpoint == "swap"
+}
+
+fn validate_fstab_entry(entry: &FstabEntry) -> Result<(), String> {
+    for (name, value) in [
+        ("device", &entry.device),
+        ("mount point", &entry.mountpoint),
+        ("filesystem type", &entry.fstype),
+        ("options", &entry.options),
+    ] {
+        if value.is_empty() || value.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            return Err(format!(
+                "Invalid {} '{}' (write spaces as \\040)",
+                name, value
+            ));
+        }
+    }
+    if !is_swap_entry(entry) && !entry.mountpoint.starts_with('/') {
+        return Err(format!(
+            "Mount point '{}' must be an absolute path",
+            entry.mountpoint
+        ));
+    }
+    if entry.pass > 2 {
+        return Err("The fsck pass must be 0, 1 or 2".to_string());
+    }
+    Ok(())
+}
+
+/// A script printing one line per entry `mount -a` would trip over: a missing mount point
+/// or a local device that isn't there. `noauto` lines are skipped like `mount -a` does, and
+/// network or virtual sources can't be checked up front (the timeout covers those).
+fn fstab_check_script(entries: &[FstabEntry]) -> String {
+    let mut checks = Vec::new();
+    for entry in entries {
+        if is_swap_entry(entry) || entry.options.split(',').any(|o| o == "noauto") {
+            continue;
+        }
+        let unescape = |value: &str| value.replace("\\040", " ").replace("\\011", "\t");
+        let mountpoint = unescape(&entry.mountpoint);
+        checks.push(format!(
+            "[ -d {0} ] || echo {1}",
+            shell_quote(&mountpoint),
+            shell_quote(&format!("Mount point {} does not exist", mountpoint))
+        ));
+        let device_path = [
+            ("UUID=", "/dev/disk/by-uuid/"),
+            ("LABEL=", "/dev/disk/by-label/"),
+            ("PARTUUID=", "/dev/disk/by-partuuid/"),
+            ("PARTLABEL=", "/dev/disk/by-partlabel/"),
+        ]
+        .iter()
+        .find_map(|(prefix, dir)| {
+            entry
+                .device
+                .strip_prefix(prefix)
+                .map(|id| format!("{}{}", dir, unescape(id.trim_matches('"'))))
+        })
+        .or_else(|| Some(unescape(&entry.device)).filter(|d| d.starts_with('/')));
+        if let Some(path) = device_path {
+            checks.push(format!(
+                "[ -e {0} ] || echo {1}",
+                shell_quote(&path),
+                shell_quote(&format!(
+                    "Device {} for {} does not exist",
+                    entry.device, mountpoint
+                ))
+            ));
+        }
+    }
+    if checks.is_empty() {
+        return "true".to_string();
+    }
+    checks.join("; ")
+}
+
+#[tauri::command]
+pub async fn get_swap_devices(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SwapDevice>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let output =
+        execute_read_only(ssh_client.as_ref(), "cat /proc/swaps").map_err(|e| e.message)?;
+    Ok(parse_proc_swaps(&output))
+}
+
+/// Create a swap file of `size_mb`, turn it on and add it to fstab. A path that already
+/// holds a swap signature is reused; any other existing file is refused.
+#[tauri::command]
+pub async fn create_swapfile(
+    path: String,
+    size_mb: u64,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    if size_mb == 0 {
+        return Err("The swap size must be at least 1 MB".to_string());
+    }
+    validate_swapfile_path(&path)?;
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let quoted = shell_quote(&path);
+    let script = format!(
+        "if awk 'NR > 1 {{ print $1 }}' /proc/swaps | grep -qxF {p}; then \
+             echo '{path} is already in use as swap'; exit 4; fi; \
+         if [ -e {p} ]; then \
+             [ \"$(sudo blkid -p -o value -s TYPE {p} 2>/dev/null)\" = swap ] || \
+                 {{ echo '{path} exists and is not a swap file'; exit 3; }}; \
+         else \
+             avail=$(df --output=avail -BM \"$(dirname {p})\" | tail -n 1 | tr -dc 0-9); \
+             [ \"${{avail:-0}}\" -gt {size} ] || \
+                 {{ echo \"Only ${{avail:-0}} MB free for a {size} MB swap file\"; exit 5; }}; \
+             {{ sudo fallocate -l {size}M {p} || sudo dd if=/dev/zero of={p} bs=1M count={size}; }} \
+                 && sudo chmod 600 {p} && sudo mkswap {p} \
+                 || {{ sudo rm -f {p}; exit 6; }}; \
+         fi; \
+         sudo swapon {p}",
+        p = quoted,
+        path = path.replace('\'', ""),
+        size = size_mb
+    );
+    let result = run_action(
+        client,
+        &script,
+        format!("Swap file {} ({} MB) enabled", path, size_mb),
+        &format!("Failed to create swap file {}", path),
+    )?;
+    if !result.success {
+        return Ok(result);
+    }
+
+    let mut entries = parse_fstab(&read_remote_file(client, FSTAB_PATH)?);
+    if entries.iter().any(|e| e.device == path) {
+        return Ok(result);
+    }
+    entries.push(FstabEntry {
+        device: path.clone(),
+        mountpoint: "none".to_string(),
+        fstype: "swap".to_string(),
+        options: "sw".to_string(),
+        dump: 0,
+        pass: 0,
+    });
+    Ok(with_fstab_update(
+        result,
+        write_fstab(client, &entries, false),
+    ))
+}
+
+/// Turn off and delete a swap file and drop its fstab line. Swap partitions and files
+/// without a swap signature are refused.
+#[tauri::command]
+pub async fn remove_swapfile(
+    path: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    validate_swapfile_path(&path)?;
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let quoted = shell_quote(&path);
+    let script = format!(
+        "[ -f {p} ] || {{ echo '{path} is not a regular file'; exit 3; }}; \
+         if awk 'NR > 1 {{ print $1 }}' /proc/swaps | grep -qxF {p}; then \
+             sudo swapoff {p} || exit 4; \
+         else \
+             [ \"$(sudo blkid -p -o value -s TYPE {p} 2>/dev/null)\" = swap ] || \
+                 {{ echo '{path} is not a swap file'; exit 3; }}; \
+         fi; \
+         sudo rm -f {p}",
+        p = quoted,
+        path = path.replace('\'', "")
+    );
+    let result = run_action(
+        client,
+        &script,
+        format!("Swap file {} removed", path),
+        &format!("Failed to remove swap file {}", path),
+    )?;
+    if !result.success {
+        return Ok(result);
+    }
+
+    let mut entries = parse_fstab(&read_remote_file(client, FSTAB_PATH)?);
+    let before = entries.len();
+    entries.retain(|e| !(e.device == path && is_swap_entry(e)));
+    if entries.len() == before {
+        return Ok(result);
+    }
+    Ok(with_fstab_update(
+        result,
+        write_fstab(client, &entries, false),
+    ))
+}
+
+/// Fold the outcome of the follow-up fstab edit into a swap action's result
+fn with_fstab_update(
+    mut result: ActionResult,
+    fstab: Result<FstabSaveResult, String>,
+) -> ActionResult {
+    match fstab {
+        Ok(saved) => result.message = format!("{}; {}", result.message, saved.message),
+        Err(error) => {
+            result.success = false;
+            result.message = format!(
+                "{}, but updating {} failed: {}",
+                result.message, FSTAB_PATH, error
+            );
+        }
+    }
+    result
+}
+
+fn validate_swapfile_path(path: &str) -> Result<(), String> {
+    if !path.starts_with('/')
+        || path.ends_with('/')
+        || path.split('/').any(|part| part == "..")
+        || path.chars().any(|c| c.is_whitespace() || c.is_control())
+    {
+        return Err(format!("Invalid swap file path '{}'", path));
+    }
+    if path.starts_with("/dev/") || path.starts_with("/proc/") || path.starts_with("/sys/") {
+        return Err(format!("{} is not a place for a swap file", path));
+    }
+    Ok(())
+}
+
+/// `/proc/swaps` rows; sizes there are in KiB
+fn parse_proc_swaps(output: &str) -> Vec<SwapDevice> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [name, swap_type, size, used, priority] = fields.as_slice() else {
+                return None;
+            };
+            Some(SwapDevice {
+                name: name.to_string(),
+                swap_type: swap_type.to_string(),
+                size_bytes: size.parse::<u64>().ok()? * 1024,
+                used_bytes: used.parse::<u64>().ok()? * 1024,
+                priority: priority.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Provenance from a container's labels (the `{{json .Config.Labels}}` map)
+fn parse_container_provenance(labels: &HashMap<String, String>) -> ContainerProvenance {
+    let label = |name: &str| {
+        labels
+            .get(&format!("com.docker.compose.{}", name))
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let project = label("project");
+    let working_dir = label("project.working_dir");
+    // Relative paths are relative to the project's working directory
+    let config_files = label("project.config_files")
+        .map(|files| {
+            files
+                .split(',')
+                .map(str::trim)
+                .filter(|f| !f.is_empty())
+                .map(|f| match &working_dir {
+                    Some(dir) if !f.starts_with('/') => {
+                        format!("{}/{}", dir.trim_end_matches('/'), f)
+                    }
+                    _ => f.to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    ContainerProvenance {
+        source: if project.is_some() {
+            ContainerSource::Compose
+        } else {
+            ContainerSource::Standalone
+        },
+        project,
+        service: label("service"),
+        config_files,
+        missing_files: Vec::new(),
+        unchecked_files: Vec::new(),
+        working_dir,
+        one_off: label("oneoff").as_deref() == Some("True"),
+        compose_version: label("version"),
+    }
+}
+
+/// How a container was created: its compose project, service and files, or standalone
+#[tauri::command]
+pub async fn get_container_provenance(
+    container_name: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ContainerProvenance, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let (output, exit_status) = client
+        .execute_with_status(&format!(
+            "docker inspect --format '{{{{json .Config.Labels}}}}' {} 2>&1",
+            shell_quote(&container_name)
+        ))
+        .map_err(|e| e.message)?;
+    if exit_status != 0 {
+        return Err(format!("Failed to inspect container: {}", output.trim()));
+    }
+    // Containers without labels report `null`
+    let labels: HashMap<String, String> =
+        serde_json::from_str::<Option<HashMap<String, String>>>(output.trim())
+            .map_err(|e| format!("Failed to parse container labels: {}", e))?
+            .unwrap_or_default();
+
+    let mut provenance = parse_container_provenance(&labels);
+    if !provenance.config_files.is_empty() {
+        let checks: Vec<String> = provenance
+            .config_files
+            .iter()
+            .map(|f| provenance_file_check(f))
+            .collect();
+        let output = client
+            .execute_command(&checks.join("; "))
+            .map_err(|e| e.message)?;
+        let (missing, unchecked) = parse_provenance_file_checks(&output);
+        provenance.missing_files = missing;
+        provenance.unchecked_files = unchecked;
+    }
+    Ok(provenance)
+}
+
+/// Prints `missing <path>` or `unchecked <path>` unless `path` is a file. Compose files
+/// are usually readable by the user; `sudo -n` is only tried for the rest, and when it
+/// needs a password the file is reported as unchecked rather than missing.
+fn provenance_file_check(path: &str) -> String {
+    format!(
+        "if [ -f {0} ]; then :; elif sudo -n true 2>/dev/null; then \
+         sudo -n test -f {0} || echo missing {0}; else echo unchecked {0}; fi",
+        shell_quote(path)
+    )
+}
+
+/// Missing and unchecked paths from the output of `provenance_file_check`
+fn parse_provenance_file_checks(output: &str) -> (Vec<String>, Vec<String>) {
+    let mut missing = Vec::new();
+    let mut unchecked = Vec::new();
+    for line in output.lines() {
+        if let Some(path) = line.strip_prefix("missing ") {
+            missing.push(path.to_string());
+        } else if let Some(path) = line.strip_prefix("unchecked ") {
+            unchecked.push(path.to_string());
+        }
+    }
+    (missing, unchecked)
+}
+
+#[tauri::command]
+pub async fn get_container_diff(
+    container_name: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ContainerDiff, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let (output, exit_status) = client
+        .execute_with_status(&format!(
+            "docker diff {} 2>&1",
+            shell_quote(&container_name)
+        ))
+        .map_err(|e| e.message)?;
+    if exit_status != 0 {
+        return Err(format!("Failed to diff container: {}", output.trim()));
+    }
+
+    Ok(parse_container_diff(&output, MAX_DIFF_ENTRIES))
+}
+
+/// Upper bound on changes returned by `get_container_diff`
+const MAX_DIFF_ENTRIES: usize = 2000;
+
+/// Parse `docker diff` output (`A /path`, `C /path`, `D /path`), sorted by path
+fn parse_container_diff(output: &str, limit: usize) -> ContainerDiff {
+    let mut changes: Vec<FsChange> = output
+        .lines()
+        .filter_map(|line| {
+            let (kind, path) = line.split_once(' ')?;
+            let kind = match kind {
+                "A" => FsChangeKind::Added,
+                "C" => FsChangeKind::Changed,
+                "D" => FsChangeKind::Deleted,
+                _ => return None,
+            };
+            Some(FsChange {
+                kind,
+                path: path.to_string(),
+            })
+        })
+        .collect();
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let total = changes.len();
+    changes.truncate(limit);
+
+    ContainerDiff {
+        changes,
+        total,
+        truncated: total > limit,
+    }
+}
+
+#[tauri::command]
+pub async fn set_container_restart_policy(
+    container_name: String,
+    policy: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let policy = policy.trim();
+    if !is_valid_restart_policy(policy) {
+        return Err(format!(
+            "Invalid restart policy '{}'. Use no, always, unless-stopped or on-failure[:N]",
+            policy
+        ));
+    }
+
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let (output, exit_status) = client
+        .execute_with_status(&format!(
+            "docker update --restart {} {} 2>&1",
+            policy,
+            shell_quote(&container_name)
+        ))
+        .map_err(|e| e.message)?;
+    if exit_status != 0 {
+        return Err(format!(
+            "Failed to update restart policy: {}",
+            output.trim()
+        ));
+    }
+
+    // Report what Docker actually applied rather than echoing the request
+    let effective = client
+        .execute_command(&format!(
+            "docker inspect --format '{{{{.HostConfig.RestartPolicy.Name}}}}:{{{{.HostConfig.RestartPolicy.MaximumRetryCount}}}}' {}",
+            shell_quote(&container_name)
+        ))
+        .map_err(|e| e.message)?;
+
+    Ok(format_restart_policy(effective.trim()))
+}
+
+/// Accepts `no`, `always`, `unless-stopped`, `on-failure` and `on-failure:N`
+fn is_valid_restart_policy(policy: &str) -> bool {
+    match policy.split_once(':') {
+        Some(("on-failure", retries)) => retries.parse::<u32>().is_ok(),
+        Some(_) => false,
+        None => matches!(policy, "no" | "always" | "unless-stopped" | "on-failure"),
+    }
+}
+
+/// Turn inspect's `Name:MaximumRetryCount` pair back into the `--restart` syntax
+fn format_restart_policy(inspected: &str) -> String {
+    let (name, retries) = inspected.split_once(':').unwrap_or((inspected, "0"));
+    let name = if name.is_empty() { "no" } else { name };
+    match retries.parse::<u32>() {
+        Ok(count) if name == "on-failure" && count > 0 => format!("on-failure:{}", count),
+        _ => name.to_string(),
+    }
+}
+
+#[tauri::command]
+pub async fn rename_container(
+    old_name: String,
+    new_name: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    if !is_valid_container_name(&new_name) {
+        return Err(format!("Invalid container name '{}'", new_name));
+    }
+
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let (output, exit_status) = client
+        .execute_with_status(&format!(
+            "docker rename {} {} 2>&1",
+            shell_quote(&old_name),
+            shell_quote(&new_name)
+        ))
+        .map_err(|e| e.message)?;
+    if exit_status != 0 {
+        return Err(format!("Failed to rename container: {}", output.trim()));
+    }
+
+    Ok(format!("Renamed {} to {}", old_name, new_name))
+}
+
+#[tauri::command]
+pub async fn recreate_container(
+    name: String,
+    new_image: Option<String>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let inspect_output = client
+        .execute_command(&format!("docker inspect {}", shell_quote(&name)))
+        .map_err(|e| e.message)?;
+    let inspect: Vec<JsonValue> = serde_json::from_str(&inspect_output)
+        .map_err(|e| format!("Failed to parse container info: {}", e))?;
+    let container = inspect.first().ok_or("Container not found")?;
+
+    // Settings the old image provided shouldn't be pinned onto the new container
+    let image_id = container
+        .get("Image")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    let image_config_output = client
+        .execute_command(&format!(
+            "docker image inspect --format '{{{{json .Config}}}}' {}",
+            shell_quote(image_id)
+        ))
+        .map_err(|e| e.message)?;
+    let image_config: JsonValue =
+        serde_json::from_str(image_config_output.trim()).unwrap_or_default();
+
+    let image = match new_image {
+        Some(image) if !image.trim().is_empty() => image.trim().to_string(),
+        _ => container
+            .get("Config")
+            .and_then(|c| c.get("Image"))
+            .and_then(|v| v.as_str())
+            .ok_or("Container has no image")?
+            .to_string(),
+    };
+
+    // Refused before anything is stopped if the container can't be reproduced
+    let spec = build_container_run_spec(container, &image_config, &name, &image)?;
+    let run_command = format!(
+        "docker run {}",
+        spec.run_args
+            .iter()
+            .map(|a| shell_quote(a))
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+
+    // Keep the old container around under a temporary name until the new one is running
+    let backup_name = format!("{}-recreate-old", name);
+    let run_checked = |command: &str| -> Result<String, String> {
+        let (output, exit_status) = client
+            .execute_with_status(&format!("{} 2>&1", command))
+            .map_err(|e| e.message)?;
+        if exit_status != 0 {
+            return Err(output.trim().to_string());
+        }
+        Ok(output)
+    };
+
+    run_checked(&format!("docker stop {}", shell_quote(&name)))
+        .map_err(|e| format!("Failed to stop container: {}", e))?;
+    run_checked(&format!(
+        "docker rename {} {}",
+        shell_quote(&name),
+        shell_quote(&backup_name)
+    ))
+    .map_err(|e| format!("Failed to rename old container: {}", e))?;
+
+    let created = run_checked(&run_command).and_then(|output| {
+        for connect in &spec.extra_networks {
+            run_checked(&format!(
+                "docker network connect {} {}",
+                connect
+                    .iter()
+                    .map(|a| shell_quote(a))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                shell_quote(&name)
+            ))?;
+        }
+        Ok(output)
+    });
+
+    match created {
+        Ok(output) => {
+            run_checked(&format!("docker rm {}", shell_quote(&backup_name)))
+                .map_err(|e| format!("New container started but old one was not removed: {}", e))?;
+            let id: String = output
+                .trim()
+                .lines()
+                .last()
+                .unwrap_or("")
+                .chars()
+                .take(12)
+                .collect();
+            Ok(format!("Recreated {} from {} ({})", name, image, id))
+        }
+        Err(e) => {
+            let _ = client.execute_command(&format!("docker rm -f {} 2>&1", shell_quote(&name)));
+            let _ = client.execute_command(&format!(
+                "docker rename {} {} 2>&1 && docker start {} 2>&1",
+                shell_quote(&backup_name),
+                shell_quote(&name),
+                shell_quote(&name)
+            ));
+            Err(format!(
+                "Failed to recreate container, restored the original: {}",
+                e
+            ))
+        }
+    }
+}
+
+/// Docker container names: `[a-zA-Z0-9][a-zA-Z0-9_.-]*`
+fn is_valid_container_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphanumeric())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+/// Arguments for `docker run` plus networks that must be attached afterwards, each as
+/// `docker network connect` arguments ending in the network name, since `docker run`
+/// only accepts a single `--network`
+#[derive(Debug, Default)]
+struct ContainerRunSpec {
+    run_args: Vec<String>,
+    extra_networks: Vec<Vec<String>>,
+}
+
+/// HostConfig settings `build_container_run_spec` has no `docker run` flag for. A
+/// container using any of them is refused rather than recreated without it.
+const UNSUPPORTED_HOST_CONFIG: &[&str] = &[
+    "Links",
+    "VolumesFrom",
+    "Mounts",
+    "DeviceRequests",
+    "DeviceCgroupRules",
+    "CgroupParent",
+    "UsernsMode",
+    "UTSMode",
+    "CpusetCpus",
+    "CpusetMems",
+    "CpuQuota",
+    "CpuPeriod",
+    "CpuRealtimePeriod",
+    "CpuRealtimeRuntime",
+    "BlkioWeight",
+    "BlkioWeightDevice",
+    "BlkioDeviceReadBps",
+    "BlkioDeviceWriteBps",
+    "BlkioDeviceReadIOps",
+    "BlkioDeviceWriteIOps",
+    "OomScoreAdj",
+    "MemorySwappiness",
+    "StorageOpt",
+    "VolumeDriver",
+];
+
+/// Docker's default /dev/shm size, which `docker inspect` reports even when unset
+const DEFAULT_SHM_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Null, false, zero (or -1, Docker's "unlimited"), empty string, array or object
+fn is_unset_json(value: Option<&JsonValue>) -> bool {
+    match value {
+        None | Some(JsonValue::Null) => true,
+        Some(JsonValue::Bool(b)) => !b,
+        Some(JsonValue::Number(n)) => matches!(n.as_i64(), Some(0 | -1)),
+        Some(JsonValue::String(s)) => s.is_empty(),
+        Some(JsonValue::Array(a)) => a.is_empty(),
+        Some(JsonValue::Object(o)) => o.is_empty(),
+    }
+}
+
+fn json_strings(value: Option<&JsonValue>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn json_str<'a>(value: &'a JsonValue, key: &str) -> &'a str {
+    value.get(key).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+/// `{"k": "v"}` as sorted `k=v` strings
+fn json_pairs(value: Option<&JsonValue>) -> Vec<String> {
+    let mut pairs: Vec<String> = value
+        .and_then(|v| v.as_object())
+        .map(|obj| {
+            obj.iter()
+                .map(|(k, v)| format!("{}={}", k, v.as_str().unwrap_or_default()))
+                .collect()
+        })
+        .unwrap_or_default();
+    pairs.sort();
+    pairs
+}
+
+fn push_arg(args: &mut Vec<String>, flag: &str, value: String) {
+    args.push(flag.to_string());
+    args.push(value);
+}
+
+/// Nanoseconds from a healthcheck setting as a Go duration for `docker run`
+fn nanos_to_duration(nanos: u64) -> String {
+    if nanos.is_multiple_of(1_000_000_000) {
+        format!("{}s", nanos / 1_000_000_000)
+    } else {
+        format!("{}ms", nanos / 1_000_000)
+    }
+}
+
+/// Rebuild a `docker run` invocation from `docker inspect` output. `image_config` is
+/// the `Config` of the image the container was created from: settings equal to the
+/// image's (Env entries, Cmd, Entrypoint, User, WorkingDir, Labels, Healthcheck) are
+/// left for the image to provide, so a new image's defaults take over. Settings with no
+/// `docker run` equivalent are an error rather than being silently dropped.
+fn build_container_run_spec(
+    container: &JsonValue,
+    image_config: &JsonValue,
+    name: &str,
+    image: &str,
+) -> Result<ContainerRunSpec, String> {
+    let config = container.get("Config").cloned().unwrap_or_default();
+    let host_config = container.get("HostConfig").cloned().unwrap_or_default();
+
+    let unsupported: Vec<&str> = UNSUPPORTED_HOST_CONFIG
+        .iter()
+        .copied()
+        .filter(|key| !is_unset_json(host_config.get(*key)))
+        .collect();
+    if !unsupported.is_empty() {
+        return Err(format!(
+            "Container {} uses settings that can't be recreated with docker run: {}",
+            name,
+            unsupported.join(", ")
+        ));
+    }
+
+    let mut args: Vec<String> = vec!["-d".to_string(), "--name".to_string(), name.to_string()];
+
+    // Ports: {"80/tcp": [{"HostIp": "", "HostPort": "8080"}]}
+    if let Some(bindings) = host_config.get("PortBindings").and_then(|v| v.as_object()) {
+        let mut container_ports: Vec<&String> = bindings.keys().collect();
+        container_ports.sort();
+        for container_port in container_ports {
+            let Some(host_bindings) = bindings[container_port].as_array() else {
+                continue;
+            };
+            for binding in host_bindings {
+                let publish = match (json_str(binding, "HostIp"), json_str(binding, "HostPort")) {
+                    (_, "") => container_port.to_string(),
+                    ("", port) => format!("{}:{}", port, container_port),
+                    (ip, port) => format!("{}:{}:{}", ip, port, container_port),
+                };
+                push_arg(&mut args, "-p", publish);
+            }
+        }
+    }
+
+    // Env, minus whatever the image already provides
+    let image_env = json_strings(image_config.get("Env"));
+    for var in json_strings(config.get("Env")) {
+        if !image_env.contains(&var) {
+            push_arg(&mut args, "-e", var);
+        }
+    }
+
+    // Volumes: explicit binds first, then named volumes that only show up in Mounts
+    let mut mounted: Vec<String> = Vec::new();
+    for bind in json_strings(host_config.get("Binds")) {
+        if let Some(destination) = bind.split(':').nth(1) {
+            mounted.push(destination.to_string());
+        }
+        push_arg(&mut args, "-v", bind);
+    }
+    if let Some(mounts) = container.get("Mounts").and_then(|v| v.as_array()) {
+        for mount in mounts {
+            let volume = json_str(mount, "Name");
+            let destination = json_str(mount, "Destination");
+            if json_str(mount, "Type") != "volume"
+                || volume.is_empty()
+                || mounted.iter().any(|d| d == destination)
+            {
+                continue;
+            }
+            let read_only = mount.get("RW").and_then(|v| v.as_bool()) == Some(false);
+            push_arg(
+                &mut args,
+                "-v",
+                if read_only {
+                    format!("{}:{}:ro", volume, destination)
+                } else {
+                    format!("{}:{}", volume, destination)
+                },
+            );
+        }
+    }
+    for (path, options) in json_pairs(host_config.get("Tmpfs"))
+        .iter()
+        .filter_map(|pair| pair.split_once('='))
+    {
+        push_arg(
+            &mut args,
+            "--tmpfs",
+            if options.is_empty() {
+                path.to_string()
+            } else {
+                format!("{}:{}", path, options)
+            },
+        );
+    }
+
+    // Restart policy
+    let restart_policy = host_config
+        .get("RestartPolicy")
+        .cloned()
+        .unwrap_or_default();
+    let restart_retries = restart_policy
+        .get("MaximumRetryCount")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    let restart = format_restart_policy(&format!(
+        "{}:{}",
+        json_str(&restart_policy, "Name"),
+        restart_retries
+    ));
+    if restart != "no" {
+        push_arg(&mut args, "--restart", restart);
+    }
+
+    // What the process runs as and where
+    for (key, flag) in [("User", "--user"), ("WorkingDir", "--workdir")] {
+        let value = json_str(&config, key);
+        if !value.is_empty() && value != json_str(image_config, key) {
+            push_arg(&mut args, flag, value.to_string());
+        }
+    }
+    let image_labels = json_pairs(image_config.get("Labels"));
+    for label in json_pairs(config.get("Labels")) {
+        if !image_labels.contains(&label) {
+            push_arg(&mut args, "--label", label);
+        }
+    }
+    let image_stop_signal = json_str(image_config, "StopSignal");
+    let stop_signal = json_str(&config, "StopSignal");
+    if !stop_signal.is_empty() && stop_signal != image_stop_signal {
+        push_arg(&mut args, "--stop-signal", stop_signal.to_string());
+    }
+    if let Some(timeout) = config.get("StopTimeout").and_then(|v| v.as_i64()) {
+        push_arg(&mut args, "--stop-timeout", timeout.to_string());
+    }
+
+    // Healthcheck, unless it's the image's own
+    let healthcheck = config.get("Healthcheck").cloned().unwrap_or_default();
+    if !healthcheck.is_null() && Some(&healthcheck) != image_config.get("Healthcheck") {
+        let test = json_strings(healthcheck.get("Test"));
+        match test.first().map(String::as_str) {
+            Some("NONE") => args.push("--no-healthcheck".to_string()),
+            Some("CMD-SHELL") if test.len() == 2 => {
+                push_arg(&mut args, "--health-cmd", test[1].clone());
+                for (key, flag) in [
+                    ("Interval", "--health-interval"),
+                    ("Timeout", "--health-timeout"),
+                    ("StartPeriod", "--health-start-period"),
+                ] {
+                    if let Some(nanos) = healthcheck.get(key).and_then(|v| v.as_u64()) {
+                        if nanos > 0 {
+                            push_arg(&mut args, flag, nanos_to_duration(nanos));
+                        }
+                    }
+                }
+                if let Some(retries) = healthcheck.get("Retries").and_then(|v| v.as_u64()) {
+                    if retries > 0 {
+                        push_arg(&mut args, "--health-retries", retries.to_string());
+                    }
+                }
+            }
+            // Exec-form checks have no docker run flag
+            _ => {
+                return Err(format!(
+                    "Container {} has a healthcheck that can't be recreated with docker run",
+                    name
+                ))
+            }
+        }
+    }
+
+    // Identity and terminal
+    let container_id = json_str(container, "Id");
+    let network_mode = host_config
+        .get("NetworkMode")
+        .and_then(|v| v.as_str())
+        .unwrap_or("default");
+    let own_network = network_mode != "host" && !network_mode.starts_with("container:");
+    let hostname = json_str(&config, "Hostname");
+    if own_network && !hostname.is_empty() && !container_id.starts_with(hostname) {
+        push_arg(&mut args, "--hostname", hostname.to_string());
+    }
+    let domainname = json_str(&config, "Domainname");
+    if !domainname.is_empty() {
+        push_arg(&mut args, "--domainname", domainname.to_string());
+    }
+    if config.get("Tty").and_then(|v| v.as_bool()) == Some(true) {
+        args.push("-t".to_string());
+    }
+    if config.get("OpenStdin").and_then(|v| v.as_bool()) == Some(true) {
+        args.push("-i".to_string());
+    }
+
+    // Privileges and isolation
+    for (key, flag) in [
+        ("Privileged", "--privileged"),
+        ("ReadonlyRootfs", "--read-only"),
+        ("Init", "--init"),
+        ("PublishAllPorts", "-P"),
+        ("AutoRemove", "--rm"),
+        ("OomKillDisable", "--oom-kill-disable"),
+    ] {
+        if host_config.get(key).and_then(|v| v.as_bool()) == Some(true) {
+            args.push(flag.to_string());
+        }
+    }
+    for (key, flag) in [
+        ("CapAdd", "--cap-add"),
+        ("CapDrop", "--cap-drop"),
+        ("SecurityOpt", "--security-opt"),
+        ("GroupAdd", "--group-add"),
+        ("ExtraHosts", "--add-host"),
+        ("Dns", "--dns"),
+        ("DnsOptions", "--dns-option"),
+        ("DnsSearch", "--dns-search"),
+    ] {
+        for value in json_strings(host_config.get(key)) {
+            push_arg(&mut args, flag, value);
+        }
+    }
+    if let Some(devices) = host_config.get("Devices").and_then(|v| v.as_array()) {
+        for device in devices {
+            push_arg(
+                &mut args,
+                "--device",
+                format!(
+                    "{}:{}:{}",
+                    json_str(device, "PathOnHost"),
+                    json_str(device, "PathInContainer"),
+                    json_str(device, "CgroupPermissions")
+                ),
+            );
+        }
+    }
+    for sysctl in json_pairs(host_config.get("Sysctls")) {
+        push_arg(&mut args, "--sysctl", sysctl);
+    }
+    let pid_mode = json_str(&host_config, "PidMode");
+    if !pid_mode.is_empty() {
+        push_arg(&mut args, "--pid", pid_mode.to_string());
+    }
+    // "private" and "shareable" are daemon defaults
+    let ipc_mode = json_str(&host_config, "IpcMode");
+    if ipc_mode == "host" || ipc_mode.starts_with("container:") {
+        push_arg(&mut args, "--ipc", ipc_mode.to_string());
+    }
+    let runtime = json_str(&host_config, "Runtime");
+    if !runtime.is_empty() && runtime != "runc" {
+        push_arg(&mut args, "--runtime", runtime.to_string());
+    }
+    let log_config = host_config.get("LogConfig").cloned().unwrap_or_default();
+    let log_driver = json_str(&log_config, "Type");
+    if !log_driver.is_empty() {
+        push_arg(&mut args, "--log-driver", log_driver.to_string());
+    }
+    for option in json_pairs(log_config.get("Config")) {
+        push_arg(&mut args, "--log-opt", option);
+    }
+
+    // Resource limits
+    let number = |key: &str| host_config.get(key).and_then(|v| v.as_i64()).unwrap_or(0);
+    let memory = number("Memory");
+    if memory > 0 {
+        push_arg(&mut args, "--memory", memory.to_string());
+    }
+    let memory_swap = number("MemorySwap");
+    // Docker defaults swap to twice the memory limit when only --memory is given
+    if memory > 0 && memory_swap != 0 && memory_swap != memory * 2 {
+        push_arg(&mut args, "--memory-swap", memory_swap.to_string());
+    }
+    for (key, flag) in [
+        ("MemoryReservation", "--memory-reservation"),
+        ("CpuShares", "--cpu-shares"),
+        ("PidsLimit", "--pids-limit"),
+    ] {
+        if number(key) > 0 {
+            push_arg(&mut args, flag, number(key).to_string());
+        }
+    }
+    let nano_cpus = number("NanoCpus");
+    if nano_cpus > 0 {
+        push_arg(&mut args, "--cpus", (nano_cpus as f64 / 1e9).to_string());
+    }
+    let shm_size = number("ShmSize");
+    if shm_size > 0 && shm_size as u64 != DEFAULT_SHM_SIZE {
+        push_arg(&mut args, "--shm-size", shm_size.to_string());
+    }
+    let ulimits = host_config.get("Ulimits").and_then(|v| v.as_array());
+    for ulimit in ulimits.into_iter().flatten() {
+        let limit = |key: &str| ulimit.get(key).and_then(|v| v.as_i64()).unwrap_or(0);
+        push_arg(
+            &mut args,
+            "--ulimit",
+            format!(
+                "{}={}:{}",
+                json_str(ulimit, "Name"),
+                limit("Soft"),
+                limit("Hard")
+            ),
+        );
+    }
+
+    // Networks: the primary one goes on `docker run`, the rest are connected later, each
+    // with the static addresses and aliases it was given (minus Docker's own aliases)
+    let networks_json = container
+        .get("NetworkSettings")
+        .and_then(|n| n.get("Networks"))
+        .and_then(|v| v.as_object())
+        .cloned()
+        .unwrap_or_default();
+    let mut networks: Vec<String> = networks_json.keys().cloned().collect();
+    networks.sort();
+    if let Some(pos) = networks.iter().position(|n| n == network_mode) {
+        let primary = networks.remove(pos);
+        networks.insert(0, primary);
+    }
+    let attachment = |network: &str, alias_flag: &str| -> Vec<String> {
+        let mut connect = Vec::new();
+        let Some(settings) = networks_json.get(network) else {
+            return connect;
+        };
+        let ipam = settings.get("IPAMConfig").cloned().unwrap_or_default();
+        for (key, flag) in [("IPv4Address", "--ip"), ("IPv6Address", "--ip6")] {
+            let address = json_str(&ipam, key);
+            if !address.is_empty() {
+                connect.push(flag.to_string());
+                connect.push(address.to_string());
+            }
+        }
+        for alias in json_strings(settings.get("Aliases")) {
+            if alias != name && !container_id.starts_with(alias.as_str()) {
+                connect.push(alias_flag.to_string());
+                connect.push(alias);
+            }
+        }
+        connect
+    };
+
+    let mut extra_networks = Vec::new();
+    if !own_network || network_mode == "none" {
+        push_arg(&mut args, "--network", network_mode.to_string());
+    } else {
+        let mut networks = networks.into_iter();
+        match networks.next() {
+            Some(primary) if primary != "bridge" && primary != "default" => {
+                let connect = attachment(&primary, "--network-alias");
+                push_arg(&mut args, "--network", primary);
+                args.extend(connect);
+            }
+            _ => {}
+        }
+        for network in networks {
+            let mut connect = attachment(&network, "--alias");
+            connect.push(network);
+            extra_networks.push(connect);
+        }
+    }
+
+    // Entrypoint and command. `--entrypoint` takes a single word and clears the image's
+    // Cmd, so the rest of a custom entrypoint and the full command follow the image.
+    let entrypoint = json_strings(config.get("Entrypoint"));
+    let cmd = json_strings(config.get("Cmd"));
+    let custom_entrypoint = entrypoint != json_strings(image_config.get("Entrypoint"));
+    let custom_cmd = cmd != json_strings(image_config.get("Cmd"));
+    if custom_entrypoint {
+        push_arg(
+            &mut args,
+            "--entrypoint",
+            entrypoint.first().cloned().unwrap_or_default(),
+        );
+    } else if custom_cmd && cmd.is_empty() {
+        return Err(format!(
+            "Container {} clears the image's command, which docker run can't do",
+            name
+        ));
+    }
+
+    args.push(image.to_string());
+    if custom_entrypoint {
+        args.extend(entrypoint.into_iter().skip(1));
+        args.extend(cmd);
+    } else if custom_cmd {
+        args.extend(cmd);
+    }
+
+    Ok(ContainerRunSpec {
+        run_args: args,
+        extra_networks,
+    })
+}
+
+#[tauri::command]
+pub async fn find_compose_files(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ComposeProject>, String> {
+    let (id, ssh_client) = state.connection(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+    if let Some(projects) = state.cache.get::<Vec<ComposeProject>>(&id, "compose").await {
+        return Ok(projects);
+    }
+
+    // Get current server profile to get server ID
+    // For now, use host as identifier
+    let server_id = client.get_host();
+
+    let projects = scan_compose_files(client, &state.compose_cache, &server_id).await?;
+    state.cache.put(&id, "compose", projects.clone()).await;
+    Ok(projects)
+}
+
+#[tauri::command]
+pub async fn refresh_compose_files(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ComposeProject>, String> {
+    let (id, ssh_client) = state.connection(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+    state.cache.invalidate_entry(&id, "compose").await;
+
+    let server_id = client.get_host();
+
+    refresh_compose_scan(client, &state.compose_cache, &server_id).await
+}
+
+#[tauri::command]
+pub async fn get_compose_cache_info(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<CacheInfo, String> {
+    let client = state.client(server_id.as_deref()).await?;
+    Ok(state.compose_cache.info(&client.get_host()).await)
+}
+
+/// Forget the last compose scan so the next lookup searches the server again
+#[tauri::command]
+pub async fn clear_compose_cache(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let (id, client) = state.connection(server_id.as_deref()).await?;
+    state.cache.invalidate_entry(&id, "compose").await;
+    state.compose_cache.invalidate(&client.get_host()).await;
+    Ok(())
+}
+
+/// Save a compose file, keeping the previous version if `docker compose config` rejects
+/// the new one, and optionally bring the project up with it.
+#[tauri::command]
+pub async fn save_compose_file(
+    path: String,
+    content: String,
+    redeploy: Option<bool>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ComposeSaveResult, String> {
+    let (id, ssh_client) = state.writable_connection(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    if !is_valid_compose_path(&path) {
+        return Err(format!("Invalid compose file path '{}'", path));
+    }
+
+    let result = write_compose_file(client, &path, &content, redeploy.unwrap_or(false));
+    // Whatever happened, the cached scan may no longer match the file on disk
+    state.compose_cache.invalidate(&client.get_host()).await;
+    state.cache.invalidate_entry(&id, "compose").await;
+    result
+}
+
+/// Set or unset environment variables of one compose service and redeploy the project.
+/// Secrets are refused for compose files other users can read.
+#[tauri::command]
+pub async fn update_compose_service_env(
+    project_path: String,
+    service: String,
+    changes: Vec<(String, Option<String>)>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ComposeSaveResult, String> {
+    let (id, ssh_client) = state.writable_connection(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    if !is_valid_compose_path(&project_path) {
+        return Err(format!("Invalid compose file path '{}'", project_path));
+    }
+    for (key, value) in &changes {
+        if key.is_empty() || key.contains(|c: char| c == '=' || c.is_whitespace()) {
+            return Err(format!("Invalid environment variable name '{}'", key));
+        }
+        if value.as_ref().is_some_and(|v| v.contains('\n')) {
+            return Err(format!("The value of {} can't span multiple lines", key));
+        }
+    }
+
+    let redactor = state.redactor.lock().await.clone();
+    let secret_keys: Vec<&str> = changes
+        .iter()
+        .filter(|(key, value)| {
+            value
+                .as_deref()
+                .is_some_and(|value| redactor.redact_value(key, value).is_some())
+        })
+        .map(|(key, _)| key.as_str())
+        .collect();
+    if !secret_keys.is_empty() {
+        let mode = client
+            .execute_command(&format!("stat -c %a {}", shell_quote(&project_path)))
+            .map_err(|e| e.message)?;
+        // Treat an unreadable mode as world-readable
+        let world_readable = match mode.trim().chars().last().and_then(|c| c.to_digit(8)) {
+            Some(others) => others & 4 != 0,
+            None => true,
+        };
+        if world_readable {
+            return Err(format!(
+                "Refusing to write {} into {}, which other users can read. \
+                 Restrict it with `chmod o-r` or move secrets to an env_file first.",
+                secret_keys.join(", "),
+                project_path
+            ));
+        }
+    }
+
+    let current = read_remote_file(client, &project_path)?;
+    let updated = update_service_environment(&current, &service, &changes)?;
+
+    let result = write_compose_file(client, &project_path, &updated, true);
+    state.compose_cache.invalidate(&client.get_host()).await;
+    state.cache.invalidate_entry(&id, "compose").await;
+    result
+}
+
+fn is_valid_compose_path(path: &str) -> bool {
+    path.starts_with('/') && !path.split('/').any(|part| part == "..")
+}
+
+/// Back up `path`, write `content`, and validate it with `docker compose config`. The
+/// backup is restored if validation fails; otherwise `redeploy` runs `up -d`.
+fn write_compose_file(
+    client: &SshClient,
+    path: &str,
+    content: &str,
+    redeploy: bool,
+) -> Result<ComposeSaveResult, String> {
+    let quoted_path = shell_quote(path);
+    let quoted_backup = shell_quote(&format!("{}.bak", path));
+
+    let previous = read_remote_file(client, path)?;
+    let (_, backup_status) = client
+        .execute_with_status(&format!("sudo cp -p {} {}", quoted_path, quoted_backup))
+        .map_err(|e| e.message)?;
+    if backup_status != 0 {
+        return Err(format!("Failed to back up {}", path));
+    }
+
+    let (_, write_status) = client
+        .execute_with_status(&format!(
+            "printf '%s' {} | sudo tee {} > /dev/null",
+            shell_quote(content),
+            quoted_path
+        ))
+        .map_err(|e| e.message)?;
+    if write_status != 0 {
+        return Err(format!("Failed to write {}", path));
+    }
+
+    let (output, exit_status) = client
+        .execute_with_status(&format!("docker compose -f {} config -q 2>&1", quoted_path))
+        .map_err(|e| e.message)?;
+    let validation = parse_compose_config_output(&output, exit_status);
+
+    if !validation.ok {
+        client
+            .execute_command(&format!("sudo cp -p {} {}", quoted_backup, quoted_path))
+            .map_err(|e| e.message)?;
+        return Ok(ComposeSaveResult {
+            message: format!(
+                "{} failed validation and was restored to its previous version",
+                path
+            ),
+            validation,
+            rolled_back: true,
+            redeployed: false,
+            redeploy_output: None,
+            diff: Vec::new(),
+        });
+    }
+
+    let diff = diff_lines(&previous, content);
+    if !redeploy {
+        return Ok(ComposeSaveResult {
+            message: format!("Saved {} and validated", path),
+            validation,
+            rolled_back: false,
+            redeployed: false,
+            redeploy_output: None,
+            diff,
+        });
+    }
+
+    let (redeploy_output, redeploy_status) = client
+        .execute_with_status(&format!("docker compose -f {} up -d 2>&1", quoted_path))
+        .map_err(|e| e.message)?;
+    let redeployed = redeploy_status == 0;
+
+    Ok(ComposeSaveResult {
+        message: if redeployed {
+            format!("Saved {} and redeployed", path)
+        } else {
+            format!("Saved {} but `docker compose up -d` failed", path)
+        },
+        validation,
+        rolled_back: false,
+        redeployed,
+        redeploy_output: Some(redeploy_output),
+        diff,
+    })
+}
+
+/// Parse `docker compose config -q` output. YAML errors carry a position such as
+/// `yaml: line 12: mapping values are not allowed in this context`.
+fn parse_compose_config_output(output: &str, exit_status: i32) -> ComposeValidation {
+    let errors = if exit_status == 0 {
+        Vec::new()
+    } else {
+        output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| ComposeConfigError {
+                message: line.to_string(),
+                line: find_number_after(line, "line "),
+                column: find_number_after(line, "column "),
+            })
+            .collect()
+    };
+
+    ComposeValidation {
+        ok: exit_status == 0,
+        errors,
+        output: output.to_string(),
+    }
+}
+
+fn find_number_after(text: &str, marker: &str) -> Option<u32> {
+    let start = text.find(marker)? + marker.len();
+    let digits: String = text[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+#[tauri::command]
+pub async fn get_container_logs_stream(
+    container_name: String,
+    lines: u32,
+    follow: bool,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let command = if follow {
+        format!(
+            "docker logs --tail {} --follow {} 2>&1",
+            lines, container_name
+        )
+    } else {
+        format!("docker logs --tail {} {} 2>&1", lines, container_name)
+    };
+
+    client.execute_command(&command).map_err(|e| e.message)
+}
+
+/// Print the `/exec/{id}/json` API response of each exec session of container `$1`, one per
+/// line. The docker CLI can't inspect exec ids, so the daemon socket is queried directly.
+/// Exits 3 when the container doesn't exist and 127 when curl is missing.
+const EXEC_SESSIONS_SCRIPT: &str = r#"ids=$(docker inspect --format '{{json .ExecIDs}}' "$1" 2>&1) || { echo "$ids"; exit 3; }
+command -v curl >/dev/null 2>&1 || exit 127
+sock=${DOCKER_HOST#unix://}
+if [ -z "$DOCKER_HOST" ] || [ "$sock" = "$DOCKER_HOST" ]; then sock=/var/run/docker.sock; fi
+for id in $(printf '%s' "$ids" | tr -d '[]"' | tr ',' ' '); do
+  [ "$id" = null ] && continue
+  curl -s --unix-socket "$sock" "http://localhost/exec/$id/json"; echo
+done"#;
+
+/// `sh -c` script taking an exec id, PID and container id: signals the PID only if the
+/// daemon still reports that exec as running it in that container, so a PID that was
+/// reused after the session ended is left alone
+const KILL_EXEC_SCRIPT: &str = r#"sock=${DOCKER_HOST#unix://}
+if [ -z "$DOCKER_HOST" ] || [ "$sock" = "$DOCKER_HOST" ]; then sock=/var/run/docker.sock; fi
+info=$(curl -s --unix-socket "$sock" "http://localhost/exec/$1/json") || { echo "Can't reach the Docker daemon"; exit 1; }
+has() { printf '%s' "$info" | grep -q "$1"; }
+has '"Running":true' && has "\"Pid\":$2[,}]" && has "\"ContainerID\":\"$3\"" || { echo 'The session has ended or no longer runs that process'; exit 3; }
+sudo kill -TERM "$2""#;
+
+/// Sessions started with `docker exec` in the container. `docker attach` clients aren't
+/// tracked by the daemon in a way that can be listed, so they don't appear.
+#[tauri::command]
+pub async fn get_container_exec_sessions(
+    container_name: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ExecSession>, String> {
+    if !is_valid_container_name(&container_name) {
+        return Err(format!("Invalid container name '{}'", container_name));
+    }
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let (sessions, _) = read_exec_sessions(ssh_client.as_ref(), &container_name)?;
+    Ok(sessions)
+}
+
+/// Stop a running exec session by signalling its process. The daemon has no call for
+/// ending an exec, so this is what `docker exec` users would otherwise do by hand.
+#[tauri::command]
+pub async fn kill_container_exec_session(
+    container_name: String,
+    exec_id: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    if !is_valid_container_name(&container_name) {
+        return Err(format!("Invalid container name '{}'", container_name));
+    }
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let (sessions, container_id) = read_exec_sessions(client, &container_name)?;
+    let session = sessions
+        .iter()
+        .find(|s| s.id == exec_id)
+        .ok_or_else(|| format!("No exec session {} in {}", exec_id, container_name))?;
+    let pid = match (session.running, session.pid) {
+        (true, Some(pid)) => pid,
+        _ => return Err(format!("Exec session {} has already finished", exec_id)),
+    };
+    run_action(
+        client,
+        &format!(
+            "sh -c {} _ {} {} {}",
+            shell_quote(KILL_EXEC_SCRIPT),
+            shell_quote(&session.id),
+            pid,
+            shell_quote(&container_id)
+        ),
+        format!("Sent SIGTERM to `{}` (pid {})", session.command, pid),
+        &format!("Failed to stop exec session {}", exec_id),
+    )
+}
+
+/// The container's exec sessions and its full id
+fn read_exec_sessions(
+    client: &SshClient,
+    container_name: &str,
+) -> Result<(Vec<ExecSession>, String), String> {
+    let (output, status) = client
+        .execute_with_status(&format!(
+            "sh -c {} _ {} 2>&1",
+            shell_quote(EXEC_SESSIONS_SCRIPT),
+            shell_quote(container_name)
+        ))
+        .map_err(|e| e.message)?;
+    match status {
+        0 => {}
+        127 => return Err("curl is needed to inspect exec sessions".to_string()),
+        _ => return Err(output.trim().to_string()),
+    }
+    let (container_id, sessions) = parse_exec_sessions(&output);
+    Ok((sessions, container_id.unwrap_or_default()))
+}
+
+/// `/exec/{id}/json` responses, one per line, with the id of the container they run in
+fn parse_exec_sessions(output: &str) -> (Option<String>, Vec<ExecSession>) {
+    let mut container_id = None;
+    let mut sessions = Vec::new();
+    for line in output.lines() {
+        let Ok(value) = serde_json::from_str::<JsonValue>(line.trim()) else {
+            continue;
+        };
+        let Some(id) = value.get("ID").and_then(JsonValue::as_str) else {
+            continue;
+        };
+        if container_id.is_none() {
+            container_id = value
+                .get("ContainerID")
+                .and_then(JsonValue::as_str)
+                .map(String::from);
+        }
+        let process = value.get("ProcessConfig");
+        let field = |name: &str| process.and_then(|p| p.get(name));
+        let mut command: Vec<&str> = field("entrypoint")
+            .and_then(JsonValue::as_str)
+            .into_iter()
+            .collect();
+        if let Some(arguments) = field("arguments").and_then(JsonValue::as_array) {
+            command.extend(arguments.iter().filter_map(JsonValue::as_str));
+        }
+        let running = value.get("Running").and_then(JsonValue::as_bool) == Some(true);
+        sessions.push(ExecSession {
+            id: id.to_string(),
+            command: command.join(" "),
+            running,
+            pid: value
+                .get("Pid")
+                .and_then(JsonValue::as_u64)
+                .filter(|pid| running && *pid > 0)
+                .map(|pid| pid as u32),
+            exit_code: if running {
+                None
+            } else {
+                value.get("ExitCode").and_then(JsonValue::as_i64)
+            },
+            user: field("user")
+                .and_then(JsonValue::as_str)
+                .filter(|u| !u.is_empty())
+                .map(String::from),
+            tty: field("tty").and_then(JsonValue::as_bool) == Some(true),
+            privileged: field("privileged").and_then(JsonValue::as_bool) == Some(true),
+        });
+    }
+    (container_id, sessions)
+}
+
+const DOCKER_EVENTS_STREAM: &str = "docker-events";
+
+#[tauri::command]
+pub async fn start_docker_events_stream(
+    server_id: Option<String>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let command = "docker events --format '{{json .}}' \
+        --filter type=container --filter type=image --filter type=network --filter type=volume";
+
+    let line_app = app.clone();
+    spawn_stream(
+        &state,
+        server_id.as_deref(),
+        DOCKER_EVENTS_STREAM,
+        command,
+        move |line| {
+            if let Some(event) = parse_docker_event(line) {
+                let _ = line_app.emit("docker-event", event);
+            }
+        },
+        move |stream_server, reason| {
+            // The daemon restarting ends `docker events`; let the UI fall back to polling
+            let _ = app.emit(
+                "docker-events-ended",
+                StreamEnded {
+                    stream: DOCKER_EVENTS_STREAM.to_string(),
+                    server_id: stream_server,
+                    reason,
+                },
+            );
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn stop_docker_events_stream(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    stop_stream(&state, server_id.as_deref(), DOCKER_EVENTS_STREAM).await;
+    Ok(())
+}
+
+const JOURNAL_STREAM: &str = "journal";
+
+#[tauri::command]
+pub async fn start_journal_stream(
+    unit: Option<String>,
+    priority: Option<String>,
+    grep: Option<String>,
+    server_id: Option<String>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    if let Some(priority) = &priority {
+        if !is_valid_journal_priority(priority) {
+            return Err(format!("Invalid log priority '{}'", priority));
+        }
+    }
+
+    {
+        let ssh_client = state.client(server_id.as_deref()).await?;
+        let (_, exit_status) = ssh_client
+            .execute_with_status(
+                "command -v journalctl >/dev/null 2>&1 && test -d /run/systemd/journal",
+            )
+            .map_err(|e| e.message)?;
+        if exit_status != 0 {
+            return Err("journald is not available on this server".to_string());
+        }
+    }
+
+    let mut command = String::from("journalctl -f -o json --no-pager");
+    if let Some(unit) = &unit {
+        command.push_str(&format!(" -u {}", shell_quote(unit)));
+    }
+    if let Some(priority) = &priority {
+        command.push_str(&format!(" -p {}", priority));
+    }
+    if let Some(grep) = &grep {
+        command.push_str(&format!(" -g {}", shell_quote(grep)));
+    }
+    command.push_str(" 2>&1");
+
+    let line_app = app.clone();
+    spawn_stream(
+        &state,
+        server_id.as_deref(),
+        JOURNAL_STREAM,
+        &command,
+        move |line| {
+            let entry = parse_journal_entry(line).unwrap_or_else(|| JournalEntry {
+                // journalctl reports bad filters as plain text
+                timestamp: 0,
+                unit: None,
+                priority: None,
+                message: line.to_string(),
+            });
+            let _ = line_app.emit("journal-line", entry);
+        },
+        move |stream_server, reason| {
+            let _ = app.emit(
+                "journal-stream-ended",
+                StreamEnded {
+                    stream: JOURNAL_STREAM.to_string(),
+                    server_id: stream_server,
+                    reason,
+                },
+            );
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn stop_journal_stream(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    stop_stream(&state, server_id.as_deref(), JOURNAL_STREAM).await;
+    Ok(())
+}
+
+/// Parse one `journalctl -o json` line. Binary messages come through as byte arrays.
+fn parse_journal_entry(line: &str) -> Option<JournalEntry> {
+    let value: JsonValue = serde_json::from_str(line).ok()?;
+    let field = |name: &str| value.get(name).and_then(|v| v.as_str());
+
+    let message = match value.get("MESSAGE")? {
+        JsonValue::String(message) => message.clone(),
+        JsonValue::Array(bytes) => {
+            let bytes: Vec<u8> = bytes
+                .iter()
+                .filter_map(|b| b.as_u64().map(|b| b as u8))
+                .collect();
+            String::from_utf8_lossy(&bytes).into_owned()
+        }
+        _ => return None,
+    };
+
+    Some(JournalEntry {
+        timestamp: field("__REALTIME_TIMESTAMP")
+            .and_then(|t| t.parse().ok())
+            .unwrap_or(0),
+        unit: field("_SYSTEMD_UNIT")
+            .or_else(|| field("SYSLOG_IDENTIFIER"))
+            .map(String::from),
+        priority: field("PRIORITY").and_then(|p| p.parse().ok()),
+        message,
+    })
+}
+
+/// Run `command` on a dedicated connection to `server_id` in a background thread, passing
+/// each output line to `on_line`. `on_end` gets the server id and the reason once the stream
+/// finishes on its own; it isn't called when the stream is stopped. Starting a stream that
+/// is already running on the same server replaces it.
+async fn spawn_stream(
+    state: &AppState,
+    server_id: Option<&str>,
+    name: &str,
+    command: &str,
+    on_line: impl FnMut(&str) + Send + 'static,
+    on_end: impl FnOnce(String, String) + Send + 'static,
+) -> Result<(), String> {
+    let (server_id, client) = state.connection(server_id).await?;
+    let stream_client = client.open_dedicated().map_err(|e| e.message)?;
+    let id = stream_id(&server_id, name);
+    let stop = state.streams.register_stream(&id);
+
+    let streams = Arc::clone(&state.streams);
+    let command = command.to_string();
+    std::thread::spawn(move || {
+        let result = stream_client.stream_lines(&command, &stop, on_line);
+        stream_client.disconnect();
+        streams.finish_stream(&id, &stop);
+
+        let reason = match result {
+            Ok(None) => return,
+            Ok(Some(status)) => format!("Stream exited with status {}", status),
+            Err(e) => e.message,
+        };
+        on_end(server_id, reason);
+    });
+
+    Ok(())
+}
+
+async fn stop_stream(state: &AppState, server_id: Option<&str>, name: &str) {
+    let server_id = match server_id {
+        Some(id) => id.to_string(),
+        None => match state.connections.lock().await.active.clone() {
+            Some(id) => id,
+            None => return,
+        },
+    };
+    state.streams.stop_stream(&stream_id(&server_id, name));
+}
+
+/// Ids of the streams currently running, as `{server id}/{stream}`
+#[tauri::command]
+pub async fn list_running_streams(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    Ok(state.streams.running_streams())
+}
+
+/// Stop every running stream on every server, e.g. when the UI leaves the views that
+/// started them. Returns how many were stopped.
+#[tauri::command]
+pub async fn stop_all_streams(state: State<'_, AppState>) -> Result<usize, String> {
+    Ok(state.streams.stop_all_streams())
+}
+
+/// Parse one `docker events --format '{{json .}}'` line
+fn parse_docker_event(line: &str) -> Option<DockerEvent> {
+    let value: JsonValue = serde_json::from_str(line).ok()?;
+    let event_type = value.get("Type")?.as_str()?;
+    if !matches!(event_type, "container" | "image" | "network" | "volume") {
+        return None;
+    }
+    let actor = value.get("Actor");
+
+    Some(DockerEvent {
+        action: value.get("Action")?.as_str()?.to_string(),
+        actor_id: actor
+            .and_then(|a| a.get("ID"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        actor_name: actor
+            .and_then(|a| a.get("Attributes"))
+            .and_then(|a| a.get("name"))
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        event_type: event_type.to_string(),
+        time: value.get("time").and_then(|v| v.as_i64()).unwrap_or(0),
+    })
+}
+
+#[tauri::command]
+pub async fn get_ufw_overview(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<UfwOverview, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    // Get UFW status verbose
+    let status_output = client
+        .execute_command(UFW_STATUS_COMMAND)
+        .map_err(|e| e.message)?;
+
+    // Get UFW numbered for rule numbers
+    let _numbered_output = client
+        .execute_command("sudo ufw status numbered 2>&1")
+        .map_err(|e| e.message)?;
+
+    // Get listening ports
+    let listening_output = client
+        .execute_command(LISTENING_PORTS_COMMAND)
+        .unwrap_or_default();
+
+    let default_ufw = client
+        .execute_command(UFW_DEFAULTS_COMMAND)
+        .unwrap_or_default();
+
+    let docker_exposures = collect_docker_exposures(client, &status_output);
+
+    Ok(build_ufw_overview(
+        &status_output,
+        &listening_output,
+        &default_ufw,
+        docker_exposures,
+    ))
+}
+
+const UFW_STATUS_COMMAND: &str = "sudo ufw status verbose 2>&1";
+/// TCP and UDP listeners; process details for other users' sockets need root
+const LISTENING_PORTS_COMMAND: &str =
+    "{ sudo -n ss -tulnp 2>/dev/null || ss -tulnp 2>&1; } | tail -n +2";
+const UFW_DEFAULTS_COMMAND: &str = "grep -E '^IPV6=' /etc/default/ufw 2>/dev/null";
+
+fn build_ufw_overview(
+    status_output: &str,
+    listening_output: &str,
+    default_ufw: &str,
+    docker_exposures: Vec<DockerExposure>,
+) -> UfwOverview {
+    let mut open_ports: Vec<PortInfo> = Vec::new();
+    let mut blocked_ports: Vec<PortInfo> = Vec::new();
+    let mut total_rules = 0u32;
+    let mut allow_rules = 0u32;
+    let mut deny_rules = 0u32;
+    let mut limit_rules = 0u32;
+
+    let mut listening_ports_map: HashMap<String, String> = HashMap::new();
+    for listener in parse_listening_ports(listening_output) {
+        if let Some(process) = listener.service_name {
+            listening_ports_map.entry(listener.port).or_insert(process);
+        }
+    }
+
+    let active = ufw_is_active(status_output);
+    let all_rules = parse_ufw_rules(status_output);
+
+    for rule in &all_rules {
+        // Extract port and protocol
+        let port = rule.port.clone().unwrap_or_default();
+        let protocol = rule
+            .rule
+            .split('/')
+            .nth(1)
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "any".to_string());
+
+        // Get service name if port is listening
+        let service_name = listening_ports_map.get(&port).cloned();
+
+        let port_info = PortInfo {
+            port,
+            protocol,
+            action: rule.action.clone(),
+            source: rule.from.clone(),
+            service_name,
+            address: None,
+            ip_version: None,
+            pid: None,
+            processes: Vec::new(),
+        };
+
+        let action = rule.action.to_uppercase();
+        if action.contains("ALLOW") {
+            open_ports.push(port_info);
+            allow_rules += 1;
+        } else if action.contains("DENY") {
+            blocked_ports.push(port_info);
+            deny_rules += 1;
+        } else if action.contains("LIMIT") {
+            open_ports.push(port_info); // LIMIT is also a form of allowing
+            limit_rules += 1;
+        }
+
+        total_rules += 1;
+    }
+
+    let ipv6 = build_ipv6_coverage(&all_rules, default_ufw);
+
+    UfwOverview {
+        active,
+        open_ports,
+        blocked_ports,
+        all_rules,
+        stats: UfwStats {
+            total_rules,
+            allow_rules,
+            deny_rules,
+            limit_rules,
+        },
+        docker_exposures,
+        ipv6,
+    }
+}
+
+/// Summarise how well the rules cover IPv6, given the `IPV6=` line of /etc/default/ufw
+fn build_ipv6_coverage(rules: &[UfwRule], default_ufw: &str) -> Ipv6Coverage {
+    let ipv6_enabled = default_ufw
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("IPV6="))
+        .map(|v| v.trim().trim_matches('"').eq_ignore_ascii_case("yes"));
+
+    let count = |version: IpVersion| rules.iter().filter(|r| r.ip_version == version).count();
+    let v4_only_targets: Vec<String> = rules
+        .iter()
+        .filter(|r| r.ip_version == IpVersion::V4 && r.from == "Anywhere")
+        .map(|r| format!("{} {} {}", r.action, r.direction.as_str(), r.rule))
+        .collect();
+
+    let warning = if ipv6_enabled == Some(false) {
+        Some("IPV6=no in /etc/default/ufw: UFW doesn't filter IPv6 traffic at all".to_string())
+    } else if !v4_only_targets.is_empty() {
+        Some(format!(
+            "{} rule(s) only apply to IPv4: {}",
+            v4_only_targets.len(),
+            v4_only_targets.join(", ")
+        ))
+    } else {
+        None
+    };
+
+    Ipv6Coverage {
+        ipv6_enabled,
+        v4_only_rules: count(IpVersion::V4) as u32,
+        v6_only_rules: count(IpVersion::V6) as u32,
+        dual_stack_rules: count(IpVersion::Both) as u32,
+        v4_only_targets,
+        warning,
+    }
+}
+
+#[tauri::command]
+pub async fn get_docker_ufw_exposure(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<DockerExposure>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let status_output = client
+        .execute_command("sudo ufw status verbose 2>&1")
+        .map_err(|e| e.message)?;
+
+    Ok(collect_docker_exposures(client, &status_output))
+}
+
+/// For each container port published on all interfaces, either a `ufw route allow` rule
+/// admitting only `source` (the address of this SSH session by default) or, when Docker
+/// bypasses UFW or UFW is off, a warning that the port is open to everyone
+#[tauri::command]
+pub async fn suggest_ufw_rules_for_containers(
+    source: Option<String>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SuggestedRule>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let source = match source
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+    {
+        Some(source) if is_valid_cidr(&source) => Some(source),
+        Some(source) => return Err(format!("Invalid source address '{}'", source)),
+        None => client
+            .execute_command("echo \"$SSH_CLIENT\"")
+            .map_err(|e| e.message)?
+            .split_whitespace()
+            .next()
+            .map(String::from),
+    };
+    let status_output = client
+        .execute_command("sudo ufw status verbose 2>&1")
+        .map_err(|e| e.message)?;
+    let exposures = collect_docker_exposures(client, &status_output);
+    Ok(build_suggested_rules(
+        &exposures,
+        ufw_is_active(&status_output),
+        source.as_deref(),
+    ))
+}
+
+/// Apply an `allow_from_source` suggestion. The command is rebuilt from the rule's fields
+/// rather than taken from `command`, so only a well-formed route rule can run.
+#[tauri::command]
+pub async fn apply_suggested_rule(
+    rule: SuggestedRule,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    if rule.kind != "allow_from_source" {
+        return Err(format!(
+            "Only allow_from_source suggestions can be applied, not '{}'",
+            rule.kind
+        ));
+    }
+    let source = rule
+        .source
+        .as_deref()
+        .ok_or("The suggestion has no source address")?;
+    let command = build_route_allow_command(source, rule.container_port, &rule.protocol)?;
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+
+    run_action(
+        ssh_client.as_ref(),
+        &command,
+        format!(
+            "{} can now reach {} port {}/{}",
+            source, rule.container, rule.container_port, rule.protocol
+        ),
+        "Failed to add the rule",
+    )
+}
+
+fn build_suggested_rules(
+    exposures: &[DockerExposure],
+    ufw_active: bool,
+    source: Option<&str>,
+) -> Vec<SuggestedRule> {
+    exposures
+        .iter()
+        .filter(|e| e.host_ip == "0.0.0.0" || e.host_ip == "::")
+        .map(|e| {
+            let port = format!("{}/{}", e.host_port, e.protocol);
+            let rule = |kind: &str, source: Option<&str>, message: String, command| SuggestedRule {
+                container: e.container.clone(),
+                host_port: e.host_port,
+                container_port: e.container_port,
+                protocol: e.protocol.clone(),
+                kind: kind.to_string(),
+                source: source.map(String::from),
+                message,
+                command,
+            };
+            if !ufw_active {
+                return rule(
+                    "world_exposed",
+                    None,
+                    format!(
+                        "{} publishes {} to everyone and UFW is inactive",
+                        e.container, port
+                    ),
+                    None,
+                );
+            }
+            if e.bypasses_ufw {
+                return rule(
+                    "world_exposed",
+                    None,
+                    format!(
+                        "{} publishes {} to everyone and Docker bypasses UFW; bind it to \
+                         127.0.0.1 or install the ufw-docker rules so it can be filtered",
+                        e.container, port
+                    ),
+                    None,
+                );
+            }
+            match source.map(|s| {
+                (
+                    s,
+                    build_route_allow_command(s, e.container_port, &e.protocol),
+                )
+            }) {
+                Some((source, Ok(command))) => rule(
+                    "allow_from_source",
+                    Some(source),
+                    format!("Allow only {} to reach {} on {}", source, e.container, port),
+                    Some(command),
+                ),
+                _ => rule(
+                    "world_exposed",
+                    None,
+                    format!(
+                        "{} publishes {}; pass a source address to get a rule limiting it",
+                        e.container, port
+                    ),
+                    None,
+                ),
+            }
+        })
+        .collect()
+}
+
+/// `ufw route allow` for forwarded traffic to a container port, as the ufw-docker rules
+/// expect; plain `ufw allow` rules don't apply to Docker's forwarded ports
+fn build_route_allow_command(source: &str, port: u16, protocol: &str) -> Result<String, String> {
+    if !is_valid_cidr(source) {
+        return Err(format!("Invalid source address '{}'", source));
+    }
+    if port == 0 {
+        return Err("Invalid port 0".to_string());
+    }
+    if protocol != "tcp" && protocol != "udp" {
+        return Err(format!(
+            "Invalid protocol '{}', expected tcp or udp",
+            protocol
+        ));
+    }
+    Ok(format!(
+        "sudo ufw route allow proto {} from {} to any port {}",
+        protocol, source, port
+    ))
+}
+
+/// Docker publishes ports through its own iptables chains, which run before UFW's, so a
+/// UFW deny does nothing for them unless DOCKER-USER hands traffic back to UFW the way
+/// `ufw-docker` sets it up.
+fn collect_docker_exposures(client: &SshClient, ufw_status: &str) -> Vec<DockerExposure> {
+    let ps_output = client
+        .execute_command(PUBLISHED_PORTS_COMMAND)
+        .unwrap_or_default();
+    let docker_user_chain = client
+        .execute_command(DOCKER_USER_CHAIN_COMMAND)
+        .unwrap_or_default();
+    build_docker_exposures(
+        &ps_output,
+        ufw_status,
+        ufw_is_active(ufw_status),
+        &docker_user_chain,
+    )
+}
+
+const PUBLISHED_PORTS_COMMAND: &str = "docker ps --format '{{.Names}}\t{{.Ports}}' 2>/dev/null";
+const DOCKER_USER_CHAIN_COMMAND: &str = "sudo iptables -S DOCKER-USER 2>/dev/null";
+
+fn ufw_is_active(ufw_status: &str) -> bool {
+    ufw_status
+        .lines()
+        .any(|l| l.trim().starts_with("Status:") && l.contains("active") && !l.contains("inactive"))
+}
+
+fn build_docker_exposures(
+    ps_output: &str,
+    ufw_status: &str,
+    ufw_active: bool,
+    docker_user_chain: &str,
+) -> Vec<DockerExposure> {
+    let ufw_docker_fix = docker_user_chain.contains("ufw-user-forward");
+
+    let mut exposures = Vec::new();
+    for line in ps_output.lines() {
+        let Some((container, ports)) = line.split_once('\t') else {
+            continue;
+        };
+        for (host_ip, host_port, container_port, protocol) in parse_published_ports(ports) {
+            // Loopback bindings are never reachable from outside
+            if host_ip.starts_with("127.") || host_ip == "::1" {
+                continue;
+            }
+            let ufw_action = ufw_action_for_port(ufw_status, host_port, &protocol);
+            let bypasses_ufw = !ufw_docker_fix;
+
+            // Only warn when UFW is meant to be filtering and doesn't explicitly allow it
+            let allowed = matches!(&ufw_action, Some(a) if a.to_uppercase().starts_with("ALLOW"));
+            let warning = if bypasses_ufw && ufw_active && !allowed {
+                Some(format!(
+                    "Container {} publishes {}/{} which UFW cannot block",
+                    container, host_port, protocol
+                ))
+            } else {
+                None
+            };
+            let suggestion = warning.as_ref().map(|_| {
+                format!(
+                    "Bind it to 127.0.0.1 (-p 127.0.0.1:{}:{}) or install the ufw-docker rules \
+                     and use `ufw route allow proto {} from any to any port {}`",
+                    host_port, container_port, protocol, container_port
+                )
+            });
+
+            exposures.push(DockerExposure {
+                container: container.to_string(),
+                host_ip,
+                host_port,
+                container_port,
+                protocol,
+                ufw_action,
+                bypasses_ufw,
+                warning,
+                suggestion,
+            });
+        }
+    }
+
+    // The same port is usually listed once for IPv4 and once for IPv6
+    exposures.dedup_by(|a, b| {
+        a.container == b.container
+            && a.host_port == b.host_port
+            && a.container_port == b.container_port
+            && a.protocol == b.protocol
+    });
+    exposures
+}
+
+/// Parse the `docker ps` ports column, e.g. `0.0.0.0:8080->80/tcp, :::8080->80/tcp, 443/tcp`.
+/// Ranges are expanded to one mapping per port; ports that are only exposed, not
+/// published, get an empty host IP and port.
+pub(crate) fn parse_docker_ports(ports: &str) -> Vec<PortMapping> {
+    let mut mappings = Vec::new();
+    for entry in ports.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (host, target) = match entry.split_once("->") {
+            Some((host, target)) => (Some(host), target),
+            None => (None, entry),
+        };
+        let (container_ports, protocol) = target.split_once('/').unwrap_or((target, "tcp"));
+        let Some((container_start, container_end)) = parse_port_range(container_ports) else {
+            continue;
+        };
+
+        match host.and_then(|h| h.rsplit_once(':')) {
+            Some((host_ip, host_ports)) => {
+                let host_ip = match host_ip.trim_matches(|c| c == '[' || c == ']') {
+                    "" | "::" => "::",
+                    ip => ip,
+                };
+                // Ranges like `8000-8002->8000-8002/tcp` map one to one
+                let Some((host_start, host_end)) = parse_port_range(host_ports) else {
+                    continue;
+                };
+                for offset in 0..=(host_end - host_start) {
+                    mappings.push(PortMapping {
+                        host_ip: host_ip.to_string(),
+                        host_port: (host_start + offset).to_string(),
+                        container_port: container_start.saturating_add(offset).to_string(),
+                        protocol: protocol.to_string(),
+                    });
+                }
+            }
+            None => {
+                for port in container_start..=container_end {
+                    mappings.push(PortMapping {
+                        host_ip: String::new(),
+                        host_port: String::new(),
+                        container_port: port.to_string(),
+                        protocol: protocol.to_string(),
+                    });
+                }
+            }
+        }
+    }
+    mappings
+}
+
+/// Published ports from a `docker ps` ports column as
+/// `(host_ip, host_port, container_port, protocol)`
+fn parse_published_ports(ports: &str) -> Vec<(String, u16, u16, String)> {
+    parse_docker_ports(ports)
+        .into_iter()
+        .filter_map(|m| {
+            Some((
+                m.host_ip,
+                m.host_port.parse().ok()?,
+                m.container_port.parse().ok()?,
+                m.protocol,
+            ))
+        })
+        .collect()
+}
+
+fn parse_port_range(range: &str) -> Option<(u16, u16)> {
+    match range.split_once('-') {
+        Some((start, end)) => {
+            let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+            if start <= end {
+                Some((start, end))
+            } else {
+                None
+            }
+        }
+        None => range.parse().ok().map(|p| (p, p)),
+    }
+}
+
+/// Action of the first `ufw status` rule for `port` (with or without a protocol)
+fn ufw_action_for_port(ufw_status: &str, port: u16, protocol: &str) -> Option<String> {
+    let port = port.to_string();
+    let with_proto = format!("{}/{}", port, protocol);
+    parse_ufw_rules(ufw_status)
+        .into_iter()
+        .find(|rule| rule.rule == port || rule.rule == with_proto)
+        .map(|rule| rule.action)
+}
+
+#[tauri::command]
+pub async fn get_listening_ports(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<PortInfo>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let output = client
+        .execute_command(LISTENING_PORTS_COMMAND)
+        .map_err(|e| e.message)?;
+    Ok(parse_listening_ports(&output))
+}
+
+/// Rules as netfilter holds them, below UFW. Docker's chains show up here, which is
+/// where its forwarded traffic escapes UFW's rules. iptables tables default to filter
+/// and nat; nftables lists every table unless `table` names one (`filter` or
+/// `inet filter`). Reading the rules needs root, so passwordless sudo is required.
+#[tauri::command]
+pub async fn get_firewall_raw(
+    table: Option<String>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<FirewallDump, String> {
+    let table = table
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+    let iptables_tables: Vec<&str> = match table.as_deref() {
+        Some(t) if IPTABLES_TABLES.contains(&t) => vec![t],
+        _ => vec!["filter", "nat"],
+    };
+
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    // Run as root throughout: both tools live in sbin, which a user's PATH may lack
+    let script = format!(
+        "if command -v iptables >/dev/null 2>&1; then \
+             echo '===IPTABLES==='; iptables -V 2>&1; \
+             for t in {}; do echo \"===TABLE $t===\"; iptables -t \"$t\" -S 2>&1; done; \
+         fi; \
+         if command -v nft >/dev/null 2>&1; then echo '===NFT==='; nft list ruleset 2>&1; fi; true",
+        iptables_tables.join(" ")
+    );
+    let (output, exit_status) = ssh_client
+        .execute_with_status(&format!(
+            "sudo -n true 2>/dev/null || exit 4; sudo -n sh -c {}",
+            shell_quote(&script)
+        ))
+        .map_err(|e| e.message)?;
+    if exit_status == 4 {
+        return Err(
+            "Reading firewall rules needs root, and passwordless sudo isn't available".to_string(),
+        );
+    }
+
+    let sections = split_sections(&output);
+    let backend = detect_backend(
+        sections.get("IPTABLES").map(String::as_str),
+        sections.get("NFT").map(String::as_str),
+    )
+    .ok_or("Neither iptables nor nft is installed")?;
+
+    let chains = match backend {
+        FirewallBackend::Iptables => {
+            if let Some(t) = table.as_deref().filter(|t| !IPTABLES_TABLES.contains(t)) {
+                return Err(format!("Unknown iptables table '{}'", t));
+            }
+            iptables_tables
+                .iter()
+                .flat_map(|t| {
+                    let rules = sections
+                        .get(&format!("TABLE {}", t))
+                        .map(String::as_str)
+                        .unwrap_or_default();
+                    parse_iptables_rules(t, rules)
+                })
+                .collect()
+        }
+        FirewallBackend::Nftables => {
+            let ruleset = sections.get("NFT").map(String::as_str).unwrap_or_default();
+            parse_nft_ruleset(ruleset)
+                .into_iter()
+                .filter(|chain| match table.as_deref() {
+                    Some(t) => chain.table == t || chain.table.split_whitespace().nth(1) == Some(t),
+                    None => true,
+                })
+                .collect()
+        }
+    };
+    Ok(FirewallDump { backend, chains })
+}
+
+/// Rows of `ss -tulnp` without the header, ordered by port. Sockets bound to the same
+/// address and port (SO_REUSEPORT workers) are merged into one entry.
+/// `tcp LISTEN 0 511 0.0.0.0:80 0.0.0.0:* users:(("nginx",pid=1002,fd=6),("nginx",pid=1001,fd=6))`
+fn parse_listening_ports(output: &str) -> Vec<PortInfo> {
+    let mut ports: Vec<PortInfo> = Vec::new();
+    for line in output.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 5 || !matches!(parts[0], "tcp" | "udp") {
+            continue;
+        }
+        let local = parts[4];
+        let Some((raw_address, port)) = local.rsplit_once(':') else {
+            continue;
+        };
+        if port.is_empty() || !port.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        let ip_version = if raw_address == "*" {
+            IpVersion::Both
+        } else if raw_address.starts_with('[') {
+            IpVersion::V6
+        } else {
+            IpVersion::V4
+        };
+        let address = parse_socket_endpoint(local)
+            .map(|endpoint| endpoint.address)
+            .unwrap_or_default();
+        // Drop the `%lo` scope of interface-bound sockets
+        let address = address.split('%').next().unwrap_or_default().to_string();
+        let processes = parse_ss_processes(&parts[5..].join(" "));
+
+        if let Some(existing) = ports.iter_mut().find(|p| {
+            p.protocol == parts[0]
+                && p.port == port
+                && p.address.as_deref() == Some(address.as_str())
+                && p.ip_version == Some(ip_version)
+        }) {
+            for process in processes {
+                if !existing.processes.contains(&process) {
+                    existing.processes.push(process);
+                }
+            }
+            continue;
+        }
+
+        ports.push(PortInfo {
+            port: port.to_string(),
+            protocol: parts[0].to_string(),
+            action: "listening".to_string(),
+            source: address.clone(),
+            service_name: processes.first().map(|p| p.name.clone()),
+            address: Some(address),
+            ip_version: Some(ip_version),
+            pid: processes.first().map(|p| p.pid),
+            processes,
+        });
+    }
+    ports.sort_by_key(|p| p.port.parse::<u32>().unwrap_or(u32::MAX));
+    ports
+}
+
+/// Every `("name",pid=N,fd=M)` entry of an ss `users:(...)` column, once per pid
+fn parse_ss_processes(field: &str) -> Vec<SocketProcess> {
+    let Some((_, users)) = field.split_once("users:(") else {
+        return Vec::new();
+    };
+    let mut processes: Vec<SocketProcess> = Vec::new();
+    for entry in users.split("(\"").skip(1) {
+        let Some((name, rest)) = entry.split_once("\",") else {
+            continue;
+        };
+        let pid = rest
+            .split("pid=")
+            .nth(1)
+            .and_then(|s| s.split([',', ')']).next())
+            .and_then(|p| p.parse().ok());
+        if let Some(pid) = pid {
+            if !processes.iter().any(|p| p.pid == pid) {
+                processes.push(SocketProcess {
+                    name: name.to_string(),
+                    pid,
+                });
+            }
+        }
+    }
+    processes
+}
+
+/// TCP and UDP sockets in any state, with their owning process. Process details for other
+/// users' sockets need root, so `sudo -n` is tried before plain `ss`.
+#[tauri::command]
+pub async fn get_network_connections(
+    state_filter: Option<String>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<NetworkConnection>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let output = client
+        .execute_command("{ sudo -n ss -tunap 2>/dev/null || ss -tunap 2>&1; } | tail -n +2")
+        .map_err(|e| e.message)?;
+    let mut connections = parse_network_connections(&output);
+
+    if let Some(filter) = state_filter
+        .as_deref()
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+    {
+        let filter = match filter.to_uppercase().replace('_', "-").as_str() {
+            "ESTABLISHED" => "ESTAB".to_string(),
+            other => other.to_string(),
+        };
+        connections.retain(|c| c.state == filter);
+    }
+    Ok(connections)
+}
+
+/// Rows of `ss -tunap` without the header:
+/// `tcp ESTAB 0 0 [2001:db8::1]:22 [2001:db8::2]:40312 users:(("sshd",pid=812,fd=4))`
+fn parse_network_connections(output: &str) -> Vec<NetworkConnection> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() < 6 || !matches!(parts[0], "tcp" | "udp") {
+                return None;
+            }
+            let process_info = parts[6..].join(" ");
+            let process = process_info
+                .split("((\"")
+                .nth(1)
+                .and_then(|s| s.split('"').next())
+                .map(String::from);
+            let pid = process_info
+                .split("pid=")
+                .nth(1)
+                .and_then(|s| s.split([',', ')']).next())
+                .and_then(|p| p.parse().ok());
+            Some(NetworkConnection {
+                proto: parts[0].to_string(),
+                state: parts[1].to_string(),
+                local: parse_socket_endpoint(parts[4])?,
+                remote: parse_socket_endpoint(parts[5])?,
+                pid,
+                process,
+            })
+        })
+        .collect()
+}
+
+/// `addr:port` as ss prints it: IPv6 in brackets, `*` for wildcards, `%iface` scopes.
+/// IPv4-mapped IPv6 addresses are reported as plain IPv4.
+fn parse_socket_endpoint(value: &str) -> Option<SocketEndpoint> {
+    let (address, port) = value.rsplit_once(':')?;
+    let address = address.trim_start_matches('[').trim_end_matches(']');
+    let address = address
+        .strip_prefix("::ffff:")
+        .filter(|v4| v4.contains('.'))
+        .unwrap_or(address);
+    Some(SocketEndpoint {
+        address: address.to_string(),
+        port: port.parse().ok(),
+    })
+}
+
+/// Denies all traffic from `ip` (an address or CIDR) ahead of any allow rules. Refuses
+/// anything covering the address this SSH session comes from, which would cut DPanel off.
+#[tauri::command]
+pub async fn block_remote_ip(
+    ip: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    let ip = ip.trim();
+    if !is_valid_cidr(ip) {
+        return Err(format!("Invalid IP address '{}'", ip));
+    }
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let session_ip = client
+        .execute_command("echo \"$SSH_CLIENT\"")
+        .map_err(|e| e.message)?;
+    if let Some(session_ip) = session_ip
+        .split_whitespace()
+        .next()
+        .filter(|session_ip| cidr_contains(ip, session_ip))
+    {
+        return Err(format!(
+            "{} covers {}, the address of this SSH session; blocking it would lock DPanel out",
+            ip, session_ip
+        ));
+    }
+
+    // `insert 1` needs an existing rule to go before, so an empty ruleset gets a plain
+    // deny. When the first rule is the other IP version ufw refuses the insert, and that
+    // is reported rather than appending the deny after the allow rules.
+    let command = format!(
+        "if sudo ufw show added | grep -q '^ufw '; then sudo ufw insert 1 deny from {0}; else sudo ufw deny from {0}; fi",
+        shell_quote(ip)
+    );
+    run_action(
+        client,
+        &command,
+        format!("Traffic from {} is now blocked", ip),
+        &format!("Failed to block {}", ip),
+    )
+}
+
+/// Hostnames and IPv4/IPv6 literals; rules out anything ping or getent would read as an option
+fn is_valid_network_target(target: &str) -> bool {
+    !target.is_empty()
+        && target.len() <= 253
+        && !target.starts_with('-')
+        && target
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':' | '_' | '%'))
+}
+
+/// Counters from ping's summary, in both the iputils form
+/// ("4 packets transmitted, 3 received, +1 errors, 25% packet loss, time 3004ms") and the
+/// busybox one ("4 packets transmitted, 3 packets received, 25% packet loss"), plus the
+/// average from the "rtt min/avg/max/mdev = ..." or "round-trip min/avg/max = ..." line
+fn parse_ping_summary(output: &str) -> Option<(u32, u32, f64, Option<f64>)> {
+    let summary = output.lines().find(|l| l.contains("packets transmitted"))?;
+    let mut sent = None;
+    let mut received = None;
+    let mut loss = None;
+    for part in summary.split(',') {
+        let part = part.trim();
+        let number = part.split_whitespace().next().unwrap_or("");
+        if part.ends_with("transmitted") {
+            sent = number.parse().ok();
+        } else if part.ends_with("received") {
+            received = number.parse().ok();
+        } else if part.ends_with("packet loss") {
+            loss = number.trim_end_matches('%').parse().ok();
+        }
+    }
+
+    let avg_ms = output
+        .lines()
+        .find(|l| l.contains("min/avg/max"))
+        .and_then(|l| l.split_once('=').map(|(_, values)| values))
+        .and_then(|values| values.trim().split('/').nth(1))
+        .and_then(|avg| avg.trim().parse().ok());
+
+    Some((sent?, received?, loss?, avg_ms))
+}
+
+/// Ping `target` from the server, to check its outbound connectivity
+#[tauri::command]
+pub async fn remote_ping(
+    target: String,
+    count: Option<u32>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<PingResult, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let target = target.trim().to_string();
+    if !is_valid_network_target(&target) {
+        return Err(format!("Invalid host: {}", target));
+    }
+    let count = count.unwrap_or(4).clamp(1, 20);
+    let family = if target.parse::<std::net::Ipv6Addr>().is_ok() {
+        " -6"
+    } else {
+        ""
+    };
+    let ping = format!("ping{} -c {} -W 2 {} 2>&1", family, count, target);
+
+    let (mut output, _) = client.execute_with_status(&ping).map_err(|e| e.message)?;
+    // Without cap_net_raw or ping_group_range access, ping can't open its socket
+    let mut used_sudo = false;
+    let lower = output.to_lowercase();
+    if lower.contains("operation not permitted") || lower.contains("permission denied") {
+        output = client
+            .execute_with_status(&format!("sudo -n {}", ping))
+            .map_err(|e| e.message)?
+            .0;
+        used_sudo = true;
+    }
+
+    let (packets_sent, packets_received, loss_percent, avg_ms) =
+        parse_ping_summary(&output).ok_or_else(|| format!("Ping failed: {}", output.trim()))?;
+    Ok(PingResult {
+        target,
+        packets_sent,
+        packets_received,
+        avg_ms,
+        loss_percent,
+        used_sudo,
+        output,
+    })
+}
+
+/// Addresses `name` resolves to on the server, through its own resolver configuration
+#[tauri::command]
+pub async fn remote_dns_lookup(
+    name: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let name = name.trim().to_string();
+    if !is_valid_network_target(&name) {
+        return Err(format!("Invalid host name: {}", name));
+    }
+    // getent goes through nsswitch like the server's own programs do; dig is the
+    // fallback on minimal images without it
+    let output = client
+        .execute_command(&format!(
+            "if command -v getent >/dev/null 2>&1; then getent ahosts {0}; \
+             else dig +short A {0}; dig +short AAAA {0}; fi 2>/dev/null",
+            name
+        ))
+        .map_err(|e| e.message)?;
+
+    let mut addresses: Vec<String> = Vec::new();
+    for line in output.lines() {
+        // dig +short also prints CNAME targets, which aren't addresses
+        let Some(address) = line.split_whitespace().next() else {
+            continue;
+        };
+        if address.parse::<std::net::IpAddr>().is_ok() && !addresses.iter().any(|a| a == address) {
+            addresses.push(address.to_string());
+        }
+    }
+    Ok(addresses)
+}
+
+/// Drop cached results of `key` (`"users"`, `"groups"`, `"images"`, `"compose"`) on
+/// every server, or the whole cache when `None`
+#[tauri::command]
+pub async fn invalidate_cache(
+    key: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.cache.invalidate(key.as_deref()).await;
+    Ok(())
+}
+
+/// How long results of `command` stay cached; 0 turns caching off for it
+#[tauri::command]
+pub async fn set_cache_ttl(
+    command: String,
+    ttl_secs: u64,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state
+        .cache
+        .set_ttl(&command, std::time::Duration::from_secs(ttl_secs))
+        .await;
+    Ok(())
+}
+
+// ==================== NGINX COMMANDS ====================
+
+#[tauri::command]
+pub async fn nginx_status(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<NginxStatus, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    // Check if nginx is running with multiple fallback methods
+    let is_running = {
+        // Method 1: systemctl is-active
+        let systemctl_check = client
+            .execute_command("systemctl is-active nginx 2>/dev/null || echo 'inactive'")
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+
+        if systemctl_check == "active" {
+            true
+        } else {
+            // Method 2: Check if nginx process exists
+            let process_check = client
+                .execute_command("pgrep -x nginx 2>/dev/null | head -1")
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+
+            if !process_check.is_empty() {
+                true
+            } else {
+                // Method 3: Check pid file
+                client
+                    .execute_command("test -f /run/nginx.pid && echo 'running' || echo 'stopped'")
+                    .unwrap_or_default()
+                    .trim()
+                    .eq("running")
+            }
+        }
+    };
+
+    // Get nginx version
+    let version = client
+        .execute_command("nginx -v 2>&1 | cut -d'/' -f2")
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+
+    // Get worker processes
+    let worker_processes = client
+        .execute_command("grep -E '^\\s*worker_processes' /etc/nginx/nginx.conf 2>/dev/null | awk '{print $2}' | tr -d ';'")
+        .unwrap_or_else(|_| "auto".to_string())
+        .trim()
+        .to_string();
+
+    // Test config
+    let config_test = client
+        .execute_command("nginx -t 2>&1")
+        .unwrap_or_else(|e| format!("Config test failed: {}", e.message));
+
+    Ok(NginxStatus {
+        running: is_running,
+        version,
+        worker_processes,
+        config_test,
+    })
+}
+
+#[tauri::command]
+pub async fn nginx_action(
+    action: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let command = format!("sudo systemctl {} nginx", action);
+    client.execute_command(&command).map_err(|e| e.message)
+}
+
+#[tauri::command]
+pub async fn nginx_test_config(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ConfigTestResult, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    run_nginx_test(client)
+}
+
+fn run_nginx_test(client: &SshClient) -> Result<ConfigTestResult, String> {
+    let (output, exit_status) = client
+        .execute_with_status("sudo nginx -t 2>&1")
+        .map_err(|e| e.message)?;
+    Ok(parse_nginx_test_output(&output, exit_status))
+}
+
+/// Parse `nginx -t` output, relying on the exit status rather than the (localised) summary text
+fn parse_nginx_test_output(output: &str, exit_status: i32) -> ConfigTestResult {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    for line in output.lines() {
+        // e.g. nginx: [emerg] unknown directive "foo" in /etc/nginx/sites-enabled/app:12
+        let rest = match line.trim().strip_prefix("nginx: [") {
+            Some(rest) => rest,
+            None => continue,
+        };
+        let (level, message) = match rest.split_once("] ") {
+            Some(parts) => parts,
+            None => continue,
+        };
+
+        let (message, file, line_number) = match message.rsplit_once(" in ") {
+            Some((msg, location)) => match location.rsplit_once(':') {
+                Some((file, num)) if file.starts_with('/') && num.parse::<u32>().is_ok() => {
+                    (msg, Some(file.to_string()), num.parse().ok())
+                }
+                _ => (message, None, None),
+            },
+            None => (message, None, None),
+        };
+
+        let entry = ConfigMessage {
+            level: level.to_string(),
+            file,
+            line: line_number,
+            message: message.to_string(),
+        };
+
+        if level == "warn" || level == "notice" || level == "info" {
+            warnings.push(entry);
+        } else {
+            errors.push(entry);
+        }
+    }
+
+    ConfigTestResult {
+        ok: exit_status == 0 && errors.is_empty(),
+        errors,
+        warnings,
+        output: output.to_string(),
+    }
+}
+
+#[tauri::command]
+pub async fn get_nginx_config(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    client
+        .execute_command("cat /etc/nginx/nginx.conf 2>&1")
+        .map_err(|e| e.message)
+}
+
+/// Block outline of an nginx config for the editor, parsed locally without the server
+#[tauri::command]
+pub fn parse_nginx_config_structure(content: String) -> Result<Vec<NginxBlock>, String> {
+    parse_structure(&content)
+}
+
+#[tauri::command]
+pub async fn save_nginx_config(
+    content: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<NginxSaveResult, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    save_config_with_rollback(client, "/etc/nginx/nginx.conf", &content, run_nginx_test)
+}
+
+/// Runs a state-changing command and reports success from its exit status rather than
+/// its output. stderr is kept in `raw_output`, and a failure message ends with the last
+/// line the command printed, which is usually the reason.
+pub(crate) fn run_action(
+    client: &SshClient,
+    command: &str,
+    success_message: String,
+    failure_message: &str,
+) -> Result<ActionResult, String> {
+    let (output, status) = client
+        .execute_with_status(&format!("{{ {}; }} 2>&1", command))
+        .map_err(|e| e.message)?;
+    Ok(action_result(
+        status,
+        output,
+        success_message,
+        failure_message,
+    ))
+}
+
+fn action_result(
+    status: i32,
+    raw_output: String,
+    success_message: String,
+    failure_message: &str,
+) -> ActionResult {
+    let message = if status == 0 {
+        success_message
+    } else {
+        match raw_output.lines().map(str::trim).rfind(|l| !l.is_empty()) {
+            Some(reason) => format!("{}: {}", failure_message, reason),
+            None => format!("{} (exit status {})", failure_message, status),
+        }
+    };
+    ActionResult {
+        success: status == 0,
+        message,
+        raw_output,
+    }
+}
+
+/// "stop" -> "stopped", for action messages
+fn action_past_tense(action: &str) -> String {
+    match action {
+        "stop" => "stopped".to_string(),
+        "rm" | "remove" => "removed".to_string(),
+        "reset" => "reset".to_string(),
+        "kill" => "killed".to_string(),
+        _ if action.ends_with('e') => format!("{}d", action),
+        _ => format!("{}ed", action),
+    }
+}
+
+/// Quote a string for safe interpolation into a remote shell command
+pub(crate) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Back up `path` to `path.bak`, write `content`, and validate. If validation fails the
+/// backup is restored (or the new file removed) and validation is re-run to confirm the
+/// server was left in a good state.
+fn save_config_with_rollback(
+    client: &SshClient,
+    path: &str,
+    content: &str,
+    validate: fn(&SshClient) -> Result<ConfigTestResult, String>,
+) -> Result<NginxSaveResult, String> {
+    let quoted_path = shell_quote(path);
+    let quoted_backup = shell_quote(&format!("{}.bak", path));
+
+    // Backup first. The existence check runs as root so an unreadable directory isn't
+    // mistaken for a new file, which a failed validation would then delete.
+    let (backup_output, backup_status) = client
+        .execute_with_status(&format!(
+            "sudo true || exit 2; \
+             if sudo test -e {0}; then sudo cp -p {0} {1} && echo backed_up || exit 1; fi",
+            quoted_path, quoted_backup
+        ))
+        .map_err(|e| e.message)?;
+    if backup_status != 0 {
+        return Err(format!(
+            "Could not back up {}; nothing was changed: {}",
+            path,
+            backup_output.trim()
+        ));
+    }
+    let had_backup = backup_output.trim() == "backed_up";
+
+    // Write new config using tee
+    let write_cmd = format!(
+        "printf '%s' {} | sudo tee {} 2>&1 > /dev/null",
+        shell_quote(content),
+        quoted_path
+    );
+    let (write_output, write_status) = client
+        .execute_with_status(&write_cmd)
+        .map_err(|e| e.message)?;
+    if write_status != 0 {
+        // tee may have truncated the file before failing
+        if had_backup {
+            client
+                .execute_command(&format!("sudo cp -p {} {}", quoted_backup, quoted_path))
+                .map_err(|e| e.message)?;
+        }
+        return Err(format!("Failed to write {}: {}", path, write_output.trim()));
+    }
+
+    let test = validate(client)?;
+    if test.ok {
+        let previous = if had_backup {
+            read_remote_file(client, &format!("{}.bak", path)).unwrap_or_default()
+        } else {
+            String::new()
+        };
+        return Ok(NginxSaveResult {
+            message: format!("Saved {} and validated. Reload to apply changes.", path),
+            test,
+            rolled_back: false,
+            rollback_test: None,
+            diff: diff_lines(&previous, content),
+        });
+    }
+
+    // Roll back so a later reload can't pick up the broken file. Without a backup the
+    // file didn't exist before this save, so removing it is the rollback.
+    let restore_cmd = if had_backup {
+        format!("sudo cp -p {} {}", quoted_backup, quoted_path)
+    } else {
+        format!("sudo rm -f {}", quoted_path)
+    };
+    client
+        .execute_command(&restore_cmd)
+        .map_err(|e| e.message)?;
+
+    let rollback_test = validate(client)?;
+    let message = if rollback_test.ok {
+        format!(
+            "Config test failed; {} was restored to its previous version and validates again.",
+            path
+        )
+    } else {
+        format!(
+            "Config test failed and {} was restored, but the configuration still does not validate.",
+            path
+        )
+    };
+
+    Ok(NginxSaveResult {
+        message,
+        test,
+        rolled_back: true,
+        rollback_test: Some(rollback_test),
+        diff: Vec::new(),
+    })
+}
+
+/// Changes of `path` relative to the `path.bak` left by the last save
+fn backup_diff(client: &SshClient, path: &str) -> Result<Vec<DiffLine>, String> {
+    let backup = read_remote_file(client, &format!("{}.bak", path))
+        .map_err(|_| format!("No backup of {} to compare against", path))?;
+    let current = read_remote_file(client, path)?;
+    Ok(diff_lines(&backup, &current))
+}
+
+/// Directory whose configs the nginx editor backs up, the only place `path.bak` files
+/// are diffed or restored from
+const BACKUP_CONFIG_DIR: &str = "/etc/nginx/";
+
+/// A config file under `BACKUP_CONFIG_DIR`, not a backup itself, with no `.`/`..` parts
+fn is_valid_backup_path(path: &str) -> bool {
+    path.strip_prefix(BACKUP_CONFIG_DIR).is_some_and(|rest| {
+        !rest.ends_with(".bak")
+            && rest
+                .split('/')
+                .all(|part| !part.is_empty() && part != "." && part != "..")
+    })
+}
+
+/// Diff a config file against the `.bak` backup its last save left behind
+#[tauri::command]
+pub async fn diff_against_backup(
+    path: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<DiffLine>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    if !is_valid_backup_path(&path) {
+        return Err(format!("Invalid path: {}", path));
+    }
+    backup_diff(client, &path)
+}
+
+/// Put a config file back to its `.bak` backup, undoing the last save. Reload or
+/// redeploy afterwards to apply it.
+#[tauri::command]
+pub async fn restore_backup(
+    path: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    if !is_valid_backup_path(&path) {
+        return Err(format!("Invalid path: {}", path));
+    }
+    let quoted_backup = shell_quote(&format!("{}.bak", path));
+    // Symlinks are resolved so neither file can point out of the directory
+    let (output, exit_status) = client
+        .execute_with_status(&format!(
+            "[ -f {0} ] || exit 3; \
+             for f in {0} {1}; do case \"$(realpath -m \"$f\")\" in {2}*) ;; *) exit 4;; esac; done; \
+             sudo cp -p {0} {1} 2>&1",
+            quoted_backup,
+            shell_quote(&path),
+            BACKUP_CONFIG_DIR
+        ))
+        .map_err(|e| e.message)?;
+    match exit_status {
+        0 => Ok(format!("Restored {} from its backup", path)),
+        3 => Err(format!("No backup of {} to restore", path)),
+        4 => Err(format!(
+            "{} or its backup resolves to a file outside {}",
+            path, BACKUP_CONFIG_DIR
+        )),
+        _ => Err(format!("Failed to restore {}: {}", path, output.trim())),
+    }
+}
+
+#[tauri::command]
+pub async fn set_nginx_gzip(
+    enabled: bool,
+    types: Vec<String>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<NginxEditResult, String> {
+    if let Some(bad) = types.iter().find(|t| !is_valid_mime_type(t)) {
+        return Err(format!("Invalid MIME type '{}'", bad));
+    }
+
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let path = "/etc/nginx/nginx.conf";
+    let current = read_remote_file(client, path)?;
+    let mut updated = upsert_directive(
+        &current,
+        "http",
+        "gzip",
+        if enabled { "on" } else { "off" },
+        |_| true,
+    );
+    if enabled && !types.is_empty() {
+        updated = upsert_directive(&updated, "http", "gzip_types", &types.join(" "), |_| true);
+    }
+
+    apply_nginx_edits(client, vec![(path.to_string(), current, updated)])
+}
+
+/// Shared memory given to a `limit_req_zone` when no size is passed; 1m holds about
+/// 16 000 client addresses
+const DEFAULT_RATE_LIMIT_ZONE_SIZE: &str = "10m";
+
+/// Limit requests to `vhost` per client address. The zone is declared in nginx.conf with
+/// `zone_size` of shared memory (`10m` by default).
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn set_nginx_rate_limit(
+    vhost: String,
+    zone: String,
+    rate: String,
+    burst: u32,
+    zone_size: Option<String>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<NginxEditResult, String> {
+    if vhost.is_empty() || vhost.contains('/') || vhost.starts_with('.') {
+        return Err(format!("Invalid vhost name '{}'", vhost));
+    }
+    if zone.is_empty() || !zone.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!("Invalid zone name '{}'", zone));
+    }
+    if !is_valid_nginx_rate(&rate) {
+        return Err(format!(
+            "Invalid rate '{}', expected e.g. 10r/s or 60r/m",
+            rate
+        ));
+    }
+    let zone_size = zone_size
+        .map(|size| size.trim().to_string())
+        .filter(|size| !size.is_empty())
+        .unwrap_or_else(|| DEFAULT_RATE_LIMIT_ZONE_SIZE.to_string());
+    if !is_valid_nginx_size(&zone_size) {
+        return Err(format!(
+            "Invalid zone size '{}', expected e.g. 512k or 10m",
+            zone_size
+        ));
+    }
+
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let zone_arg = format!("zone={}", zone);
+    let zone_prefix = format!("zone={}:", zone);
+
+    // The zone is shared http-level state, the limit applies per server block
+    let conf_path = "/etc/nginx/nginx.conf".to_string();
+    let conf = read_remote_file(client, &conf_path)?;
+    let conf_updated = upsert_directive(
+        &conf,
+        "http",
+        "limit_req_zone",
+        &format!(
+            "$binary_remote_addr {}{} rate={}",
+            zone_prefix, zone_size, rate
+        ),
+        |args| args.split_whitespace().any(|a| a.starts_with(&zone_prefix)),
+    );
+
+    let vhost_path = format!("/etc/nginx/sites-available/{}", vhost);
+    let vhost_conf = read_remote_file(client, &vhost_path)?;
+    let vhost_updated = upsert_directive(
+        &vhost_conf,
+        "server",
+        "limit_req",
+        &format!("{} burst={} nodelay", zone_arg, burst),
+        |args| args.split_whitespace().any(|a| a == zone_arg),
+    );
+
+    apply_nginx_edits(
+        client,
+        vec![
+            (conf_path, conf, conf_updated),
+            (vhost_path, vhost_conf, vhost_updated),
+        ],
+    )
+}
+
+/// `location` arguments: a path, optionally after a `=`, `^~`, `~` or `~*` modifier
+fn is_valid_location_path(path: &str) -> bool {
+    let words: Vec<&str> = path.split_whitespace().collect();
+    let target = match words.as_slice() {
+        [path] => path,
+        [modifier, path] if matches!(*modifier, "=" | "^~" | "~" | "~*") => path,
+        _ => return false,
+    };
+    !target.is_empty() && !target.contains(['{', '}', ';', '#', '"', '\''])
+}
+
+/// Add a `location` block to an existing vhost, proxying to `proxy_pass` or serving files
+/// from `root`, then test and reload nginx (rolled back if the test fails)
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn add_nginx_location(
+    vhost: String,
+    path: String,
+    proxy_pass: Option<String>,
+    root: Option<String>,
+    extra_directives: Vec<String>,
+    server_name: Option<String>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<NginxEditResult, String> {
+    if vhost.is_empty() || vhost.contains('/') || vhost.starts_with('.') {
+        return Err(format!("Invalid vhost name '{}'", vhost));
+    }
+    if !is_valid_location_path(&path) {
+        return Err(format!("Invalid location path '{}'", path));
+    }
+
+    let mut body = Vec::new();
+    if let Some(upstream) = proxy_pass
+        .as_deref()
+        .map(str::trim)
+        .filter(|u| !u.is_empty())
+    {
+        let valid = parse_proxy_target(upstream)
+            .is_some_and(|target| matches!(target.scheme.as_str(), "http" | "https"))
+            && !upstream.contains(|c: char| c.is_whitespace() || matches!(c, ';' | '{' | '}'));
+        if !valid {
+            return Err(format!("Invalid proxy_pass target '{}'", upstream));
+        }
+        body.push(format!("proxy_pass {};", upstream));
+        body.push("proxy_set_header Host $host;".to_string());
+        body.push("proxy_set_header X-Real-IP $remote_addr;".to_string());
+        body.push("proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;".to_string());
+        body.push("proxy_set_header X-Forwarded-Proto $scheme;".to_string());
+    }
+    if let Some(root) = root.as_deref().map(str::trim).filter(|r| !r.is_empty()) {
+        if !root.starts_with('/')
+            || root.contains(|c: char| c.is_whitespace() || matches!(c, ';' | '{' | '}'))
+        {
+            return Err(format!("Invalid root '{}'", root));
+        }
+        body.push(format!("root {};", root));
+    }
+    for directive in &extra_directives {
+        let directive = directive.trim().trim_end_matches(';').trim();
+        if directive.is_empty() || directive.contains(['{', '}', ';', '\n']) {
+            return Err(format!("Invalid directive '{}'", directive));
+        }
+        body.push(format!("{};", directive));
+    }
+    if body.is_empty() {
+        return Err("Give a proxy_pass target, a root or directives for the location".to_string());
+    }
+
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let vhost_path = format!("/etc/nginx/sites-available/{}", vhost);
+    let current = read_remote_file(client, &vhost_path)?;
+    let updated = insert_location(&current, &path, &body, server_name.as_deref())?;
+    apply_nginx_edits(client, vec![(vhost_path, current, updated)])
+}
+
+fn read_remote_file(client: &SshClient, path: &str) -> Result<String, String> {
+    let result = client
+        .execute_bounded(&format!("sudo cat {}", shell_quote(path)))
+        .map_err(|e| e.message)?;
+    remote_file_content(path, result)
+}
+
+/// The file `sudo cat` printed. Output cut off at the output limit is refused, so a
+/// partial file is never edited and written back.
+fn remote_file_content(path: &str, result: CommandOutput) -> Result<String, String> {
+    if result.truncated {
+        return Err(format!(
+            "{} is larger than the output limit and couldn't be read completely",
+            path
+        ));
+    }
+    if result.exit_status != 0 {
+        return Err(format!("Failed to read {}", path));
+    }
+    Ok(result.output)
+}
+
+/// Save each changed `(path, old, new)` file with rollback, then reload nginx. If a later
+/// file fails validation, files saved before it are put back so the edit is all-or-nothing.
+fn apply_nginx_edits(
+    client: &SshClient,
+    edits: Vec<(String, String, String)>,
+) -> Result<NginxEditResult, String> {
+    let changed: Vec<&(String, String, String)> =
+        edits.iter().filter(|(_, old, new)| old != new).collect();
+    let diff: String = changed
+        .iter()
+        .map(|(path, old, new)| unified_diff(path, old, new))
+        .collect();
+
+    if changed.is_empty() {
+        return Ok(NginxEditResult {
+            message: "Configuration already up to date".to_string(),
+            diff,
+            save: None,
+            reloaded: false,
+        });
+    }
+
+    let mut last_save = None;
+    for (idx, (path, _, new)) in changed.iter().enumerate() {
+        let save = save_config_with_rollback(client, path, new, run_nginx_test)?;
+        if save.rolled_back {
+            // Written back directly: going through save_config_with_rollback again would
+            // overwrite each `.bak` with the edit that's being undone
+            for (earlier_path, old, _) in &changed[..idx] {
+                let (output, status) = client
+                    .execute_with_status(&format!(
+                        "printf '%s' {} | sudo tee {} 2>&1 > /dev/null",
+                        shell_quote(old),
+                        shell_quote(earlier_path)
+                    ))
+                    .map_err(|e| e.message)?;
+                if status != 0 {
+                    return Err(format!(
+                        "{}; restoring {} also failed: {}",
+                        save.message,
+                        earlier_path,
+                        output.trim()
+                    ));
+                }
+            }
+            return Ok(NginxEditResult {
+                message: save.message.clone(),
+                diff,
+                save: Some(save),
+                reloaded: false,
+            });
+        }
+        last_save = Some(save);
+    }
+
+    let (reload_output, reload_status) = client
+        .execute_with_status("sudo systemctl reload nginx 2>&1")
+        .map_err(|e| e.message)?;
+    let reloaded = reload_status == 0;
+
+    Ok(NginxEditResult {
+        message: if reloaded {
+            "Configuration updated and nginx reloaded".to_string()
+        } else {
+            format!(
+                "Configuration updated but reload failed: {}",
+                reload_output.trim()
+            )
+        },
+        diff,
+        save: last_save,
+        reloaded,
+    })
+}
+
+fn is_valid_mime_type(mime: &str) -> bool {
+    !mime.is_empty()
+        && mime
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '.' | '+' | '-' | '*'))
+}
+
+/// nginx sizes: a number of bytes, or one with a `k` or `m` suffix
+fn is_valid_nginx_size(size: &str) -> bool {
+    let digits = size.trim_end_matches(['k', 'K', 'm', 'M']);
+    !digits.is_empty()
+        && size.len() - digits.len() <= 1
+        && digits.chars().all(|c| c.is_ascii_digit())
+        && digits.parse::<u64>().is_ok_and(|n| n > 0)
+}
+
+/// `limit_req_zone` rates look like `10r/s` or `60r/m`
+fn is_valid_nginx_rate(rate: &str) -> bool {
+    match rate.split_once("r/") {
+        Some((count, unit)) => {
+            !count.is_empty()
+                && count.chars().all(|c| c.is_ascii_digit())
+                && matches!(unit, "s" | "m")
+        }
+        None => false,
+    }
+}
+
+/// The distro's `default` site is hidden unless `include_default` is set, so it isn't
+/// edited or disabled by accident
+#[tauri::command]
+pub async fn get_nginx_vhosts(
+    include_default: Option<bool>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<NginxVhost>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+    let include_default = include_default.unwrap_or(false);
+
+    let mut vhosts = Vec::new();
+
+    // Get sites from sites-available
+    let available_output = client
+        .execute_command("ls -1 /etc/nginx/sites-available/ 2>/dev/null")
+        .unwrap_or_default();
+
+    // Get enabled sites (symlinks in sites-enabled)
+    let enabled_output = client
+        .execute_command("ls -1 /etc/nginx/sites-enabled/ 2>/dev/null")
+        .unwrap_or_default();
+
+    let enabled_sites: Vec<&str> = enabled_output.lines().collect();
+    let mut names: Vec<&str> = available_output.lines().collect();
+    // Some images ship `default` as a plain file in sites-enabled only
+    if include_default && !names.contains(&"default") && enabled_sites.contains(&"default") {
+        names.push("default");
+    }
+
+    for name in names {
+        if name.is_empty() || (name == "default" && !include_default) {
+            continue;
+        }
+
+        let enabled = enabled_sites.contains(&name);
+
+        // Read config to extract details
+        let config = client
+            .execute_command(&format!(
+                "cat {}",
+                shell_quote(&vhost_config_path(client, name))
+            ))
+            .unwrap_or_default();
+
+        // Extract server_name
+        let server_name = config
+            .lines()
+            .find(|l| l.trim().starts_with("server_name"))
+            .map(|l| {
+                l.split_whitespace()
+                    .nth(1)
+                    .unwrap_or("*")
+                    .trim_end_matches(';')
+            })
+            .unwrap_or("*")
+            .to_string();
+
+        // Extract listen port
+        let listen_port = config
+            .lines()
+            .find(|l| l.trim().starts_with("listen"))
+            .and_then(|l| {
+                l.split_whitespace()
+                    .nth(1)
+                    .map(|s| s.trim_end_matches(';').to_string())
+            })
+            .unwrap_or_else(|| "80".to_string());
+
+        // Check SSL
+        let ssl_enabled = config.contains("ssl_certificate");
+
+        // Extract root path
+        let root_path = config
+            .lines()
+            .find(|l| l.trim().starts_with("root"))
+            .map(|l| {
+                l.split_whitespace()
+                    .nth(1)
+                    .unwrap_or("")
+                    .trim_end_matches(';')
+            })
+            .unwrap_or("")
+            .to_string();
+
+        vhosts.push(NginxVhost {
+            name: name.to_string(),
+            enabled,
+            server_name,
+            listen_port,
+            ssl_enabled,
+            root_path,
+        });
+    }
+
+    Ok(vhosts)
+}
+
+#[tauri::command]
+pub async fn get_vhost_config(
+    name: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    client
+        .execute_command(&format!(
+            "cat {}",
+            shell_quote(&vhost_config_path(client, &name))
+        ))
+        .map_err(|e| e.message)
+}
+
+/// Where a site's config lives: normally sites-available, but a site that only exists as
+/// a regular file in sites-enabled (often `default`) is read and saved in place
+fn vhost_config_path(client: &SshClient, name: &str) -> String {
+    let available = format!("/etc/nginx/sites-available/{}", name);
+    let enabled = format!("/etc/nginx/sites-enabled/{}", name);
+    let enabled_only = client
+        .execute_with_status(&format!(
+            "[ ! -e {} ] && [ -f {} ] && [ ! -L {} ]",
+            shell_quote(&available),
+            shell_quote(&enabled),
+            shell_quote(&enabled)
+        ))
+        .is_ok_and(|(_, status)| status == 0);
+    if enabled_only {
+        enabled
+    } else {
+        available
+    }
+}
+
+#[tauri::command]
+pub async fn save_vhost_config(
+    name: String,
+    content: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<NginxSaveResult, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let path = vhost_config_path(client, &name);
+    save_config_with_rollback(client, &path, &content, run_nginx_test)
+}
+
+/// Printed by the enable script before the symlink's target
+const VHOST_LINK_MARKER: &str = "LINK_TARGET:";
+
+/// Link sites-enabled/<name> to sites-available/<name>, then test and reload, removing
+/// the new link if the test fails. Safe to retry: an existing correct link is left as
+/// is, and nothing already in sites-enabled is overwritten, whether a regular file or a
+/// link to somewhere else.
+#[tauri::command]
+pub async fn enable_vhost(
+    name: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<VhostEnableResult, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    // Exit 0 = already linked, 1 = linked now, 3 = nothing to link, 4 = a regular file is
+    // in the way, 5 = the link points elsewhere. readlink -f resolves relative links.
+    let cmd = format!(
+        "a={}; e={}; \
+         [ -e \"$a\" ] || exit 3; \
+         if [ -L \"$e\" ]; then created=0; \
+         elif [ -e \"$e\" ]; then exit 4; \
+         else sudo ln -s \"$a\" \"$e\" 2>&1 || exit 2; created=1; fi; \
+         echo \"{marker}$(readlink \"$e\")\"; \
+         [ \"$(readlink -f \"$e\")\" = \"$(readlink -f \"$a\")\" ] || exit 5; \
+         exit $created",
+        shell_quote(&format!("/etc/nginx/sites-available/{}", name)),
+        shell_quote(&format!("/etc/nginx/sites-enabled/{}", name)),
+        marker = VHOST_LINK_MARKER
+    );
+    let (output, status) = client.execute_with_status(&cmd).map_err(|e| e.message)?;
+    let link_target = output
+        .lines()
+        .find_map(|line| line.strip_prefix(VHOST_LINK_MARKER))
+        .map(String::from);
+    let outcome = match status {
+        0 => VhostEnableOutcome::AlreadyEnabled,
+        1 => VhostEnableOutcome::Enabled,
+        3 => return Err(format!("Vhost '{}' doesn't exist in sites-available", name)),
+        4 => {
+            return Err(format!(
+                "sites-enabled/{} is a regular file, not a link; move or remove it first",
+                name
+            ))
+        }
+        5 => VhostEnableOutcome::TargetMismatch,
+        _ => return Err(output.trim().to_string()),
+    };
+    match outcome {
+        VhostEnableOutcome::AlreadyEnabled => {
+            return Ok(VhostEnableResult {
+                outcome,
+                message: format!("Vhost '{}' is already enabled", name),
+                link_target,
+            })
+        }
+        VhostEnableOutcome::TargetMismatch => {
+            return Ok(VhostEnableResult {
+                outcome,
+                message: format!(
+                    "sites-enabled/{} links to {} instead of sites-available/{}; left unchanged",
+                    name,
+                    link_target.as_deref().unwrap_or("an unreadable target"),
+                    name
+                ),
+                link_target,
+            })
+        }
+        VhostEnableOutcome::Enabled => {}
+    }
+
+    // Test and reload
+    let test = run_nginx_test(client)?;
+    if test.ok {
+        client
+            .execute_command("sudo systemctl reload nginx 2>&1")
+            .map_err(|e| e.message)?;
+        Ok(VhostEnableResult {
+            outcome,
+            message: format!("Vhost '{}' enabled and nginx reloaded.", name),
+            link_target,
+        })
+    } else {
+        // The link was made by this call, so taking it away leaves nginx as it was
+        let enabled = format!("/etc/nginx/sites-enabled/{}", name);
+        let (output, status) = client
+            .execute_with_status(&format!(
+                "[ -L {0} ] && sudo rm -f {0} 2>&1",
+                shell_quote(&enabled)
+            ))
+            .map_err(|e| e.message)?;
+        if status == 0 {
+            Err(format!(
+                "Config test failed, so sites-enabled/{} was removed again: {}",
+                name, test.output
+            ))
+        } else {
+            Err(format!(
+                "Config test failed and sites-enabled/{} could not be removed ({}): {}",
+                name,
+                output.trim(),
+                test.output
+            ))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn disable_vhost(
+    name: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    // Symlinks are just removed. A regular file is the site itself, so it moves to
+    // sites-available instead, or is dropped only when sites-available has the same copy.
+    let cmd = format!(
+        "a={}; e={}; \
+         if [ -L \"$e\" ] || [ ! -e \"$e\" ]; then sudo rm -f \"$e\" 2>&1; \
+         elif [ ! -e \"$a\" ]; then sudo mv \"$e\" \"$a\" 2>&1; \
+         elif sudo cmp -s \"$e\" \"$a\"; then sudo rm -f \"$e\" 2>&1; \
+         else exit 3; fi",
+        shell_quote(&format!("/etc/nginx/sites-available/{}", name)),
+        shell_quote(&format!("/etc/nginx/sites-enabled/{}", name))
+    );
+    let (output, status) = client.execute_with_status(&cmd).map_err(|e| e.message)?;
+    match status {
+        0 => {}
+        3 => {
+            return Err(format!(
+                "sites-enabled/{0} is a separate file that differs from sites-available/{0}; \
+                 reconcile them before disabling",
+                name
+            ))
+        }
+        _ => return Err(output.trim().to_string()),
+    }
+    client
+        .execute_command("sudo systemctl reload nginx 2>&1")
+        .map_err(|e| e.message)?;
+
+    Ok(format!("Vhost '{}' disabled and nginx reloaded.", name))
+}
+
+/// Seconds before a vhost test request gives up
+const VHOST_TEST_TIMEOUT_SECS: u32 = 10;
+/// Marks curl's `-w` output, which can land on either side of its error message
+const CURL_PROBE_MARKER: &str = "CURL_PROBE:";
+
+/// Request `/` of a site from the server itself, through nginx, with the site's
+/// `server_name`. HTTPS sites are reached with `--resolve` so SNI picks the right
+/// certificate; the certificate itself isn't verified, only that the site answers.
+#[tauri::command]
+pub async fn test_vhost_response(
+    vhost: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<VhostTestResult, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let config = read_remote_file(client, &vhost_config_path(client, &vhost))?;
+    let probe = vhost_probe(&config)?;
+
+    let (url, target) = if probe.https {
+        let url = format!("https://{}:{}/", probe.server_name, probe.port);
+        let resolve = format!(
+            "-k --resolve {}",
+            shell_quote(&format!(
+                "{}:{}:{}",
+                probe.server_name, probe.port, probe.address
+            ))
+        );
+        (url, resolve)
+    } else {
+        let url = format!("http://{}:{}/", probe.address, probe.port);
+        let host = format!(
+            "-H {}",
+            shell_quote(&format!("Host: {}", probe.server_name))
+        );
+        (url, host)
+    };
+    let (output, status) = client
+        .execute_with_status(&format!(
+            "command -v curl >/dev/null || exit 127; \
+             curl -sS -o /dev/null --max-time {} -w '\\n{}%{{http_code}} %{{time_total}}\\n' {} {} 2>&1",
+            VHOST_TEST_TIMEOUT_SECS,
+            CURL_PROBE_MARKER,
+            target,
+            shell_quote(&url)
+        ))
+        .map_err(|e| e.message)?;
+    if status == 127 {
+        return Err("curl is not installed on the server".to_string());
+    }
+
+    let (status_code, time_total_secs, error) = parse_curl_probe(&output);
+    let upstream_ok = match (&probe.upstream, status_code) {
+        (Some(_), Some(code)) => Some(!matches!(code, 502..=504)),
+        _ => None,
+    };
+    Ok(VhostTestResult {
+        vhost,
+        url,
+        server_name: probe.server_name,
+        status_code,
+        time_total_secs,
+        expected_upstream: probe.upstream,
+        upstream_ok,
+        error,
+    })
+}
+
+/// The status code and time from curl's marked `-w` line, and whatever else it printed
+/// as the error. curl reports code 000 when no response came back.
+fn parse_curl_probe(output: &str) -> (Option<u16>, Option<f64>, Option<String>) {
+    let mut code = None;
+    let mut time = None;
+    let mut error = Vec::new();
+    for line in output.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        match line.strip_prefix(CURL_PROBE_MARKER) {
+            Some(numbers) => {
+                let mut fields = numbers.split_whitespace();
+                code = fields.next().and_then(|c| c.parse::<u16>().ok());
+                time = fields.next().and_then(|t| t.parse::<f64>().ok());
+            }
+            None => error.push(line),
+        }
+    }
+    if code.is_none() && error.is_empty() {
+        error.push("curl printed nothing");
+    }
+    let error = Some(error.join("\n")).filter(|e| !e.is_empty());
+    (code.filter(|&c| c != 0), time, error)
+}
+
+#[tauri::command]
+pub async fn delete_vhost(
+    name: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    // Remove from both available and enabled
+    client
+        .execute_command(&format!(
+            "sudo rm -f /etc/nginx/sites-available/{} 2>&1",
+            name
+        ))
+        .map_err(|e| e.message)?;
+    client
+        .execute_command(&format!(
+            "sudo rm -f /etc/nginx/sites-enabled/{} 2>&1",
+            name
+        ))
+        .map_err(|e| e.message)?;
+
+    Ok(format!("Vhost '{}' deleted.", name))
+}
+
+#[tauri::command]
+pub async fn get_nginx_logs(
+    log_type: String,
+    lines: u32,
+    rotation: Option<u32>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let log_path = match log_type.as_str() {
+        "error" => "/var/log/nginx/error.log",
+        "access" => "/var/log/nginx/access.log",
+        _ => "/var/log/nginx/error.log",
+    };
+
+    client
+        .execute_command(&log_tail_command(log_path, lines, rotation))
+        .map_err(|e| e.message)
+}
+
+// Whole-config snapshots complement the per-file `.bak` of each save: a tarball of
+// /etc/nginx taken before a multi-file change can put every file back at once.
+
+/// Root-only directory the snapshots are kept in, on the server itself
+const NGINX_SNAPSHOT_DIR: &str = "/var/backups/dpanel-nginx";
+/// Taking a snapshot prunes the oldest ones beyond this many
+const NGINX_SNAPSHOT_RETENTION: usize = 10;
+/// Where the live config is moved while a restored one is tested
+const NGINX_RESTORE_ASIDE: &str = "/etc/nginx.dpanel-restore";
+
+/// Filename-safe form of a snapshot label: spaces become dashes and anything other than
+/// letters, digits, `-` and `_` is dropped
+fn nginx_snapshot_label(label: &str) -> String {
+    label
+        .trim()
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_ascii_alphanumeric() || c == '-' || c == '_' => Some(c),
+            c if c.is_whitespace() => Some('-'),
+            _ => None,
+        })
+        .take(48)
+        .collect()
+}
+
+/// `<unix time>-<label>.tar.gz` (or `<unix time>.tar.gz`) and its size; `None` for any
+/// other file
+fn parse_nginx_snapshot(name: &str, size: u64) -> Option<NginxSnapshot> {
+    let id = name.strip_suffix(".tar.gz")?;
+    let (timestamp, label) = id.split_once('-').unwrap_or((id, ""));
+    let created_at = timestamp.parse().ok()?;
+    if nginx_snapshot_label(label) != label {
+        return None;
+    }
+    Some(NginxSnapshot {
+        id: id.to_string(),
+        label: label.to_string(),
+        created_at,
+        size,
+    })
+}
+
+/// `name|size` lines from `find -printf`, newest snapshot first
+fn parse_nginx_snapshots(output: &str) -> Vec<NginxSnapshot> {
+    let mut snapshots: Vec<NginxSnapshot> = output
+        .lines()
+        .filter_map(|line| {
+            let (name, size) = line.trim().rsplit_once('|')?;
+            parse_nginx_snapshot(name, size.parse().ok()?)
+        })
+        .collect();
+    snapshots.sort_by(|a, b| (b.created_at, &b.id).cmp(&(a.created_at, &a.id)));
+    snapshots
+}
+
+fn read_nginx_snapshots(client: &SshClient) -> Result<Vec<NginxSnapshot>, String> {
+    let output = client
+        .execute_command(&format!(
+            "sudo find {} -maxdepth 1 -type f -name '*.tar.gz' -printf '%f|%s\\n' 2>/dev/null",
+            NGINX_SNAPSHOT_DIR
+        ))
+        .map_err(|e| e.message)?;
+    Ok(parse_nginx_snapshots(&output))
+}
+
+/// Archive /etc/nginx into the snapshot directory, then prune the oldest snapshots
+/// beyond `NGINX_SNAPSHOT_RETENTION`
+#[tauri::command]
+pub async fn snapshot_nginx_config(
+    label: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<NginxSnapshot, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let label = nginx_snapshot_label(&label);
+    let name_suffix = if label.is_empty() {
+        ".tar.gz".to_string()
+    } else {
+        format!("-{}.tar.gz", label)
+    };
+    // Timestamped on the server so snapshots order by its clock, not this machine's
+    let (output, exit_status) = client
+        .execute_with_status(&format!(
+            "[ -d /etc/nginx ] || exit 3; \
+             sudo mkdir -p {0} && sudo chmod 700 {0} || exit 1; \
+             f={0}/$(date +%s){1}; sudo test -e \"$f\" && exit 4; \
+             sudo tar -czf \"$f\" -C /etc nginx 2>&1 || {{ sudo rm -f \"$f\"; exit 1; }}; \
+             echo \"$(basename \"$f\")|$(sudo stat -c %s \"$f\")\"",
+            NGINX_SNAPSHOT_DIR,
+            shell_quote(&name_suffix)
+        ))
+        .map_err(|e| e.message)?;
+    match exit_status {
+        0 => {}
+        3 => return Err("/etc/nginx does not exist".to_string()),
+        4 => return Err("A snapshot was taken this second already; try again".to_string()),
+        _ => return Err(format!("Failed to snapshot /etc/nginx: {}", output.trim())),
+    }
+    let snapshot = parse_nginx_snapshots(&output)
+        .into_iter()
+        .next()
+        .ok_or("Snapshot was written but could not be read back")?;
+
+    let expired: Vec<String> = read_nginx_snapshots(client)?
+        .iter()
+        .skip(NGINX_SNAPSHOT_RETENTION)
+        .map(|s| shell_quote(&format!("{}/{}.tar.gz", NGINX_SNAPSHOT_DIR, s.id)))
+        .collect();
+    if !expired.is_empty() {
+        client
+            .execute_command(&format!("sudo rm -f {}", expired.join(" ")))
+            .map_err(|e| e.message)?;
+    }
+    Ok(snapshot)
+}
+
+#[tauri::command]
+pub async fn list_nginx_snapshots(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<NginxSnapshot>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    read_nginx_snapshots(ssh_client.as_ref())
+}
+
+/// Replace /etc/nginx with a snapshot, test it and reload. The live config is moved
+/// aside until `nginx -t` passes and put back if it doesn't, so nginx never reloads an
+/// untested restore.
+#[tauri::command]
+pub async fn restore_nginx_snapshot(
+    id: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<NginxRestoreResult, String> {
+    let snapshot = parse_nginx_snapshot(&format!("{}.tar.gz", id), 0)
+        .ok_or_else(|| format!("Invalid snapshot id '{}'", id))?;
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let archive = shell_quote(&format!("{}/{}.tar.gz", NGINX_SNAPSHOT_DIR, snapshot.id));
+    let (output, exit_status) = client
+        .execute_with_status(&format!(
+            "sudo test -f {0} || exit 3; \
+             sudo tar -tzf {0} | grep -qv '^nginx/' && exit 4; \
+             sudo test -e {1} && exit 5; \
+             sudo mv /etc/nginx {1} || exit 1; \
+             sudo tar -xzpf {0} -C /etc 2>&1 || {{ sudo rm -rf /etc/nginx; sudo mv {1} /etc/nginx; exit 1; }}",
+            archive, NGINX_RESTORE_ASIDE
+        ))
+        .map_err(|e| e.message)?;
+    match exit_status {
+        0 => {}
+        3 => return Err(format!("Snapshot '{}' does not exist", id)),
+        4 => return Err(format!("Snapshot '{}' holds files outside nginx/", id)),
+        5 => {
+            return Err(format!(
+                "{} is left over from an earlier restore; check and remove it first",
+                NGINX_RESTORE_ASIDE
+            ))
+        }
+        _ => return Err(format!("Failed to extract snapshot: {}", output.trim())),
+    }
+
+    let test = run_nginx_test(client)?;
+    if !test.ok {
+        client
+            .execute_command(&format!(
+                "sudo rm -rf /etc/nginx && sudo mv {} /etc/nginx",
+                NGINX_RESTORE_ASIDE
+            ))
+            .map_err(|e| e.message)?;
+        let rollback_test = run_nginx_test(client)?;
+        return Ok(NginxRestoreResult {
+            message: format!(
+                "Snapshot '{}' failed the config test; the previous /etc/nginx was put back",
+                id
+            ),
+            test,
+            rolled_back: true,
+            rollback_test: Some(rollback_test),
+            reloaded: false,
+        });
+    }
+
+    client
+        .execute_command(&format!("sudo rm -rf {}", NGINX_RESTORE_ASIDE))
+        .map_err(|e| e.message)?;
+    let (reload_output, reload_status) = client
+        .execute_with_status("sudo systemctl reload nginx 2>&1")
+        .map_err(|e| e.message)?;
+    let reloaded = reload_status == 0;
+    Ok(NginxRestoreResult {
+        message: if reloaded {
+            format!("Restored snapshot '{}' and reloaded nginx", id)
+        } else {
+            format!(
+                "Restored snapshot '{}' but reload failed: {}",
+                id,
+                reload_output.trim()
+            )
+        },
+        test,
+        rolled_back: false,
+        rollback_test: None,
+        reloaded,
+    })
+}
+
+// ==================== LOGROTATE COMMANDS ====================
+
+const LOGROTATE_DIR: &str = "/etc/logrotate.d";
+
+/// Path of a drop-in in /etc/logrotate.d, refusing names that would escape it
+fn logrotate_config_path(name: &str) -> Result<String, String> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(format!("{}/{}", LOGROTATE_DIR, name))
+    } else {
+        Err(format!("Invalid logrotate config name '{}'", name))
+    }
+}
+
+/// Every block of every file in /etc/logrotate.d
+#[tauri::command]
+pub async fn get_logrotate_configs(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<LogrotateConfig>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let output = ssh_client
+        .execute_command(&format!(
+            "for f in {}/*; do [ -f \"$f\" ] && {{ echo \"===$f===\"; sudo cat \"$f\"; }}; done; true",
+            LOGROTATE_DIR
+        ))
+        .map_err(|e| e.message)?;
+    let mut files: Vec<(String, String)> = split_sections(&output).into_iter().collect();
+    files.sort();
+    Ok(files
+        .iter()
+        .flat_map(|(file, content)| parse_logrotate_config(file, content))
+        .collect())
+}
+
+/// Raw content of one drop-in, for the editor
+#[tauri::command]
+pub async fn get_logrotate_config(
+    name: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let path = logrotate_config_path(&name)?;
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    read_remote_file(ssh_client.as_ref(), &path)
+}
+
+/// Marks the temporary copy's path in the dry-run output
+const LOGROTATE_TEMP_MARKER: &str = "TEMP_FILE:";
+const LOGROTATE_MAIN_CONFIG: &str = "/etc/logrotate.conf";
+
+/// Global directives of /etc/logrotate.conf, empty when the file doesn't exist
+fn read_logrotate_globals(client: &SshClient) -> Result<String, String> {
+    let (output, status) = client
+        .execute_with_status(&format!(
+            "if sudo test -e {0}; then sudo cat {0} 2>&1; fi",
+            LOGROTATE_MAIN_CONFIG
+        ))
+        .map_err(|e| e.message)?;
+    if status != 0 {
+        return Err(format!(
+            "Can't read {}: {}",
+            LOGROTATE_MAIN_CONFIG,
+            output.trim()
+        ));
+    }
+    Ok(global_directives(&output))
+}
+
+/// Shell creating a temporary config in `$g` that holds `globals` and then includes
+/// `file` (a quoted path or `"$var"`). logrotate only applies the defaults from
+/// /etc/logrotate.conf to files it includes, so a drop-in is never run on its own.
+fn logrotate_wrapper_script(globals: &str, file: &str) -> String {
+    format!(
+        "g=$(sudo mktemp) && {{ printf '%s' {}; printf 'include %s\\n' {}; }} | sudo tee \"$g\" > /dev/null",
+        shell_quote(globals),
+        file
+    )
+}
+
+/// Dry-run `content` with `logrotate -d` from a temporary root-owned copy, included from
+/// a config holding the global defaults, and install it only if that passes. Unlike the
+/// nginx editor no `.bak` is kept next to the file: logrotate would read it as another
+/// config and complain about duplicate log entries.
+#[tauri::command]
+pub async fn save_logrotate_config(
+    name: String,
+    content: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<LogrotateSaveResult, String> {
+    let path = logrotate_config_path(&name)?;
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let content = format!("{}\n", content.trim_end_matches('\n'));
+    let globals = read_logrotate_globals(client)?;
+    let (output, status) = client
+        .execute_with_status(&format!(
+            "t=$(sudo mktemp) || exit 1; echo \"{}$t\"; \
+             printf '%s' {} | sudo tee \"$t\" > /dev/null && {} && sudo logrotate -d \"$g\" 2>&1; \
+             s=$?; sudo rm -f \"$t\" ${{g:+\"$g\"}}; exit $s",
+            LOGROTATE_TEMP_MARKER,
+            shell_quote(&content),
+            logrotate_wrapper_script(&globals, "\"$t\"")
+        ))
+        .map_err(|e| e.message)?;
+    let test = parse_logrotate_test(&output, status, &path);
+    if !test.ok {
+        return Ok(LogrotateSaveResult {
+            saved: false,
+            message: format!("logrotate rejected the config; {} was not changed", path),
+            test,
+            diff: Vec::new(),
+        });
+    }
+
+    let (previous, _) = client
+        .execute_with_status(&format!("sudo cat {} 2>/dev/null", shell_quote(&path)))
+        .map_err(|e| e.message)?;
+    let result = run_action(
+        client,
+        &format!(
+            "printf '%s' {} | sudo tee {1} > /dev/null && sudo chmod 644 {1}",
+            shell_quote(&content),
+            shell_quote(&path)
+        ),
+        format!("Saved {}", path),
+        &format!("Failed to write {}", path),
+    )?;
+    Ok(LogrotateSaveResult {
+        saved: result.success,
+        message: result.message,
+        test,
+        diff: if result.success {
+            diff_lines(&previous, &content)
+        } else {
+            Vec::new()
+        },
+    })
+}
+
+/// Rotate the logs of one drop-in now, with the global defaults of /etc/logrotate.conf;
+/// `force` rotates even when they aren't due
+#[tauri::command]
+pub async fn run_logrotate_now(
+    name: String,
+    force: bool,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    let path = logrotate_config_path(&name)?;
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+    let globals = read_logrotate_globals(client)?;
+    run_action(
+        client,
+        &format!(
+            "sudo test -f {0} || {{ echo 'No such config'; exit 3; }}; \
+             {1} && sudo logrotate -v {2}\"$g\"; s=$?; sudo rm -f ${{g:+\"$g\"}}; exit $s",
+            shell_quote(&path),
+            logrotate_wrapper_script(&globals, &shell_quote(&path)),
+            if force { "-f " } else { "" }
+        ),
+        format!(
+            "Ran logrotate{} for {}",
+            if force { " (forced)" } else { "" },
+            path
+        ),
+        &format!("logrotate failed for {}", path),
+    )
+}
+
+/// `logrotate -d` output with the temporary file's name replaced by `path`. Missing log
+/// files are only warnings: a config is often written before its service first logs.
+fn parse_logrotate_test(output: &str, exit_status: i32, path: &str) -> ConfigTestResult {
+    let temp = output
+        .lines()
+        .find_map(|line| line.strip_prefix(LOGROTATE_TEMP_MARKER))
+        .filter(|t| !t.is_empty());
+    let output: String = output
+        .lines()
+        .filter(|line| !line.starts_with(LOGROTATE_TEMP_MARKER))
+        .map(|line| match temp {
+            Some(temp) => line.replace(temp, path),
+            None => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    for line in output.lines() {
+        let (level, message) = match line.trim().split_once(": ") {
+            Some((level @ ("error" | "warning"), message)) => (level, message),
+            _ => continue,
+        };
+        // e.g. "error: /etc/logrotate.d/app:3 unknown option 'rotat' -- ignoring line"
+        let (file, line_number, message) = match message.split_once(' ') {
+            Some((location, rest)) if location.starts_with('/') => {
+                match location.trim_end_matches(':').rsplit_once(':') {
+                    Some((file, num)) if num.parse::<u32>().is_ok() => {
+                        (Some(file.to_string()), num.parse().ok(), rest)
+                    }
+                    _ => (None, None, message),
+                }
+            }
+            _ => (None, None, message),
+        };
+        let entry = ConfigMessage {
+            level: level.to_string(),
+            file,
+            line: line_number,
+            message: message.to_string(),
+        };
+        if level == "warning" || message.starts_with("stat of") {
+            warnings.push(entry);
+        } else {
+            errors.push(entry);
+        }
+    }
+
+    ConfigTestResult {
+        // logrotate also exits 1 for the missing logs that are only warnings here
+        ok: errors.is_empty() && (exit_status == 0 || !warnings.is_empty()),
+        errors,
+        warnings,
+        output,
+    }
+}
+
+// ==================== SSHD COMMANDS ====================
+
+const SSHD_CONFIG_PATH: &str = "/etc/ssh/sshd_config";
+
+/// Directives whose change can shut users, or DPanel's own connection, out of the server.
+/// Changing one needs explicit confirmation, and the returned warning says why.
+const SSHD_LOCKOUT_DIRECTIVES: &[(&str, &str)] = &[
+    (
+        "PasswordAuthentication",
+        "Turning off password logins locks out every account without an authorized SSH key.",
+    ),
+    (
+        "PermitRootLogin",
+        "Restricting root logins locks out anyone connecting as root, DPanel included if it does.",
+    ),
+    (
+        "Port",
+        "sshd stops listening on the current port. The firewall has to allow the new one and the server's DPanel connection settings have to be updated.",
+    ),
+    (
+        "ListenAddress",
+        "sshd stops listening on addresses that aren't listed, which can make it unreachable.",
+    ),
+    (
+        "PubkeyAuthentication",
+        "Turning off key logins locks out every account that doesn't use a password.",
+    ),
+    (
+        "AuthenticationMethods",
+        "Accounts that can't satisfy the required methods can no longer log in.",
+    ),
+    ("AllowUsers", "Users that aren't listed can no longer log in."),
+    ("AllowGroups", "Users outside the listed groups can no longer log in."),
+    ("DenyUsers", "Listed users can no longer log in."),
+    ("DenyGroups", "Members of the listed groups can no longer log in."),
+];
+
+/// Absolute path of an `Include` pattern; relative ones are resolved against /etc/ssh
+fn sshd_include_path(pattern: &str) -> String {
+    if pattern.starts_with('/') {
+        pattern.to_string()
+    } else {
+        format!("/etc/ssh/{}", pattern)
+    }
+}
+
+/// Directives of sshd_config with each `Include`d file spliced in where it's included,
+/// the order sshd reads them in. Includes nested inside included files aren't followed.
+fn read_sshd_config(client: &SshClient) -> Result<Vec<SshdDirective>, String> {
+    let main = read_remote_file(client, SSHD_CONFIG_PATH)?;
+    let directives = parse_sshd_directives(SSHD_CONFIG_PATH, &main);
+    let include_patterns = |directive: &SshdDirective| -> Vec<String> {
+        if !directive.key.eq_ignore_ascii_case("Include") {
+            return Vec::new();
+        }
+        directive
+            .value
+            .split_whitespace()
+            .map(sshd_include_path)
+            // Expanded by the shell below, so only plain paths and wildcards
+            .filter(|p| {
+                p.chars().all(|c| {
+                    c.is_ascii_alphanumeric() || matches!(c, '/' | '.' | '_' | '-' | '*' | '?')
+                })
+            })
+            .collect()
+    };
+
+    let patterns: Vec<String> = directives.iter().flat_map(include_patterns).collect();
+    let included = if patterns.is_empty() {
+        HashMap::new()
+    } else {
+        let script = format!(
+            "for f in {}; do [ -f \"$f\" ] && {{ echo \"===$f===\"; cat \"$f\"; }}; done; true",
+            patterns.join(" ")
+        );
+        let output = client
+            .execute_command(&format!("sudo sh -c {}", shell_quote(&script)))
+            .map_err(|e| e.message)?;
+        split_sections(&output)
+    };
+
+    let mut all = Vec::new();
+    for directive in directives {
+        let patterns = include_patterns(&directive);
+        all.push(directive);
+        for pattern in patterns {
+            let mut files: Vec<&String> = included
+                .keys()
+                .filter(|file| glob_matches(&pattern, file))
+                .collect();
+            files.sort();
+            for file in files {
+                all.extend(parse_sshd_directives(file, &included[file]));
+            }
+        }
+    }
+    Ok(all)
+}
+
+#[tauri::command]
+pub async fn get_sshd_config(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SshdDirective>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    read_sshd_config(ssh_client.as_ref())
+}
+
+/// Set `key` in the file holding its effective global value (sshd_config itself when it
+/// isn't set yet), backing it up and rolling back if `sshd -t` rejects the result.
+/// Keywords sshd adds up over every line (`Port`, `AllowUsers`, ...) end up with `value`
+/// as their only global line; when their lines are spread over several files nothing is
+/// changed, since editing one file would leave the others in effect. Directives in
+/// `SSHD_LOCKOUT_DIRECTIVES` are left untouched unless `confirm_lockout_risk` is set.
+/// sshd is reloaded only when asked to and the check passes; a reload keeps existing
+/// sessions open, including this one.
+#[tauri::command]
+pub async fn set_sshd_directive(
+    key: String,
+    value: String,
+    confirm_lockout_risk: bool,
+    reload: bool,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<SshdSaveResult, String> {
+    let key = key.trim();
+    let value = value.trim();
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(format!("Invalid sshd directive '{}'", key));
+    }
+    if key.eq_ignore_ascii_case("Include") || key.eq_ignore_ascii_case("Match") {
+        return Err(format!("{} lines can't be set as a directive", key));
+    }
+    if value.is_empty() || value.contains(['\n', '\r']) {
+        return Err(format!("Invalid value for {}", key));
+    }
+
+    let warning = SSHD_LOCKOUT_DIRECTIVES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(key))
+        .map(|(name, risk)| {
+            format!(
+                "Changing {} can lock you out of this server. {} Open a new SSH session to check the change before closing this one.",
+                name, risk
+            )
+        });
+    if warning.is_some() && !confirm_lockout_risk {
+        return Ok(SshdSaveResult {
+            requires_confirmation: true,
+            message: format!("{} was not changed; confirm the change to apply it.", key),
+            warning,
+            file: None,
+            save: None,
+            reloaded: false,
+        });
+    }
+
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let directives = read_sshd_config(client)?;
+    let global: Vec<&SshdDirective> = directives
+        .iter()
+        .filter(|d| d.match_block.is_none() && d.key.eq_ignore_ascii_case(key))
+        .collect();
+    let effective = global.first().copied();
+    let accumulating = is_accumulating(key);
+    if accumulating {
+        let mut files: Vec<&str> = global.iter().map(|d| d.file.as_str()).collect();
+        files.sort();
+        files.dedup();
+        if files.len() > 1 {
+            return Err(format!(
+                "{} is set in {}. sshd uses every {} line, so setting it in one file would leave the others in effect; remove the extra lines first.",
+                key,
+                files.join(", "),
+                key
+            ));
+        }
+    }
+    let already_set = if accumulating {
+        global.len() == 1 && global[0].value == value
+    } else {
+        effective.is_some_and(|d| d.value == value)
+    };
+    if already_set {
+        return Ok(SshdSaveResult {
+            requires_confirmation: false,
+            message: format!("{} is already set to {}", key, value),
+            warning,
+            file: effective.map(|d| d.file.clone()),
+            save: None,
+            reloaded: false,
+        });
+    }
+    let file = effective.map_or(SSHD_CONFIG_PATH.to_string(), |d| d.file.clone());
+
+    let content = read_remote_file(client, &file)?;
+    let updated = crate::sshd_config::set_sshd_directive(&content, key, value);
+    let save = save_config_with_rollback(client, &file, &updated, run_sshd_test)?;
+    if save.rolled_back {
+        return Ok(SshdSaveResult {
+            requires_confirmation: false,
+            message: save.message.clone(),
+            warning,
+            file: Some(file),
+            save: Some(save),
+            reloaded: false,
+        });
+    }
+
+    let (reloaded, message) = if reload {
+        let (output, status) = client
+            .execute_with_status(
+                "sudo systemctl reload ssh 2>&1 || sudo systemctl reload sshd 2>&1",
+            )
+            .map_err(|e| e.message)?;
+        if status == 0 {
+            (
+                true,
+                format!("Set {} {} in {} and reloaded sshd", key, value, file),
+            )
+        } else {
+            (
+                false,
+                format!(
+                    "Set {} {} in {}, but reloading sshd failed: {}",
+                    key,
+                    value,
+                    file,
+                    output.trim()
+                ),
+            )
+        }
+    } else {
+        (
+            false,
+            format!(
+                "Set {} {} in {}. Reload sshd to apply it.",
+                key, value, file
+            ),
+        )
+    };
+
+    Ok(SshdSaveResult {
+        requires_confirmation: false,
+        message,
+        warning,
+        file: Some(file),
+        save: Some(save),
+        reloaded,
+    })
+}
+
+fn run_sshd_test(client: &SshClient) -> Result<ConfigTestResult, String> {
+    let (output, exit_status) = client
+        .execute_with_status("sudo sshd -t 2>&1")
+        .map_err(|e| e.message)?;
+    Ok(parse_sshd_test(&output, exit_status))
+}
+
+/// Parse `sshd -t` output. Every message is an error when the check fails; when it passes
+/// the remaining ones (deprecated options and the like) are warnings.
+fn parse_sshd_test(output: &str, exit_status: i32) -> ConfigTestResult {
+    let level = if exit_status == 0 { "warning" } else { "error" };
+    let entries: Vec<ConfigMessage> = output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            // e.g. "/etc/ssh/sshd_config: line 14: Bad configuration option: Foo", or
+            // "/etc/ssh/sshd_config line 14: unsupported option" without the first colon
+            let location = line.split_once(" line ").and_then(|(file, rest)| {
+                let (number, message) = rest.split_once(": ")?;
+                let file = file.trim_end_matches(':');
+                let number = number.parse::<u32>().ok()?;
+                file.starts_with('/')
+                    .then(|| (file.to_string(), number, message))
+            });
+            let (file, line_number, message) = match location {
+                Some((file, number, message)) => (Some(file), Some(number), message),
+                None => (None, None, line),
+            };
+            ConfigMessage {
+                level: level.to_string(),
+                file,
+                line: line_number,
+                message: message.to_string(),
+            }
+        })
+        .collect();
+
+    let (errors, warnings) = if exit_status == 0 {
+        (Vec::new(), entries)
+    } else {
+        (entries, Vec::new())
+    };
+    ConfigTestResult {
+        ok: exit_status == 0,
+        errors,
+        warnings,
+        output: output.to_string(),
+    }
+}
+
+// ==================== CRON COMMANDS ====================
+
+#[tauri::command]
+pub async fn get_user_crontab(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    client
+        .execute_command("crontab -l 2>&1")
+        .map_err(|e| e.message)
+}
+
+#[tauri::command]
+pub async fn save_user_crontab(
+    content: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    install_crontab(client, &content)
+}
+
+#[tauri::command]
+pub async fn get_user_cron_jobs(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<CronJob>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let crontab = read_user_crontab(client)?;
+    Ok(parse_crontab(&crontab, false, "user"))
+}
+
+#[tauri::command]
+pub async fn get_system_crontab(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    client
+        .execute_command("cat /etc/crontab 2>&1")
+        .map_err(|e| e.message)
+}
+
+#[tauri::command]
+pub async fn get_cron_d_jobs(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<CronJob>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let mut jobs = Vec::new();
+
+    // List files in /etc/cron.d/
+    let files = client
+        .execute_command("ls -1 /etc/cron.d/ 2>/dev/null")
+        .unwrap_or_default();
+
+    for file in files.lines() {
+        if file.is_empty() || file == "README" || file == ".placeholder" {
+            continue;
+        }
+
+        let content = client
+            .execute_command(&format!("cat /etc/cron.d/{}", file))
+            .unwrap_or_default();
+
+        // Files renamed by `toggle_cron_d_file` stay listed, with every job disabled
+        let file_disabled = file.ends_with(CRON_D_DISABLED_SUFFIX);
+        jobs.extend(
+            parse_crontab(&content, true, &format!("/etc/cron.d/{}", file))
+                .into_iter()
+                .map(|mut job| {
+                    job.enabled &= !file_disabled;
+                    job
+                }),
+        );
+    }
+
+    Ok(jobs)
+}
+
+/// cron skips /etc/cron.d files whose names contain a dot, so this suffix disables one
+const CRON_D_DISABLED_SUFFIX: &str = ".disabled";
+
+/// Enable or disable a whole /etc/cron.d drop-in by renaming it to and from `<name>.disabled`
+#[tauri::command]
+pub async fn toggle_cron_d_file(
+    filename: String,
+    enabled: bool,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let name = cron_d_file_name(&filename)?;
+    let active = format!("/etc/cron.d/{}", name);
+    let disabled = format!("{}{}", active, CRON_D_DISABLED_SUFFIX);
+    let (from, to) = if enabled {
+        (&disabled, &active)
+    } else {
+        (&active, &disabled)
+    };
+
+    let command = format!(
+        "[ -e {from} ] || exit 3; [ -e {to} ] && exit 4; sudo mv {from} {to} 2>&1",
+        from = shell_quote(from),
+        to = shell_quote(to)
+    );
+    let (output, exit_status) = client
+        .execute_with_status(&command)
+        .map_err(|e| e.message)?;
+    match exit_status {
+        0 => Ok(format!(
+            "{} {}",
+            active,
+            if enabled { "enabled" } else { "disabled" }
+        )),
+        3 => Err(format!("{} does not exist", from)),
+        4 => Err(format!("{} already exists", to)),
+        _ => Err(format!("Failed to rename {}: {}", from, output.trim())),
+    }
+}
+
+/// Base name of a cron.d file, accepting it with or without the disabled suffix
+fn cron_d_file_name(filename: &str) -> Result<String, String> {
+    let name = filename
+        .strip_suffix(CRON_D_DISABLED_SUFFIX)
+        .unwrap_or(filename);
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if valid {
+        Ok(name.to_string())
+    } else {
+        Err(format!("Invalid cron.d file name: {}", filename))
+    }
+}
+
+#[tauri::command]
+pub async fn get_cron_folders(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<CronFolder>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let mut folders = Vec::new();
+
+    for folder_name in &["cron.daily", "cron.weekly", "cron.monthly", "cron.hourly"] {
+        let path = format!("/etc/{}", folder_name);
+        let scripts_output = client
+            .execute_command(&format!("ls -1 {} 2>/dev/null", path))
+            .unwrap_or_default();
+
+        let scripts: Vec<String> = scripts_output
+            .lines()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .collect();
+
+        if !scripts.is_empty() {
+            folders.push(CronFolder {
+                name: folder_name.to_string(),
+                path,
+                scripts,
+            });
+        }
+    }
+
+    Ok(folders)
+}
+
+#[tauri::command]
+pub async fn get_cron_logs(
+    lines: u32,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    // Try to find cron logs in various locations
+    let log_paths = ["/var/log/cron", "/var/log/syslog", "/var/log/messages"];
+
+    for log_path in &log_paths {
+        if !is_remote_file(client, log_path) {
+            continue;
+        }
+        let grep_cmd = format!("grep -i cron {} | tail -n {} 2>&1", log_path, lines);
+        if let Ok(logs) = client.execute_command(&grep_cmd) {
+            if !logs.is_empty() {
+                return Ok(logs);
+            }
+        }
+    }
+
+    // Fallback: try journalctl
+    client
+        .execute_command(&format!("journalctl -u cron -n {} --no-pager 2>&1", lines))
+        .map_err(|e| e.message)
+}
+
+#[tauri::command]
+pub async fn add_cron_job(
+    schedule: String,
+    command: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    // Get current crontab
+    let current = read_user_crontab(client)?;
+
+    // Add new job
+    let new_entry = format!("{} {}", schedule, command);
+    let new_crontab = if current.trim().is_empty() {
+        new_entry
+    } else {
+        format!("{}\n{}", current.trim_end(), new_entry)
+    };
+
+    install_crontab(client, &new_crontab)
+}
+
+#[tauri::command]
+pub async fn delete_cron_job(
+    line_number: usize,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let current = read_user_crontab(client)?;
+    let new_crontab = remove_cron_job_line(&current, line_number)?;
+    install_crontab(client, &new_crontab)
+}
+
+#[tauri::command]
+pub async fn toggle_cron_job(
+    line_number: usize,
+    enabled: bool,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let current = read_user_crontab(client)?;
+    let new_crontab = toggle_cron_job_line(&current, line_number, enabled)?;
+    install_crontab(client, &new_crontab)
+}
+
+/// Directory every job log written by `wrap_cron_job_logging` lives in
+const CRON_LOG_DIR: &str = "/var/log/dpanel-cron";
+
+/// Append `>> <log> 2>&1` to a job so its output is kept. Returns the log path.
+/// `log_path` names a file in `CRON_LOG_DIR`; the file is handed to the SSH user, so
+/// anything outside that directory is refused.
+#[tauri::command]
+pub async fn wrap_cron_job_logging(
+    line_number: usize,
+    log_path: Option<String>,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let current = read_user_crontab(client)?;
+    let job = find_user_cron_job(&current, line_number)?;
+    let log_path = cron_log_path(log_path.as_deref(), &job.command)?;
+    let new_crontab = wrap_cron_job_line(&current, line_number, &log_path)?;
+
+    // The job runs as the SSH user, who must be able to append to the log
+    let prepare_cmd = format!(
+        "sudo install -d -m 755 {dir} && sudo touch {log} && sudo chown \"$(id -un)\": {log} && sudo chmod 640 {log}",
+        dir = CRON_LOG_DIR,
+        log = shell_quote(&log_path)
+    );
+    let (output, exit_status) = client
+        .execute_with_status(&format!("{} 2>&1", prepare_cmd))
+        .map_err(|e| e.message)?;
+    if exit_status != 0 {
+        return Err(format!("Failed to create {}: {}", log_path, output.trim()));
+    }
+
+    install_crontab(client, &new_crontab)?;
+    Ok(log_path)
+}
+
+/// Remove the output redirect added by `wrap_cron_job_logging`. The log file is kept.
+#[tauri::command]
+pub async fn unwrap_cron_job_logging(
+    line_number: usize,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let current = read_user_crontab(client)?;
+    let new_crontab = unwrap_cron_job_line(&current, line_number)?;
+    install_crontab(client, &new_crontab)
+}
+
+/// Which users may run `crontab`, as listed in /etc/cron.allow and /etc/cron.deny
+#[tauri::command]
+pub async fn get_cron_access(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<CronAccess, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let files = read_cron_access_files(ssh_client.as_ref())?;
+    Ok(cron_access_from_files(&files))
+}
+
+/// Let `user` use `crontab` or stop them, editing whichever file cron consults for them:
+/// cron.allow when it exists, since cron.deny is ignored then, and cron.deny otherwise
+#[tauri::command]
+pub async fn set_cron_access(
+    user: String,
+    allow: bool,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ActionResult, String> {
+    let user = user.trim();
+    if user.is_empty()
+        || !user
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._-$".contains(c))
+    {
+        return Err(format!("Invalid user name '{}'", user));
+    }
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let files = read_cron_access_files(client)?;
+    let access = cron_access_from_files(&files);
+    if cron_access_allows(&access, user) == allow {
+        return Ok(ActionResult {
+            success: true,
+            message: format!(
+                "{} is already {} to use crontab",
+                user,
+                if allow { "allowed" } else { "denied" }
+            ),
+            raw_output: String::new(),
+        });
+    }
+
+    let changes = cron_access_changes(
+        files.get(CRON_ALLOW_PATH).map(String::as_str),
+        files.get(CRON_DENY_PATH).map(String::as_str),
+        user,
+        allow,
+    );
+    let script: Vec<String> = changes
+        .iter()
+        .map(|(path, content)| {
+            format!(
+                "printf '%s' {} | sudo tee {} > /dev/null",
+                shell_quote(content),
+                path
+            )
+        })
+        .collect();
+    let files: Vec<&str> = changes.iter().map(|(path, _)| *path).collect();
+    run_action(
+        client,
+        &script.join(" && "),
+        format!(
+            "{} {} to use crontab (updated {})",
+            user,
+            if allow { "allowed" } else { "denied" },
+            files.join(", ")
+        ),
+        "Failed to update cron access",
+    )
+}
+
+const CRON_ALLOW_PATH: &str = "/etc/cron.allow";
+const CRON_DENY_PATH: &str = "/etc/cron.deny";
+
+/// Contents of cron.allow and cron.deny, keyed by path, for the ones that exist. A file
+/// that exists but can't be read is an error, not an empty list.
+fn read_cron_access_files(client: &SshClient) -> Result<HashMap<String, String>, String> {
+    let (output, status) = client
+        .execute_with_status(&format!(
+            "for f in {} {}; do if [ -f \"$f\" ]; then echo \"===$f===\"; sudo cat \"$f\" 2>&1 || exit 1; fi; done",
+            CRON_ALLOW_PATH, CRON_DENY_PATH
+        ))
+        .map_err(|e| e.message)?;
+    if status != 0 {
+        return Err(format!(
+            "Failed to read cron access files: {}",
+            output.lines().last().unwrap_or("").trim()
+        ));
+    }
+    Ok(split_sections(&output))
+}
+
+/// A list is `None` when its file doesn't exist, which cron treats differently from an
+/// empty file
+fn cron_access_from_files(files: &HashMap<String, String>) -> CronAccess {
+    let list = |path: &str| files.get(path).map(|content| parse_cron_user_list(content));
+    CronAccess {
+        allow: list(CRON_ALLOW_PATH),
+        deny: list(CRON_DENY_PATH),
+    }
+}
+
+/// One user name per line; blank lines and `#` comments are ignored
+fn parse_cron_user_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+/// Whether cron lets `user` have a crontab. With neither file only root may on some
+/// distributions; Debian and Ubuntu let everyone, which is what's assumed here.
+fn cron_access_allows(access: &CronAccess, user: &str) -> bool {
+    let listed = |list: &Vec<String>| list.iter().any(|u| u == user);
+    match (&access.allow, &access.deny) {
+        (Some(allow), _) => listed(allow),
+        (None, Some(deny)) => !listed(deny),
+        (None, None) => true,
+    }
+}
+
+/// `content` with `user`'s line added or removed, or `None` when it's already that way.
+/// Only that line changes, so comments and the order of the rest survive.
+fn edit_cron_user_list(content: &str, user: &str, present: bool) -> Option<String> {
+    let listed = parse_cron_user_list(content).iter().any(|u| u == user);
+    if listed == present {
+        return None;
+    }
+    if present {
+        let mut updated = content.to_string();
+        if !updated.is_empty() && !updated.ends_with('\n') {
+            updated.push('\n');
+        }
+        updated.push_str(user);
+        updated.push('\n');
+        Some(updated)
+    } else {
+        Some(
+            content
+                .lines()
+                .filter(|line| line.trim() != user)
+                .map(|line| format!("{}\n", line))
+                .collect(),
         )
-        .map_err(|e| e.message)?;
+    }
+}
 
-    let mut volumes: Vec<DockerVolume> = Vec::new();
-    for line in output.lines() {
-        let parts: Vec<&str> = line.split('|').collect();
-        if parts.len() >= 4 {
-            volumes.push(DockerVolume {
-                name: parts[0].to_string(),
-                driver: parts[1].to_string(),
-                mountpoint: parts[2].to_string(),
-                scope: parts[3].to_string(),
-                labels: Vec::new(),
-            });
+/// New contents for the files that need to change so `user` ends up allowed or denied,
+/// given the current contents (`None` for a missing file). A user being allowed is also
+/// taken off cron.deny so the files don't disagree.
+fn cron_access_changes(
+    allow_file: Option<&str>,
+    deny_file: Option<&str>,
+    user: &str,
+    allow: bool,
+) -> Vec<(&'static str, String)> {
+    let mut changes = Vec::new();
+    match (allow_file, allow) {
+        (Some(content), _) => {
+            if let Some(content) = edit_cron_user_list(content, user, allow) {
+                changes.push((CRON_ALLOW_PATH, content));
+            }
+        }
+        (None, false) => {
+            if let Some(content) = edit_cron_user_list(deny_file.unwrap_or_default(), user, true) {
+                changes.push((CRON_DENY_PATH, content));
+            }
         }
+        (None, true) => {}
     }
-    Ok(volumes)
+    if allow {
+        if let Some(content) = deny_file.and_then(|deny| edit_cron_user_list(deny, user, false)) {
+            changes.push((CRON_DENY_PATH, content));
+        }
+    }
+    changes
 }
 
-#[tauri::command]
-pub async fn get_docker_networks(state: State<'_, AppState>) -> Result<Vec<DockerNetwork>, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+/// Prefix for at commands: exits 127 when `at` isn't installed, so callers can say so
+/// instead of showing "command not found"
+const AT_CHECK: &str = "command -v at >/dev/null 2>&1 || exit 127;";
 
-    let output = client
-        .execute_command("docker network ls --format '{{.ID}}|{{.Name}}|{{.Driver}}|{{.Scope}}'")
-        .map_err(|e| e.message)?;
+fn at_missing_error() -> String {
+    "`at` is not installed on this server (install the 'at' package and enable atd)".to_string()
+}
 
-    let mut networks: Vec<DockerNetwork> = Vec::new();
-    for line in output.lines() {
-        let parts: Vec<&str> = line.split('|').collect();
-        if parts.len() >= 4 {
-            networks.push(DockerNetwork {
-                id: parts[0].to_string(),
-                name: parts[1].to_string(),
-                driver: parts[2].to_string(),
-                scope: parts[3].to_string(),
-                subnet: None,
-                gateway: None,
-                containers: Vec::new(),
-            });
-        }
+/// Run `command` once at `when`, any timespec `at` accepts ("02:00 tomorrow", "now + 1 hour")
+#[tauri::command]
+pub async fn schedule_at_job(
+    command: String,
+    when: String,
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<AtJobScheduled, String> {
+    let when = when.trim();
+    if command.trim().is_empty() {
+        return Err("Command is empty".to_string());
+    }
+    if when.is_empty() || when.starts_with('-') {
+        return Err(format!("Invalid time '{}'", when));
+    }
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
+
+    let (output, status) = client
+        .execute_with_status(&format!(
+            "{} printf '%s\\n' {} | at {} 2>&1",
+            AT_CHECK,
+            shell_quote(&command),
+            shell_quote(when)
+        ))
+        .map_err(|e| e.message)?;
+    match status {
+        0 => parse_at_scheduled(&output)
+            .ok_or_else(|| format!("Unexpected output from at: {}", output.trim())),
+        127 => Err(at_missing_error()),
+        // at rejects a bad timespec with "Garbled time" and a non-zero exit
+        _ => Err(format!("at rejected '{}': {}", when, output.trim())),
     }
-    Ok(networks)
 }
 
 #[tauri::command]
-pub async fn get_docker_images(state: State<'_, AppState>) -> Result<Vec<DockerImage>, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+pub async fn list_at_jobs(
+    server_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<AtJob>, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
 
-    let output = client
-        .execute_command("docker images --format '{{.ID}}|{{.Repository}}|{{.Tag}}|{{.Size}}|{{.CreatedAt}}' --no-trunc")
+    let (output, status) = client
+        .execute_with_status(&format!("{} atq 2>&1", AT_CHECK))
         .map_err(|e| e.message)?;
-
-    let mut images: Vec<DockerImage> = Vec::new();
-    for line in output.lines() {
-        let parts: Vec<&str> = line.split('|').collect();
-        if parts.len() >= 5 {
-            images.push(DockerImage {
-                id: parts[0].to_string(),
-                repository: parts[1].to_string(),
-                tag: parts[2].to_string(),
-                size: 0, // Would need additional parsing
-                created: parts[4].to_string(),
-                architecture: "amd64".to_string(),
-            });
-        }
+    match status {
+        0 => Ok(parse_atq(&output)),
+        127 => Err(at_missing_error()),
+        _ => Err(output.trim().to_string()),
     }
-    Ok(images)
 }
 
 #[tauri::command]
-pub async fn get_container_env(
-    container_name: String,
-    show_secrets: bool,
+pub async fn remove_at_job(
+    id: u32,
+    server_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<Vec<String>, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+) -> Result<String, String> {
+    let ssh_client = state.writable_client(server_id.as_deref()).await?;
+    let client = ssh_client.as_ref();
 
-    let output = client
-        .execute_command(&format!(
-            "docker inspect --format '{{{{json .Config.Env}}}}' {}",
-            container_name
-        ))
+    let (output, status) = client
+        .execute_with_status(&format!("{} atrm {} 2>&1", AT_CHECK, id))
         .map_err(|e| e.message)?;
+    match status {
+        0 => Ok(format!("Job {} removed", id)),
+        127 => Err(at_missing_error()),
+        _ => Err(format!("Failed to remove job {}: {}", id, output.trim())),
+    }
+}
 
-    let env_vars: Vec<String> = serde_json::from_str(&output).unwrap_or_default();
+/// The "job 7 at Thu Oct 17 02:00:00 2026" line `at` prints after its warnings
+fn parse_at_scheduled(output: &str) -> Option<AtJobScheduled> {
+    output.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix("job ")?;
+        let (id, scheduled_for) = rest.split_once(" at ")?;
+        Some(AtJobScheduled {
+            job_id: id.parse().ok()?,
+            scheduled_for: scheduled_for.trim().to_string(),
+        })
+    })
+}
 
-    if show_secrets {
-        Ok(env_vars)
-    } else {
-        // Filter out sensitive variables
-        Ok(env_vars
-            .into_iter()
-            .filter(|e| {
-                !e.contains("PASSWORD")
-                    && !e.contains("SECRET")
-                    && !e.contains("KEY")
-                    && !e.contains("TOKEN")
+/// `atq` lines: "7\tThu Oct 17 02:00:00 2026 a alice"
+fn parse_atq(output: &str) -> Vec<AtJob> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (id, rest) = line.split_once('\t')?;
+            let mut fields: Vec<&str> = rest.split_whitespace().collect();
+            let user = fields.pop()?.to_string();
+            let queue = fields.pop()?.to_string();
+            Some(AtJob {
+                id: id.trim().parse().ok()?,
+                scheduled_for: fields.join(" "),
+                queue,
+                user,
             })
-            .collect())
+        })
+        .collect()
+}
+
+/// The current user's crontab, or an empty string if they don't have one yet
+fn read_user_crontab(client: &SshClient) -> Result<String, String> {
+    let (output, exit_status) = client
+        .execute_with_status("crontab -l 2>/dev/null")
+        .map_err(|e| e.message)?;
+    Ok(if exit_status == 0 {
+        output
+    } else {
+        String::new()
+    })
+}
+
+fn install_crontab(client: &SshClient, content: &str) -> Result<String, String> {
+    let content = format!("{}\n", content.trim_end_matches('\n'));
+    let install_cmd = format!("printf '%s' {} | crontab - 2>&1", shell_quote(&content));
+    let output = client
+        .execute_command(&install_cmd)
+        .map_err(|e| e.message)?;
+    if output.contains("are not allowed to use this program") {
+        return Err(format!(
+            "{} This server limits crontab through {} and {}; check Cron access.",
+            output.trim(),
+            CRON_ALLOW_PATH,
+            CRON_DENY_PATH
+        ));
     }
+    Ok(output)
 }
 
-#[tauri::command]
-pub async fn find_compose_files(state: State<'_, AppState>) -> Result<Vec<ComposeProject>, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+/// Parse a crontab into jobs, recognising `#`-disabled jobs and skipping comments,
+/// blank lines and `NAME=value` settings. `line_number` is the 1-based raw line the
+/// job came from, which is what `delete_cron_job`/`toggle_cron_job` operate on.
+/// System crontabs (`/etc/crontab`, `/etc/cron.d/*`) carry a user field after the schedule.
+fn parse_crontab(content: &str, has_user_field: bool, source: &str) -> Vec<CronJob> {
+    let mut jobs = Vec::new();
 
-    // Get current server profile to get server ID
-    // For now, use host as identifier
-    let server_id = client.get_host();
+    for (idx, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        let (enabled, entry) = match trimmed.strip_prefix('#') {
+            Some(rest) => (false, rest.trim()),
+            None => (true, trimmed),
+        };
+        if entry.is_empty() || is_cron_env_line(entry) {
+            continue;
+        }
+
+        let parts: Vec<&str> = entry.split_whitespace().collect();
+        let schedule_len = if parts[0].starts_with('@') { 1 } else { 5 };
+        let min_len = schedule_len + if has_user_field { 2 } else { 1 };
+        if parts.len() < min_len || !is_cron_schedule(&parts[..schedule_len]) {
+            continue;
+        }
+
+        let (user, command_start) = if has_user_field {
+            (parts[schedule_len].to_string(), schedule_len + 1)
+        } else {
+            (String::new(), schedule_len)
+        };
+
+        jobs.push(CronJob {
+            id: jobs.len(),
+            line_number: idx + 1,
+            schedule: parts[..schedule_len].join(" "),
+            command: parts[command_start..].join(" "),
+            user,
+            enabled,
+            source: source.to_string(),
+        });
+    }
 
-    scan_compose_files(client, &state.compose_cache, &server_id).await
+    jobs
 }
 
-#[tauri::command]
-pub async fn refresh_compose_files(
-    state: State<'_, AppState>,
-) -> Result<Vec<ComposeProject>, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+fn is_cron_env_line(line: &str) -> bool {
+    match line.split_once('=') {
+        Some((name, _)) => {
+            let name = name.trim();
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
 
-    let server_id = client.get_host();
+/// Validate schedule fields so prose comments like `# m h dom mon dow command` aren't
+/// taken for jobs
+fn is_cron_schedule(fields: &[&str]) -> bool {
+    const NAMES: &[&str] = &[
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec", "sun",
+        "mon", "tue", "wed", "thu", "fri", "sat",
+    ];
+    const MACROS: &[&str] = &[
+        "@reboot",
+        "@yearly",
+        "@annually",
+        "@monthly",
+        "@weekly",
+        "@daily",
+        "@midnight",
+        "@hourly",
+    ];
 
-    refresh_compose_scan(client, &state.compose_cache, &server_id).await
+    if let [single] = fields {
+        return MACROS.contains(single);
+    }
+
+    let is_value = |v: &str| {
+        !v.is_empty()
+            && (v.chars().all(|c| c.is_ascii_digit()) || NAMES.contains(&v.to_lowercase().as_str()))
+    };
+    fields.iter().all(|field| {
+        field.split(',').all(|item| {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => (range, Some(step)),
+                None => (item, None),
+            };
+            let step_ok = match step {
+                Some(s) => !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()),
+                None => true,
+            };
+            let range_ok = range == "*"
+                || match range.split_once('-') {
+                    Some((from, to)) => is_value(from) && is_value(to),
+                    None => is_value(range),
+                };
+            step_ok && range_ok
+        })
+    })
 }
 
-#[tauri::command]
-pub async fn get_container_logs_stream(
-    container_name: String,
-    lines: u32,
-    follow: bool,
-    state: State<'_, AppState>,
+fn find_user_cron_job(content: &str, line_number: usize) -> Result<CronJob, String> {
+    parse_crontab(content, false, "user")
+        .into_iter()
+        .find(|job| job.line_number == line_number)
+        .ok_or_else(|| format!("No cron job on line {}", line_number))
+}
+
+fn remove_cron_job_line(content: &str, line_number: usize) -> Result<String, String> {
+    find_user_cron_job(content, line_number)?;
+    Ok(content
+        .lines()
+        .enumerate()
+        .filter(|(i, _)| i + 1 != line_number)
+        .map(|(_, line)| line)
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn toggle_cron_job_line(
+    content: &str,
+    line_number: usize,
+    enabled: bool,
 ) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+    let job = find_user_cron_job(content, line_number)?;
+    Ok(content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            if i + 1 != line_number || job.enabled == enabled {
+                line.to_string()
+            } else if enabled {
+                line.trim_start()
+                    .trim_start_matches('#')
+                    .trim_start()
+                    .to_string()
+            } else {
+                format!("# {}", line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
 
-    let command = if follow {
-        format!(
-            "docker logs --tail {} --follow {} 2>&1",
-            lines, container_name
-        )
-    } else {
-        format!("docker logs --tail {} {} 2>&1", lines, container_name)
+/// `/var/log/dpanel-cron/<hash>.log`, keyed by the job's command so re-wrapping a job
+/// keeps appending to the same file. FNV-1a, because it must be stable across builds.
+fn default_cron_log_path(command: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in command.trim().bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{}/{:016x}.log", CRON_LOG_DIR, hash)
+}
+
+/// Log for a job: `custom` as a file name in `CRON_LOG_DIR` (given bare or with the
+/// directory), or the default derived from the command
+fn cron_log_path(custom: Option<&str>, command: &str) -> Result<String, String> {
+    let Some(custom) = custom.map(str::trim).filter(|c| !c.is_empty()) else {
+        return Ok(default_cron_log_path(command));
     };
+    let name = custom
+        .strip_prefix(CRON_LOG_DIR)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .unwrap_or(custom);
+    let path = format!("{}/{}", CRON_LOG_DIR, name);
+    if name.contains('/') || name.starts_with('.') || !is_valid_cron_log_path(&path) {
+        return Err(format!(
+            "Invalid log path '{}': job logs must be files in {}",
+            custom, CRON_LOG_DIR
+        ));
+    }
+    Ok(path)
+}
 
-    client.execute_command(&command).map_err(|e| e.message)
+/// Absolute path without characters that are special to the shell or to cron (`%`)
+fn is_valid_cron_log_path(path: &str) -> bool {
+    path.starts_with('/')
+        && !path.ends_with('/')
+        && !path.split('/').any(|part| part == "..")
+        && path
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '.' | '_' | '-'))
 }
 
-#[tauri::command]
-pub async fn get_ufw_overview(state: State<'_, AppState>) -> Result<UfwOverview, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+/// Log file a job's command already appends all of its output to, if any
+fn cron_log_redirect(command: &str) -> Option<&str> {
+    let rest = command.trim_end().strip_suffix("2>&1")?.trim_end();
+    let (before, path) = rest.rsplit_once(' ')?;
+    before.trim_end().strip_suffix(">>")?;
+    Some(path)
+}
 
-    // Get UFW status verbose
-    let status_output = client
-        .execute_command("sudo ufw status verbose 2>&1")
-        .map_err(|e| e.message)?;
+fn wrap_cron_job_line(content: &str, line_number: usize, log_path: &str) -> Result<String, String> {
+    let job = find_user_cron_job(content, line_number)?;
+    if let Some(existing) = cron_log_redirect(&job.command) {
+        return Err(format!(
+            "Job on line {} already logs to {}",
+            line_number, existing
+        ));
+    }
+    Ok(content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            if i + 1 == line_number {
+                format!("{} >> {} 2>&1", line.trim_end(), log_path)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
 
-    // Get UFW numbered for rule numbers
-    let _numbered_output = client
-        .execute_command("sudo ufw status numbered 2>&1")
-        .map_err(|e| e.message)?;
+fn unwrap_cron_job_line(content: &str, line_number: usize) -> Result<String, String> {
+    let job = find_user_cron_job(content, line_number)?;
+    let log_path = cron_log_redirect(&job.command)
+        .ok_or_else(|| format!("Job on line {} doesn't log to a file", line_number))?;
+    let suffix = format!(">> {} 2>&1", log_path);
+    Ok(content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let trimmed = line.trim_end();
+            match trimmed.strip_suffix(suffix.as_str()) {
+                Some(rest) if i + 1 == line_number => rest.trim_end().to_string(),
+                _ => line.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
 
-    // Get listening ports
-    let listening_output = client
-        .execute_command("ss -tlnp 2>&1 | tail -n +2 | awk '{print $4, $6}'")
-        .unwrap_or_default();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let mut active = false;
-    let mut open_ports: Vec<PortInfo> = Vec::new();
-    let mut blocked_ports: Vec<PortInfo> = Vec::new();
-    let mut all_rules: Vec<UfwRule> = Vec::new();
-    let mut total_rules = 0u32;
-    let mut allow_rules = 0u32;
-    let mut deny_rules = 0u32;
-    let mut limit_rules = 0u32;
+    #[test]
+    fn test_shell_quote_escapes_single_quotes() {
+        assert_eq!(shell_quote("plain"), "'plain'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
 
-    // Parse listening ports
-    let mut listening_ports_map: std::collections::HashMap<String, String> =
-        std::collections::HashMap::new();
-    for line in listening_output.lines() {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 2 {
-            let addr = parts[0];
-            let process = parts[1].trim_matches('"').to_string();
-            if let Some(port) = addr.rsplit(':').next() {
-                if port.chars().all(|c| c.is_numeric()) {
-                    listening_ports_map.insert(port.to_string(), process);
-                }
-            }
+    #[test]
+    fn test_parse_nginx_test_output_ok() {
+        let output = "nginx: the configuration file /etc/nginx/nginx.conf syntax is ok\n\
+                      nginx: configuration file /etc/nginx/nginx.conf test is successful\n";
+        let result = parse_nginx_test_output(output, 0);
+        assert!(result.ok);
+        assert!(result.errors.is_empty());
+    }
+
+    #[test]
+    fn test_parse_nginx_test_output_emerg_with_location() {
+        let output =
+            "nginx: [emerg] unknown directive \"proxy_pas\" in /etc/nginx/sites-enabled/app:14\n\
+                      nginx: configuration file /etc/nginx/nginx.conf test failed\n";
+        let result = parse_nginx_test_output(output, 1);
+        assert!(!result.ok);
+        assert_eq!(result.errors.len(), 1);
+        let error = &result.errors[0];
+        assert_eq!(error.level, "emerg");
+        assert_eq!(error.file.as_deref(), Some("/etc/nginx/sites-enabled/app"));
+        assert_eq!(error.line, Some(14));
+        assert_eq!(error.message, "unknown directive \"proxy_pas\"");
+    }
+
+    #[test]
+    fn test_parse_nginx_test_output_without_location() {
+        let output =
+            "nginx: [warn] conflicting server name \"example.com\" on 0.0.0.0:80, ignored\n\
+                      nginx: [emerg] bind() to 0.0.0.0:80 failed (98: Address already in use)\n";
+        let result = parse_nginx_test_output(output, 1);
+        assert!(!result.ok);
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(result.warnings[0].file, None);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].line, None);
+        assert!(result.errors[0]
+            .message
+            .starts_with("bind() to 0.0.0.0:80 failed"));
+    }
+
+    #[test]
+    fn test_parse_nginx_test_output_nested_quotes_in_message() {
+        let output = "nginx: [emerg] open() \"/etc/nginx/snippets/ssl.conf\" failed (2: No such file or directory) in /etc/nginx/sites-enabled/site:9\n";
+        let result = parse_nginx_test_output(output, 1);
+        assert_eq!(
+            result.errors[0].file.as_deref(),
+            Some("/etc/nginx/sites-enabled/site")
+        );
+        assert_eq!(result.errors[0].line, Some(9));
+    }
+
+    #[test]
+    fn test_parse_gpu_metrics_with_processes() {
+        let output = "0, NVIDIA A100-SXM4-40GB, 87, 31201, 40960, 64, GPU-aaaa\n\
+                      1, NVIDIA A100-SXM4-40GB, [N/A], 0, 40960, 38, GPU-bbbb\n\
+                      ===APPS===\n\
+                      GPU-aaaa, 4211, python3, 31000\n";
+        let gpus = parse_gpu_metrics(output);
+
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(gpus[0].name, "NVIDIA A100-SXM4-40GB");
+        assert_eq!(gpus[0].util_percent, 87.0);
+        assert_eq!(gpus[0].mem_used_mb, 31201);
+        assert_eq!(gpus[0].mem_total_mb, 40960);
+        assert_eq!(gpus[0].temp_c, 64.0);
+        assert_eq!(gpus[0].processes.len(), 1);
+        assert_eq!(gpus[0].processes[0].pid, 4211);
+        assert_eq!(gpus[1].util_percent, 0.0);
+        assert!(gpus[1].processes.is_empty());
+    }
+
+    #[test]
+    fn test_parse_gpu_metrics_without_nvidia_smi() {
+        assert!(parse_gpu_metrics("").is_empty());
+    }
+
+    #[test]
+    fn test_restart_policy_validation() {
+        for policy in [
+            "no",
+            "always",
+            "unless-stopped",
+            "on-failure",
+            "on-failure:5",
+        ] {
+            assert!(
+                is_valid_restart_policy(policy),
+                "{} should be valid",
+                policy
+            );
+        }
+        for policy in [
+            "",
+            "sometimes",
+            "always:3",
+            "on-failure:",
+            "on-failure:-1",
+            "no; rm -rf /",
+        ] {
+            assert!(
+                !is_valid_restart_policy(policy),
+                "{} should be invalid",
+                policy
+            );
         }
     }
 
-    // Parse rules from verbose output
-    let lines: Vec<&str> = status_output.lines().collect();
-    let mut in_rules = false;
+    #[test]
+    fn test_format_restart_policy() {
+        assert_eq!(format_restart_policy("on-failure:5"), "on-failure:5");
+        assert_eq!(format_restart_policy("on-failure:0"), "on-failure");
+        assert_eq!(format_restart_policy("always:0"), "always");
+        assert_eq!(format_restart_policy(":0"), "no");
+    }
 
-    for line in lines {
-        let line = line.trim();
+    #[test]
+    fn test_build_container_run_spec_reproduces_flags() {
+        let inspect: JsonValue = serde_json::from_str(
+            r#"{
+                "Config": {
+                    "Image": "myapp:1.0",
+                    "Env": ["PATH=/usr/local/bin:/usr/bin", "DATABASE_URL=postgres://db/app", "MODE=prod"]
+                },
+                "HostConfig": {
+                    "PortBindings": {
+                        "80/tcp": [{"HostIp": "", "HostPort": "8080"}],
+                        "9090/tcp": [{"HostIp": "127.0.0.1", "HostPort": "9090"}]
+                    },
+                    "Binds": ["/srv/app/data:/data", "/etc/app:/etc/app:ro"],
+                    "RestartPolicy": {"Name": "on-failure", "MaximumRetryCount": 3},
+                    "NetworkMode": "backend"
+                },
+                "Mounts": [
+                    {"Type": "bind", "Source": "/srv/app/data", "Destination": "/data", "RW": true},
+                    {"Type": "volume", "Name": "cache", "Destination": "/cache", "RW": true}
+                ],
+                "NetworkSettings": {"Networks": {"backend": {}, "frontend": {}}}
+            }"#,
+        )
+        .unwrap();
+        let image_config: JsonValue =
+            serde_json::from_str(r#"{"Env": ["PATH=/usr/local/bin:/usr/bin"]}"#).unwrap();
+
+        let spec = build_container_run_spec(&inspect, &image_config, "web", "myapp:2.0").unwrap();
+
+        assert_eq!(
+            spec.run_args,
+            vec![
+                "-d",
+                "--name",
+                "web",
+                "-p",
+                "8080:80/tcp",
+                "-p",
+                "127.0.0.1:9090:9090/tcp",
+                "-e",
+                "DATABASE_URL=postgres://db/app",
+                "-e",
+                "MODE=prod",
+                "-v",
+                "/srv/app/data:/data",
+                "-v",
+                "/etc/app:/etc/app:ro",
+                "-v",
+                "cache:/cache",
+                "--restart",
+                "on-failure:3",
+                "--network",
+                "backend",
+                "myapp:2.0",
+            ]
+        );
+        assert_eq!(spec.extra_networks, vec![vec!["frontend"]]);
+    }
 
-        if line.starts_with("Status:") {
-            active = line.contains("active");
-        } else if line.starts_with("---") {
-            in_rules = true;
-        } else if in_rules && !line.is_empty() {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 3 {
-                let rule_str = parts[0].to_string();
-                let action = if parts.len() > 1 {
-                    parts[1].to_string()
-                } else {
-                    "".to_string()
-                };
-                let from = if parts.len() > 2 {
-                    parts[2..].join(" ")
-                } else {
-                    "Anywhere".to_string()
-                };
+    #[test]
+    fn test_build_container_run_spec_default_bridge() {
+        let inspect: JsonValue = serde_json::from_str(
+            r#"{
+                "Config": {"Image": "redis:7", "Env": []},
+                "HostConfig": {"RestartPolicy": {"Name": "no"}, "NetworkMode": "default"},
+                "NetworkSettings": {"Networks": {"bridge": {}}}
+            }"#,
+        )
+        .unwrap();
+
+        let spec =
+            build_container_run_spec(&inspect, &JsonValue::Null, "cache", "redis:7").unwrap();
+
+        assert_eq!(spec.run_args, vec!["-d", "--name", "cache", "redis:7"]);
+        assert!(spec.extra_networks.is_empty());
+    }
+
+    #[test]
+    fn test_build_container_run_spec_keeps_runtime_settings() {
+        let image_config: JsonValue = serde_json::from_str(
+            r#"{
+                "Cmd": ["nginx", "-g", "daemon off;"],
+                "Entrypoint": ["/docker-entrypoint.sh"],
+                "User": "",
+                "Labels": {"maintainer": "NGINX"}
+            }"#,
+        )
+        .unwrap();
+        let inspect: JsonValue = serde_json::from_str(
+            r#"{
+                "Id": "0123456789abcdef",
+                "Config": {
+                    "Hostname": "0123456789ab",
+                    "Cmd": ["nginx", "-c", "/etc/nginx/alt.conf"],
+                    "Entrypoint": ["/docker-entrypoint.sh"],
+                    "User": "101:101",
+                    "WorkingDir": "/srv",
+                    "Labels": {"maintainer": "NGINX", "team": "web"}
+                },
+                "HostConfig": {
+                    "NetworkMode": "backend",
+                    "CapAdd": ["NET_ADMIN"],
+                    "CapDrop": ["ALL"],
+                    "ShmSize": 67108864,
+                    "IpcMode": "private",
+                    "LogConfig": {"Type": "json-file", "Config": {"max-size": "10m"}}
+                },
+                "NetworkSettings": {"Networks": {
+                    "backend": {"Aliases": ["proxy", "0123456789ab"], "IPAMConfig": {"IPv4Address": "172.20.0.5"}},
+                    "frontend": {"Aliases": ["edge"]}
+                }}
+            }"#,
+        )
+        .unwrap();
+
+        let spec =
+            build_container_run_spec(&inspect, &image_config, "proxy-1", "nginx:1.27").unwrap();
+        let args = spec.run_args.join(" ");
+        assert!(args.contains("--user 101:101 --workdir /srv --label team=web"));
+        assert!(!args.contains("maintainer"));
+        assert!(args.contains("--cap-add NET_ADMIN --cap-drop ALL"));
+        assert!(args.contains("--log-driver json-file --log-opt max-size=10m"));
+        assert!(args.contains("--network backend --ip 172.20.0.5 --network-alias proxy"));
+        assert!(
+            !args.contains("--shm-size") && !args.contains("--ipc") && !args.contains("--hostname")
+        );
+        // The image's entrypoint is kept, only the changed command is passed
+        assert!(args.ends_with("nginx:1.27 nginx -c /etc/nginx/alt.conf"));
+        assert_eq!(
+            spec.extra_networks,
+            vec![vec!["--alias", "edge", "frontend"]]
+        );
+
+        let mut custom = inspect.clone();
+        custom["Config"]["Entrypoint"] = serde_json::json!(["tini", "--"]);
+        let spec =
+            build_container_run_spec(&custom, &image_config, "proxy-1", "nginx:1.27").unwrap();
+        assert!(spec
+            .run_args
+            .join(" ")
+            .ends_with("--entrypoint tini nginx:1.27 -- nginx -c /etc/nginx/alt.conf"));
+    }
+
+    #[test]
+    fn test_build_container_run_spec_refuses_unsupported_settings() {
+        let inspect: JsonValue = serde_json::from_str(
+            r#"{
+                "Config": {"Image": "app"},
+                "HostConfig": {"VolumesFrom": ["data"], "CpuQuota": 50000, "OomScoreAdj": 0}
+            }"#,
+        )
+        .unwrap();
+        let err = build_container_run_spec(&inspect, &JsonValue::Null, "app", "app").unwrap_err();
+        assert!(err.ends_with("VolumesFrom, CpuQuota"));
+    }
+
+    #[test]
+    fn test_container_name_validation() {
+        assert!(is_valid_container_name("web-1.blue_green"));
+        assert!(!is_valid_container_name("-web"));
+        assert!(!is_valid_container_name("web app"));
+        assert!(!is_valid_container_name(""));
+    }
+
+    const CRONTAB_WITH_COMMENTS: &str = "# m h  dom mon dow   command\n\
+                                         SHELL=/bin/bash\n\
+                                         PATH=/usr/local/bin:/usr/bin:/bin\n\
+                                         \n\
+                                         # nightly backup\n\
+                                         0 2 * * * /usr/local/bin/backup.sh\n\
+                                         # */5 * * * * /usr/local/bin/poll.sh\n\
+                                         @reboot /usr/local/bin/warmup.sh";
+
+    #[test]
+    fn test_parse_crontab_maps_jobs_to_raw_lines() {
+        let jobs = parse_crontab(CRONTAB_WITH_COMMENTS, false, "user");
+
+        assert_eq!(jobs.len(), 3);
+        assert_eq!(jobs[0].line_number, 6);
+        assert_eq!(jobs[0].schedule, "0 2 * * *");
+        assert_eq!(jobs[0].command, "/usr/local/bin/backup.sh");
+        assert!(jobs[0].enabled);
+        assert_eq!(jobs[1].line_number, 7);
+        assert_eq!(jobs[1].schedule, "*/5 * * * *");
+        assert!(!jobs[1].enabled);
+        assert_eq!(jobs[2].line_number, 8);
+        assert_eq!(jobs[2].schedule, "@reboot");
+    }
 
-                // Extract port and protocol
-                let port_protocol: Vec<&str> = rule_str.split('/').collect();
-                let port = port_protocol
-                    .get(0)
-                    .map(|s| s.to_string())
-                    .unwrap_or_default();
-                let protocol = port_protocol
-                    .get(1)
-                    .map(|s| s.to_string())
-                    .unwrap_or_else(|| "any".to_string());
-
-                // Get service name if port is listening
-                let service_name = listening_ports_map.get(&port).cloned();
-
-                let port_info = PortInfo {
-                    port: port.clone(),
-                    protocol: protocol.clone(),
-                    action: action.clone(),
-                    source: from.replace(" (v6)", ""),
-                    service_name,
-                };
+    #[test]
+    fn test_delete_cron_job_removes_intended_line() {
+        let jobs = parse_crontab(CRONTAB_WITH_COMMENTS, false, "user");
+        let updated = remove_cron_job_line(CRONTAB_WITH_COMMENTS, jobs[2].line_number).unwrap();
 
-                if action.to_uppercase().contains("ALLOW") || action.to_uppercase() == "ALLOW" {
-                    open_ports.push(port_info.clone());
-                    allow_rules += 1;
-                } else if action.to_uppercase().contains("DENY") || action.to_uppercase() == "DENY"
-                {
-                    blocked_ports.push(port_info.clone());
-                    deny_rules += 1;
-                } else if action.to_uppercase().contains("LIMIT") {
-                    open_ports.push(port_info.clone()); // LIMIT is also a form of allowing
-                    limit_rules += 1;
-                }
+        assert!(!updated.contains("warmup.sh"));
+        assert!(updated.contains("PATH=/usr/local/bin"));
+        assert!(updated.contains("backup.sh"));
+        assert!(updated.contains("poll.sh"));
 
-                all_rules.push(UfwRule {
-                    rule: rule_str,
-                    to: "Anywhere".to_string(),
-                    action,
-                    from: from.replace(" (v6)", ""),
-                    port: if port.is_empty() { None } else { Some(port) },
-                });
+        // Comment and env lines are never treated as jobs
+        assert!(remove_cron_job_line(CRONTAB_WITH_COMMENTS, 3).is_err());
+        assert!(remove_cron_job_line(CRONTAB_WITH_COMMENTS, 1).is_err());
+    }
 
-                total_rules += 1;
-            }
-        }
+    #[test]
+    fn test_toggle_cron_job_line() {
+        let disabled = toggle_cron_job_line(CRONTAB_WITH_COMMENTS, 6, false).unwrap();
+        assert!(disabled.contains("# 0 2 * * * /usr/local/bin/backup.sh"));
+
+        let enabled = toggle_cron_job_line(CRONTAB_WITH_COMMENTS, 7, true).unwrap();
+        assert!(enabled
+            .lines()
+            .any(|l| l == "*/5 * * * * /usr/local/bin/poll.sh"));
+
+        assert!(toggle_cron_job_line(CRONTAB_WITH_COMMENTS, 5, true).is_err());
     }
 
-    Ok(UfwOverview {
-        active,
-        open_ports,
-        blocked_ports,
-        all_rules,
-        stats: UfwStats {
-            total_rules,
-            allow_rules,
-            deny_rules,
-            limit_rules,
-        },
-    })
-}
+    #[test]
+    fn test_parse_crontab_with_user_field() {
+        let jobs = parse_crontab(
+            "MAILTO=root\n30 3 * * sun root /usr/sbin/logrotate /etc/logrotate.conf",
+            true,
+            "/etc/cron.d/logrotate",
+        );
 
-#[tauri::command]
-pub async fn get_listening_ports(state: State<'_, AppState>) -> Result<Vec<PortInfo>, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].user, "root");
+        assert_eq!(jobs[0].command, "/usr/sbin/logrotate /etc/logrotate.conf");
+        assert_eq!(jobs[0].line_number, 2);
+    }
 
-    // Get listening TCP ports
-    let output = client
-        .execute_command("ss -tlnp 2>&1 | tail -n +2")
-        .map_err(|e| e.message)?;
+    #[test]
+    fn test_parse_docker_event() {
+        let line = r#"{"status":"die","id":"4f1c","from":"nginx:alpine","Type":"container","Action":"die","Actor":{"ID":"4f1c","Attributes":{"exitCode":"137","image":"nginx:alpine","name":"web"}},"scope":"local","time":1700000000,"timeNano":1700000000123456789}"#;
+        let event = parse_docker_event(line).unwrap();
 
-    let mut ports: Vec<PortInfo> = Vec::new();
+        assert_eq!(event.action, "die");
+        assert_eq!(event.actor_id, "4f1c");
+        assert_eq!(event.actor_name.as_deref(), Some("web"));
+        assert_eq!(event.event_type, "container");
+        assert_eq!(event.time, 1700000000);
+    }
 
-    for line in output.lines() {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 6 {
-            let local_addr = parts[3];
-            let process_info = parts[5].trim_matches('"').to_string();
-
-            if let Some(port) = local_addr.rsplit(':').next() {
-                if port.chars().all(|c| c.is_numeric()) {
-                    // Extract process name
-                    let process_name = if process_info.contains("users:") {
-                        process_info
-                            .split("users:")
-                            .nth(1)
-                            .and_then(|s| s.split('"').nth(1))
-                            .unwrap_or("unknown")
-                            .to_string()
-                    } else {
-                        "unknown".to_string()
-                    };
-
-                    ports.push(PortInfo {
-                        port: port.to_string(),
-                        protocol: "tcp".to_string(),
-                        action: "listening".to_string(),
-                        source: "0.0.0.0".to_string(),
-                        service_name: Some(process_name),
-                    });
-                }
-            }
+    #[test]
+    fn test_parse_docker_event_skips_other_types() {
+        let line =
+            r#"{"Type":"daemon","Action":"reload","Actor":{"ID":"abc","Attributes":{}},"time":1}"#;
+        assert!(parse_docker_event(line).is_none());
+        assert!(parse_docker_event("not json").is_none());
+    }
+
+    #[test]
+    fn test_parse_container_diff() {
+        let output = "C /var\nA /var/lib/app/cache.db\nD /etc/motd\nC /var/lib\n";
+        let diff = parse_container_diff(output, 10);
+
+        assert_eq!(diff.total, 4);
+        assert!(!diff.truncated);
+        assert_eq!(diff.changes[0].path, "/etc/motd");
+        assert_eq!(diff.changes[0].kind, FsChangeKind::Deleted);
+        assert_eq!(diff.changes[3].path, "/var/lib/app/cache.db");
+        assert_eq!(diff.changes[3].kind, FsChangeKind::Added);
+
+        let capped = parse_container_diff(output, 2);
+        assert_eq!(capped.changes.len(), 2);
+        assert_eq!(capped.total, 4);
+        assert!(capped.truncated);
+    }
+
+    #[test]
+    fn test_journal_priority_validation() {
+        for priority in ["err", "warning", "3", "emerg..err", "0..4"] {
+            assert!(
+                is_valid_journal_priority(priority),
+                "{} should be valid",
+                priority
+            );
+        }
+        for priority in ["", "error", "8", "err..", "err; reboot"] {
+            assert!(
+                !is_valid_journal_priority(priority),
+                "{} should be invalid",
+                priority
+            );
         }
     }
 
-    Ok(ports)
-}
+    #[test]
+    fn test_parse_boot_range() {
+        let output = "{\"_BOOT_ID\":\"aaa111\",\"__CURSOR\":\"s=1\"}\n{\"_BOOT_ID\":\"bbb222\",\"__CURSOR\":\"s=2\"}\n";
+        let range = parse_boot_range(output).unwrap();
+        assert_eq!(range.first_boot_id, "aaa111");
+        assert_eq!(range.last_boot_id, "bbb222");
+        assert!(parse_boot_range("").is_none());
+    }
 
-// ==================== NGINX COMMANDS ====================
+    #[test]
+    fn test_nginx_rate_validation() {
+        assert!(is_valid_nginx_rate("10r/s"));
+        assert!(is_valid_nginx_rate("60r/m"));
+        assert!(!is_valid_nginx_rate("10r/h"));
+        assert!(!is_valid_nginx_rate("r/s"));
+        assert!(!is_valid_nginx_rate("10/s"));
+
+        assert!(is_valid_nginx_size("10m"));
+        assert!(is_valid_nginx_size("512k"));
+        assert!(is_valid_nginx_size("65536"));
+        assert!(!is_valid_nginx_size("m"));
+        assert!(!is_valid_nginx_size("10mm"));
+        assert!(!is_valid_nginx_size("0m"));
+        assert!(!is_valid_nginx_size("10g"));
+    }
 
-#[tauri::command]
-pub async fn nginx_status(state: State<'_, AppState>) -> Result<NginxStatus, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+    #[test]
+    fn test_parse_published_ports() {
+        let ports = parse_published_ports(
+            "0.0.0.0:5432->5432/tcp, :::5432->5432/tcp, 127.0.0.1:6379->6379/tcp, 9000/tcp, \
+             0.0.0.0:8000-8001->80-81/udp",
+        );
+        assert_eq!(ports.len(), 5);
+        assert_eq!(
+            ports[0],
+            ("0.0.0.0".to_string(), 5432, 5432, "tcp".to_string())
+        );
+        assert_eq!(ports[1].0, "::");
+        assert_eq!(ports[2].0, "127.0.0.1");
+        assert_eq!(
+            ports[4],
+            ("0.0.0.0".to_string(), 8001, 81, "udp".to_string())
+        );
+    }
 
-    // Check if nginx is running with multiple fallback methods
-    let is_running = {
-        // Method 1: systemctl is-active
-        let systemctl_check = client
-            .execute_command("systemctl is-active nginx 2>/dev/null || echo 'inactive'")
-            .unwrap_or_default()
-            .trim()
-            .to_string();
+    #[test]
+    fn test_build_docker_exposures() {
+        let ps = "db\t0.0.0.0:5432->5432/tcp, :::5432->5432/tcp\n\
+                  web\t0.0.0.0:80->8080/tcp\n\
+                  cache\t127.0.0.1:6379->6379/tcp\n";
+        let ufw = "Status: active\n\nTo                         Action      From\n\
+                   --                         ------      ----\n\
+                   5432/tcp                   DENY IN     Anywhere\n\
+                   80/tcp                     ALLOW IN    Anywhere\n";
+
+        let exposures = build_docker_exposures(ps, ufw, true, "");
+        assert_eq!(exposures.len(), 2);
+        assert_eq!(exposures[0].container, "db");
+        assert_eq!(exposures[0].ufw_action.as_deref(), Some("DENY"));
+        assert!(exposures[0].bypasses_ufw);
+        assert_eq!(
+            exposures[0].warning.as_deref(),
+            Some("Container db publishes 5432/tcp which UFW cannot block")
+        );
+        // Explicitly allowed, so nothing surprising
+        assert!(exposures[1].warning.is_none());
 
-        if systemctl_check == "active" {
-            true
-        } else {
-            // Method 2: Check if nginx process exists
-            let process_check = client
-                .execute_command("pgrep -x nginx 2>/dev/null | head -1")
-                .unwrap_or_default()
-                .trim()
-                .to_string();
+        let fixed = build_docker_exposures(ps, ufw, true, "-A DOCKER-USER -j ufw-user-forward");
+        assert!(fixed.iter().all(|e| !e.bypasses_ufw && e.warning.is_none()));
+    }
 
-            if !process_check.is_empty() {
-                true
-            } else {
-                // Method 3: Check pid file
-                client
-                    .execute_command("test -f /run/nginx.pid && echo 'running' || echo 'stopped'")
-                    .unwrap_or_default()
-                    .trim()
-                    .eq("running")
-            }
-        }
-    };
+    #[test]
+    fn test_parse_journal_entry() {
+        let entry = parse_journal_entry(
+            r#"{"__REALTIME_TIMESTAMP":"1700000000123456","_SYSTEMD_UNIT":"nginx.service","PRIORITY":"3","MESSAGE":"bind() failed"}"#,
+        )
+        .unwrap();
+        assert_eq!(entry.timestamp, 1700000000123456);
+        assert_eq!(entry.unit.as_deref(), Some("nginx.service"));
+        assert_eq!(entry.priority, Some(3));
+        assert_eq!(entry.message, "bind() failed");
+
+        let binary =
+            parse_journal_entry(r#"{"SYSLOG_IDENTIFIER":"kernel","MESSAGE":[104,105]}"#).unwrap();
+        assert_eq!(binary.unit.as_deref(), Some("kernel"));
+        assert_eq!(binary.message, "hi");
+
+        assert!(parse_journal_entry("Failed to add match 'foo': Invalid argument").is_none());
+    }
 
-    // Get nginx version
-    let version = client
-        .execute_command("nginx -v 2>&1 | cut -d'/' -f2")
-        .unwrap_or_default()
-        .trim()
-        .to_string();
+    #[test]
+    fn test_parse_docker_ports() {
+        let ports = parse_docker_ports("0.0.0.0:8000-8002->8000-8002/tcp, 80/tcp, 7000-7001/udp");
+        assert_eq!(ports.len(), 6);
+        assert_eq!(ports[0].host_ip, "0.0.0.0");
+        assert_eq!(ports[2].host_port, "8002");
+        assert_eq!(ports[2].container_port, "8002");
+
+        // Exposed but not published
+        assert_eq!(ports[3].host_ip, "");
+        assert_eq!(ports[3].host_port, "");
+        assert_eq!(ports[3].container_port, "80");
+        assert_eq!(ports[5].container_port, "7001");
+        assert_eq!(ports[5].protocol, "udp");
+
+        assert!(parse_docker_ports("").is_empty());
+    }
 
-    // Get worker processes
-    let worker_processes = client
-        .execute_command("grep -E '^\\s*worker_processes' /etc/nginx/nginx.conf 2>/dev/null | awk '{print $2}' | tr -d ';'")
-        .unwrap_or_else(|_| "auto".to_string())
-        .trim()
-        .to_string();
+    #[test]
+    fn test_parse_compose_config_output() {
+        let ok = parse_compose_config_output("", 0);
+        assert!(ok.ok);
+        assert!(ok.errors.is_empty());
 
-    // Test config
-    let config_test = client
-        .execute_command("nginx -t 2>&1")
-        .unwrap_or_else(|e| format!("Config test failed: {}", e.message));
+        let failed = parse_compose_config_output(
+            "yaml: line 12: mapping values are not allowed in this context\n",
+            15,
+        );
+        assert!(!failed.ok);
+        assert_eq!(failed.errors.len(), 1);
+        assert_eq!(failed.errors[0].line, Some(12));
+        assert_eq!(failed.errors[0].column, None);
+
+        let schema = parse_compose_config_output(
+            "validating /srv/app/compose.yml: services.web Additional property prots is not allowed",
+            15,
+        );
+        assert_eq!(schema.errors[0].line, None);
 
-    Ok(NginxStatus {
-        running: is_running,
-        version,
-        worker_processes,
-        config_test,
-    })
-}
+        let positioned =
+            parse_compose_config_output("yaml: line 3, column 9: did not find expected key", 1);
+        assert_eq!(positioned.errors[0].line, Some(3));
+        assert_eq!(positioned.errors[0].column, Some(9));
+    }
 
-#[tauri::command]
-pub async fn nginx_action(action: String, state: State<'_, AppState>) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+    #[test]
+    fn test_build_health_report() {
+        let output = "===NGINX===\nactive\n===FAILED===\n\
+                      ===CONTAINERS===\n/web|running|healthy\n/job|exited|\n\
+                      ===DISK===\n/ 42%\n/var 93%\n\
+                      ===MEM===\n8000000\n4000000\n\
+                      ===PSI===\nsome avg10=0.00 avg60=0.00 avg300=0.00 total=0\n===END===\n";
+        let report = build_health_report(&split_sections(output));
+
+        assert_eq!(report.status, HealthStatus::Warn);
+        let by_name = |name: &str| report.checks.iter().find(|c| c.name == name).unwrap();
+        assert_eq!(by_name("Nginx").status, HealthStatus::Ok);
+        assert_eq!(by_name("Containers").detail, "1 stopped: job");
+        assert_eq!(by_name("Disk").detail, "/var at 93%");
+        assert_eq!(by_name("Memory").status, HealthStatus::Ok);
+
+        let failing = "===NGINX===\ninactive\n===FAILED===\nbackup.service\n\
+                       ===CONTAINERS===\n/db|running|unhealthy\n===DISK===\n===MEM===\n===END===\n";
+        let report = build_health_report(&split_sections(failing));
+        assert_eq!(report.status, HealthStatus::Critical);
+        assert_eq!(
+            report
+                .checks
+                .iter()
+                .filter(|c| c.status == HealthStatus::Critical)
+                .count(),
+            3
+        );
+    }
 
-    let command = format!("sudo systemctl {} nginx", action);
-    client.execute_command(&command).map_err(|e| e.message)
-}
+    #[test]
+    fn test_parse_container_health() {
+        let health = parse_container_health("abc123|healthy\ndef456|\nfff000|unhealthy\n");
+        assert_eq!(health.len(), 2);
+        assert_eq!(health.get("abc123").map(String::as_str), Some("healthy"));
+        assert!(!health.contains_key("def456"));
+        assert_eq!(health.get("fff000").map(String::as_str), Some("unhealthy"));
+    }
 
-#[tauri::command]
-pub async fn nginx_test_config(state: State<'_, AppState>) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+    #[test]
+    fn test_parse_du_output() {
+        let output =
+            "4096\t/var/log/apt\n1048576\t/var/log/journal\nbogus line\n2097152\t/var/log\n";
+        let usage = parse_du_output(output, 2);
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].path, "/var/log");
+        assert_eq!(usage[0].bytes, 2097152);
+        assert_eq!(usage[1].path, "/var/log/journal");
+    }
 
-    client
-        .execute_command("sudo nginx -t 2>&1")
-        .map_err(|e| e.message)
-}
+    #[test]
+    fn test_parse_advanced_storage() {
+        let zfs = parse_zfs_storage(
+            "rpool\t1000\t400\t600\t12\t40\tONLINE\n",
+            "rpool\t400\t560\t96\t/\nrpool/data\t300\t560\t300\tnone\n",
+            "rpool/data@daily\t20\t280\t1700000000\n",
+        )
+        .unwrap();
+        assert_eq!(zfs.pools[0].capacity_percent, 40);
+        assert_eq!(zfs.pools[0].fragmentation_percent, Some(12));
+        assert_eq!(zfs.datasets[1].mountpoint, None);
+        assert_eq!(zfs.snapshots[0].created, 1700000000);
+        assert!(parse_zfs_storage("", "", "").is_none());
+
+        let btrfs = parse_btrfs_filesystems(
+            "@@ /dev/sda2[/@] /\n\
+             Data, single: total=8589934592, used=4294967296\n\
+             Metadata, DUP: total=1073741824, used=268435456\n\
+             @@ /dev/sda2[/@home] /home\n\
+             Data, single: total=8589934592, used=4294967296\n",
+        )
+        .unwrap();
+        assert_eq!(btrfs.len(), 1);
+        assert_eq!(btrfs[0].mount_point, "/");
+        assert_eq!(btrfs[0].allocations.len(), 2);
+        assert_eq!(btrfs[0].allocations[1].profile, "DUP");
+        assert!(parse_btrfs_filesystems("").is_none());
+
+        let lvm = parse_lvm_storage(
+            "  vg0|500000|100000|3|1\n",
+            "  root|vg0|200000||-wi-ao----||\n  pool|vg0|150000|42.50|twi-aotz--||\n  snap|vg0|50000|3.10|swi-a-s---|root|\n",
+        )
+        .unwrap();
+        assert_eq!(lvm.volume_groups[0].free, 100000);
+        assert_eq!(lvm.logical_volumes[0].data_percent, None);
+        assert_eq!(lvm.logical_volumes[1].data_percent, Some(42.5));
+        assert_eq!(lvm.logical_volumes[2].origin.as_deref(), Some("root"));
+        assert!(parse_lvm_storage("", "").is_none());
+    }
 
-#[tauri::command]
-pub async fn get_nginx_config(state: State<'_, AppState>) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+    #[test]
+    fn test_parse_docker_context() {
+        let rootless = "===MODE===\nenv\n===HOST===\nunix:///run/user/1000/docker.sock\n\
+                        ===INFO===\n24.0.7|[\"name=seccomp,profile=builtin\",\"name=rootless\"]\n\
+                        ===CONTEXT===\ndefault\nunix:///var/run/docker.sock\n===END===\n";
+        let (context, preamble) = parse_docker_context(&split_sections(rootless));
+        assert!(context.available);
+        assert!(context.rootless);
+        assert_eq!(context.version.as_deref(), Some("24.0.7"));
+        assert_eq!(
+            context.socket.as_deref(),
+            Some("unix:///run/user/1000/docker.sock")
+        );
+        assert_eq!(
+            preamble,
+            "export DOCKER_HOST='unix:///run/user/1000/docker.sock'; "
+        );
 
-    client
-        .execute_command("cat /etc/nginx/nginx.conf 2>&1")
-        .map_err(|e| e.message)
-}
+        let via_sudo = "===MODE===\nsudo\n===HOST===\n\n===INFO===\n26.1.0|[]\n\
+                        ===CONTEXT===\ndefault\nunix:///var/run/docker.sock\n===END===\n";
+        let (context, preamble) = parse_docker_context(&split_sections(via_sudo));
+        assert!(!context.rootless);
+        assert_eq!(
+            context.socket.as_deref(),
+            Some("unix:///var/run/docker.sock")
+        );
+        assert!(preamble.starts_with("docker() { sudo -n docker"));
 
-#[tauri::command]
-pub async fn save_nginx_config(
-    content: String,
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+        let missing = "===MODE===\nnone\n===HOST===\n\n===INFO===\n\n===CONTEXT===\n===END===\n";
+        let (context, preamble) = parse_docker_context(&split_sections(missing));
+        assert!(!context.available);
+        assert_eq!(context.version, None);
+        assert!(preamble.is_empty());
+    }
 
-    // Backup first
-    client
-        .execute_command("sudo cp /etc/nginx/nginx.conf /etc/nginx/nginx.conf.bak 2>&1")
-        .map_err(|e| e.message)?;
+    #[test]
+    fn test_diagnose_connection_stops_at_tcp() {
+        // Bind then drop a listener to get a local port nothing listens on
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let profile = ServerProfile {
+            id: "diag".to_string(),
+            name: "diag".to_string(),
+            host: "127.0.0.1".to_string(),
+            port,
+            username: "nobody".to_string(),
+            auth_method: AuthMethod::Password {
+                password: String::new(),
+            },
+            keepalive_secs: None,
+            connect_timeout_secs: Some(1),
+            retry_attempts: None,
+            probe_interval_secs: None,
+            max_output_bytes: None,
+        };
 
-    // Write new config using tee
-    let write_cmd = format!(
-        "echo '{}' | sudo tee /etc/nginx/nginx.conf > /dev/null",
-        content
-    );
-    client.execute_command(&write_cmd).map_err(|e| e.message)?;
+        let diagnosis = diagnose_connection(profile).unwrap();
+        assert!(!diagnosis.success);
+        assert_eq!(diagnosis.failed_stage.as_deref(), Some("tcp"));
+        let statuses: Vec<StageStatus> = diagnosis.stages.iter().map(|s| s.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                StageStatus::Failed,
+                StageStatus::Skipped,
+                StageStatus::Skipped
+            ]
+        );
+        assert_eq!(
+            diagnosis.stages[0].error_code,
+            Some(CommandError::CONNECTION_REFUSED)
+        );
+    }
 
-    // Test config
-    let test_result = client
-        .execute_command("sudo nginx -t 2>&1")
-        .map_err(|e| e.message)?;
+    #[test]
+    fn test_parse_ufw_rules_ip_versions() {
+        let status = "Status: active\n\
+                      Logging: on (low)\n\
+                      Default: deny (incoming), allow (outgoing), disabled (routed)\n\
+                      \n\
+                      To                         Action      From\n\
+                      --                         ------      ----\n\
+                      22/tcp                     ALLOW IN    Anywhere\n\
+                      3306                       DENY IN     Anywhere\n\
+                      80/tcp                     ALLOW IN    10.0.0.0/8                 # lan\n\
+                      22/tcp (v6)                ALLOW IN    Anywhere (v6)\n\
+                      8080 (v6)                  ALLOW IN    Anywhere (v6)\n";
+        let rules = parse_ufw_rules(status);
+
+        assert_eq!(rules.len(), 4);
+        assert_eq!(rules[0].ip_version, IpVersion::Both);
+        assert_eq!(rules[0].rule_numbers, vec![1, 4]);
+        assert_eq!(rules[0].action, "ALLOW");
+        assert_eq!(rules[0].direction, UfwDirection::In);
+        assert_eq!(rules[0].from, "Anywhere");
+        assert_eq!(rules[1].ip_version, IpVersion::V4);
+        assert_eq!(rules[1].port.as_deref(), Some("3306"));
+        assert_eq!(rules[2].from, "10.0.0.0/8");
+        assert_eq!(rules[3].ip_version, IpVersion::V6);
+        assert_eq!(rules[3].rule, "8080");
+
+        let coverage = build_ipv6_coverage(&rules, "IPV6=yes\n");
+        assert_eq!(coverage.ipv6_enabled, Some(true));
+        assert_eq!(
+            (
+                coverage.v4_only_rules,
+                coverage.v6_only_rules,
+                coverage.dual_stack_rules
+            ),
+            (2, 1, 1)
+        );
+        // The LAN-only rule can't have an IPv6 twin, so only the DENY is flagged
+        assert_eq!(coverage.v4_only_targets, vec!["DENY IN 3306"]);
 
-    if test_result.contains("syntax is ok") && test_result.contains("test is successful") {
-        Ok("Config saved and validated. Reload nginx to apply changes.".to_string())
-    } else {
-        Err(format!("Config saved but test failed: {}", test_result))
+        let disabled = build_ipv6_coverage(&rules, "IPV6=no\n");
+        assert!(disabled.warning.unwrap().contains("IPV6=no"));
     }
-}
 
-#[tauri::command]
-pub async fn get_nginx_vhosts(state: State<'_, AppState>) -> Result<Vec<NginxVhost>, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+    #[test]
+    fn test_wrap_and_unwrap_cron_job_logging() {
+        let log = default_cron_log_path("/usr/local/bin/backup.sh");
+        assert!(log.starts_with("/var/log/dpanel-cron/") && log.ends_with(".log"));
+        assert!(is_valid_cron_log_path(&log));
+        assert!(!is_valid_cron_log_path("/tmp/a b.log"));
+        assert!(!is_valid_cron_log_path("/var/log/../etc/passwd"));
+        assert_eq!(
+            cron_log_path(None, "/usr/local/bin/backup.sh").unwrap(),
+            log
+        );
+        assert_eq!(
+            cron_log_path(Some("backup.log"), "x").unwrap(),
+            "/var/log/dpanel-cron/backup.log"
+        );
+        assert_eq!(
+            cron_log_path(Some("/var/log/dpanel-cron/backup.log"), "x").unwrap(),
+            "/var/log/dpanel-cron/backup.log"
+        );
+        for outside in [
+            "/etc/shadow",
+            "/var/log/dpanel-cron/../../../etc/passwd",
+            "sub/x.log",
+            "..",
+        ] {
+            assert!(cron_log_path(Some(outside), "x").is_err(), "{}", outside);
+        }
 
-    let mut vhosts = Vec::new();
+        let wrapped = wrap_cron_job_line(CRONTAB_WITH_COMMENTS, 6, &log).unwrap();
+        let expected = format!("0 2 * * * /usr/local/bin/backup.sh >> {} 2>&1", log);
+        assert!(wrapped.lines().any(|l| l == expected));
+        assert!(wrap_cron_job_line(&wrapped, 6, &log).is_err());
 
-    // Get sites from sites-available
-    let available_output = client
-        .execute_command("ls -1 /etc/nginx/sites-available/ 2>/dev/null | grep -v '^default$'")
-        .unwrap_or_default();
+        // Disabled jobs keep their `#` and the rest of the file is untouched
+        let disabled = wrap_cron_job_line(CRONTAB_WITH_COMMENTS, 7, "/tmp/poll.log").unwrap();
+        assert!(disabled.contains("# */5 * * * * /usr/local/bin/poll.sh >> /tmp/poll.log 2>&1"));
 
-    // Get enabled sites (symlinks in sites-enabled)
-    let enabled_output = client
-        .execute_command("ls -1 /etc/nginx/sites-enabled/ 2>/dev/null")
-        .unwrap_or_default();
+        assert_eq!(
+            unwrap_cron_job_line(&wrapped, 6).unwrap(),
+            CRONTAB_WITH_COMMENTS
+        );
+        assert!(unwrap_cron_job_line(CRONTAB_WITH_COMMENTS, 6).is_err());
+    }
 
-    let enabled_sites: Vec<&str> = enabled_output.lines().collect();
+    #[test]
+    fn test_parse_docker_containers_merges_health_and_stats() {
+        let ps = "abc123|web|nginx:alpine|Up 2 hours|running|0.0.0.0:80->80/tcp\n\
+                  def456|worker|app:latest|Exited (1) 3 minutes ago|exited|";
+        let health = "abc123|healthy\ndef456|";
+        let stats = "web|1.50%|12.5MiB / 1GiB";
+        let containers = parse_docker_containers(ps, health, stats);
+
+        assert_eq!(containers.len(), 2);
+        assert_eq!(containers[0].health.as_deref(), Some("healthy"));
+        assert_eq!(containers[0].cpu_percent, 1.5);
+        assert_eq!(containers[0].ports.len(), 1);
+        assert_eq!(containers[1].health, None);
+        assert_eq!(containers[1].memory_usage, 0);
+    }
 
-    for name in available_output.lines() {
-        if name.is_empty() {
-            continue;
+    #[test]
+    fn test_cron_d_file_name() {
+        assert_eq!(cron_d_file_name("certbot").unwrap(), "certbot");
+        assert_eq!(
+            cron_d_file_name("e2scrub_all.disabled").unwrap(),
+            "e2scrub_all"
+        );
+        for bad in [
+            "",
+            "../passwd",
+            "sub/file",
+            ".hidden",
+            ".disabled",
+            "a b",
+            "x;rm",
+        ] {
+            assert!(cron_d_file_name(bad).is_err(), "{} should be rejected", bad);
         }
+    }
 
-        let enabled = enabled_sites.contains(&name);
-
-        // Read config to extract details
-        let config = client
-            .execute_command(&format!("cat /etc/nginx/sites-available/{}", name))
-            .unwrap_or_default();
+    #[test]
+    fn test_read_only_connection_refuses_writes() {
+        let state = AppState::default();
+        let profile = ServerProfile {
+            id: "web".to_string(),
+            name: "web".to_string(),
+            host: "127.0.0.1".to_string(),
+            port: 22,
+            username: "deploy".to_string(),
+            auth_method: AuthMethod::Password {
+                password: String::new(),
+            },
+            keepalive_secs: None,
+            connect_timeout_secs: None,
+            retry_attempts: None,
+            probe_interval_secs: None,
+            max_output_bytes: None,
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            {
+                let mut connections = state.connections.lock().await;
+                connections
+                    .clients
+                    .insert("web".to_string(), Arc::new(SshClient::new(profile)));
+                connections.active = Some("web".to_string());
+            }
+            assert!(state.writable_client(None).await.is_ok());
+
+            state
+                .connections
+                .lock()
+                .await
+                .permission_modes
+                .insert("web".to_string(), PermissionMode::ReadOnly);
+            assert!(state.client(None).await.is_ok());
+            match state.writable_client(Some("web")).await {
+                Ok(_) => panic!("writes should be refused in read-only mode"),
+                Err(error) => assert!(error.contains("read-only mode")),
+            }
+        });
+    }
 
-        // Extract server_name
-        let server_name = config
-            .lines()
-            .find(|l| l.trim().starts_with("server_name"))
-            .map(|l| {
-                l.split_whitespace()
-                    .nth(1)
-                    .unwrap_or("*")
-                    .trim_end_matches(';')
-            })
-            .unwrap_or("*")
-            .to_string();
+    #[test]
+    fn test_parse_journal_usage_and_vacuum() {
+        let output = "===USAGE===\n\
+             Archived and active journals take up 1.5G in the file system.\n\
+             ===OLDEST===\n\
+             2024-03-01T08:15:02+0000 web1 kernel: Linux version 6.1.0\n\
+             ===VERIFY===\n\
+             File corruption detected at /var/log/journal/ab/system@1.journal:4120 (of 8388608 bytes, 0%).\n\
+             FAIL: /var/log/journal/ab/system@1.journal (Bad message)\n";
+        let usage = parse_journal_disk_usage(&split_sections(output)).unwrap();
+        assert_eq!(usage.bytes, 1_610_612_736);
+        assert_eq!(
+            usage.oldest_entry.as_deref(),
+            Some("2024-03-01T08:15:02+0000")
+        );
+        assert_eq!(
+            usage.corrupt_files,
+            vec!["/var/log/journal/ab/system@1.journal"]
+        );
+        assert!(parse_journal_disk_usage(&split_sections(
+            "===USAGE===\nNo journal files were found.\n"
+        ))
+        .is_err());
 
-        // Extract listen port
-        let listen_port = config
-            .lines()
-            .find(|l| l.trim().starts_with("listen"))
-            .and_then(|l| {
-                l.split_whitespace()
-                    .nth(1)
-                    .map(|s| s.trim_end_matches(';').to_string())
-            })
-            .unwrap_or_else(|| "80".to_string());
+        let vacuum = parse_journal_vacuum(
+            "Deleted archived journal /var/log/journal/ab/system@2.journal (8.0M).\n\
+             Vacuuming done, freed 8.0M of archived journals from /var/log/journal/ab.\n\
+             Vacuuming done, freed 0B of archived journals from /run/log/journal.\n",
+        );
+        assert_eq!(vacuum.freed_bytes, 8 * 1024 * 1024);
+        assert_eq!(
+            vacuum.deleted_files,
+            vec!["/var/log/journal/ab/system@2.journal"]
+        );
+    }
 
-        // Check SSL
-        let ssl_enabled = config.contains("ssl_certificate");
+    #[test]
+    fn test_parse_ping_summary() {
+        let iputils = "PING example.com (93.184.216.34) 56(84) bytes of data.\n\
+             \n\
+             --- example.com ping statistics ---\n\
+             4 packets transmitted, 3 received, +1 errors, 25% packet loss, time 3004ms\n\
+             rtt min/avg/max/mdev = 11.201/12.345/13.900/0.998 ms\n";
+        assert_eq!(
+            parse_ping_summary(iputils),
+            Some((4, 3, 25.0, Some(12.345)))
+        );
 
-        // Extract root path
-        let root_path = config
-            .lines()
-            .find(|l| l.trim().starts_with("root"))
-            .map(|l| {
-                l.split_whitespace()
-                    .nth(1)
-                    .unwrap_or("")
-                    .trim_end_matches(';')
-            })
-            .unwrap_or("")
-            .to_string();
+        let busybox = "--- 10.0.0.1 ping statistics ---\n\
+             2 packets transmitted, 0 packets received, 100% packet loss\n";
+        assert_eq!(parse_ping_summary(busybox), Some((2, 0, 100.0, None)));
+        assert_eq!(parse_ping_summary("ping: unknown host nope.invalid"), None);
 
-        vhosts.push(NginxVhost {
-            name: name.to_string(),
-            enabled,
-            server_name,
-            listen_port,
-            ssl_enabled,
-            root_path,
-        });
+        assert!(is_valid_network_target("2001:db8::1"));
+        assert!(!is_valid_network_target("-f example.com"));
+        assert!(!is_valid_network_target("a;reboot"));
     }
 
-    Ok(vhosts)
-}
-
-#[tauri::command]
-pub async fn get_vhost_config(name: String, state: State<'_, AppState>) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+    #[test]
+    fn test_parse_grep_context() {
+        let output = "3-GET /health 200\n\
+                      4:ERROR db timeout: retrying\n\
+                      5:ERROR db timeout\n\
+                      6-GET / 200\n\
+                      --\n\
+                      40-worker started\n\
+                      41:ERROR queue full\n";
+        let matches = parse_grep_context(output, 1);
+        assert_eq!(matches.len(), 3);
+        assert_eq!(matches[0].line_number, 4);
+        assert_eq!(matches[0].line, "ERROR db timeout: retrying");
+        assert_eq!(matches[0].context_before, vec!["GET /health 200"]);
+        assert_eq!(matches[0].context_after, vec!["ERROR db timeout"]);
+        assert_eq!(matches[1].context_after, vec!["GET / 200"]);
+        assert_eq!(matches[2].line_number, 41);
+        assert_eq!(matches[2].context_before, vec!["worker started"]);
+        assert!(matches[2].context_after.is_empty());
+    }
 
-    client
-        .execute_command(&format!("cat /etc/nginx/sites-available/{}", name))
-        .map_err(|e| e.message)
-}
+    #[test]
+    fn test_parse_sudo_access() {
+        let nopasswd = "===ID===\n1000\nbob sudo docker\n===SUDO===\n\
+             Matching Defaults entries for bob on web1:\n\
+             \x20   env_reset, secure_path=/usr/sbin\\:/usr/bin\n\
+             \n\
+             User bob may run the following commands on web1:\n\
+             \x20   (ALL : ALL) NOPASSWD: ALL\n";
+        let access = parse_sudo_access(&split_sections(nopasswd));
+        assert!(access.installed && access.can_sudo && access.passwordless);
+        assert_eq!(access.allowed_commands, vec!["(ALL : ALL) NOPASSWD: ALL"]);
+        assert!(access.warning.is_none());
+
+        let password = "===ID===\n1000\nbob sudo\n===SUDO===\nsudo: a password is required\n";
+        let access = parse_sudo_access(&split_sections(password));
+        assert!(access.can_sudo && !access.passwordless);
+        assert!(access.warning.unwrap().contains("password"));
+
+        let denied = "===ID===\n1001\neve\n===SUDO===\nSorry, user eve may not run sudo on web1.\n";
+        let access = parse_sudo_access(&split_sections(denied));
+        assert!(access.installed && !access.can_sudo);
+        assert!(access.warning.unwrap().contains("not allowed"));
+
+        let missing = parse_sudo_access(&split_sections("===ID===\n1001\neve\n"));
+        assert!(!missing.installed && !missing.can_sudo);
+        assert!(missing.warning.unwrap().contains("not installed"));
+
+        let root = parse_sudo_access(&split_sections("===ID===\n0\nroot\n"));
+        assert!(root.is_root && root.passwordless && root.warning.is_none());
+    }
 
-#[tauri::command]
-pub async fn save_vhost_config(
-    name: String,
-    content: String,
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+    #[test]
+    fn test_location_path_validation() {
+        assert!(is_valid_location_path("/api"));
+        assert!(is_valid_location_path("~* \\.(png|jpg)$"));
+        assert!(is_valid_location_path("= /health"));
+        assert!(!is_valid_location_path("/api { return 200; }"));
+        assert!(!is_valid_location_path("@ /x"));
+        assert!(!is_valid_location_path(""));
+    }
 
-    // Backup first
-    let backup_cmd = format!(
-        "sudo cp /etc/nginx/sites-available/{} /etc/nginx/sites-available/{}.bak 2>&1",
-        name, name
-    );
-    client.execute_command(&backup_cmd).map_err(|e| e.message)?;
+    #[test]
+    fn test_parse_environ() {
+        let raw = "PATH=/usr/bin\0DB_PASSWORD=hunter2\0MOTD=line one\nline two\0\0";
+        assert_eq!(
+            parse_environ(raw, Some(&Redactor::default())),
+            vec![
+                "PATH=/usr/bin",
+                "DB_PASSWORD=[REDACTED]",
+                "MOTD=line one\nline two"
+            ]
+        );
+        assert_eq!(parse_environ(raw, None)[1], "DB_PASSWORD=hunter2");
+        assert!(parse_environ("", None).is_empty());
+    }
 
-    // Write new config
-    let write_cmd = format!(
-        "echo '{}' | sudo tee /etc/nginx/sites-available/{} > /dev/null",
-        content, name
-    );
-    client.execute_command(&write_cmd).map_err(|e| e.message)?;
+    #[test]
+    fn test_parse_container_provenance() {
+        let labels: HashMap<String, String> = [
+            ("com.docker.compose.project", "shop"),
+            ("com.docker.compose.service", "api"),
+            ("com.docker.compose.project.working_dir", "/srv/shop"),
+            (
+                "com.docker.compose.project.config_files",
+                "/srv/shop/compose.yml,override.yml",
+            ),
+            ("com.docker.compose.oneoff", "False"),
+            ("com.docker.compose.version", "2.24.5"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let provenance = parse_container_provenance(&labels);
+        assert_eq!(provenance.source, ContainerSource::Compose);
+        assert_eq!(provenance.project.as_deref(), Some("shop"));
+        assert_eq!(provenance.service.as_deref(), Some("api"));
+        assert_eq!(
+            provenance.config_files,
+            vec!["/srv/shop/compose.yml", "/srv/shop/override.yml"]
+        );
+        assert!(!provenance.one_off);
+
+        let standalone = parse_container_provenance(&HashMap::new());
+        assert_eq!(standalone.source, ContainerSource::Standalone);
+        assert!(standalone.config_files.is_empty());
+
+        assert_eq!(
+            parse_provenance_file_checks(
+                "missing /srv/shop/override.yml\nunchecked /root/app/compose.yml\n"
+            ),
+            (
+                vec!["/srv/shop/override.yml".to_string()],
+                vec!["/root/app/compose.yml".to_string()]
+            )
+        );
+    }
 
-    Ok(format!(
-        "Vhost '{}' saved. Reload nginx to apply changes.",
-        name
-    ))
-}
+    #[test]
+    fn test_log_tail_command_reads_rotations_oldest_first() {
+        assert_eq!(
+            log_tail_command("/var/log/nginx/access.log", 50, None),
+            "tail -n 50 '/var/log/nginx/access.log' 2>&1"
+        );
+        let cmd = log_tail_command("/var/log/nginx/access.log", 50, Some(3));
+        assert!(cmd.contains("seq 3 -1 1"));
+        assert!(cmd.contains("zcat \"$f.gz\""));
+        assert!(cmd.ends_with("cat '/var/log/nginx/access.log'; } 2>/dev/null | tail -n 50"));
+        assert!(log_tail_command("/x.log", 10, Some(500)).contains("seq 30 -1 1"));
+    }
 
-#[tauri::command]
-pub async fn enable_vhost(name: String, state: State<'_, AppState>) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+    #[test]
+    fn test_build_ufw_rule() {
+        let rule = build_ufw_rule(
+            "allow",
+            Some("443".into()),
+            Some("10.0.0.0/8".into()),
+            None,
+            Some("tcp".into()),
+        )
+        .unwrap();
+        assert_eq!(
+            rule.command,
+            "sudo ufw allow proto tcp from 10.0.0.0/8 to any port 443"
+        );
+        assert_eq!(rule.description, "Allow TCP port 443 from 10.0.0.0/8");
+
+        let rule = build_ufw_rule(
+            "deny",
+            Some("8000:8010".into()),
+            None,
+            None,
+            Some("udp".into()),
+        )
+        .unwrap();
+        assert_eq!(rule.description, "Deny UDP ports 8000-8010 from anywhere");
+        let rule =
+            build_ufw_rule("limit", Some("22".into()), Some("any".into()), None, None).unwrap();
+        assert_eq!(rule.command, "sudo ufw limit from any to any port 22");
+
+        for port in ["abc", "0", "65536", "80-90", "90:80", "22 ; reboot", ","] {
+            assert!(
+                build_ufw_rule("allow", Some(port.into()), None, None, Some("tcp".into())).is_err(),
+                "{}",
+                port
+            );
+        }
+        // Ranges are only valid with a protocol
+        assert!(build_ufw_rule("allow", Some("80,443".into()), None, None, None).is_err());
+
+        for cidr in [
+            "10.0.0.0/33",
+            "300.1.1.1",
+            "10.0.0/8",
+            "fe80::/129",
+            "10.0.0.0/",
+            "evil.com",
+        ] {
+            assert!(
+                build_ufw_rule("allow", None, Some(cidr.into()), None, None).is_err(),
+                "{}",
+                cidr
+            );
+        }
+        assert!(build_ufw_rule("allow", None, Some("2001:db8::/32".into()), None, None).is_ok());
+        assert!(build_ufw_rule("allow", None, None, None, Some("icmp".into())).is_err());
+        assert!(build_ufw_rule("open", None, None, None, None).is_err());
+    }
 
-    let cmd = format!(
-        "sudo ln -sf /etc/nginx/sites-available/{} /etc/nginx/sites-enabled/{} 2>&1",
-        name, name
-    );
-    client.execute_command(&cmd).map_err(|e| e.message)?;
+    #[test]
+    fn test_parse_remote_stat() {
+        let stat = parse_remote_stat("0 644 0 4 1717171717 regular empty file\n").unwrap();
+        assert_eq!(stat.size, 0);
+        assert_eq!(stat.mode, "644");
+        assert_eq!((stat.uid, stat.gid), (0, 4));
+        assert_eq!(stat.modified, 1717171717);
+        assert!(!stat.is_dir);
+        assert!(
+            parse_remote_stat("4096 755 0 0 1717171717 directory")
+                .unwrap()
+                .is_dir
+        );
+        assert!(parse_remote_stat("stat: cannot statx").is_none());
+    }
 
-    // Test and reload
-    let test = client
-        .execute_command("sudo nginx -t 2>&1")
-        .map_err(|e| e.message)?;
-    if test.contains("syntax is ok") {
-        client
-            .execute_command("sudo systemctl reload nginx 2>&1")
-            .map_err(|e| e.message)?;
-        Ok(format!("Vhost '{}' enabled and nginx reloaded.", name))
-    } else {
-        Err(format!("Vhost enabled but config test failed: {}", test))
+    #[test]
+    fn test_parse_network_connections() {
+        let output = "tcp   ESTAB  0  0  10.0.0.5:22  203.0.113.9:51234  users:((\"sshd\",pid=1234,fd=4))
+tcp   ESTAB  0  0  [::ffff:10.0.0.5]:443  [::ffff:198.51.100.7]:5555  users:((\"nginx\",pid=99,fd=3),(\"nginx\",pid=100,fd=3))
+udp   UNCONN 0  0  127.0.0.53%lo:53  0.0.0.0:*
+tcp   LISTEN 0  128  [::]:22  [::]:*  users:((\"sshd\",pid=700,fd=3))
+tcp   ESTAB  0  0  [2001:db8::1]:22  [2001:db8::2]:40312
+";
+        let connections = parse_network_connections(output);
+        assert_eq!(connections.len(), 5);
+        assert_eq!(connections[0].remote.address, "203.0.113.9");
+        assert_eq!(connections[0].remote.port, Some(51234));
+        assert_eq!(connections[0].pid, Some(1234));
+        assert_eq!(connections[0].process.as_deref(), Some("sshd"));
+        assert_eq!(connections[1].local.address, "10.0.0.5");
+        assert_eq!(connections[1].remote.address, "198.51.100.7");
+        assert_eq!(connections[2].local.address, "127.0.0.53%lo");
+        assert_eq!(connections[2].remote.port, None);
+        assert_eq!(connections[2].pid, None);
+        assert_eq!(connections[3].local.address, "::");
+        assert_eq!(connections[4].remote.address, "2001:db8::2");
+        assert_eq!(connections[4].remote.port, Some(40312));
     }
-}
 
-#[tauri::command]
-pub async fn disable_vhost(name: String, state: State<'_, AppState>) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+    #[test]
+    fn test_cidr_contains() {
+        assert!(cidr_contains("203.0.113.9", "203.0.113.9"));
+        assert!(cidr_contains("203.0.113.0/24", "203.0.113.9"));
+        assert!(cidr_contains("0.0.0.0/0", "198.51.100.7"));
+        assert!(!cidr_contains("203.0.113.0/25", "203.0.113.200"));
+        assert!(cidr_contains("2001:db8::/32", "2001:db8:1::2"));
+        assert!(cidr_contains("::/0", "2001:db8::2"));
+        assert!(!cidr_contains("2001:db8::/48", "2001:db8:1::2"));
+        // ss and $SSH_CLIENT can report IPv4 sessions in mapped form
+        assert!(cidr_contains("10.0.0.0/8", "::ffff:10.1.2.3"));
+        assert!(!cidr_contains("10.0.0.0/8", "2001:db8::2"));
+        assert!(!cidr_contains("10.0.0.0/33", "10.0.0.1"));
+    }
 
-    let cmd = format!("sudo rm -f /etc/nginx/sites-enabled/{} 2>&1", name);
-    client.execute_command(&cmd).map_err(|e| e.message)?;
-    client
-        .execute_command("sudo systemctl reload nginx 2>&1")
-        .map_err(|e| e.message)?;
+    #[test]
+    fn test_action_result() {
+        let ok = action_result(0, "Rules updated\n".into(), "Rule added".into(), "Failed");
+        assert!(ok.success);
+        assert_eq!(ok.message, "Rule added");
+        assert_eq!(ok.raw_output, "Rules updated\n");
+
+        let failed = action_result(
+            5,
+            "Job for nginx.service failed.\nSee \"journalctl -xeu nginx.service\" for details.\n\n"
+                .into(),
+            "Service 'nginx' restarted".into(),
+            "Failed to restart service 'nginx'",
+        );
+        assert!(!failed.success);
+        assert_eq!(
+            failed.message,
+            "Failed to restart service 'nginx': See \"journalctl -xeu nginx.service\" for details."
+        );
+        assert_eq!(
+            action_result(1, String::new(), String::new(), "Failed").message,
+            "Failed (exit status 1)"
+        );
 
-    Ok(format!("Vhost '{}' disabled and nginx reloaded.", name))
-}
+        assert_eq!(action_past_tense("stop"), "stopped");
+        assert_eq!(action_past_tense("restart"), "restarted");
+        assert_eq!(action_past_tense("enable"), "enabled");
+        assert_eq!(action_past_tense("unpause"), "unpaused");
+    }
 
-#[tauri::command]
-pub async fn delete_vhost(name: String, state: State<'_, AppState>) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+    #[test]
+    fn test_parse_at_jobs() {
+        let output =
+            "warning: commands will be executed using /bin/sh\njob 7 at Thu Oct 17 02:00:00 2026\n";
+        let scheduled = parse_at_scheduled(output).unwrap();
+        assert_eq!(scheduled.job_id, 7);
+        assert_eq!(scheduled.scheduled_for, "Thu Oct 17 02:00:00 2026");
+        assert!(parse_at_scheduled("syntax error. Last token seen: foo\nGarbled time\n").is_none());
+
+        let jobs =
+            parse_atq("7\tThu Oct 17 02:00:00 2026 a alice\n12\tFri Oct 18 09:30:00 2026 = root\n");
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].id, 7);
+        assert_eq!(jobs[0].scheduled_for, "Thu Oct 17 02:00:00 2026");
+        assert_eq!(jobs[0].queue, "a");
+        assert_eq!(jobs[0].user, "alice");
+        // "=" is the queue of a job that's running right now
+        assert_eq!(jobs[1].queue, "=");
+    }
 
-    // Remove from both available and enabled
-    client
-        .execute_command(&format!(
-            "sudo rm -f /etc/nginx/sites-available/{} 2>&1",
-            name
-        ))
-        .map_err(|e| e.message)?;
-    client
-        .execute_command(&format!(
-            "sudo rm -f /etc/nginx/sites-enabled/{} 2>&1",
-            name
-        ))
-        .map_err(|e| e.message)?;
+    #[test]
+    fn test_parse_container_oom() {
+        let output = "abc123|healthy|false|0\ndef456||true|137\nfff000||false|137\n111111||false|1\nold|healthy\n";
+        let oom = parse_container_oom(output);
+        assert_eq!(oom.len(), 2);
+        assert!(oom.contains("def456") && oom.contains("fff000"));
+        assert_eq!(
+            parse_container_health(output)
+                .get("abc123")
+                .map(String::as_str),
+            Some("healthy")
+        );
+    }
 
-    Ok(format!("Vhost '{}' deleted.", name))
-}
+    #[test]
+    fn test_sysctl_helpers() {
+        assert!(is_valid_sysctl_key("vm.swappiness"));
+        assert!(is_valid_sysctl_key("net.ipv4.ip_forward"));
+        assert!(!is_valid_sysctl_key("vm.swappiness; reboot"));
+        assert!(!is_valid_sysctl_key("-a"));
+        assert!(!is_valid_sysctl_key("Net.IPv4"));
+
+        let entries = parse_sysctl_all("vm.swappiness = 60\nnet.ipv4.tcp_rmem = 4096\t131072\t6291456\nkernel.domainname = (none)\nfs.empty =\n");
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].key, "vm.swappiness");
+        assert_eq!(entries[0].value, "60");
+        assert_eq!(entries[1].value, "4096\t131072\t6291456");
+        assert_eq!(entries[3].value, "");
+
+        assert_eq!(
+            upsert_sysctl_conf("", "vm.swappiness", "10"),
+            "# Managed by DPanel\nvm.swappiness = 10\n"
+        );
+        let existing = "# tuning\nvm.swappiness=60\nnet.ipv4.ip_forward = 0\nvm.swappiness = 30\n";
+        assert_eq!(
+            upsert_sysctl_conf(existing, "vm.swappiness", "10"),
+            "# tuning\nvm.swappiness = 10\nnet.ipv4.ip_forward = 0\n"
+        );
+        assert_eq!(
+            upsert_sysctl_conf(existing, "net.core.somaxconn", "1024"),
+            "# tuning\nvm.swappiness=60\nnet.ipv4.ip_forward = 0\nvm.swappiness = 30\nnet.core.somaxconn = 1024\n"
+        );
+    }
 
-#[tauri::command]
-pub async fn get_nginx_logs(
-    log_type: String,
-    lines: u32,
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+    #[test]
+    fn test_parse_problem_processes() {
+        let ps = "Ss       0     1 systemd
+S        1   812 sshd
+Z      812  4410 sshd <defunct>
+D        2  5120 kworker/u8:2+flush-8:0
+Ss       1  9001 java
+R+    9001  9100 ps
+";
+        let fds = "9001 4096\n4410 2000\n1 1200\n";
+        let problems = parse_problem_processes(ps, fds, 1000, true);
+
+        assert_eq!(problems.zombies.len(), 1);
+        assert_eq!(problems.zombies[0].pid, 4410);
+        assert_eq!(problems.zombies[0].command, "sshd <defunct>");
+        assert_eq!(problems.zombies[0].parent_command.as_deref(), Some("sshd"));
+        assert_eq!(problems.uninterruptible.len(), 1);
+        assert_eq!(
+            problems.uninterruptible[0].command,
+            "kworker/u8:2+flush-8:0"
+        );
+        assert_eq!(problems.uninterruptible[0].parent_command, None);
 
-    let log_path = match log_type.as_str() {
-        "error" => "/var/log/nginx/error.log",
-        "access" => "/var/log/nginx/access.log",
-        _ => "/var/log/nginx/error.log",
-    };
+        let open_files: Vec<(u32, Option<u32>)> = problems
+            .many_open_files
+            .iter()
+            .map(|p| (p.pid, p.open_files))
+            .collect();
+        assert_eq!(
+            open_files,
+            vec![(9001, Some(4096)), (4410, Some(2000)), (1, Some(1200))]
+        );
+        assert!(problems.fd_counts_complete);
+    }
 
-    client
-        .execute_command(&format!("tail -n {} {} 2>&1", lines, log_path))
-        .map_err(|e| e.message)
-}
+    #[test]
+    fn test_fstab_round_trip() {
+        let fstab = "# /etc/fstab\nUUID=abcd  /  ext4  errors=remount-ro  0  1\n\n# data disk\n/dev/sdb1 /data xfs defaults 0 2\n/swapfile none swap sw 0 0\nproc /proc proc defaults\n";
+        let entries = parse_fstab(fstab);
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].device, "UUID=abcd");
+        assert_eq!(entries[0].pass, 1);
+        assert_eq!(entries[1].mountpoint, "/data");
+        assert!(is_swap_entry(&entries[2]));
+        // dump and pass are optional
+        assert_eq!((entries[3].dump, entries[3].pass), (0, 0));
+
+        // Unchanged lines keep their spacing, comments stay put
+        assert_eq!(render_fstab(fstab, &entries), fstab);
+
+        let mut edited = entries.clone();
+        edited[1].options = "defaults,noatime".to_string();
+        edited.remove(3);
+        edited.push(FstabEntry {
+            device: "LABEL=backup".to_string(),
+            mountpoint: "/mnt/my\\040backup".to_string(),
+            fstype: "ext4".to_string(),
+            options: "defaults,nofail".to_string(),
+            dump: 0,
+            pass: 2,
+        });
+        assert_eq!(
+            render_fstab(fstab, &edited),
+            "# /etc/fstab\nUUID=abcd  /  ext4  errors=remount-ro  0  1\n\n# data disk\n/dev/sdb1\t/data\txfs\tdefaults,noatime\t0\t2\n/swapfile none swap sw 0 0\nLABEL=backup\t/mnt/my\\040backup\text4\tdefaults,nofail\t0\t2\n"
+        );
 
-// ==================== CRON COMMANDS ====================
+        assert!(validate_fstab_entry(&edited[3]).is_ok());
+        let mut bad = edited[3].clone();
+        bad.mountpoint = "/mnt/my backup".to_string();
+        assert!(validate_fstab_entry(&bad).is_err());
+        bad.mountpoint = "mnt".to_string();
+        assert!(validate_fstab_entry(&bad).is_err());
+
+        let script = fstab_check_script(&edited);
+        assert!(script.contains("[ -d '/mnt/my backup' ]"));
+        assert!(script.contains("[ -e '/dev/disk/by-label/backup' ]"));
+        assert!(script.contains("[ -e '/dev/sdb1' ]"));
+        assert!(!script.contains("swapfile"));
+
+        assert!(!drops_root_entry(&entries, &edited));
+        assert!(drops_root_entry(&entries, &edited[1..]));
+        assert!(!drops_root_entry(&edited[1..], &edited[1..]));
+    }
 
-#[tauri::command]
-pub async fn get_user_crontab(state: State<'_, AppState>) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+    #[test]
+    fn test_parse_dependency_tree() {
+        let output = "nginx.service\n\u{1b}[0;1;32m●\u{1b}[0m ├─system.slice\n● ├─-.mount\n○ │ └─systemd-journald.socket\n● └─sysinit.target\n●   └─dev-hugepages.mount\n";
+        let nodes = parse_dependency_lines(output);
+        assert_eq!(
+            nodes,
+            vec![
+                (0, "nginx.service".to_string()),
+                (1, "system.slice".to_string()),
+                (1, "-.mount".to_string()),
+                (2, "systemd-journald.socket".to_string()),
+                (1, "sysinit.target".to_string()),
+                (2, "dev-hugepages.mount".to_string()),
+            ]
+        );
+        // Without a UTF-8 locale systemctl draws the tree in ASCII
+        assert_eq!(
+            parse_dependency_lines("a.target\n* |-b.service\n*   `-c.service\n"),
+            vec![
+                (0, "a.target".to_string()),
+                (1, "b.service".to_string()),
+                (2, "c.service".to_string()),
+            ]
+        );
 
-    client
-        .execute_command("crontab -l 2>&1")
-        .map_err(|e| e.message)
-}
+        let mut states = HashMap::new();
+        states.insert(
+            "sysinit.target",
+            HashMap::from([("ActiveState", "active"), ("SubState", "active")]),
+        );
+        let mut pos = 0;
+        let tree = build_dependency_tree(&nodes, &mut pos, None, &states).remove(0);
+        assert_eq!(tree.unit, "nginx.service");
+        assert_eq!(tree.children.len(), 3);
+        assert_eq!(tree.children[1].children[0].unit, "systemd-journald.socket");
+        assert_eq!(tree.children[2].active_state, "active");
+        assert_eq!(tree.children[2].children[0].unit, "dev-hugepages.mount");
+        assert_eq!(strip_ansi("\u{1b}[1mbold\u{1b}[0m text"), "bold text");
+    }
 
-#[tauri::command]
-pub async fn save_user_crontab(
-    content: String,
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+    #[test]
+    fn test_swap_helpers() {
+        let swaps = "Filename\t\t\t\tType\t\tSize\t\tUsed\t\tPriority\n/swapfile                               file\t\t2097148\t\t1024\t\t-2\n/dev/sda2 partition 1048572 0 5\n";
+        let devices = parse_proc_swaps(swaps);
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].name, "/swapfile");
+        assert_eq!(devices[0].swap_type, "file");
+        assert_eq!(devices[0].size_bytes, 2097148 * 1024);
+        assert_eq!(devices[0].used_bytes, 1024 * 1024);
+        assert_eq!(devices[0].priority, -2);
+        assert_eq!(devices[1].priority, 5);
+        assert!(parse_proc_swaps("Filename Type Size Used Priority\n").is_empty());
+
+        assert!(validate_swapfile_path("/swapfile").is_ok());
+        assert!(validate_swapfile_path("/var/swap.img").is_ok());
+        assert!(validate_swapfile_path("swapfile").is_err());
+        assert!(validate_swapfile_path("/mnt/../etc/passwd").is_err());
+        assert!(validate_swapfile_path("/dev/sda2").is_err());
+        assert!(validate_swapfile_path("/swap file").is_err());
+    }
 
-    // Write to temp file and install
-    let write_cmd = format!("echo '{}' | crontab - 2>&1", content);
-    client.execute_command(&write_cmd).map_err(|e| e.message)
-}
+    #[test]
+    fn test_build_suggested_rules() {
+        let ps = "db\t0.0.0.0:5432->5432/tcp, :::5432->5432/tcp\n\
+                  web\t10.0.0.5:80->8080/tcp\n";
+        let ufw = "Status: active\n";
+        let fixed = build_docker_exposures(ps, ufw, true, "-A DOCKER-USER -j ufw-user-forward");
+        let rules = build_suggested_rules(&fixed, true, Some("203.0.113.7"));
+        // Only the port published on every interface gets a suggestion
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].kind, "allow_from_source");
+        assert_eq!(
+            rules[0].command.as_deref(),
+            Some("sudo ufw route allow proto tcp from 203.0.113.7 to any port 5432")
+        );
 
-#[tauri::command]
-pub async fn get_system_crontab(state: State<'_, AppState>) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+        let bypassed = build_docker_exposures(ps, ufw, true, "");
+        let rules = build_suggested_rules(&bypassed, true, Some("203.0.113.7"));
+        assert_eq!(rules[0].kind, "world_exposed");
+        assert!(rules[0].command.is_none());
+        assert!(build_suggested_rules(&fixed, false, None)[0]
+            .message
+            .contains("UFW is inactive"));
+
+        assert!(build_route_allow_command("203.0.113.0/24", 443, "tcp").is_ok());
+        assert!(build_route_allow_command("any; reboot", 443, "tcp").is_err());
+        assert!(build_route_allow_command("203.0.113.7", 443, "icmp").is_err());
+    }
 
-    client
-        .execute_command("cat /etc/crontab 2>&1")
-        .map_err(|e| e.message)
-}
+    #[test]
+    fn test_parse_dmesg() {
+        let iso = "kern  :info  : 2024-05-01T12:00:00,123456+00:00 Linux version 6.1.0\n\
+                   kern  :err   : 2024-05-01T12:05:00,000000+00:00 Out of memory: Killed process 4242 (java)\n\
+                   \x20 continued detail\n";
+        let entries = parse_dmesg(iso);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].timestamp, "2024-05-01T12:00:00,123456+00:00");
+        assert_eq!(entries[0].level, "info");
+        assert_eq!(entries[0].message, "Linux version 6.1.0");
+        assert_eq!(entries[1].level, "err");
+        assert_eq!(
+            entries[1].message,
+            "Out of memory: Killed process 4242 (java)\ncontinued detail"
+        );
 
-#[tauri::command]
-pub async fn get_cron_d_jobs(state: State<'_, AppState>) -> Result<Vec<CronJob>, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+        // util-linux without --time-format prints seconds since boot
+        let relative = parse_dmesg("kern  :warn  : [   12.345678] ata1: link is slow\n");
+        assert_eq!(relative[0].timestamp, "12.345678");
+        assert_eq!(relative[0].level, "warn");
+        assert_eq!(relative[0].message, "ata1: link is slow");
+    }
 
-    let mut jobs = Vec::new();
+    #[test]
+    fn test_parse_time_config() {
+        let output = "===SHOW===\nTimezone=Europe/Berlin\nLocalRTC=no\nCanNTP=yes\nNTP=yes\nNTPSynchronized=no\n\
+                      ===LOCAL===\n2024-05-01 14:00:00 CEST\n===UTC===\n2024-05-01 12:00:00 UTC\n\
+                      ===NTP===\nsystemd-timesyncd\n";
+        let config = parse_time_config(&split_sections(output)).unwrap();
+        assert_eq!(config.timezone, "Europe/Berlin");
+        assert_eq!(config.local_time, "2024-05-01 14:00:00 CEST");
+        assert_eq!(config.utc_time, "2024-05-01 12:00:00 UTC");
+        assert!(config.ntp_enabled);
+        assert!(!config.ntp_synced);
+        assert_eq!(config.ntp_service.as_deref(), Some("systemd-timesyncd"));
+
+        let no_daemon =
+            parse_time_config(&split_sections("===SHOW===\nTimezone=UTC\n===NTP===\n")).unwrap();
+        assert!(no_daemon.ntp_service.is_none());
+        assert!(parse_time_config(&split_sections("===SHOW===\n")).is_err());
+    }
 
-    // List files in /etc/cron.d/
-    let files = client
-        .execute_command("ls -1 /etc/cron.d/ 2>/dev/null")
-        .unwrap_or_default();
+    #[test]
+    fn test_checksum_helpers() {
+        assert_eq!(checksum_tool(" SHA256"), Some("sha256sum"));
+        assert_eq!(checksum_tool("sha512"), None);
+        assert_eq!(
+            checksum_algorithm_for("d41d8cd98f00b204e9800998ecf8427e"),
+            Some("md5")
+        );
+        assert_eq!(checksum_algorithm_for(&"a".repeat(64)), Some("sha256"));
+        assert_eq!(checksum_algorithm_for(&"g".repeat(40)), None);
+        assert_eq!(
+            parse_checksum_output(
+                "DA39A3EE5E6B4B0D3255BFEF95601890AFD80709  /etc/hosts\n",
+                "sha1sum"
+            ),
+            Some("da39a3ee5e6b4b0d3255bfef95601890afd80709".to_string())
+        );
+        assert_eq!(
+            parse_checksum_output("\\d41d8cd98f00b204e9800998ecf8427e  /tmp/a\\nb", "md5sum"),
+            Some("d41d8cd98f00b204e9800998ecf8427e".to_string())
+        );
+        assert_eq!(
+            parse_checksum_output("md5sum: /root/x: Permission denied", "md5sum"),
+            None
+        );
+    }
 
-    for file in files.lines() {
-        if file.is_empty() || file == "README" || file == ".placeholder" {
-            continue;
-        }
+    #[test]
+    fn test_parse_large_files() {
+        let output = "1073741824 1700000000.1234567890 /var/log/big file.log\n\
+                      2147483648 1690000000.0000000000 /var/lib/docker/overlay2/x.img\n\
+                      garbage line\n";
+        let files = parse_large_files(output, 10);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "/var/lib/docker/overlay2/x.img");
+        assert_eq!(files[0].size, 2147483648);
+        assert_eq!(files[1].path, "/var/log/big file.log");
+        assert_eq!(files[1].modified, 1700000000);
+        assert_eq!(parse_large_files(output, 1).len(), 1);
+    }
 
-        let content = client
-            .execute_command(&format!("cat /etc/cron.d/{}", file))
-            .unwrap_or_default();
+    #[test]
+    fn test_parse_swarm_objects() {
+        let output = "k3j2h1|db_password|2024-05-01 10:00:00 +0000 UTC\n\
+                      x9y8z7|tls.cert|3 days ago\n\
+                      bogus\n";
+        let rows = parse_swarm_objects(output);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].1, "db_password");
+        assert_eq!(
+            rows[1],
+            (
+                "x9y8z7".to_string(),
+                "tls.cert".to_string(),
+                "3 days ago".to_string()
+            )
+        );
+        assert!(validate_swarm_object_name("secret", "db_password").is_ok());
+        assert!(validate_swarm_object_name("secret", "-rf").is_err());
+        assert!(validate_swarm_object_name("config", &"a".repeat(65)).is_err());
+    }
 
-        for (idx, line) in content.lines().enumerate() {
-            let trimmed = line.trim();
-            if trimmed.is_empty()
-                || trimmed.starts_with('#')
-                || trimmed.starts_with("SHELL=")
-                || trimmed.starts_with("PATH=")
-            {
-                continue;
-            }
+    #[test]
+    fn test_parse_curl_probe() {
+        assert_eq!(
+            parse_curl_probe("\nCURL_PROBE:200 0.012345\n"),
+            (Some(200), Some(0.012345), None)
+        );
+        let (code, time, error) = parse_curl_probe(
+            "\nCURL_PROBE:000 0.000412\ncurl: (7) Failed to connect to 127.0.0.1 port 8080: Connection refused",
+        );
+        assert_eq!((code, time), (None, Some(0.000412)));
+        assert!(error.unwrap().contains("Connection refused"));
+        assert_eq!(parse_curl_probe("").0, None);
+    }
 
-            // Parse cron line: minute hour day month weekday user command
-            let parts: Vec<&str> = trimmed.split_whitespace().collect();
-            if parts.len() >= 7 {
-                jobs.push(CronJob {
-                    id: idx,
-                    schedule: parts[0..5].join(" "),
-                    command: parts[6..].join(" "),
-                    user: parts[5].to_string(),
-                    enabled: !trimmed.starts_with('#'),
-                    source: format!("/etc/cron.d/{}", file),
-                });
-            }
-        }
+    #[test]
+    fn test_environment_file_edits() {
+        let content = "PATH=\"/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin\"\n\
+                       # proxy settings\n\
+                       export http_proxy='http://proxy:3128'\n\
+                       JAVA_HOME=/usr/lib/jvm/java-11\n\
+                       not a variable\n";
+        let vars = parse_environment_file(content);
+        assert_eq!(vars.len(), 3);
+        assert_eq!(
+            vars[1],
+            ("http_proxy".to_string(), "http://proxy:3128".to_string())
+        );
+        assert_eq!(vars[2].1, "/usr/lib/jvm/java-11");
+
+        let updated = set_environment_line(content, "JAVA_HOME", "/usr/lib/jvm/java-17");
+        assert_eq!(updated.lines().count(), 5);
+        assert!(updated.contains("\nJAVA_HOME=\"/usr/lib/jvm/java-17\"\nnot a variable\n"));
+        let appended = set_environment_line(content, "EDITOR", "vim");
+        assert!(appended.ends_with("not a variable\nEDITOR=\"vim\"\n"));
+        assert_eq!(set_environment_line("", "A", "1"), "A=\"1\"\n");
+
+        let removed = remove_environment_lines(content, "http_proxy");
+        assert!(!removed.contains("proxy:3128"));
+        assert!(removed.contains("# proxy settings"));
+        assert_eq!(remove_environment_lines(content, "MISSING"), content);
+
+        assert!(is_shell_identifier("_JAVA_OPTS2"));
+        assert!(!is_shell_identifier("2FAST"));
+        assert!(!is_shell_identifier("A-B"));
     }
 
-    Ok(jobs)
-}
+    #[test]
+    fn test_cron_access_precedence() {
+        let users = |names: &[&str]| Some(names.iter().map(|n| n.to_string()).collect());
+        assert_eq!(
+            parse_cron_user_list("# managed\nroot\n\n  alice \n"),
+            vec!["root", "alice"]
+        );
 
-#[tauri::command]
-pub async fn get_cron_folders(state: State<'_, AppState>) -> Result<Vec<CronFolder>, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+        let files = |allow: Option<&str>, deny: Option<&str>| {
+            let mut files = HashMap::new();
+            for (path, content) in [(CRON_ALLOW_PATH, allow), (CRON_DENY_PATH, deny)] {
+                if let Some(content) = content {
+                    files.insert(path.to_string(), content.to_string());
+                }
+            }
+            cron_access_from_files(&files)
+        };
 
-    let mut folders = Vec::new();
+        let open = files(None, None);
+        assert_eq!(open.allow, None);
+        assert!(cron_access_allows(&open, "bob"));
+        assert!(cron_access_changes(None, None, "bob", true).is_empty());
+        assert_eq!(
+            cron_access_changes(None, None, "bob", false),
+            vec![(CRON_DENY_PATH, "bob\n".to_string())]
+        );
 
-    for folder_name in &["cron.daily", "cron.weekly", "cron.monthly", "cron.hourly"] {
-        let path = format!("/etc/{}", folder_name);
-        let scripts_output = client
-            .execute_command(&format!("ls -1 {} 2>/dev/null", path))
-            .unwrap_or_default();
+        // cron.allow wins: deny is ignored once it exists
+        let allow = "# managed by ops\nroot\n";
+        let both = files(Some(allow), Some("alice"));
+        assert_eq!(both.allow, users(&["root"]));
+        assert!(!cron_access_allows(&both, "bob"));
+        assert!(cron_access_allows(&both, "root"));
+        assert_eq!(
+            cron_access_changes(Some(allow), Some("alice"), "alice", true),
+            vec![
+                (
+                    CRON_ALLOW_PATH,
+                    "# managed by ops\nroot\nalice\n".to_string()
+                ),
+                (CRON_DENY_PATH, String::new()),
+            ]
+        );
+        assert_eq!(
+            cron_access_changes(Some(allow), None, "root", false),
+            vec![(CRON_ALLOW_PATH, "# managed by ops\n".to_string())]
+        );
+        assert!(cron_access_changes(Some(allow), None, "bob", false).is_empty());
 
-        let scripts: Vec<String> = scripts_output
-            .lines()
-            .filter(|s| !s.is_empty())
-            .map(|s| s.to_string())
-            .collect();
+        // An empty existing cron.allow still lets nobody but root in
+        assert_eq!(files(Some(""), None).allow, Some(Vec::new()));
 
-        if !scripts.is_empty() {
-            folders.push(CronFolder {
-                name: folder_name.to_string(),
-                path,
-                scripts,
-            });
-        }
+        let deny = "alice\n# temporary\nbob\n";
+        let deny_only = files(None, Some(deny));
+        assert!(!cron_access_allows(&deny_only, "bob"));
+        assert_eq!(
+            cron_access_changes(None, Some(deny), "bob", true),
+            vec![(CRON_DENY_PATH, "alice\n# temporary\n".to_string())]
+        );
     }
 
-    Ok(folders)
-}
+    #[test]
+    fn test_file_permission_helpers() {
+        let parsed = parse_stat_permissions("644|-rw-r--r--|www-data|www-data\n").unwrap();
+        assert_eq!(parsed.mode_octal, "0644");
+        assert_eq!(parsed.symbolic, "-rw-r--r--");
+        assert_eq!(parsed.owner, "www-data");
+        assert_eq!(
+            parse_stat_permissions("2775|drwxrwsr-x|root|deploy")
+                .unwrap()
+                .mode_octal,
+            "2775"
+        );
+        assert!(parse_stat_permissions("stat: cannot statx").is_none());
+
+        assert!(is_valid_octal_mode("0600"));
+        assert!(is_valid_octal_mode("755"));
+        assert!(!is_valid_octal_mode("789"));
+        assert!(!is_valid_octal_mode("u+x"));
+        assert!(!is_valid_octal_mode("12345"));
+
+        assert!(is_valid_account_name("www-data"));
+        assert!(is_valid_account_name("1000"));
+        assert!(!is_valid_account_name("-R"));
+        assert!(!is_valid_account_name("root;id"));
+        assert!(is_valid_account_name("host01$"));
+        assert!(!is_valid_account_name("$(id)"));
+        assert!(!is_valid_account_name("a$b"));
+        assert!(!is_valid_account_name("$"));
+    }
 
-#[tauri::command]
-pub async fn get_cron_logs(lines: u32, state: State<'_, AppState>) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+    #[test]
+    fn test_parse_listening_ports() {
+        let output = "\
+udp   UNCONN 0      0      127.0.0.53%lo:53         0.0.0.0:*    users:((\"systemd-resolve\",pid=612,fd=13))
+udp   UNCONN 0      0            0.0.0.0:51820      0.0.0.0:*
+tcp   LISTEN 0      4096         0.0.0.0:8080       0.0.0.0:*    users:((\"docker-proxy\",pid=2311,fd=4))
+tcp   LISTEN 0      4096            [::]:8080          [::]:*    users:((\"docker-proxy\",pid=2318,fd=4))
+tcp   LISTEN 0      511          0.0.0.0:80         0.0.0.0:*    users:((\"nginx\",pid=1003,fd=6),(\"nginx\",pid=1002,fd=6),(\"nginx\",pid=1001,fd=6))
+tcp   LISTEN 0      511          0.0.0.0:80         0.0.0.0:*    users:((\"nginx\",pid=1004,fd=6))
+tcp   LISTEN 0      128                *:22               *:*    users:((\"sshd\",pid=812,fd=3))
+tcp   LISTEN 0      244        127.0.0.1:5432       0.0.0.0:*
+";
+        let ports = parse_listening_ports(output);
+        assert_eq!(ports.len(), 7);
+        let find = |port: &str, protocol: &str, version: IpVersion| {
+            ports
+                .iter()
+                .find(|p| p.port == port && p.protocol == protocol && p.ip_version == Some(version))
+                .unwrap()
+        };
 
-    // Try to find cron logs in various locations
-    let log_paths = ["/var/log/cron", "/var/log/syslog", "/var/log/messages"];
+        let ssh = find("22", "tcp", IpVersion::Both);
+        assert_eq!(ssh.address.as_deref(), Some("*"));
+        assert_eq!(ssh.pid, Some(812));
+
+        let dns = find("53", "udp", IpVersion::V4);
+        assert_eq!(dns.address.as_deref(), Some("127.0.0.53"));
+        assert_eq!(dns.service_name.as_deref(), Some("systemd-resolve"));
+
+        // SO_REUSEPORT workers are merged into one listener
+        let web = find("80", "tcp", IpVersion::V4);
+        let pids: Vec<u32> = web.processes.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![1003, 1002, 1001, 1004]);
+
+        let proxy_v6 = find("8080", "tcp", IpVersion::V6);
+        assert_eq!(proxy_v6.address.as_deref(), Some("::"));
+        assert_eq!(proxy_v6.service_name.as_deref(), Some("docker-proxy"));
+        assert_eq!(proxy_v6.pid, Some(2318));
+        assert_eq!(find("8080", "tcp", IpVersion::V4).pid, Some(2311));
+
+        // Kernel sockets (WireGuard) and ones owned by other users without root have no process
+        let wireguard = find("51820", "udp", IpVersion::V4);
+        assert!(wireguard.processes.is_empty() && wireguard.service_name.is_none());
+        assert_eq!(ports.last().unwrap().port, "51820");
+    }
 
-    for log_path in &log_paths {
-        let test_cmd = format!("test -f {} && echo 'exists'", log_path);
-        if let Ok(result) = client.execute_command(&test_cmd) {
-            if result.trim() == "exists" {
-                let grep_cmd = format!("grep -i cron {} | tail -n {} 2>&1", log_path, lines);
-                if let Ok(logs) = client.execute_command(&grep_cmd) {
-                    if !logs.is_empty() {
-                        return Ok(logs);
-                    }
-                }
-            }
-        }
+    #[test]
+    fn test_parse_security_module() {
+        let sections = |output: &str| split_sections(output);
+
+        let apparmor = parse_security_module(&sections(
+            "===SELINUX===\n===SELINUX_MODULES===\n===APPARMOR===\nY\n===APPARMOR_PROFILES===\n\
+             /usr/sbin/cupsd (enforce)\n\
+             docker-default (enforce)\n\
+             /usr/bin/man (complain)\n\
+             snap.lxd.lxc (enforce)\n",
+        ));
+        assert_eq!(apparmor.kind, SecurityModuleKind::AppArmor);
+        assert_eq!(apparmor.mode, EnforcementMode::Enforcing);
+        assert_eq!(apparmor.profiles_loaded, 4);
+        assert_eq!(apparmor.profiles[0].name, "/usr/bin/man");
+        assert_eq!(apparmor.profiles[0].mode, "complain");
+
+        // Without root the profile list is unreadable, but AppArmor is still reported
+        let unreadable = parse_security_module(&sections(
+            "===SELINUX===\n===SELINUX_MODULES===\n===APPARMOR===\nY\n===APPARMOR_PROFILES===\n",
+        ));
+        assert_eq!(unreadable.kind, SecurityModuleKind::AppArmor);
+        assert_eq!(unreadable.profiles_loaded, 0);
+
+        let selinux = parse_security_module(&sections(
+            "===SELINUX===\nPermissive\n===SELINUX_MODULES===\n412\n===APPARMOR===\n===APPARMOR_PROFILES===\n",
+        ));
+        assert_eq!(selinux.kind, SecurityModuleKind::SELinux);
+        assert_eq!(selinux.mode, EnforcementMode::Permissive);
+        assert_eq!(selinux.profiles_loaded, 412);
+
+        let disabled = parse_security_module(&sections(
+            "===SELINUX===\nDisabled\n===SELINUX_MODULES===\n===APPARMOR===\nN\n===APPARMOR_PROFILES===\n",
+        ));
+        assert_eq!(disabled.kind, SecurityModuleKind::SELinux);
+        assert_eq!(disabled.mode, EnforcementMode::Disabled);
+
+        let none = parse_security_module(&sections(
+            "===SELINUX===\n===SELINUX_MODULES===\n===APPARMOR===\n===APPARMOR_PROFILES===\n",
+        ));
+        assert_eq!(none.kind, SecurityModuleKind::None);
+        assert_eq!(none.mode, EnforcementMode::Disabled);
     }
 
-    // Fallback: try journalctl
-    client
-        .execute_command(&format!("journalctl -u cron -n {} --no-pager 2>&1", lines))
-        .map_err(|e| e.message)
-}
+    #[test]
+    fn test_parse_reboot_status() {
+        let status = parse_reboot_status(&split_sections(
+            "===RUNNING===\n5.15.0-97-generic\n===REQUIRED===\n===PACKAGES===\n\
+             ===KERNELS===\n5.15.0-105-generic\n5.15.0-97-generic\n5.15.0-101-generic\n",
+        ));
+        assert!(status.required);
+        assert_eq!(
+            status.installed_kernel.as_deref(),
+            Some("5.15.0-105-generic")
+        );
 
-#[tauri::command]
-pub async fn add_cron_job(
-    schedule: String,
-    command: String,
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+        let status = parse_reboot_status(&split_sections(
+            "===RUNNING===\n6.1.0-21-amd64\n===REQUIRED===\nreboot-required\n\
+             ===PACKAGES===\nlibc6\nlinux-base\nlibc6\n===KERNELS===\n6.1.0-21-amd64\n6.1.0-18-amd64\n",
+        ));
+        assert!(status.required);
+        assert_eq!(status.packages, vec!["libc6", "linux-base"]);
+
+        let status = parse_reboot_status(&split_sections(
+            "===RUNNING===\n6.8.1-arch1-1\n===REQUIRED===\n===PACKAGES===\n===KERNELS===\nlinux\n",
+        ));
+        assert!(!status.required);
+        assert_eq!(status.installed_kernel, None);
+
+        assert!(compare_versions("4.18.0-513.el8", "4.18.0-477.el8").is_gt());
+        assert!(compare_versions("6.1.0-18-amd64", "6.1.0-18-amd64").is_eq());
+    }
 
-    // Get current crontab
-    let current = client
-        .execute_command("crontab -l 2>&1")
-        .unwrap_or_default();
+    #[test]
+    fn test_parse_exec_sessions() {
+        let output = concat!(
+            r#"{"CanRemove":false,"ContainerID":"b5c1f3","DetachKeys":"","ID":"9e2f0a","Running":true,"ExitCode":null,"ProcessConfig":{"tty":true,"entrypoint":"psql","arguments":["-U","postgres"],"privileged":false,"user":"postgres"},"OpenStdin":true,"Pid":48211}"#,
+            "\n",
+            r#"{"ContainerID":"b5c1f3","ID":"41d7c8","Running":false,"ExitCode":130,"ProcessConfig":{"tty":false,"entrypoint":"sh","arguments":[],"privileged":true,"user":""},"Pid":47902}"#,
+            "\n\n{\"message\":\"No such exec instance: 0000\"}\n"
+        );
+        let (container_id, sessions) = parse_exec_sessions(output);
+        assert_eq!(container_id.as_deref(), Some("b5c1f3"));
+        assert_eq!(sessions.len(), 2);
+
+        assert_eq!(sessions[0].command, "psql -U postgres");
+        assert_eq!(sessions[0].pid, Some(48211));
+        assert_eq!(sessions[0].user.as_deref(), Some("postgres"));
+        assert!(sessions[0].tty && sessions[0].exit_code.is_none());
+
+        assert!(!sessions[1].running);
+        assert_eq!(sessions[1].pid, None);
+        assert_eq!(sessions[1].exit_code, Some(130));
+        assert_eq!(sessions[1].user, None);
+        assert!(sessions[1].privileged);
+
+        assert!(parse_exec_sessions("").1.is_empty());
+    }
 
-    // Add new job
-    let new_entry = format!("{} {}", schedule, command);
-    let new_crontab = if current.contains("command not found") {
-        new_entry
-    } else {
-        format!("{}\n{}", current.trim_end(), new_entry)
-    };
+    #[test]
+    fn test_parse_proc_limits_and_open_files() {
+        let limits = "\
+Limit                     Soft Limit           Hard Limit           Units     
+Max cpu time              unlimited            unlimited            seconds   
+Max open files            1024                 524288               files     
+Max nice priority         0                    0                    
+Max realtime timeout      unlimited            unlimited            us        
+";
+        let limits = parse_proc_limits(limits);
+        assert_eq!(limits.len(), 4);
+        assert_eq!(limits[1].name, "Max open files");
+        assert_eq!(limits[1].soft, "1024");
+        assert_eq!(limits[1].hard, "524288");
+        assert_eq!(limits[1].units.as_deref(), Some("files"));
+        assert_eq!(limits[2].units, None);
+        assert_eq!(limits[3].soft, "unlimited");
+        assert!(parse_proc_limits("").is_empty());
+
+        let fds = "3\ntotal 0\n\
+                   lr-x------ 1 www-data www-data 64 Oct 16 09:12 0 -> /dev/null\n\
+                   l-wx------ 1 www-data www-data 64 Oct 16 09:12 2 -> /var/log/nginx/error.log\n\
+                   lrwx------ 1 www-data www-data 64 Oct 16 09:12 6 -> socket:[31337]\n";
+        let open = parse_open_files(fds).unwrap();
+        assert_eq!(open.count, 3);
+        assert_eq!(open.sample[1], "2 -> /var/log/nginx/error.log");
+        assert_eq!(open.sample[2], "6 -> socket:[31337]");
+        assert!(parse_open_files("ls: cannot open directory").is_none());
+    }
 
-    // Install new crontab
-    let install_cmd = format!("echo '{}' | crontab - 2>&1", new_crontab);
-    client.execute_command(&install_cmd).map_err(|e| e.message)
-}
+    #[test]
+    fn test_parse_logrotate_test() {
+        let output = "TEMP_FILE:/tmp/tmp.Xy12\n\
+                      reading config file /tmp/tmp.Xy12\n\
+                      error: /tmp/tmp.Xy12:3 unknown option 'rotat' -- ignoring line\n\
+                      Handling 1 logs\n";
+        let test = parse_logrotate_test(output, 1, "/etc/logrotate.d/app");
+        assert!(!test.ok);
+        assert_eq!(test.errors[0].file.as_deref(), Some("/etc/logrotate.d/app"));
+        assert_eq!(test.errors[0].line, Some(3));
+        assert_eq!(
+            test.errors[0].message,
+            "unknown option 'rotat' -- ignoring line"
+        );
+        assert!(!test.output.contains("tmp.Xy12"));
 
-#[tauri::command]
-pub async fn delete_cron_job(
-    line_number: usize,
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+        let missing_log = "TEMP_FILE:/tmp/tmp.Ab\n\
+                           error: stat of /var/log/app/app.log failed: No such file or directory\n";
+        let test = parse_logrotate_test(missing_log, 1, "/etc/logrotate.d/app");
+        assert!(test.ok);
+        assert_eq!(test.warnings.len(), 1);
 
-    // Get current crontab and remove line
-    let current = client
-        .execute_command("crontab -l 2>&1")
-        .unwrap_or_default();
+        assert!(!parse_logrotate_test("sudo: a password is required\n", 1, "/x").ok);
 
-    if current.contains("command not found") {
-        return Err("No crontab found".to_string());
+        assert_eq!(
+            logrotate_config_path("unattended-upgrades").unwrap(),
+            "/etc/logrotate.d/unattended-upgrades"
+        );
+        assert!(logrotate_config_path("../passwd").is_err());
+        assert!(logrotate_config_path(".hidden").is_err());
     }
 
-    let new_crontab: String = current
-        .lines()
-        .enumerate()
-        .filter(|(i, _)| *i != line_number - 1) // 1-indexed
-        .map(|(_, line)| line.to_string())
-        .collect::<Vec<_>>()
-        .join("\n");
-
-    let install_cmd = format!("echo '{}' | crontab - 2>&1", new_crontab);
-    client.execute_command(&install_cmd).map_err(|e| e.message)
-}
+    #[test]
+    fn test_container_log_flags() {
+        let flags =
+            container_log_flags(200, true, Some(" 2026-10-16T09:00:00Z "), Some("")).unwrap();
+        assert_eq!(
+            flags,
+            vec![
+                ("--tail", Some("200".to_string())),
+                ("--timestamps", None),
+                ("--since", Some("2026-10-16T09:00:00Z".to_string())),
+            ]
+        );
+        assert_eq!(
+            container_log_flags(100, false, None, Some("30m"))
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(container_log_flags(100, false, Some("--follow"), None).is_err());
+        assert!(container_log_flags(100, false, None, Some("1h; reboot")).is_err());
+    }
 
-#[tauri::command]
-pub async fn toggle_cron_job(
-    line_number: usize,
-    enabled: bool,
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected")?;
+    #[test]
+    fn test_parse_sshd_test() {
+        let failed = parse_sshd_test(
+            "/etc/ssh/sshd_config: line 14: Bad configuration option: PasswordAuth\n\
+             /etc/ssh/sshd_config.d/50-cloud-init.conf line 2: unsupported option \"x\"\n\
+             /etc/ssh/sshd_config: terminating, 2 bad configuration options\n",
+            255,
+        );
+        assert!(!failed.ok);
+        assert_eq!(failed.errors.len(), 3);
+        assert_eq!(
+            failed.errors[0].file.as_deref(),
+            Some("/etc/ssh/sshd_config")
+        );
+        assert_eq!(failed.errors[0].line, Some(14));
+        assert_eq!(
+            failed.errors[0].message,
+            "Bad configuration option: PasswordAuth"
+        );
+        assert_eq!(failed.errors[1].line, Some(2));
+        assert_eq!(failed.errors[2].file, None);
 
-    let current = client
-        .execute_command("crontab -l 2>&1")
-        .unwrap_or_default();
+        let passed = parse_sshd_test(
+            "/etc/ssh/sshd_config line 9: Deprecated option UsePrivilegeSeparation\n",
+            0,
+        );
+        assert!(passed.ok);
+        assert!(passed.errors.is_empty());
+        assert_eq!(passed.warnings[0].level, "warning");
+        assert!(parse_sshd_test("", 0).warnings.is_empty());
+    }
 
-    if current.contains("command not found") {
-        return Err("No crontab found".to_string());
+    #[test]
+    fn test_nginx_snapshot_names() {
+        assert_eq!(
+            nginx_snapshot_label(" before SSL/TLS move! "),
+            "before-SSLTLS-move"
+        );
+        assert_eq!(nginx_snapshot_label(""), "");
+
+        let output = "1760600000-before-ssl.tar.gz|20480\n\
+                      1760700000.tar.gz|20500\n\
+                      notes.txt|12\n\
+                      1760650000-bad;name.tar.gz|1\n\
+                      1760500000-old_one.tar.gz|19000\n";
+        let snapshots = parse_nginx_snapshots(output);
+        let ids: Vec<&str> = snapshots.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec!["1760700000", "1760600000-before-ssl", "1760500000-old_one"]
+        );
+        assert_eq!(snapshots[0].label, "");
+        assert_eq!(snapshots[1].label, "before-ssl");
+        assert_eq!(snapshots[1].created_at, 1760600000);
+        assert_eq!(snapshots[1].size, 20480);
+
+        // Restore ids are checked the same way before reaching a shell
+        assert!(parse_nginx_snapshot("1760600000-x.tar.gz", 0).is_some());
+        assert!(parse_nginx_snapshot("../etc/passwd.tar.gz", 0).is_none());
+        assert!(parse_nginx_snapshot("1760600000-a b.tar.gz", 0).is_none());
     }
 
-    let new_crontab: String = current
-        .lines()
-        .enumerate()
-        .map(|(i, line)| {
-            if i == line_number - 1 {
-                // Toggle comment
-                let trimmed = line.trim();
-                if enabled {
-                    // Remove leading #
-                    trimmed.trim_start_matches('#').trim_start().to_string()
-                } else {
-                    // Add # if not already commented
-                    if !trimmed.starts_with('#') {
-                        format!("# {}", line)
-                    } else {
-                        line.to_string()
-                    }
-                }
-            } else {
-                line.to_string()
-            }
-        })
-        .collect::<Vec<_>>()
-        .join("\n");
+    #[test]
+    fn test_truncated_output_reaches_callers() {
+        let endless =
+            || crate::ssh::collect_output(std::io::repeat(b'y'), 4096, |_| Ok(0)).unwrap();
+        assert!(remote_file_content("/etc/nginx/nginx.conf", endless())
+            .unwrap_err()
+            .contains("output limit"));
+
+        let multi = multi_result("web".to_string(), Ok(endless()));
+        assert!(multi.truncated);
+        assert_eq!(multi.exit_status, Some(-1));
+        assert_eq!(multi.output.map(|o| o.len()), Some(4096));
+
+        let complete = crate::ssh::collect_output("a = 1\n".as_bytes(), 4096, |_| Ok(0)).unwrap();
+        assert_eq!(
+            remote_file_content("/etc/a.conf", complete).unwrap(),
+            "a = 1\n"
+        );
+        let failed = crate::ssh::collect_output(&b""[..], 4096, |_| Ok(1)).unwrap();
+        assert!(remote_file_content("/etc/a.conf", failed).is_err());
+    }
 
-    let install_cmd = format!("echo '{}' | crontab - 2>&1", new_crontab);
-    client.execute_command(&install_cmd).map_err(|e| e.message)
+    #[test]
+    fn test_is_valid_backup_path() {
+        assert!(is_valid_backup_path("/etc/nginx/nginx.conf"));
+        assert!(is_valid_backup_path("/etc/nginx/sites-available/shop"));
+        assert!(!is_valid_backup_path("/etc/shadow"));
+        assert!(!is_valid_backup_path("/etc/nginx/"));
+        assert!(!is_valid_backup_path("/etc/nginx/../shadow"));
+        assert!(!is_valid_backup_path("/etc/nginx//nginx.conf"));
+        assert!(!is_valid_backup_path("/etc/nginx/nginx.conf.bak"));
+        assert!(!is_valid_backup_path("/etc/nginxx/nginx.conf"));
+    }
 }
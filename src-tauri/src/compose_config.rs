@@ -0,0 +1,521 @@
+//! Editing compose files.
+//!
+//! The file is read into a serde_yaml model first, which says what the environment of a
+//! service really is (aliases resolved, `<<` merges applied) and what it must be after
+//! the edit. Writing the model back with serde_yaml would drop every comment and reflow
+//! quoting and key order, so the change itself is made line by line like in
+//! `nginx_config`, then checked against the model: the result must parse, hold exactly
+//! the expected environment and leave the rest of the document as it was. Flow-style
+//! `environment: [...]` / `{...}` is rewritten in block style from the model; an
+//! environment shared through an anchor is refused, since editing it would change every
+//! service that uses it.
+
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Blank and comment-only lines don't end or belong to any block
+fn is_filler(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with('#')
+}
+
+/// `key: rest` → (`key` without quotes, `rest` without a trailing comment)
+fn split_key(line: &str) -> Option<(&str, &str)> {
+    let (key, rest) = line.trim().split_once(':')?;
+    let key = key.trim().trim_matches(|c| c == '"' || c == '\'');
+    let rest = match rest.find(" #") {
+        Some(pos) => &rest[..pos],
+        None => rest,
+    };
+    Some((key, rest.trim()))
+}
+
+/// Index one past the last line belonging to the block opened at `start`
+fn block_end(lines: &[String], start: usize) -> usize {
+    let indent = indent_of(&lines[start]);
+    let mut end = start + 1;
+    let mut last = start + 1;
+    while end < lines.len() {
+        if !is_filler(&lines[end]) {
+            if indent_of(&lines[end]) <= indent {
+                break;
+            }
+            last = end + 1;
+        }
+        end += 1;
+    }
+    last
+}
+
+/// Child lines of the block opened at `start` that start a key or list item, with the
+/// index one past their own nested lines
+fn children(lines: &[String], start: usize) -> Vec<(usize, usize)> {
+    let end = block_end(lines, start);
+    let mut result = Vec::new();
+    let mut child_indent = None;
+    for idx in start + 1..end {
+        if is_filler(&lines[idx]) {
+            continue;
+        }
+        let indent = indent_of(&lines[idx]);
+        match child_indent {
+            None => child_indent = Some(indent),
+            Some(ci) if indent > ci => continue,
+            _ => {}
+        }
+        result.push((idx, block_end(lines, idx).max(idx + 1)));
+    }
+    result
+}
+
+/// Render `value` as a YAML scalar, reusing the quote style of the value it replaces
+fn format_scalar(value: &str, quote: Option<char>) -> String {
+    let plain_safe = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '/' | '+' | '-'))
+        && !matches!(
+            value.to_lowercase().as_str(),
+            "true" | "false" | "yes" | "no" | "on" | "off" | "y" | "n" | "null" | "~"
+        );
+
+    match quote {
+        Some('\'') => format!("'{}'", value.replace('\'', "''")),
+        Some(_) => format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"")),
+        None if plain_safe => value.to_string(),
+        None => format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"")),
+    }
+}
+
+fn quote_char(scalar: &str) -> Option<char> {
+    scalar.chars().next().filter(|c| *c == '"' || *c == '\'')
+}
+
+/// One environment entry as a line. `None` is a variable passed through from the host
+/// (`- KEY` or `KEY:`).
+fn render_entry(
+    indent: usize,
+    key: &str,
+    value: Option<&str>,
+    list_form: bool,
+    quote: Option<char>,
+) -> String {
+    let indent = " ".repeat(indent);
+    match (value, list_form) {
+        (None, true) => format!("{}- {}", indent, key),
+        (None, false) => format!("{}{}:", indent, key),
+        (Some(value), true) => {
+            let entry = format!("{}={}", key, value);
+            let entry = match quote {
+                Some(_) => format_scalar(&entry, quote),
+                None if format_scalar(value, None) == value => entry,
+                None => format_scalar(&entry, None),
+            };
+            format!("{}- {}", indent, entry)
+        }
+        (Some(value), false) => format!("{}{}: {}", indent, key, format_scalar(value, quote)),
+    }
+}
+
+fn scalar_string(value: &Value) -> Result<Option<String>, String> {
+    match value {
+        Value::Null => Ok(None),
+        Value::String(s) => Ok(Some(s.clone())),
+        Value::Bool(b) => Ok(Some(b.to_string())),
+        Value::Number(n) => Ok(Some(n.to_string())),
+        _ => Err("Environment values must be plain scalars".to_string()),
+    }
+}
+
+/// Entries of an `environment:` value in either form, in file order, with `<<` merges
+/// applied
+fn model_environment(env: &Value) -> Result<Vec<(String, Option<String>)>, String> {
+    match env {
+        Value::Null => Ok(Vec::new()),
+        Value::Sequence(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::String(entry) => Ok(match entry.split_once('=') {
+                    Some((key, value)) => (key.to_string(), Some(value.to_string())),
+                    None => (entry.clone(), None),
+                }),
+                _ => Err("Environment list entries must be strings".to_string()),
+            })
+            .collect(),
+        Value::Mapping(_) => {
+            let mut merged = env.clone();
+            merged
+                .apply_merge()
+                .map_err(|e| format!("Can't resolve a YAML merge: {}", e))?;
+            let Value::Mapping(map) = merged else {
+                unreachable!("apply_merge keeps the value a mapping")
+            };
+            map.iter()
+                .map(|(key, value)| {
+                    let key = scalar_string(key)?.ok_or("Environment keys can't be empty")?;
+                    Ok((key, scalar_string(value)?))
+                })
+                .collect()
+        }
+        _ => Err("environment must be a list or a map".to_string()),
+    }
+}
+
+fn parse_compose(content: &str) -> Result<Value, String> {
+    serde_yaml::from_str(content).map_err(|e| format!("The compose file isn't valid YAML: {}", e))
+}
+
+fn service_model<'a>(doc: &'a Value, service: &str) -> Result<&'a Value, String> {
+    doc.get("services")
+        .ok_or("No services section in compose file")?
+        .get(service)
+        .ok_or_else(|| format!("Service '{}' not found in compose file", service))
+}
+
+/// `doc` without the environment of `service`, to compare everything else. A service
+/// left empty reads as null, which counts as an empty map here.
+fn without_environment(mut doc: Value, service: &str) -> Value {
+    if let Some(entry) = doc
+        .get_mut("services")
+        .and_then(|services| services.get_mut(service))
+    {
+        if let Value::Mapping(map) = entry {
+            map.remove("environment");
+        } else if entry.is_null() {
+            *entry = Value::Mapping(Default::default());
+        }
+    }
+    doc
+}
+
+/// Set (`Some`) or remove (`None`) environment variables of `service` in a compose file.
+/// Both the list form (`- KEY=value`) and the map form (`KEY: value`) are supported; a
+/// missing `environment:` block is created in map form.
+pub fn update_service_environment(
+    content: &str,
+    service: &str,
+    changes: &[(String, Option<String>)],
+) -> Result<String, String> {
+    let doc = parse_compose(content)?;
+    let env_model = service_model(&doc, service)?.get("environment");
+    let list_model = matches!(env_model, Some(Value::Sequence(_)));
+    let mut expected = match env_model {
+        Some(env) => model_environment(env)?,
+        None => Vec::new(),
+    };
+    for (key, value) in changes {
+        match (expected.iter().position(|(k, _)| k == key), value) {
+            (Some(idx), Some(value)) => expected[idx].1 = Some(value.clone()),
+            (Some(idx), None) => {
+                expected.remove(idx);
+            }
+            (None, Some(value)) => expected.push((key.clone(), Some(value.clone()))),
+            (None, None) => {}
+        }
+    }
+
+    let updated = edit_environment_lines(content, service, changes, &expected, list_model)?;
+
+    // The line edit only understands the layout; the model says whether it did the right
+    // thing, e.g. a key removed here could still come in through a `<<` merge
+    let result = parse_compose(&updated)
+        .map_err(|e| format!("The edit produced invalid YAML, nothing was saved: {}", e))?;
+    let actual = match service_model(&result, service)?.get("environment") {
+        Some(env) => model_environment(env)?,
+        None => Vec::new(),
+    };
+    let as_map =
+        |entries: &[(String, Option<String>)]| entries.iter().cloned().collect::<BTreeMap<_, _>>();
+    if as_map(&actual) != as_map(&expected) {
+        return Err(format!(
+            "The environment of '{}' can't be edited safely here (it may be inherited \
+             through a YAML merge); nothing was saved",
+            service
+        ));
+    }
+    if without_environment(result, service) != without_environment(doc, service) {
+        return Err(format!(
+            "Editing '{}' would change more than its environment; nothing was saved",
+            service
+        ));
+    }
+    Ok(updated)
+}
+
+/// The text edit: `changes` applied to block-style entries in place, or a flow-style
+/// value replaced by `expected` in block style
+fn edit_environment_lines(
+    content: &str,
+    service: &str,
+    changes: &[(String, Option<String>)],
+    expected: &[(String, Option<String>)],
+    list_model: bool,
+) -> Result<String, String> {
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+
+    let services_idx = lines
+        .iter()
+        .position(|l| indent_of(l) == 0 && matches!(split_key(l), Some(("services", ""))))
+        .ok_or("No services section in compose file")?;
+    let (service_idx, _) = children(&lines, services_idx)
+        .into_iter()
+        .find(|(idx, _)| matches!(split_key(&lines[*idx]), Some((key, "")) if key == service))
+        .ok_or_else(|| {
+            format!(
+                "Service '{}' isn't written in block style, so it can't be edited in place",
+                service
+            )
+        })?;
+
+    let service_children = children(&lines, service_idx);
+    let child_indent = match service_children.first() {
+        Some((idx, _)) => indent_of(&lines[*idx]),
+        None => indent_of(&lines[service_idx]) + 2,
+    };
+    let unit = child_indent - indent_of(&lines[service_idx]);
+
+    let existing_env = service_children
+        .iter()
+        .find(|(idx, _)| matches!(split_key(&lines[*idx]), Some(("environment", _))))
+        .copied();
+    let env_idx = match existing_env {
+        Some((idx, end)) => {
+            let (_, rest) = split_key(&lines[idx]).unwrap_or_default();
+            if rest.starts_with('&') || rest.starts_with('*') {
+                return Err(format!(
+                    "The environment of '{}' is shared through a YAML anchor; edit it where \
+                     the anchor is defined",
+                    service
+                ));
+            }
+            if !rest.is_empty() {
+                // Flow style: write the whole environment out again in block style
+                let mut block = vec![format!("{}environment:", " ".repeat(child_indent))];
+                block.extend(expected.iter().map(|(key, value)| {
+                    render_entry(child_indent + unit, key, value.as_deref(), list_model, None)
+                }));
+                if expected.is_empty() {
+                    block.truncate(0);
+                }
+                lines.splice(idx..end, block);
+                return Ok(join_lines(lines, content));
+            }
+            idx
+        }
+        None => {
+            if changes.iter().all(|(_, value)| value.is_none()) {
+                return Ok(content.to_string());
+            }
+            lines.insert(
+                service_idx + 1,
+                format!("{}environment:", " ".repeat(child_indent)),
+            );
+            service_idx + 1
+        }
+    };
+
+    for (key, value) in changes {
+        let items = children(&lines, env_idx);
+        let list_form = items
+            .first()
+            .is_some_and(|(idx, _)| lines[*idx].trim_start().starts_with('-'));
+        let item_indent = match items.first() {
+            Some((idx, _)) => indent_of(&lines[*idx]),
+            None => child_indent + unit,
+        };
+
+        // Find the existing entry for `key` and the quote style of its value
+        let existing = items.iter().find_map(|&(start, end)| {
+            let line = lines[start].trim_start();
+            if let Some(entry) = line.strip_prefix('-') {
+                let entry = entry.trim();
+                let quote = quote_char(entry);
+                let unquoted = entry.trim_matches(|c| c == '"' || c == '\'');
+                let name = unquoted.split('=').next().unwrap_or("");
+                (name.trim() == key).then_some((start, end, quote))
+            } else {
+                let (name, rest) = split_key(line)?;
+                (name == key).then_some((start, end, quote_char(rest)))
+            }
+        });
+
+        let rendered = value.as_deref().map(|value| {
+            let quote = existing.and_then(|(_, _, quote)| quote);
+            render_entry(item_indent, key, Some(value), list_form, quote)
+        });
+
+        match (existing, rendered) {
+            (Some((start, end, _)), Some(line)) => {
+                lines.splice(start..end, [line]);
+            }
+            (Some((start, end, _)), None) => {
+                lines.drain(start..end);
+            }
+            (None, Some(line)) => {
+                let insert_at = items.last().map(|(_, end)| *end).unwrap_or(env_idx + 1);
+                lines.insert(insert_at, line);
+            }
+            (None, None) => {}
+        }
+    }
+
+    // Drop an `environment:` key left without entries
+    if children(&lines, env_idx).is_empty() {
+        lines.remove(env_idx);
+    }
+    Ok(join_lines(lines, content))
+}
+
+fn join_lines(lines: Vec<String>, original: &str) -> String {
+    let mut result = lines.join("\n");
+    if original.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMPOSE: &str = "services:\n\
+                           \x20 web:\n\
+                           \x20   image: nginx\n\
+                           \x20   environment:\n\
+                           \x20     - MODE=prod\n\
+                           \x20     - \"GREETING=hello world\"\n\
+                           \x20   ports:\n\
+                           \x20     - \"80:80\"\n\
+                           \x20 db:\n\
+                           \x20   image: postgres\n\
+                           \x20   environment:\n\
+                           \x20     POSTGRES_USER: app # owner\n\
+                           \x20     POSTGRES_DB: 'app'\n";
+
+    fn set(key: &str, value: &str) -> (String, Option<String>) {
+        (key.to_string(), Some(value.to_string()))
+    }
+
+    #[test]
+    fn test_update_list_environment() {
+        let updated = update_service_environment(
+            COMPOSE,
+            "web",
+            &[set("MODE", "dev"), set("GREETING", "hi"), set("NEW", "a b")],
+        )
+        .unwrap();
+        assert!(updated.contains(
+            "      - MODE=dev\n      - \"GREETING=hi\"\n      - \"NEW=a b\"\n    ports:"
+        ));
+        // The other service is untouched
+        assert!(updated.ends_with(&COMPOSE[COMPOSE.find("  db:").unwrap()..]));
+    }
+
+    #[test]
+    fn test_update_map_environment() {
+        let updated = update_service_environment(
+            COMPOSE,
+            "db",
+            &[
+                set("POSTGRES_DB", "main"),
+                ("POSTGRES_USER".to_string(), None),
+                set("DEBUG", "true"),
+            ],
+        )
+        .unwrap();
+        assert!(updated
+            .ends_with("    environment:\n      POSTGRES_DB: 'main'\n      DEBUG: \"true\"\n"));
+    }
+
+    #[test]
+    fn test_update_creates_and_removes_environment() {
+        let compose = "services:\n  app:\n    image: busybox\n";
+        let added = update_service_environment(compose, "app", &[set("PORT", "8080")]).unwrap();
+        assert_eq!(
+            added,
+            "services:\n  app:\n    environment:\n      PORT: 8080\n    image: busybox\n"
+        );
+
+        let removed =
+            update_service_environment(&added, "app", &[("PORT".to_string(), None)]).unwrap();
+        assert_eq!(removed, compose);
+    }
+
+    #[test]
+    fn test_update_rejects_unknown_service() {
+        assert!(update_service_environment(COMPOSE, "cache", &[set("A", "1")]).is_err());
+        assert!(update_service_environment("services: [", "app", &[set("A", "1")]).is_err());
+    }
+
+    #[test]
+    fn test_update_flow_style_environment() {
+        let list = "services:\n  app:\n    image: busybox\n    environment: [A=1, \"B=two words\"]\n    ports: [\"80:80\"]\n";
+        assert_eq!(
+            update_service_environment(list, "app", &[set("A", "2"), set("C", "3")]).unwrap(),
+            "services:\n  app:\n    image: busybox\n    environment:\n      - A=2\n      - \"B=two words\"\n      - C=3\n    ports: [\"80:80\"]\n"
+        );
+
+        let map = "services:\n  app:\n    environment: {A: 1, KEEP: }\n";
+        assert_eq!(
+            update_service_environment(map, "app", &[("A".to_string(), None)]).unwrap(),
+            "services:\n  app:\n    environment:\n      KEEP:\n"
+        );
+        assert_eq!(
+            update_service_environment(
+                "services:\n  app:\n    environment: [A=1]\n",
+                "app",
+                &[("A".to_string(), None)]
+            )
+            .unwrap(),
+            "services:\n  app:\n"
+        );
+    }
+
+    #[test]
+    fn test_update_anchored_environment() {
+        let compose = "\
+x-env: &common
+  TZ: UTC
+  LEVEL: info
+services:
+  api:
+    environment: *common
+  worker:
+    environment:
+      <<: *common
+      QUEUE: jobs
+  cron:
+    environment: &cron-env
+      SCHEDULE: daily
+";
+        // Shared values can't be edited through one of their users or their definition
+        assert!(
+            update_service_environment(compose, "api", &[set("TZ", "CET")])
+                .unwrap_err()
+                .contains("anchor")
+        );
+        assert!(update_service_environment(compose, "cron", &[set("A", "1")]).is_err());
+
+        // Keys of its own next to a merge are fine, and override the merged ones
+        let updated = update_service_environment(
+            compose,
+            "worker",
+            &[set("QUEUE", "mail"), set("LEVEL", "debug")],
+        )
+        .unwrap();
+        assert!(
+            updated.contains("      <<: *common\n      QUEUE: mail\n      LEVEL: debug\n  cron:")
+        );
+
+        // A merged key can't be removed from here
+        assert!(
+            update_service_environment(compose, "worker", &[("TZ".to_string(), None)])
+                .unwrap_err()
+                .contains("merge")
+        );
+    }
+}
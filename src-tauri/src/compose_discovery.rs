@@ -1,5 +1,5 @@
 use crate::ssh::SshClient;
-use crate::types::ComposeProject;
+use crate::types::{CacheInfo, ComposeProject};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -7,6 +7,9 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+/// How long a scan is reused before the server is scanned again
+pub const COMPOSE_CACHE_TTL_SECS: u64 = 86400;
+
 /// Cache entry for a single server
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComposeCacheEntry {
@@ -92,6 +95,27 @@ impl ComposeDiscoveryCache {
         Ok(())
     }
 
+    /// What's cached for a server, with empty fields when it has never been scanned
+    pub async fn info(&self, server_id: &str) -> CacheInfo {
+        let Some(entry) = self.get(server_id).await else {
+            return CacheInfo {
+                last_scan: None,
+                age_secs: None,
+                expired: true,
+                project_count: 0,
+                scan_paths: Vec::new(),
+            };
+        };
+        let age_secs = unix_now().saturating_sub(entry.last_scan);
+        CacheInfo {
+            last_scan: Some(entry.last_scan),
+            age_secs: Some(age_secs),
+            expired: age_secs >= COMPOSE_CACHE_TTL_SECS,
+            project_count: entry.projects.len(),
+            scan_paths: entry.scan_paths,
+        }
+    }
+
     pub async fn invalidate(&self, server_id: &str) {
         let mut cache = self.cache.lock().await;
         cache.remove(server_id);
@@ -107,6 +131,13 @@ impl Default for ComposeDiscoveryCache {
     }
 }
 
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
 /// Scan for Docker Compose files on the remote server
 pub async fn scan_compose_files(
     client: &SshClient,
@@ -115,13 +146,7 @@ pub async fn scan_compose_files(
 ) -> Result<Vec<ComposeProject>, String> {
     // Check cache first (valid for 24 hours)
     if let Some(entry) = cache.get(server_id).await {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-
-        // Cache valid for 24 hours (86400 seconds)
-        if now - entry.last_scan < 86400 {
+        if unix_now().saturating_sub(entry.last_scan) < COMPOSE_CACHE_TTL_SECS {
             log::info!("Using cached compose files for server {}", server_id);
             return compose_projects_from_cache(client, &entry).await;
         }
@@ -162,10 +187,7 @@ async fn scan_and_cache(
     cache: &ComposeDiscoveryCache,
     server_id: &str,
 ) -> Result<Vec<ComposeProject>, String> {
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
+    let now = unix_now();
 
     // Scan common locations with limited depth
     let scan_paths = vec![
@@ -1,30 +1,23 @@
 use crate::types::*;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use serde_json::{json, Value as JsonValue};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use tauri::State;
-
-// Configuration files to scan
-const CONFIG_PATTERNS: &[&str] = &[
-    "package.json",
-    "tauri.conf.json",
-    "Cargo.toml",
-    "vite.config.ts",
-    "tailwind.config.js",
-    "tsconfig.json",
-    "tsconfig.node.json",
-    ".eslintrc.json",
-    ".prettierrc",
-    "postcss.config.js",
-    ".github/workflows/*.yml",
-    ".github/workflows/*.yaml",
-];
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, State};
 
 // File extensions to scan
 const CONFIG_EXTENSIONS: &[&str] = &["json", "toml", "yaml", "yml", "ts", "js", "mts", "cts"];
 
+// Editors often write a file several times per save; wait this long for things to settle
+const WATCH_DEBOUNCE_MS: u64 = 400;
+
 pub struct ConfigGraphState {
     pub project_root: PathBuf,
+    watcher: Mutex<Option<RecommendedWatcher>>,
 }
 
 impl Default for ConfigGraphState {
@@ -36,7 +29,10 @@ impl Default for ConfigGraphState {
             .map(|p| p.to_path_buf())
             .unwrap_or_else(|| PathBuf::from("."));
         
-        ConfigGraphState { project_root }
+        ConfigGraphState {
+            project_root,
+            watcher: Mutex::new(None),
+        }
     }
 }
 
@@ -67,10 +63,7 @@ fn scan_directory(
         
         // Skip hidden directories and node_modules, target, dist, etc.
         if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-            if name.starts_with('.') && name != ".github" && name != ".vscode" && name != ".qwen" {
-                continue;
-            }
-            if name == "node_modules" || name == "target" || name == "dist" || name == ".git" {
+            if is_ignored_name(name) {
                 continue;
             }
         }
@@ -87,6 +80,14 @@ fn scan_directory(
     Ok(())
 }
 
+fn is_ignored_name(name: &str) -> bool {
+    (name.starts_with('.') && name != ".github" && name != ".vscode" && name != ".qwen")
+        || name == "node_modules"
+        || name == "target"
+        || name == "dist"
+        || name == ".git"
+}
+
 fn parse_config_file(path: &Path) -> Option<ConfigFile> {
     let _file_name = path.file_name()?.to_str()?;
     let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
@@ -346,38 +347,416 @@ pub async fn get_config_dependencies(state: State<'_, ConfigGraphState>) -> Resu
         }
     }
     
-    // Add edges between related files
-    let file_pairs: Vec<(&str, &str)> = vec![
-        ("package.json", "tsconfig.json"),
-        ("vite.config.ts", "package.json"),
-        ("tailwind.config.js", "package.json"),
-        ("Cargo.toml", "tauri.conf.json"),
-        ("vite.config.ts", "tauri.conf.json"),
-    ];
-    
-    for (file1, file2) in file_pairs {
-        let node1_id = config_files
-            .iter()
-            .find(|c| c.path.ends_with(file1))
-            .map(|c| format!("file:{}", c.path));
-        let node2_id = config_files
-            .iter()
-            .find(|c| c.path.ends_with(file2))
-            .map(|c| format!("file:{}", c.path));
-        
-        if let (Some(id1), Some(id2)) = (node1_id, node2_id) {
+    // Add dependency nodes for every manifest we can parse
+    let manifests: Vec<(&ConfigFile, &'static str, Vec<ManifestDependency>)> = config_files
+        .iter()
+        .filter_map(|config| {
+            manifest_dependencies(Path::new(&config.path))
+                .map(|(ecosystem, deps)| (config, ecosystem, deps))
+        })
+        .collect();
+
+    for (config, ecosystem, deps) in &manifests {
+        let file_node_id = format!("file:{}", config.path);
+        let workspace_root = if *ecosystem == "cargo" {
+            find_workspace_root(&config.path, &manifests)
+        } else {
+            None
+        };
+
+        for dep in deps {
+            let dep_node_id = dependency_node_id(&config.path, dep);
+
+            // Inherited Cargo deps take their version from the workspace root
+            let inherited = match (&dep.version.kind, workspace_root) {
+                (VersionKind::Workspace, Some((root_path, root_deps))) => root_deps
+                    .iter()
+                    .find(|d| d.section == "workspace.dependencies" && d.name == dep.name)
+                    .map(|root_dep| (root_path, root_dep)),
+                _ => None,
+            };
+            let resolved_version = match inherited {
+                Some((_, root_dep)) => root_dep.version.version.clone(),
+                None => dep.version.version.clone(),
+            };
+
+            nodes.push(GraphNode {
+                id: dep_node_id.clone(),
+                label: dep.name.clone(),
+                node_type: GraphNodeType::Module,
+                metadata: json!({
+                    "ecosystem": ecosystem,
+                    "manifest": config.path,
+                    "section": dep.section,
+                    "version": dep.version.raw,
+                    "versionKind": dep.version.kind,
+                    "resolvedVersion": resolved_version
+                }),
+            });
+
             edges.push(GraphEdge {
-                source: id1,
-                target: id2,
-                edge_type: "relates".to_string(),
-                label: Some("references".to_string()),
+                source: file_node_id.clone(),
+                target: dep_node_id.clone(),
+                edge_type: "depends_on".to_string(),
+                label: Some(dep.version.raw.clone()),
             });
+
+            if let Some((root_path, root_dep)) = inherited {
+                edges.push(GraphEdge {
+                    source: dep_node_id,
+                    target: dependency_node_id(root_path, root_dep),
+                    edge_type: "inherits".to_string(),
+                    label: Some("workspace".to_string()),
+                });
+            }
         }
     }
-    
+
     Ok(GraphData { nodes, edges })
 }
 
+fn dependency_node_id(manifest_path: &str, dep: &ManifestDependency) -> String {
+    format!("dep:{}:{}:{}", manifest_path, dep.section, dep.name)
+}
+
+/// Parse the dependency list of a manifest file, returning the ecosystem it belongs to
+fn manifest_dependencies(path: &Path) -> Option<(&'static str, Vec<ManifestDependency>)> {
+    let file_name = path.file_name()?.to_str()?;
+    match file_name {
+        "package.json" => {
+            let content = fs::read_to_string(path).ok()?;
+            Some(("npm", parse_package_json_dependencies(&content)))
+        }
+        "Cargo.toml" => {
+            let content = fs::read_to_string(path).ok()?;
+            Some(("cargo", parse_cargo_dependencies(&content)))
+        }
+        _ => None,
+    }
+}
+
+/// Find the nearest Cargo manifest above `member_path` that declares `[workspace.dependencies]`
+fn find_workspace_root<'a>(
+    member_path: &str,
+    manifests: &'a [(&ConfigFile, &'static str, Vec<ManifestDependency>)],
+) -> Option<(&'a str, &'a [ManifestDependency])> {
+    let member_dir = Path::new(member_path).parent()?;
+
+    manifests
+        .iter()
+        .filter(|(_, ecosystem, deps)| {
+            *ecosystem == "cargo" && deps.iter().any(|d| d.section == "workspace.dependencies")
+        })
+        .filter_map(|(config, _, deps)| {
+            let root_dir = Path::new(&config.path).parent()?;
+            if member_dir.starts_with(root_dir) {
+                Some((root_dir.components().count(), config.path.as_str(), deps.as_slice()))
+            } else {
+                None
+            }
+        })
+        .max_by_key(|(depth, _, _)| *depth)
+        .map(|(_, path, deps)| (path, deps))
+}
+
+const NPM_DEPENDENCY_SECTIONS: &[&str] = &["dependencies", "devDependencies"];
+
+const CARGO_DEPENDENCY_SECTIONS: &[&str] = &["dependencies", "dev-dependencies", "build-dependencies"];
+
+fn parse_package_json_dependencies(content: &str) -> Vec<ManifestDependency> {
+    let mut deps = Vec::new();
+
+    let value = match serde_json::from_str::<JsonValue>(content) {
+        Ok(value) => value,
+        Err(_) => return deps,
+    };
+
+    for section in NPM_DEPENDENCY_SECTIONS {
+        if let Some(obj) = value.get(section).and_then(|v| v.as_object()) {
+            for (name, spec) in obj {
+                deps.push(ManifestDependency {
+                    name: name.clone(),
+                    section: section.to_string(),
+                    version: parse_npm_version(spec.as_str().unwrap_or("")),
+                });
+            }
+        }
+    }
+
+    deps
+}
+
+/// Classify an npm version requirement (`^1.2.3`, `~1.2`, `git+https://...`, `file:../lib`, ...)
+fn parse_npm_version(raw: &str) -> VersionSpec {
+    let spec = raw.trim();
+
+    // Aliases like "npm:react@^18.2.0" carry the real requirement after the last '@'
+    if let Some(alias) = spec.strip_prefix("npm:") {
+        let requirement = alias.rsplit_once('@').map(|(_, v)| v).unwrap_or("");
+        return VersionSpec {
+            raw: raw.to_string(),
+            ..parse_npm_version(requirement)
+        };
+    }
+
+    let (kind, version) = if spec.is_empty() || spec == "*" || spec == "x" {
+        (VersionKind::Any, None)
+    } else if spec.starts_with("git+")
+        || spec.starts_with("git://")
+        || spec.starts_with("github:")
+        || spec.starts_with("gitlab:")
+        || spec.starts_with("bitbucket:")
+        || spec.ends_with(".git")
+        || is_github_shorthand(spec)
+    {
+        // A committish or semver range can follow '#'
+        let version = spec
+            .split_once('#')
+            .map(|(_, fragment)| fragment.trim_start_matches("semver:").to_string());
+        (VersionKind::Git, version)
+    } else if spec.starts_with("file:") || spec.starts_with("link:") {
+        (VersionKind::Path, None)
+    } else if let Some(rest) = spec.strip_prefix("workspace:") {
+        let version = rest.trim_start_matches(['^', '~']);
+        let version = if version.is_empty() || version == "*" {
+            None
+        } else {
+            Some(version.to_string())
+        };
+        (VersionKind::Workspace, version)
+    } else if spec.starts_with("http://") || spec.starts_with("https://") {
+        (VersionKind::Url, None)
+    } else if let Some(rest) = spec.strip_prefix('^') {
+        (VersionKind::Caret, Some(rest.trim().to_string()))
+    } else if let Some(rest) = spec.strip_prefix('~') {
+        (VersionKind::Tilde, Some(rest.trim().to_string()))
+    } else if is_plain_version(spec.trim_start_matches(['=', 'v'])) {
+        (VersionKind::Exact, Some(spec.trim_start_matches(['=', 'v']).to_string()))
+    } else if spec.chars().next().is_some_and(|c| c.is_ascii_alphabetic()) {
+        // Dist-tags such as "latest" or "next"
+        (VersionKind::Tag, None)
+    } else {
+        (VersionKind::Range, None)
+    };
+
+    VersionSpec {
+        raw: raw.to_string(),
+        kind,
+        version,
+    }
+}
+
+/// npm treats "user/repo" (optionally with "#ref") as a GitHub dependency
+fn is_github_shorthand(spec: &str) -> bool {
+    let repo = spec.split('#').next().unwrap_or("");
+    let mut parts = repo.split('/');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(user), Some(name), None) => {
+            !user.is_empty()
+                && !name.is_empty()
+                && !user.starts_with('@')
+                && !user.starts_with('.')
+                && !repo.contains(':')
+        }
+        _ => false,
+    }
+}
+
+fn is_plain_version(spec: &str) -> bool {
+    spec.chars().next().is_some_and(|c| c.is_ascii_digit())
+        && spec
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '+')
+        && !spec.contains(".x")
+}
+
+fn parse_cargo_dependencies(content: &str) -> Vec<ManifestDependency> {
+    let mut deps = Vec::new();
+
+    let manifest = match content.parse::<toml::Table>() {
+        Ok(manifest) => manifest,
+        Err(_) => return deps,
+    };
+
+    for section in CARGO_DEPENDENCY_SECTIONS {
+        if let Some(table) = manifest.get(*section).and_then(|v| v.as_table()) {
+            collect_cargo_table(table, section, &mut deps);
+        }
+    }
+
+    // [target.'cfg(...)'.dependencies]
+    if let Some(targets) = manifest.get("target").and_then(|v| v.as_table()) {
+        for (target, target_table) in targets {
+            for section in CARGO_DEPENDENCY_SECTIONS {
+                if let Some(table) = target_table.get(*section).and_then(|v| v.as_table()) {
+                    collect_cargo_table(table, &format!("target.{}.{}", target, section), &mut deps);
+                }
+            }
+        }
+    }
+
+    // [workspace.dependencies] declares versions members inherit with `workspace = true`
+    if let Some(table) = manifest
+        .get("workspace")
+        .and_then(|v| v.get("dependencies"))
+        .and_then(|v| v.as_table())
+    {
+        collect_cargo_table(table, "workspace.dependencies", &mut deps);
+    }
+
+    deps
+}
+
+fn collect_cargo_table(table: &toml::Table, section: &str, deps: &mut Vec<ManifestDependency>) {
+    for (name, spec) in table {
+        deps.push(ManifestDependency {
+            name: name.clone(),
+            section: section.to_string(),
+            version: parse_cargo_spec(spec),
+        });
+    }
+}
+
+/// Classify a Cargo dependency, either `"1.0"` or a `{ version, git, path, workspace }` table
+fn parse_cargo_spec(spec: &toml::Value) -> VersionSpec {
+    let table = match spec {
+        toml::Value::String(requirement) => return parse_cargo_version(requirement),
+        toml::Value::Table(table) => table,
+        other => return parse_cargo_version(&other.to_string()),
+    };
+
+    let get_str = |key: &str| table.get(key).and_then(|v| v.as_str()).map(String::from);
+
+    if table.get("workspace").and_then(|v| v.as_bool()) == Some(true) {
+        VersionSpec {
+            raw: "workspace = true".to_string(),
+            kind: VersionKind::Workspace,
+            version: None,
+        }
+    } else if let Some(git) = get_str("git") {
+        VersionSpec {
+            raw: git,
+            kind: VersionKind::Git,
+            version: get_str("version")
+                .or_else(|| get_str("tag"))
+                .or_else(|| get_str("rev"))
+                .or_else(|| get_str("branch")),
+        }
+    } else if let Some(path) = get_str("path") {
+        VersionSpec {
+            raw: path,
+            kind: VersionKind::Path,
+            version: get_str("version"),
+        }
+    } else {
+        parse_cargo_version(&get_str("version").unwrap_or_default())
+    }
+}
+
+/// Cargo requirements default to caret semantics, so a bare `1.2` is `^1.2`
+fn parse_cargo_version(raw: &str) -> VersionSpec {
+    let spec = raw.trim();
+
+    let (kind, version) = if spec.is_empty() || spec == "*" {
+        (VersionKind::Any, None)
+    } else if spec.contains(',') || spec.contains('<') || spec.contains('>') || spec.contains('*') {
+        (VersionKind::Range, None)
+    } else if let Some(rest) = spec.strip_prefix('=') {
+        (VersionKind::Exact, Some(rest.trim().to_string()))
+    } else if let Some(rest) = spec.strip_prefix('~') {
+        (VersionKind::Tilde, Some(rest.trim().to_string()))
+    } else {
+        (VersionKind::Caret, Some(spec.trim_start_matches('^').trim().to_string()))
+    };
+
+    VersionSpec {
+        raw: raw.to_string(),
+        kind,
+        version,
+    }
+}
+
+/// Start watching the project for config file changes, emitting `config-file-changed` events
+#[tauri::command]
+pub fn watch_config_files(
+    app: tauri::AppHandle,
+    state: State<'_, ConfigGraphState>,
+) -> Result<(), String> {
+    let root = state.project_root.clone();
+    let (tx, rx) = mpsc::channel();
+
+    let mut watcher = notify::recommended_watcher(tx)
+        .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+    watcher
+        .watch(&root, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", root.display(), e))?;
+
+    // Replacing the previous watcher drops its sender, which ends its debounce thread
+    *state.watcher.lock().unwrap() = Some(watcher);
+
+    std::thread::spawn(move || {
+        let debounce = Duration::from_millis(WATCH_DEBOUNCE_MS);
+        let mut pending: HashMap<PathBuf, ConfigChangeKind> = HashMap::new();
+
+        loop {
+            match rx.recv_timeout(debounce) {
+                Ok(Ok(event)) => {
+                    let kind = match event.kind {
+                        EventKind::Create(_) => ConfigChangeKind::Created,
+                        EventKind::Modify(notify::event::ModifyKind::Name(_)) => ConfigChangeKind::Renamed,
+                        EventKind::Modify(_) => ConfigChangeKind::Modified,
+                        EventKind::Remove(_) => ConfigChangeKind::Removed,
+                        _ => continue,
+                    };
+                    for path in event.paths {
+                        if is_watched_config_path(&root, &path) {
+                            pending.insert(path, kind.clone());
+                        }
+                    }
+                }
+                Ok(Err(e)) => log::warn!("Config watcher error: {}", e),
+                Err(RecvTimeoutError::Timeout) => flush_config_changes(&app, &mut pending),
+                Err(RecvTimeoutError::Disconnected) => {
+                    flush_config_changes(&app, &mut pending);
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_watching_config_files(state: State<'_, ConfigGraphState>) -> Result<(), String> {
+    state.watcher.lock().unwrap().take();
+    Ok(())
+}
+
+fn flush_config_changes(app: &tauri::AppHandle, pending: &mut HashMap<PathBuf, ConfigChangeKind>) {
+    for (path, kind) in pending.drain() {
+        let change = ConfigFileChange {
+            path: path.to_string_lossy().to_string(),
+            kind,
+        };
+        if let Err(e) = app.emit("config-file-changed", change) {
+            log::warn!("Failed to emit config change: {}", e);
+        }
+    }
+}
+
+/// Apply the same ignore list and extension filter as `scan_config_files`
+fn is_watched_config_path(root: &Path, path: &Path) -> bool {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let ignored = relative
+        .components()
+        .filter_map(|c| c.as_os_str().to_str())
+        .any(is_ignored_name);
+
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    !ignored && CONFIG_EXTENSIONS.contains(&extension)
+}
+
 #[tauri::command]
 pub fn get_config_content(file_path: String) -> Result<String, String> {
     let path = Path::new(&file_path);
@@ -484,3 +863,90 @@ fn extract_json_value(json: &JsonValue, key: &str) -> Option<String> {
     
     Some(current.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_npm_version_forms() {
+        let caret = parse_npm_version("^18.2.0");
+        assert_eq!(caret.kind, VersionKind::Caret);
+        assert_eq!(caret.version.as_deref(), Some("18.2.0"));
+
+        let tilde = parse_npm_version("~5.0");
+        assert_eq!(tilde.kind, VersionKind::Tilde);
+        assert_eq!(tilde.version.as_deref(), Some("5.0"));
+
+        assert_eq!(parse_npm_version("1.2.3").kind, VersionKind::Exact);
+        assert_eq!(parse_npm_version(">=1.0.0 <2.0.0").kind, VersionKind::Range);
+        assert_eq!(parse_npm_version("latest").kind, VersionKind::Tag);
+        assert_eq!(parse_npm_version("file:../shared").kind, VersionKind::Path);
+
+        let git = parse_npm_version("git+https://github.com/user/repo.git#v1.4.0");
+        assert_eq!(git.kind, VersionKind::Git);
+        assert_eq!(git.version.as_deref(), Some("v1.4.0"));
+        assert_eq!(parse_npm_version("user/repo#main").kind, VersionKind::Git);
+
+        let alias = parse_npm_version("npm:react@^18.2.0");
+        assert_eq!(alias.kind, VersionKind::Caret);
+        assert_eq!(alias.raw, "npm:react@^18.2.0");
+    }
+
+    #[test]
+    fn test_parse_package_json_dependencies() {
+        let content = r#"{
+  "name": "app",
+  "dependencies": { "react": "^18.2.0" },
+  "devDependencies": { "vite": "~5.0.0" }
+}"#;
+        let deps = parse_package_json_dependencies(content);
+        assert_eq!(deps.len(), 2);
+        assert_eq!(deps[0].name, "react");
+        assert_eq!(deps[0].section, "dependencies");
+        assert_eq!(deps[1].name, "vite");
+        assert_eq!(deps[1].section, "devDependencies");
+    }
+
+    #[test]
+    fn test_parse_cargo_dependencies() {
+        let content = r#"
+[package]
+name = "member"
+
+[dependencies]
+serde = { version = "1", features = ["derive"] }
+tokio = { workspace = true }
+log.workspace = true
+local = { path = "../local" }
+remote = { git = "https://github.com/user/remote", tag = "v0.3.0" }
+pinned = "=0.9.5"
+
+[dev-dependencies]
+tempfile = "3"
+
+[workspace.dependencies]
+tokio = "1.35"
+"#;
+        let deps = parse_cargo_dependencies(content);
+        let find = |name: &str, section: &str| {
+            deps.iter()
+                .find(|d| d.name == name && d.section == section)
+                .unwrap_or_else(|| panic!("missing {} in {}", name, section))
+        };
+
+        assert_eq!(find("serde", "dependencies").version.kind, VersionKind::Caret);
+        assert_eq!(find("tokio", "dependencies").version.kind, VersionKind::Workspace);
+        assert_eq!(find("log", "dependencies").version.kind, VersionKind::Workspace);
+        assert_eq!(find("local", "dependencies").version.kind, VersionKind::Path);
+        let remote = &find("remote", "dependencies").version;
+        assert_eq!(remote.kind, VersionKind::Git);
+        assert_eq!(remote.version.as_deref(), Some("v0.3.0"));
+        assert_eq!(find("pinned", "dependencies").version.kind, VersionKind::Exact);
+        assert_eq!(find("tempfile", "dev-dependencies").version.version.as_deref(), Some("3"));
+        assert_eq!(
+            find("tokio", "workspace.dependencies").version.version.as_deref(),
+            Some("1.35")
+        );
+    }
+}
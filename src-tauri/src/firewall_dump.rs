@@ -0,0 +1,295 @@
+//! Parsing raw netfilter rules from `iptables -S` and `nft list ruleset`.
+//!
+//! UFW and Docker both program netfilter, so this is the layer where it shows that
+//! Docker's chains see forwarded traffic before UFW's. `iptables -S` prints one table as
+//! `-P`/`-N`/`-A` commands; `nft list ruleset` prints every table as nested blocks, where
+//! sets, maps and other objects sit next to chains and are skipped here.
+
+use crate::types::{FirewallBackend, FirewallChain, FirewallRule};
+
+/// Tables `iptables -t` accepts, which iptables-nft also creates under these names
+pub const IPTABLES_TABLES: &[&str] = &["filter", "nat", "mangle", "raw", "security"];
+
+const NFT_VERDICTS: &[&str] = &[
+    "accept",
+    "drop",
+    "reject",
+    "return",
+    "queue",
+    "continue",
+    "masquerade",
+    "snat",
+    "dnat",
+    "redirect",
+];
+
+fn is_docker_chain(table: &str, name: &str) -> bool {
+    name.starts_with("DOCKER") || table.contains("docker")
+}
+
+fn new_chain(table: &str, name: &str) -> FirewallChain {
+    FirewallChain {
+        table: table.to_string(),
+        name: name.to_string(),
+        policy: None,
+        docker: is_docker_chain(table, name),
+        rules: Vec::new(),
+    }
+}
+
+/// Chains of one table from `iptables -t <table> -S`, in the order they're listed
+pub fn parse_iptables_rules(table: &str, output: &str) -> Vec<FirewallChain> {
+    let mut chains: Vec<FirewallChain> = Vec::new();
+    for line in output.lines() {
+        let mut tokens = line.split_whitespace();
+        let (Some(command), Some(chain)) = (tokens.next(), tokens.next()) else {
+            continue;
+        };
+        if !matches!(command, "-P" | "-N" | "-A") {
+            continue;
+        }
+        let idx = match chains.iter().position(|c| c.name == chain) {
+            Some(idx) => idx,
+            None => {
+                chains.push(new_chain(table, chain));
+                chains.len() - 1
+            }
+        };
+        match command {
+            "-P" => chains[idx].policy = tokens.next().map(String::from),
+            "-A" => {
+                let args: Vec<&str> = tokens.collect();
+                let target = args
+                    .windows(2)
+                    .find(|pair| matches!(pair[0], "-j" | "-g"))
+                    .map(|pair| pair[1].to_string());
+                chains[idx].rules.push(FirewallRule {
+                    rule: args.join(" "),
+                    target,
+                });
+            }
+            _ => {}
+        }
+    }
+    chains
+}
+
+/// Verdict of an nft rule, or the chain it jumps to; quoted comments are ignored
+fn nft_rule_target(rule: &str) -> Option<String> {
+    let unquoted: String = rule.split('"').step_by(2).collect::<Vec<_>>().join(" ");
+    let tokens: Vec<&str> = unquoted.split_whitespace().collect();
+    let mut target = None;
+    for (idx, token) in tokens.iter().enumerate() {
+        if matches!(*token, "jump" | "goto") {
+            target = tokens.get(idx + 1).map(|t| t.to_string());
+        } else if NFT_VERDICTS.contains(token) {
+            target = Some(token.to_string());
+        }
+    }
+    target
+}
+
+/// Chains of every table in `nft list ruleset`, tables named `<family> <name>`
+pub fn parse_nft_ruleset(output: &str) -> Vec<FirewallChain> {
+    let mut chains = Vec::new();
+    let mut table: Option<String> = None;
+    let mut chain: Option<FirewallChain> = None;
+    // Depth inside a set, map or other non-chain block being skipped
+    let mut skip_depth: i64 = 0;
+
+    for raw in output.lines() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if skip_depth > 0 {
+            skip_depth += line.matches('{').count() as i64 - line.matches('}').count() as i64;
+            continue;
+        }
+
+        if let Some(current) = chain.as_mut() {
+            if line == "}" {
+                chains.extend(chain.take());
+            } else if line.starts_with("type ") {
+                // type filter hook input priority filter; policy drop;
+                current.policy = line
+                    .split(';')
+                    .find_map(|part| part.trim().strip_prefix("policy "))
+                    .map(|policy| policy.trim().to_string());
+            } else if !line.starts_with("comment ") {
+                current.rules.push(FirewallRule {
+                    rule: line.to_string(),
+                    target: nft_rule_target(line),
+                });
+            }
+            continue;
+        }
+
+        match table.as_deref() {
+            Some(current) => {
+                if line == "}" {
+                    table = None;
+                } else if let Some(name) = line.strip_prefix("chain ") {
+                    let name = name.trim_end_matches('{').trim().trim_matches('"');
+                    chain = Some(new_chain(current, name));
+                } else if line.ends_with('{') {
+                    skip_depth = 1;
+                }
+            }
+            None => {
+                if let Some(name) = line.strip_prefix("table ") {
+                    table = Some(name.trim_end_matches('{').trim().to_string());
+                }
+            }
+        }
+    }
+    chains
+}
+
+/// iptables unless it's missing, or it's the nf_tables variant and nftables also holds
+/// tables of its own (firewalld, a hand-written nftables.conf, Docker's nftables
+/// backend) that `iptables -S` wouldn't show. `None` when neither tool is installed.
+pub fn detect_backend(
+    iptables_version: Option<&str>,
+    nft_ruleset: Option<&str>,
+) -> Option<FirewallBackend> {
+    let native_nft_tables = nft_ruleset.is_some_and(|ruleset| {
+        ruleset.lines().any(|line| {
+            let Some(table) = line.strip_prefix("table ") else {
+                return false;
+            };
+            let mut parts = table.trim_end_matches('{').split_whitespace();
+            let family = parts.next().unwrap_or_default();
+            let name = parts.next().unwrap_or_default();
+            !matches!(family, "ip" | "ip6") || !IPTABLES_TABLES.contains(&name)
+        })
+    });
+    match (iptables_version, nft_ruleset) {
+        (Some(version), _) if !(version.contains("nf_tables") && native_nft_tables) => {
+            Some(FirewallBackend::Iptables)
+        }
+        (_, Some(_)) => Some(FirewallBackend::Nftables),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IPTABLES_FILTER: &str = "\
+-P INPUT DROP
+-P FORWARD DROP
+-P OUTPUT ACCEPT
+-N DOCKER
+-N DOCKER-USER
+-N ufw-user-input
+-A INPUT -j ufw-user-input
+-A FORWARD -j DOCKER-USER
+-A FORWARD -o docker0 -j DOCKER
+-A DOCKER -d 172.17.0.2/32 ! -i docker0 -o docker0 -p tcp -m tcp --dport 5432 -j ACCEPT
+-A DOCKER-USER -j RETURN
+-A ufw-user-input -p tcp -m tcp --dport 22 -m comment --comment \"'dapp_OpenSSH'\" -j ACCEPT
+";
+
+    const NFT_RULESET: &str = "\
+table inet filter {
+\tset blocked {
+\t\ttype ipv4_addr
+\t\telements = { 10.0.0.1, 10.0.0.2 }
+\t}
+\tchain input {
+\t\ttype filter hook input priority filter; policy drop;
+\t\tct state established,related accept
+\t\tip saddr @blocked drop comment \"accept nothing here\"
+\t\ttcp dport { 22, 443 } accept
+\t\tjump extra
+\t}
+\tchain extra {
+\t}
+}
+table ip nat {
+\tchain DOCKER {
+\t\tiifname \"docker0\" counter packets 0 bytes 0 return
+\t}
+}
+";
+
+    #[test]
+    fn test_parse_iptables_rules() {
+        let chains = parse_iptables_rules("filter", IPTABLES_FILTER);
+        let names: Vec<&str> = chains.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "INPUT",
+                "FORWARD",
+                "OUTPUT",
+                "DOCKER",
+                "DOCKER-USER",
+                "ufw-user-input"
+            ]
+        );
+        assert_eq!(chains[1].policy.as_deref(), Some("DROP"));
+        assert_eq!(chains[3].policy, None);
+        assert!(chains[3].docker && chains[4].docker && !chains[1].docker);
+
+        let forward: Vec<_> = chains[1]
+            .rules
+            .iter()
+            .map(|r| r.target.as_deref())
+            .collect();
+        assert_eq!(forward, vec![Some("DOCKER-USER"), Some("DOCKER")]);
+        assert_eq!(
+            chains[3].rules[0].rule,
+            "-d 172.17.0.2/32 ! -i docker0 -o docker0 -p tcp -m tcp --dport 5432 -j ACCEPT"
+        );
+        assert_eq!(chains[5].rules[0].target.as_deref(), Some("ACCEPT"));
+        assert!(parse_iptables_rules("nat", "").is_empty());
+    }
+
+    #[test]
+    fn test_parse_nft_ruleset() {
+        let chains = parse_nft_ruleset(NFT_RULESET);
+        assert_eq!(chains.len(), 3);
+
+        let input = &chains[0];
+        assert_eq!(input.table, "inet filter");
+        assert_eq!(input.name, "input");
+        assert_eq!(input.policy.as_deref(), Some("drop"));
+        let targets: Vec<_> = input.rules.iter().map(|r| r.target.as_deref()).collect();
+        assert_eq!(
+            targets,
+            vec![Some("accept"), Some("drop"), Some("accept"), Some("extra")]
+        );
+
+        assert_eq!(chains[1].name, "extra");
+        assert!(chains[1].rules.is_empty() && chains[1].policy.is_none());
+        assert_eq!(chains[2].table, "ip nat");
+        assert!(chains[2].docker);
+        assert_eq!(chains[2].rules[0].target.as_deref(), Some("return"));
+    }
+
+    #[test]
+    fn test_detect_backend() {
+        let compat = "table ip filter {\n}\ntable ip6 nat {\n}\n";
+        assert_eq!(
+            detect_backend(Some("iptables v1.8.7 (nf_tables)"), Some(compat)),
+            Some(FirewallBackend::Iptables)
+        );
+        assert_eq!(
+            detect_backend(Some("iptables v1.8.9 (nf_tables)"), Some(NFT_RULESET)),
+            Some(FirewallBackend::Nftables)
+        );
+        // Legacy rules aren't visible to nft at all
+        assert_eq!(
+            detect_backend(Some("iptables v1.8.7 (legacy)"), Some(NFT_RULESET)),
+            Some(FirewallBackend::Iptables)
+        );
+        assert_eq!(
+            detect_backend(None, Some("")),
+            Some(FirewallBackend::Nftables)
+        );
+        assert_eq!(detect_backend(None, None), None);
+    }
+}
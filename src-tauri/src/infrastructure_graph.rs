@@ -1,20 +1,110 @@
+use crate::commands::{apply_container_stats, parse_docker_ports, DOCKER_STATS_COMMAND};
+use crate::nginx_config::parse_proxy_target;
 use crate::types::*;
 use serde_json::json;
 use std::collections::HashMap;
 use tauri::State;
+use tokio::sync::Mutex;
 
-pub struct InfraGraphState;
+pub struct InfraGraphState {
+    /// Limits used to flag busy containers in the graph
+    pub thresholds: Mutex<GraphThresholds>,
+}
+
+/// Image names (without registry, namespace or tag) that are treated as databases,
+/// paired with the engine reported in node metadata
+const DATABASE_IMAGES: &[(&str, &str)] = &[
+    ("postgres", "postgres"),
+    ("postgresql", "postgres"),
+    ("postgis", "postgres"),
+    ("timescaledb", "postgres"),
+    ("mysql", "mysql"),
+    ("mysql-server", "mysql"),
+    ("percona", "mysql"),
+    ("mariadb", "mariadb"),
+    ("mongo", "mongodb"),
+    ("mongodb", "mongodb"),
+    ("redis", "redis"),
+    ("redis-stack", "redis"),
+    ("redis-stack-server", "redis"),
+    ("valkey", "redis"),
+    ("keydb", "redis"),
+    ("memcached", "memcached"),
+    ("elasticsearch", "elasticsearch"),
+    ("opensearch", "opensearch"),
+    ("cassandra", "cassandra"),
+    ("clickhouse-server", "clickhouse"),
+    ("couchdb", "couchdb"),
+    ("influxdb", "influxdb"),
+    ("neo4j", "neo4j"),
+    ("cockroach", "cockroachdb"),
+];
+
+/// Env, network membership, DNS names, bind mounts and health of a container, from `docker inspect`
+#[derive(Debug, Default)]
+struct ContainerRuntimeInfo {
+    env: Vec<String>,
+    networks: Vec<String>,
+    hostnames: Vec<String>,
+    /// Host paths bind-mounted into the container, for matching unix socket upstreams
+    mounts: Vec<String>,
+    health: Option<String>,
+    oom_killed: bool,
+}
 
 impl Default for InfraGraphState {
     fn default() -> Self {
-        InfraGraphState
+        InfraGraphState { thresholds: Mutex::new(GraphThresholds::default()) }
     }
 }
 
 #[tauri::command]
-pub async fn get_infrastructure_graph(state: State<'_, crate::commands::AppState>) -> Result<InfrastructureGraph, String> {
-    let ssh_client = state.ssh_client.lock().await;
-    let client = ssh_client.as_ref().ok_or("Not connected to server")?;
+pub async fn get_graph_thresholds(graph_state: State<'_, InfraGraphState>) -> Result<GraphThresholds, String> {
+    Ok(graph_state.thresholds.lock().await.clone())
+}
+
+#[tauri::command]
+pub async fn set_graph_thresholds(thresholds: GraphThresholds, graph_state: State<'_, InfraGraphState>) -> Result<(), String> {
+    for (name, value) in [("CPU", thresholds.cpu_percent), ("memory", thresholds.memory_percent)] {
+        if !(value > 0.0 && value <= 100.0) {
+            return Err(format!("The {} threshold must be between 0 and 100%", name));
+        }
+    }
+    *graph_state.thresholds.lock().await = thresholds;
+    Ok(())
+}
+
+/// Status of a container node, with the metric that made it anything but Running/Stopped
+fn container_status(container: &DockerContainer, info: &ContainerRuntimeInfo, thresholds: &GraphThresholds) -> (NodeStatus, Option<serde_json::Value>) {
+    if container.state == "restarting" {
+        return (NodeStatus::Critical, Some(json!({ "metric": "state", "value": "restarting" })));
+    }
+    if info.oom_killed {
+        return (NodeStatus::Critical, Some(json!({ "metric": "oom_killed", "value": true })));
+    }
+    if container.state != "running" {
+        return (NodeStatus::Stopped, None);
+    }
+    if info.health.as_deref() == Some("unhealthy") {
+        return (NodeStatus::Warning, Some(json!({ "metric": "health", "value": "unhealthy" })));
+    }
+    if container.cpu_percent > thresholds.cpu_percent {
+        return (NodeStatus::Warning, Some(json!({ "metric": "cpu", "value": container.cpu_percent, "threshold": thresholds.cpu_percent })));
+    }
+    if container.memory_limit > 0 {
+        let memory_percent = container.memory_usage as f64 * 100.0 / container.memory_limit as f64;
+        if memory_percent > thresholds.memory_percent {
+            return (NodeStatus::Warning, Some(json!({ "metric": "memory", "value": memory_percent, "threshold": thresholds.memory_percent })));
+        }
+    }
+    (NodeStatus::Running, None)
+}
+
+#[tauri::command]
+pub async fn get_infrastructure_graph(server_id: Option<String>, state: State<'_, crate::commands::AppState>, graph_state: State<'_, InfraGraphState>) -> Result<InfrastructureGraph, String> {
+    let ssh_client = state.client(server_id.as_deref()).await?;
+    let client = &ssh_client;
+    let thresholds = graph_state.thresholds.lock().await.clone();
 
     let mut nodes = Vec::new();
     let mut edges = Vec::new();
@@ -90,7 +180,7 @@ pub async fn get_infrastructure_graph(state: State<'_, crate::commands::AppState
 
     // ============== LAYER 3: VHOSTS & DIRECT PORTS ==============
     let vhosts = get_vhosts_for_graph(client)?;
-    let mut vhost_to_backend: HashMap<String, String> = HashMap::new();
+    let mut vhost_to_backend: HashMap<String, Vec<ProxyTarget>> = HashMap::new();
 
     for vhost in &vhosts {
         let vhost_id = format!("vhost:{}", vhost.name);
@@ -117,36 +207,64 @@ pub async fn get_infrastructure_graph(state: State<'_, crate::commands::AppState
         });
 
         // Parse proxy_pass
-        if let Ok(backend) = extract_proxy_target(client, &vhost.name).await {
-            vhost_to_backend.insert(vhost_id.clone(), backend.clone());
+        if let Ok(targets) = extract_proxy_targets(client, &vhost.name).await {
+            vhost_to_backend.insert(vhost_id.clone(), targets);
         }
     }
 
     // ============== LAYER 4: DOCKER CONTAINERS ==============
     let containers = get_containers_for_graph(client)?;
+    let runtime_info = get_container_runtime_info(client, &containers);
+    let empty_info = ContainerRuntimeInfo::default();
     
     for container in &containers {
         let container_id = format!("container:{}", container.name);
+        let database_engine = database_engine(&container.image);
+        let (status, status_reason) = container_status(container, runtime_info.get(&container.name).unwrap_or(&empty_info), &thresholds);
         nodes.push(InfraGraphNode {
             id: container_id.clone(),
             label: container.name.clone(),
-            node_type: InfraGraphNodeType::Container,
-            status: if container.state == "running" { NodeStatus::Running } else { NodeStatus::Stopped },
+            node_type: if database_engine.is_some() { InfraGraphNodeType::Database } else { InfraGraphNodeType::Container },
+            status,
             metadata: json!({
                 "id": container.id,
                 "image": container.image,
-                "state": container.state
+                "state": container.state,
+                "cpu": container.cpu_percent,
+                "memory": container.memory_usage,
+                "database": database_engine.is_some(),
+                "engine": database_engine,
+                "status_reason": status_reason
             }),
         });
 
         // Edge: Vhost -> Container (proxy_pass)
-        for (vhost_id, backend) in &vhost_to_backend {
-            if backend.contains(&container.name) || backend.contains(&container.id[..12.min(container.id.len())]) {
+        let info = runtime_info.get(&container.name).unwrap_or(&empty_info);
+        for (vhost_id, targets) in &vhost_to_backend {
+            for target in targets.iter().filter(|t| proxies_to_container(t, container, info)) {
                 edges.push(InfraGraphEdge {
                     source: vhost_id.clone(),
                     target: container_id.clone(),
                     edge_type: "proxies_to".to_string(),
-                    label: Some(backend.clone()),
+                    label: Some(target.raw.clone()),
+                    metadata: Some(json!(target)),
+                });
+            }
+        }
+    }
+
+    // Edge: App container -> Database (shared network + env referencing the db host)
+    for database in containers.iter().filter(|c| database_engine(&c.image).is_some()) {
+        let db_info = runtime_info.get(&database.name).unwrap_or(&empty_info);
+        for app in containers.iter().filter(|c| database_engine(&c.image).is_none()) {
+            let app_info = runtime_info.get(&app.name).unwrap_or(&empty_info);
+            let shares_network = app_info.networks.iter().any(|n| db_info.networks.contains(n));
+            if shares_network && env_references_host(&app_info.env, &db_info.hostnames) {
+                edges.push(InfraGraphEdge {
+                    source: format!("container:{}", app.name),
+                    target: format!("container:{}", database.name),
+                    edge_type: "uses_database".to_string(),
+                    label: database_engine(&database.image).map(String::from),
                     metadata: None,
                 });
             }
@@ -327,7 +445,7 @@ fn get_vhosts_for_graph(client: &std::sync::Arc<crate::ssh::SshClient>) -> Resul
 
 fn get_containers_for_graph(client: &std::sync::Arc<crate::ssh::SshClient>) -> Result<Vec<DockerContainer>, String> {
     let ps_output = client
-        .execute_command("docker ps --format '{{.ID}}|{{.Names}}|{{.Image}}|{{.State}}' --no-trunc")
+        .execute_command("docker ps --format '{{.ID}}|{{.Names}}|{{.Image}}|{{.State}}|{{.Ports}}' --no-trunc")
         .map_err(|e| e.message)?;
 
     let mut containers = Vec::new();
@@ -343,14 +461,115 @@ fn get_containers_for_graph(client: &std::sync::Arc<crate::ssh::SshClient>) -> R
                 cpu_percent: 0.0,
                 memory_usage: 0,
                 memory_limit: 0,
-                ports: Vec::new(),
+                ports: parts.get(4).map(|p| parse_docker_ports(p)).unwrap_or_default(),
+                health: None,
+                oom_killed: false,
+                cpu_history: Vec::new(),
+                mem_history: Vec::new(),
             });
         }
     }
 
+    // Usage only matters for the status colour, so a failing `docker stats` isn't fatal
+    let stats_output = client.execute_command(DOCKER_STATS_COMMAND).unwrap_or_default();
+    apply_container_stats(&mut containers, &stats_output);
     Ok(containers)
 }
 
+/// Database engine for a container image, matched against `DATABASE_IMAGES`
+fn database_engine(image: &str) -> Option<&'static str> {
+    // "registry:5000/bitnami/postgresql:15@sha256:..." -> "postgresql"
+    let without_digest = image.split('@').next().unwrap_or(image);
+    let name = without_digest.rsplit('/').next().unwrap_or(without_digest);
+    let name = name.split(':').next().unwrap_or(name).to_lowercase();
+
+    DATABASE_IMAGES
+        .iter()
+        .find(|(image_name, _)| *image_name == name)
+        .map(|(_, engine)| *engine)
+}
+
+/// Whether any env value mentions one of `hosts` as a whole hostname token,
+/// e.g. `DATABASE_URL=postgres://app:secret@db:5432/app` references `db`
+fn env_references_host(env: &[String], hosts: &[String]) -> bool {
+    env.iter()
+        .filter_map(|var| var.split_once('=').map(|(_, value)| value))
+        .flat_map(|value| value.split(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_')))
+        .any(|token| !token.is_empty() && hosts.iter().any(|host| host.eq_ignore_ascii_case(token)))
+}
+
+fn get_container_runtime_info(client: &std::sync::Arc<crate::ssh::SshClient>, containers: &[DockerContainer]) -> HashMap<String, ContainerRuntimeInfo> {
+    let mut info = HashMap::new();
+    if containers.is_empty() {
+        return info;
+    }
+
+    let names: Vec<&str> = containers.iter().map(|c| c.name.as_str()).collect();
+    let output = client
+        .execute_command(&format!("docker inspect {}", names.join(" ")))
+        .unwrap_or_default();
+    let inspected: Vec<serde_json::Value> = serde_json::from_str(&output).unwrap_or_default();
+
+    for container in &inspected {
+        let name = container
+            .get("Name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .trim_start_matches('/')
+            .to_string();
+        let config = container.get("Config");
+
+        let env = config
+            .and_then(|c| c.get("Env"))
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(|v| v.as_str()).map(String::from).collect())
+            .unwrap_or_default();
+
+        let mut hostnames = vec![name.clone()];
+        if let Some(service) = config
+            .and_then(|c| c.get("Labels"))
+            .and_then(|l| l.get("com.docker.compose.service"))
+            .and_then(|v| v.as_str())
+        {
+            hostnames.push(service.to_string());
+        }
+
+        let mut networks = Vec::new();
+        if let Some(attached) = container
+            .get("NetworkSettings")
+            .and_then(|n| n.get("Networks"))
+            .and_then(|v| v.as_object())
+        {
+            for (network, settings) in attached {
+                networks.push(network.clone());
+                if let Some(aliases) = settings.get("Aliases").and_then(|v| v.as_array()) {
+                    hostnames.extend(aliases.iter().filter_map(|v| v.as_str()).map(String::from));
+                }
+            }
+        }
+        hostnames.sort();
+        hostnames.dedup();
+
+        let mounts = container
+            .get("Mounts")
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter()
+                .filter(|m| m.get("Type").and_then(|t| t.as_str()) == Some("bind"))
+                .filter_map(|m| m.get("Source").and_then(|s| s.as_str()))
+                .map(String::from)
+                .collect())
+            .unwrap_or_default();
+
+        let state = container.get("State");
+        let health = state.and_then(|s| s.get("Health")).and_then(|h| h.get("Status")).and_then(|v| v.as_str()).map(String::from);
+        let oom_killed = state.and_then(|s| s.get("OOMKilled")).and_then(|v| v.as_bool()).unwrap_or(false);
+
+        info.insert(name, ContainerRuntimeInfo { env, networks, hostnames, mounts, health, oom_killed });
+    }
+
+    info
+}
+
 fn get_docker_networks_for_graph(client: &std::sync::Arc<crate::ssh::SshClient>) -> Result<Vec<DockerNetwork>, String> {
     let output = client
         .execute_command("docker network ls --format '{{.ID}}|{{.Name}}|{{.Driver}}|{{.Scope}}'")
@@ -402,22 +621,52 @@ fn get_docker_networks_for_graph(client: &std::sync::Arc<crate::ssh::SshClient>)
     Ok(networks)
 }
 
-async fn extract_proxy_target(client: &std::sync::Arc<crate::ssh::SshClient>, vhost_name: &str) -> Result<String, String> {
+/// Every `proxy_pass` target in a vhost; empty for static sites
+async fn extract_proxy_targets(client: &std::sync::Arc<crate::ssh::SshClient>, vhost_name: &str) -> Result<Vec<ProxyTarget>, String> {
     let content = client
         .execute_command(&format!("cat /etc/nginx/sites-available/{}", vhost_name))
         .map_err(|e| e.message)?;
 
+    let mut targets: Vec<ProxyTarget> = Vec::new();
     for line in content.lines() {
         let line = line.trim();
-        if line.starts_with("proxy_pass") {
-            if let Some(start) = line.find(' ') {
-                let url = line[start..].trim().trim_end_matches(';');
-                return Ok(url.to_string());
+        if let Some(value) = line.strip_prefix("proxy_pass") {
+            if let Some(target) = parse_proxy_target(value.trim()) {
+                if !targets.contains(&target) {
+                    targets.push(target);
+                }
             }
         }
     }
+    Ok(targets)
+}
 
-    Ok("static".to_string())
+/// Addresses that reach a container through a port it publishes on the host
+const LOOPBACK_HOSTS: &[&str] = &["127.0.0.1", "localhost", "::1", "0.0.0.0", "::"];
+
+/// Whether a proxy target reaches `container`: a loopback address on a port the container
+/// publishes, its name, id or a network alias, or a unix socket inside one of its bind mounts.
+/// Variable targets can't be resolved and never match.
+fn proxies_to_container(target: &ProxyTarget, container: &DockerContainer, info: &ContainerRuntimeInfo) -> bool {
+    if target.uses_variables {
+        return false;
+    }
+    if let Some(socket) = &target.unix_socket {
+        return info.mounts.iter().any(|source| {
+            let source = source.trim_end_matches('/');
+            socket == source || socket.starts_with(&format!("{}/", source))
+        });
+    }
+    let Some(host) = target.host.as_deref() else { return false };
+    if LOOPBACK_HOSTS.contains(&host) {
+        let Some(port) = target.port.map(|p| p.to_string()) else { return false };
+        return container.ports.iter().any(|m| {
+            m.host_port == port && matches!(m.host_ip.as_str(), "0.0.0.0" | "::" | "127.0.0.1" | "::1")
+        });
+    }
+    container.name.eq_ignore_ascii_case(host)
+        || info.hostnames.iter().any(|h| h.eq_ignore_ascii_case(host))
+        || (host.len() >= 12 && container.id.starts_with(host))
 }
 
 fn extract_server_name(content: &str) -> Option<String> {
@@ -458,3 +707,80 @@ fn extract_root_path(content: &str) -> Option<String> {
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_database_engine_matches_known_images() {
+        assert_eq!(database_engine("postgres:16-alpine"), Some("postgres"));
+        assert_eq!(database_engine("docker.io/bitnami/postgresql:15"), Some("postgres"));
+        assert_eq!(database_engine("registry:5000/library/redis@sha256:abc"), Some("redis"));
+        assert_eq!(database_engine("MariaDB:11"), Some("mariadb"));
+        assert_eq!(database_engine("mongo-express:latest"), None);
+        assert_eq!(database_engine("nginx:alpine"), None);
+    }
+
+    #[test]
+    fn test_env_references_host() {
+        let env = vec![
+            "DATABASE_URL=postgres://app:secret@db:5432/app".to_string(),
+            "REDIS_HOST=cache".to_string(),
+        ];
+        assert!(env_references_host(&env, &["db".to_string()]));
+        assert!(env_references_host(&env, &["cache".to_string()]));
+        assert!(!env_references_host(&env, &["app-db".to_string()]));
+        // Substrings of longer hostnames don't count
+        assert!(!env_references_host(&["HOST=mydb.internal".to_string()], &["db".to_string()]));
+    }
+
+    fn container(state: &str, cpu_percent: f64, memory_usage: u64, memory_limit: u64) -> DockerContainer {
+        DockerContainer {
+            id: "abc".to_string(), name: "web".to_string(), image: "nginx".to_string(), status: String::new(), state: state.to_string(),
+            cpu_percent, memory_usage, memory_limit, ports: Vec::new(), health: None,
+            oom_killed: false, cpu_history: Vec::new(), mem_history: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_container_status_thresholds() {
+        let thresholds = GraphThresholds::default();
+        let idle = ContainerRuntimeInfo::default();
+        let metric = |(_, reason): (NodeStatus, Option<serde_json::Value>)| reason.map(|r| r["metric"].as_str().unwrap_or_default().to_string());
+
+        assert!(matches!(container_status(&container("running", 5.0, 10, 100), &idle, &thresholds), (NodeStatus::Running, None)));
+        assert!(matches!(container_status(&container("exited", 0.0, 0, 0), &idle, &thresholds), (NodeStatus::Stopped, None)));
+        assert_eq!(metric(container_status(&container("running", 95.0, 10, 100), &idle, &thresholds)), Some("cpu".to_string()));
+        assert_eq!(metric(container_status(&container("running", 5.0, 95, 100), &idle, &thresholds)), Some("memory".to_string()));
+
+        let unhealthy = ContainerRuntimeInfo { health: Some("unhealthy".to_string()), ..Default::default() };
+        assert!(matches!(container_status(&container("running", 5.0, 10, 100), &unhealthy, &thresholds), (NodeStatus::Warning, Some(_))));
+
+        let oom = ContainerRuntimeInfo { oom_killed: true, ..Default::default() };
+        assert!(matches!(container_status(&container("exited", 0.0, 0, 0), &oom, &thresholds), (NodeStatus::Critical, Some(_))));
+        assert!(matches!(container_status(&container("restarting", 0.0, 0, 0), &idle, &thresholds), (NodeStatus::Critical, Some(_))));
+    }
+
+    #[test]
+    fn test_proxies_to_container() {
+        let mut web = container("running", 0.0, 0, 0);
+        web.ports = parse_docker_ports("0.0.0.0:3000->3000/tcp, [::]:3000->3000/tcp");
+        let info = ContainerRuntimeInfo {
+            hostnames: vec!["web".to_string(), "frontend".to_string()],
+            mounts: vec!["/run/web/".to_string()],
+            ..Default::default()
+        };
+        let matches = |target: &str| proxies_to_container(&parse_proxy_target(target).unwrap(), &web, &info);
+
+        assert!(matches("http://127.0.0.1:3000"));
+        assert!(matches("http://localhost:3000/api"));
+        assert!(!matches("http://127.0.0.1:3001"));
+        assert!(matches("https://frontend"));
+        // Names are matched whole, not as substrings
+        assert!(!matches("http://web-admin:8080"));
+        assert!(matches("http://unix:/run/web/app.sock:/"));
+        assert!(!matches("http://unix:/run/other/app.sock"));
+        assert!(!matches("http://$upstream"));
+    }
+}
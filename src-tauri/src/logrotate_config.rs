@@ -0,0 +1,248 @@
+//! Reading logrotate configuration files.
+//!
+//! A file holds one or more blocks: a list of log paths (possibly spanning lines, quoted
+//! when they contain spaces) followed by directives in braces. Script sections such as
+//! `postrotate ... endscript` hold arbitrary shell and are skipped. Directives outside
+//! any block are global defaults and aren't reported.
+
+use crate::types::LogrotateConfig;
+
+const FREQUENCIES: &[&str] = &["hourly", "daily", "weekly", "monthly", "yearly"];
+const SCRIPT_DIRECTIVES: &[&str] = &[
+    "prerotate",
+    "postrotate",
+    "firstaction",
+    "lastaction",
+    "preremove",
+];
+
+/// Split on whitespace, keeping double- or single-quoted runs together without quotes
+fn tokenize(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c.is_whitespace() => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            None => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Every block of `content`, tagged with the file it came from
+pub fn parse_logrotate_config(file: &str, content: &str) -> Vec<LogrotateConfig> {
+    let mut configs = Vec::new();
+    let mut pending_paths: Vec<String> = Vec::new();
+    let mut block: Option<LogrotateConfig> = None;
+    let mut in_script = false;
+
+    for raw in content.lines() {
+        let line = raw.trim();
+        if in_script {
+            in_script = line != "endscript";
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some(current) = block.as_mut() else {
+            // Paths until the opening brace, which may sit on its own line
+            let (paths, opens) = match line.split_once('{') {
+                Some((paths, _)) => (paths, true),
+                None => (line, false),
+            };
+            let tokens = tokenize(paths);
+            // A global directive (`weekly`, `rotate 4`) rather than a path list
+            if !opens && tokens.first().is_some_and(|t| !t.starts_with('/')) {
+                continue;
+            }
+            pending_paths.extend(tokens);
+            if opens {
+                block = Some(LogrotateConfig {
+                    file: file.to_string(),
+                    paths: std::mem::take(&mut pending_paths),
+                    rotate_count: None,
+                    frequency: None,
+                    compress: false,
+                    size: None,
+                });
+            }
+            continue;
+        };
+
+        if line.starts_with('}') {
+            configs.extend(block.take());
+            continue;
+        }
+        let tokens = tokenize(line);
+        let Some(directive) = tokens.first().map(String::as_str) else {
+            continue;
+        };
+        // `size=100M` is accepted as well as `size 100M`
+        let (directive, inline_value) = match directive.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (directive, None),
+        };
+        let value = inline_value
+            .or_else(|| tokens.get(1).map(String::as_str))
+            .map(String::from);
+        match directive {
+            d if FREQUENCIES.contains(&d) => current.frequency = Some(d.to_string()),
+            d if SCRIPT_DIRECTIVES.contains(&d) => in_script = true,
+            "rotate" => current.rotate_count = value.and_then(|v| v.parse().ok()),
+            "compress" => current.compress = true,
+            "nocompress" => current.compress = false,
+            "size" => current.size = value,
+            _ => {}
+        }
+    }
+    configs
+}
+
+/// The global directives of a main config such as /etc/logrotate.conf (`weekly`,
+/// `rotate 4`, `create`, `su root adm`...) without its `include` lines or log blocks, to
+/// put in front of a single drop-in so it runs with the defaults the daily run gives it
+pub fn global_directives(content: &str) -> String {
+    let mut lines = Vec::new();
+    let mut in_block = false;
+    let mut in_script = false;
+    for raw in content.lines() {
+        let line = raw.trim();
+        if in_script {
+            in_script = line != "endscript";
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if in_block {
+            let directive = tokenize(line).into_iter().next().unwrap_or_default();
+            in_script = SCRIPT_DIRECTIVES.contains(&directive.as_str());
+            in_block = !line.starts_with('}');
+            continue;
+        }
+        if line.contains('{') {
+            in_block = true;
+            continue;
+        }
+        // Path lines of a block whose brace is on a later line
+        let Some(directive) = tokenize(line).into_iter().next() else {
+            continue;
+        };
+        if directive.starts_with('/') || directive == "include" {
+            continue;
+        }
+        lines.push(format!("{}\n", line));
+    }
+    lines.concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_blocks_and_scripts() {
+        let content = "\
+# global defaults don't count
+weekly
+
+/var/log/nginx/*.log {
+    daily
+    missingok
+    rotate 14
+    compress
+    delaycompress
+    postrotate
+        if [ -f /var/run/nginx.pid ]; then
+            kill -USR1 `cat /var/run/nginx.pid`
+        fi
+        # size 1G inside a script isn't a directive
+        size 1G
+    endscript
+}
+
+\"/srv/my app/logs/app.log\"
+/srv/worker.log
+{
+    size=100M
+    rotate 3
+    nocompress
+}
+";
+        let configs = parse_logrotate_config("/etc/logrotate.d/web", content);
+        assert_eq!(configs.len(), 2);
+
+        let nginx = &configs[0];
+        assert_eq!(nginx.file, "/etc/logrotate.d/web");
+        assert_eq!(nginx.paths, vec!["/var/log/nginx/*.log"]);
+        assert_eq!(nginx.frequency.as_deref(), Some("daily"));
+        assert_eq!(nginx.rotate_count, Some(14));
+        assert!(nginx.compress);
+        assert_eq!(nginx.size, None);
+
+        let app = &configs[1];
+        assert_eq!(
+            app.paths,
+            vec!["/srv/my app/logs/app.log", "/srv/worker.log"]
+        );
+        assert_eq!(app.size.as_deref(), Some("100M"));
+        assert_eq!(app.rotate_count, Some(3));
+        assert_eq!(app.frequency, None);
+        assert!(!app.compress);
+    }
+
+    #[test]
+    fn test_parse_one_line_block_header() {
+        let content = "/var/log/a.log /var/log/b.log {\n  monthly\n  size 10k\n}\n";
+        let configs = parse_logrotate_config("/etc/logrotate.d/ab", content);
+        assert_eq!(configs[0].paths.len(), 2);
+        assert_eq!(configs[0].frequency.as_deref(), Some("monthly"));
+        assert_eq!(configs[0].size.as_deref(), Some("10k"));
+        assert!(parse_logrotate_config("x", "").is_empty());
+    }
+
+    #[test]
+    fn test_global_directives() {
+        let content = "\
+# see \"man logrotate\" for details
+weekly
+su root adm
+rotate 4
+create
+
+include /etc/logrotate.d
+
+/var/log/wtmp {
+    missingok
+    monthly
+    postrotate
+        echo }
+    endscript
+    rotate 1
+}
+\"/var/log/btmp\"
+{
+    rotate 1
+}
+dateext
+";
+        assert_eq!(
+            global_directives(content),
+            "weekly\nsu root adm\nrotate 4\ncreate\ndateext\n"
+        );
+        assert_eq!(global_directives(""), "");
+    }
+}
@@ -2,13 +2,26 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod commands;
+mod compose_config;
 mod compose_discovery;
+mod config_graph;
+mod firewall_dump;
 mod infrastructure_graph;
+mod logrotate_config;
+mod nginx_config;
+mod prometheus;
+mod redact;
+mod response_cache;
 mod ssh;
+mod sshd_config;
+mod stream_registry;
 mod types;
+mod ufw_parse;
+mod units;
 mod user_commands;
 
 use commands::*;
+use config_graph::*;
 use infrastructure_graph::*;
 use user_commands::*;
 
@@ -18,66 +31,191 @@ fn main() {
         .plugin(tauri_plugin_store::Builder::default().build())
         .manage(commands::AppState::default())
         .manage(infrastructure_graph::InfraGraphState::default())
+        .manage(config_graph::ConfigGraphState::default())
+        .setup(|app| {
+            tauri::async_runtime::spawn(commands::restore_redaction_patterns(app.handle().clone()));
+            tauri::async_runtime::spawn(commands::autoconnect_on_startup(app.handle().clone()));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             test_connection,
+            diagnose_connection,
             connect_to_server,
             disconnect_server,
+            list_active_connections,
+            set_permission_mode,
+            get_connection_health,
+            get_host_key_fingerprints,
+            check_sudo_access,
             get_system_metrics,
+            export_metrics_prometheus,
+            get_metrics_config,
+            set_metrics_config,
+            dashboard_snapshot,
+            get_gpu_metrics,
+            get_system_health,
+            get_directory_usage,
+            find_large_files,
+            truncate_file,
+            get_file_permissions,
+            set_file_permissions,
+            checksum_file,
+            verify_checksums,
+            get_advanced_storage,
             get_docker_containers,
+            get_docker_context,
             docker_container_action,
             get_container_logs,
+            export_container_logs,
+            search_container_logs,
             get_services,
             service_action,
+            get_unit_dependencies,
+            reload_services,
             get_service_logs,
+            remote_stat,
+            get_journal_disk_usage,
+            vacuum_journal,
+            start_journal_stream,
+            stop_journal_stream,
+            list_running_streams,
+            stop_all_streams,
             execute_command,
+            execute_command_multi,
             save_server_profile,
             get_server_profiles,
             delete_server_profile,
             update_server_profile_metadata,
+            get_startup_connect_results,
+            get_skip_autoconnect,
+            set_skip_autoconnect,
             get_ufw_status,
             get_ufw_stats,
             get_ufw_overview,
+            get_docker_ufw_exposure,
+            suggest_ufw_rules_for_containers,
+            apply_suggested_rule,
             get_listening_ports,
+            get_firewall_raw,
+            get_network_connections,
+            block_remote_ip,
+            invalidate_cache,
+            set_cache_ttl,
+            remote_ping,
+            remote_dns_lookup,
             ufw_action,
             ufw_add_rule,
+            preview_ufw_rule,
             ufw_delete_rule,
             ufw_set_default,
             ufw_set_logging,
             get_container_details,
             get_docker_volumes,
             get_docker_networks,
+            get_docker_secrets,
+            get_docker_configs,
+            create_docker_secret,
+            remove_docker_secret,
+            create_docker_config,
+            remove_docker_config,
             get_docker_images,
             get_container_env,
+            get_redaction_patterns,
+            set_redaction_patterns,
+            get_container_provenance,
+            get_process_environ,
+            get_process_limits,
+            get_process_open_files,
+            get_problem_processes,
+            get_dmesg,
+            get_time_config,
+            set_timezone,
+            set_ntp,
+            get_system_environment,
+            set_system_environment_var,
+            unset_system_environment_var,
+            get_reboot_status,
+            get_security_module_status,
+            set_enforcement_mode,
+            set_apparmor_profile_mode,
+            get_sysctl,
+            set_sysctl,
+            get_fstab,
+            add_fstab_entry,
+            remove_fstab_entry,
+            save_fstab,
+            get_swap_devices,
+            create_swapfile,
+            remove_swapfile,
+            get_container_diff,
+            set_container_restart_policy,
+            rename_container,
+            recreate_container,
             find_compose_files,
             refresh_compose_files,
+            get_compose_cache_info,
+            clear_compose_cache,
+            save_compose_file,
+            update_compose_service_env,
             get_container_logs_stream,
+            get_container_exec_sessions,
+            kill_container_exec_session,
+            start_docker_events_stream,
+            stop_docker_events_stream,
             // Nginx
             nginx_status,
             nginx_action,
             nginx_test_config,
             get_nginx_config,
+            parse_nginx_config_structure,
             save_nginx_config,
+            snapshot_nginx_config,
+            list_nginx_snapshots,
+            restore_nginx_snapshot,
+            diff_against_backup,
+            restore_backup,
+            set_nginx_gzip,
+            set_nginx_rate_limit,
+            add_nginx_location,
             get_nginx_vhosts,
             get_vhost_config,
             save_vhost_config,
             enable_vhost,
             disable_vhost,
             delete_vhost,
+            test_vhost_response,
             get_nginx_logs,
             // Cron
             get_user_crontab,
             save_user_crontab,
+            get_user_cron_jobs,
             get_system_crontab,
             get_cron_d_jobs,
+            toggle_cron_d_file,
             get_cron_folders,
             get_cron_logs,
             add_cron_job,
             delete_cron_job,
             toggle_cron_job,
+            wrap_cron_job_logging,
+            unwrap_cron_job_logging,
+            get_logrotate_configs,
+            get_logrotate_config,
+            save_logrotate_config,
+            run_logrotate_now,
+            get_sshd_config,
+            set_sshd_directive,
+            get_cron_access,
+            set_cron_access,
+            schedule_at_job,
+            list_at_jobs,
+            remove_at_job,
             // User Management
             get_system_users,
+            get_user_resource_usage,
             get_system_groups,
             create_user,
+            create_users_bulk,
             delete_user,
             lock_user,
             unlock_user,
@@ -85,11 +223,23 @@ fn main() {
             add_user_to_group,
             remove_user_from_group,
             add_ssh_key,
+            add_ssh_key_from_file,
             delete_ssh_key,
+            add_ssh_key_with_options,
+            get_ssh_keys,
             create_group,
             delete_group,
             // Infrastructure Graph
             get_infrastructure_graph,
+            get_graph_thresholds,
+            set_graph_thresholds,
+            // Config Graph
+            scan_config_files,
+            get_config_dependencies,
+            get_config_content,
+            search_config_usage,
+            watch_config_files,
+            stop_watching_config_files,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
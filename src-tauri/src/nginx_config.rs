@@ -0,0 +1,913 @@
+//! Text-level editing of nginx configuration files.
+//!
+//! These helpers work line by line and keep everything they don't touch byte-for-byte
+//! identical, so the diff shown to the user only contains the intended change.
+
+use crate::types::{DiffLine, DiffLineKind, NginxBlock, NginxDirective, ProxyTarget};
+
+/// Strip a trailing `# comment`, ignoring `#` inside quotes
+fn strip_comment(line: &str) -> &str {
+    let mut quote: Option<char> = None;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '#') => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Set `name args;` directly inside every `block { ... }` in `content`.
+///
+/// An existing `name` directive whose arguments satisfy `same` is replaced in place;
+/// otherwise the directive is inserted right after the block's opening line. Commented
+/// out directives are left alone.
+pub fn upsert_directive(
+    content: &str,
+    block: &str,
+    name: &str,
+    args: &str,
+    same: impl Fn(&str) -> bool,
+) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let directive = format!("{} {};", name, args);
+
+    let mut replacements: Vec<(usize, String)> = Vec::new();
+    let mut insertions: Vec<(usize, String)> = Vec::new();
+    // (block name, opening line index, directive found)
+    let mut stack: Vec<(String, usize, bool)> = Vec::new();
+
+    for (idx, line) in lines.iter().enumerate() {
+        let code = strip_comment(line).trim();
+        let first_word = code
+            .split(|c: char| c.is_whitespace() || c == '{' || c == ';')
+            .next()
+            .unwrap_or("");
+
+        if let Some((top, _, found)) = stack.last_mut() {
+            if top == block && first_word == name && !code.contains('{') {
+                let rest = code[name.len()..].trim().trim_end_matches(';').trim();
+                if same(rest) {
+                    let indent: String = line.chars().take_while(|c| c.is_whitespace()).collect();
+                    replacements.push((idx, format!("{}{}", indent, directive)));
+                    *found = true;
+                }
+            }
+        }
+
+        for c in code.chars() {
+            match c {
+                '{' => stack.push((first_word.to_string(), idx, false)),
+                '}' => {
+                    if let Some((opened, open_idx, found)) = stack.pop() {
+                        if opened == block && !found {
+                            let indent: String = lines[open_idx]
+                                .chars()
+                                .take_while(|c| c.is_whitespace())
+                                .collect();
+                            insertions.push((open_idx, format!("{}    {}", indent, directive)));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut output: Vec<String> = Vec::with_capacity(lines.len() + insertions.len());
+    for (idx, line) in lines.iter().enumerate() {
+        match replacements.iter().find(|(i, _)| *i == idx) {
+            Some((_, replacement)) => output.push(replacement.clone()),
+            None => output.push(line.to_string()),
+        }
+        for (_, inserted) in insertions.iter().filter(|(i, _)| *i == idx) {
+            output.push(inserted.clone());
+        }
+    }
+
+    let mut result = output.join("\n");
+    if content.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// Parse `content` into its tree of blocks, tracking brace depth.
+///
+/// Statements end at `;`, `{` or `}` rather than at line ends, so a directive spanning
+/// several lines is reported once, at the line it starts on. Quotes, `${var}` and
+/// comments are honoured. Top-level directives such as `user` are not returned.
+pub fn parse_structure(content: &str) -> Result<Vec<NginxBlock>, String> {
+    fn block(kind: String, name: String, start_line: usize) -> NginxBlock {
+        NginxBlock {
+            kind,
+            name,
+            start_line,
+            end_line: 0,
+            directives: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    // Open blocks, innermost last; the bottom entry collects the top-level blocks
+    let mut stack = vec![block(String::new(), String::new(), 0)];
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    let mut statement_line = 0;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut in_variable = false;
+    let mut line_number = 0;
+
+    for (idx, line) in content.lines().enumerate() {
+        line_number = idx + 1;
+        if quote.is_some() {
+            word.push('\n');
+        }
+
+        for c in line.chars() {
+            if let Some(q) = quote {
+                word.push(c);
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+                continue;
+            }
+            if in_variable {
+                word.push(c);
+                in_variable = c != '}';
+                continue;
+            }
+
+            match c {
+                '#' if word.is_empty() => break,
+                '{' if word.ends_with('$') => {
+                    word.push(c);
+                    in_variable = true;
+                }
+                ';' | '{' | '}' => {
+                    if !word.is_empty() {
+                        words.push(std::mem::take(&mut word));
+                    }
+                    match c {
+                        ';' if !words.is_empty() => {
+                            let name = words.remove(0);
+                            let directive = NginxDirective {
+                                name,
+                                args: words.join(" "),
+                                line: statement_line,
+                            };
+                            words.clear();
+                            if let Some(open) = stack.last_mut() {
+                                open.directives.push(directive);
+                            }
+                        }
+                        '{' => {
+                            if words.is_empty() {
+                                return Err(format!("Line {}: block without a name", line_number));
+                            }
+                            let kind = words.remove(0);
+                            stack.push(block(kind, words.join(" "), statement_line));
+                            words.clear();
+                        }
+                        '}' => {
+                            if !words.is_empty() {
+                                return Err(format!(
+                                    "Line {}: missing ';' after '{}'",
+                                    line_number, words[0]
+                                ));
+                            }
+                            let mut closed = match stack.pop() {
+                                Some(closed) if !stack.is_empty() => closed,
+                                _ => return Err(format!("Line {}: unexpected '}}'", line_number)),
+                            };
+                            closed.end_line = line_number;
+                            if let Some(parent) = stack.last_mut() {
+                                parent.children.push(closed);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                c if c.is_whitespace() => {
+                    if !word.is_empty() {
+                        words.push(std::mem::take(&mut word));
+                    }
+                }
+                _ => {
+                    if word.is_empty() && words.is_empty() {
+                        statement_line = line_number;
+                    }
+                    if c == '"' || c == '\'' {
+                        quote = Some(c);
+                    }
+                    word.push(c);
+                }
+            }
+        }
+
+        if quote.is_none() && !in_variable && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+    }
+
+    if quote.is_some() {
+        return Err(format!("Line {}: unterminated quote", statement_line));
+    }
+    if let Some(open) = stack.get(1..).and_then(|open| open.last()) {
+        return Err(format!(
+            "Line {}: '{}' block is never closed",
+            open.start_line, open.kind
+        ));
+    }
+    if let Some(first) = words.first() {
+        return Err(format!(
+            "Line {}: missing ';' after '{}'",
+            line_number.max(statement_line),
+            first
+        ));
+    }
+    Ok(stack.pop().map(|root| root.children).unwrap_or_default())
+}
+
+fn collect_servers<'a>(blocks: &'a [NginxBlock], servers: &mut Vec<&'a NginxBlock>) {
+    for block in blocks {
+        if block.kind == "server" {
+            servers.push(block);
+        } else {
+            collect_servers(&block.children, servers);
+        }
+    }
+}
+
+fn directive_args<'a>(server: &'a NginxBlock, name: &'a str) -> impl Iterator<Item = &'a str> {
+    server
+        .directives
+        .iter()
+        .filter(move |d| d.name == name)
+        .map(|d| d.args.as_str())
+}
+
+fn is_https_server(server: &NginxBlock) -> bool {
+    directive_args(server, "listen").any(|args| {
+        args.split_whitespace()
+            .any(|arg| arg == "ssl" || arg == "443" || arg.ends_with(":443"))
+    }) || directive_args(server, "ssl_certificate").next().is_some()
+}
+
+/// Servers that only `return` a redirect, like the port 80 block certbot leaves behind
+fn is_redirect_server(server: &NginxBlock) -> bool {
+    directive_args(server, "return").next().is_some() && server.children.is_empty()
+}
+
+/// Insert a `location <path> { <body> }` block into the server block of `content` it
+/// belongs in, just before that server's closing brace.
+///
+/// The server is picked from those whose `server_name` includes `server_name` (when
+/// given), skipping redirect-only servers and preferring HTTPS ones. It's an error when
+/// that still leaves several candidates, or when the server already has the location.
+pub fn insert_location(
+    content: &str,
+    path: &str,
+    body: &[String],
+    server_name: Option<&str>,
+) -> Result<String, String> {
+    let blocks = parse_structure(content)?;
+    let mut servers = Vec::new();
+    collect_servers(&blocks, &mut servers);
+
+    if let Some(wanted) = server_name {
+        servers.retain(|server| {
+            directive_args(server, "server_name")
+                .any(|args| args.split_whitespace().any(|name| name == wanted))
+        });
+    }
+    if servers.iter().any(|server| !is_redirect_server(server)) {
+        servers.retain(|server| !is_redirect_server(server));
+    }
+    if servers.iter().any(|server| is_https_server(server)) {
+        servers.retain(|server| is_https_server(server));
+    }
+    let server = match servers.as_slice() {
+        [server] => *server,
+        [] => return Err("No matching server block to add the location to".to_string()),
+        _ => {
+            return Err(format!(
+                "{} server blocks match; choose one by server_name",
+                servers.len()
+            ))
+        }
+    };
+
+    let normalized = path.split_whitespace().collect::<Vec<_>>().join(" ");
+    if server
+        .children
+        .iter()
+        .any(|child| child.kind == "location" && child.name == normalized)
+    {
+        return Err(format!(
+            "The server already has a 'location {}' block",
+            normalized
+        ));
+    }
+
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    let close_idx = server.end_line - 1;
+    if lines[close_idx].trim() != "}" {
+        return Err(format!(
+            "Line {}: the server block doesn't end on a line of its own",
+            server.end_line
+        ));
+    }
+
+    let indent_of =
+        |line: &str| -> String { line.chars().take_while(|c| c.is_whitespace()).collect() };
+    let indent = match server.directives.first() {
+        Some(directive) => indent_of(&lines[directive.line - 1]),
+        None => format!("{}    ", indent_of(&lines[server.start_line - 1])),
+    };
+
+    let mut block = Vec::new();
+    let previous = lines[close_idx - 1].trim();
+    if !previous.is_empty() && !previous.ends_with('{') {
+        block.push(String::new());
+    }
+    block.push(format!("{}location {} {{", indent, normalized));
+    block.extend(body.iter().map(|line| format!("{}    {}", indent, line)));
+    block.push(format!("{}}}", indent));
+    lines.splice(close_idx..close_idx, block);
+
+    let mut result = lines.join("\n");
+    if content.ends_with('\n') {
+        result.push('\n');
+    }
+    Ok(result)
+}
+
+/// Split a `proxy_pass` argument into its parts. Handles `host:port`, `[v6]:port`,
+/// `unix:/path.sock:/uri` and nginx's default ports; an address built from variables
+/// (`http://$backend`) is flagged instead of parsed. `None` when there's no scheme.
+pub fn parse_proxy_target(value: &str) -> Option<ProxyTarget> {
+    let raw = value.split_whitespace().next()?.trim_end_matches(';');
+    let (scheme, rest) = raw.split_once("://")?;
+    let scheme = scheme.to_ascii_lowercase();
+    let mut target = ProxyTarget {
+        raw: raw.to_string(),
+        scheme: scheme.clone(),
+        host: None,
+        port: None,
+        unix_socket: None,
+        uses_variables: false,
+    };
+
+    if let Some(socket) = rest.strip_prefix("unix:") {
+        // The socket path ends at the `:` that starts the URI, if any
+        let path = socket.split(':').next().unwrap_or_default();
+        target.uses_variables = path.contains('$');
+        target.unix_socket = Some(path.to_string()).filter(|_| !target.uses_variables);
+        return Some(target);
+    }
+
+    let mut authority = rest.split(['/', '?']).next().unwrap_or_default();
+    if let Some((address, _)) = authority.split_once('$') {
+        // `host:port$request_uri` only puts the variable in the URI; anything
+        // else means the address itself is built at request time
+        let complete_port = address
+            .rsplit_once(':')
+            .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+        if !complete_port {
+            target.uses_variables = true;
+            return Some(target);
+        }
+        authority = address;
+    }
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(v6) => {
+            let (host, after) = v6.split_once(']')?;
+            (host, after.strip_prefix(':'))
+        }
+        None => match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        },
+    };
+    if host.is_empty() {
+        return None;
+    }
+    target.host = Some(host.to_ascii_lowercase());
+    target.port = match port {
+        Some(port) => Some(port.parse().ok()?),
+        None if scheme == "https" => Some(443),
+        None => Some(80),
+    };
+    Some(target)
+}
+
+/// Where to send a test request for a site
+#[derive(Debug, Clone, PartialEq)]
+pub struct VhostProbe {
+    /// A literal name from `server_name`, `localhost` when it only has wildcards or `_`
+    pub server_name: String,
+    /// The `listen` address when it's a specific IPv4 one, otherwise loopback
+    pub address: String,
+    pub port: u16,
+    pub https: bool,
+    /// `proxy_pass` of the server's `location /`, when it proxies
+    pub upstream: Option<String>,
+}
+
+/// Pick the server block of `content` to probe the same way `insert_location` picks one
+/// to edit: redirect-only servers are skipped and HTTPS ones are preferred.
+pub fn vhost_probe(content: &str) -> Result<VhostProbe, String> {
+    let blocks = parse_structure(content)?;
+    let mut servers = Vec::new();
+    collect_servers(&blocks, &mut servers);
+    if servers.iter().any(|server| !is_redirect_server(server)) {
+        servers.retain(|server| !is_redirect_server(server));
+    }
+    let server = servers
+        .iter()
+        .find(|server| is_https_server(server))
+        .or_else(|| servers.first())
+        .ok_or("The vhost has no server block")?;
+
+    let server_name = directive_args(server, "server_name")
+        .flat_map(str::split_whitespace)
+        .map(|name| name.trim_matches('"').trim_start_matches('.'))
+        .find(|name| !name.is_empty() && *name != "_" && !name.contains(['*', '~', '$']))
+        .unwrap_or("localhost")
+        .to_string();
+
+    let listens: Vec<(String, u16, bool)> = directive_args(server, "listen")
+        .filter_map(parse_listen)
+        .collect();
+    let https = is_https_server(server);
+    let (address, port, https) = listens
+        .iter()
+        .find(|(_, _, ssl)| *ssl == https)
+        .or_else(|| listens.first())
+        .cloned()
+        .unwrap_or_else(|| ("127.0.0.1".to_string(), 80, false));
+
+    let upstream = server
+        .children
+        .iter()
+        .find(|child| child.kind == "location" && child.name == "/")
+        .and_then(|location| directive_args(location, "proxy_pass").next())
+        .map(|args| args.trim().to_string());
+
+    Ok(VhostProbe {
+        server_name,
+        address,
+        port,
+        https,
+        upstream,
+    })
+}
+
+/// The address to connect to, the port and whether it's TLS for one `listen` directive;
+/// `None` for unix sockets
+fn parse_listen(args: &str) -> Option<(String, u16, bool)> {
+    let mut words = args.split_whitespace();
+    let endpoint = words.next()?;
+    if endpoint.starts_with("unix:") {
+        return None;
+    }
+    let (host, port) = match endpoint.strip_prefix('[') {
+        Some(v6) => {
+            let (host, after) = v6.split_once(']')?;
+            (Some(host), after.strip_prefix(':'))
+        }
+        None if endpoint.chars().all(|c| c.is_ascii_digit()) => (None, Some(endpoint)),
+        None => match endpoint.rsplit_once(':') {
+            Some((host, port)) => (Some(host), Some(port)),
+            None => (Some(endpoint), None),
+        },
+    };
+    let port: u16 = match port {
+        Some(port) => port.parse().ok()?,
+        None => 80,
+    };
+    let address = host
+        .and_then(|host| host.parse::<std::net::Ipv4Addr>().ok())
+        .filter(|ip| !ip.is_unspecified())
+        .map_or_else(|| "127.0.0.1".to_string(), |ip| ip.to_string());
+    let ssl = port == 443 || words.any(|word| word == "ssl");
+    Some((address, port, ssl))
+}
+
+/// Line-based unified diff of `old` and `new` with three lines of context
+pub fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    let lines = diff_lines(old, new);
+    if lines.is_empty() {
+        return String::new();
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", path, path);
+    for line in lines {
+        let tag = match line.kind {
+            DiffLineKind::Hunk => "",
+            DiffLineKind::Context => " ",
+            DiffLineKind::Added => "+",
+            DiffLineKind::Removed => "-",
+        };
+        out.push_str(&format!("{}{}\n", tag, line.text));
+    }
+    out
+}
+
+/// Above this many cells in the LCS table the changed region is shown as removed and
+/// re-added in full, rather than allocating hundreds of megabytes for a precise diff
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// The hunks of a unified diff of `old` and `new` (three lines of context), as structured
+/// lines with 1-based line numbers on each side. Empty when nothing changed.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    const CONTEXT: usize = 3;
+
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    if a == b {
+        return Vec::new();
+    }
+
+    // Only the region between the common prefix and suffix needs the LCS
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_end, b_end) = (a.len() - suffix, b.len() - suffix);
+    let (m, n) = (a_end - prefix, b_end - prefix);
+
+    // (tag, old line number, new line number, text)
+    let mut ops: Vec<(char, usize, usize, &str)> = (0..prefix).map(|i| (' ', i, i, a[i])).collect();
+    if m.saturating_mul(n) > MAX_DIFF_CELLS {
+        ops.extend((prefix..a_end).map(|i| ('-', i, prefix, a[i])));
+        ops.extend((prefix..b_end).map(|j| ('+', a_end, j, b[j])));
+    } else {
+        // Longest common subsequence table of the middle, filled from the end
+        let mut lcs = vec![vec![0usize; n + 1]; m + 1];
+        for i in (0..m).rev() {
+            for j in (0..n).rev() {
+                lcs[i][j] = if a[prefix + i] == b[prefix + j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < m || j < n {
+            let (x, y) = (prefix + i, prefix + j);
+            if i < m && j < n && a[x] == b[y] {
+                ops.push((' ', x, y, a[x]));
+                i += 1;
+                j += 1;
+            } else if i < m && (j == n || lcs[i + 1][j] >= lcs[i][j + 1]) {
+                // Removals first, so a changed line reads as `-old` then `+new`
+                ops.push(('-', x, y, a[x]));
+                i += 1;
+            } else {
+                ops.push(('+', x, y, b[y]));
+                j += 1;
+            }
+        }
+    }
+    ops.extend((0..suffix).map(|k| (' ', a_end + k, b_end + k, a[a_end + k])));
+
+    let mut out = Vec::new();
+    let changed: Vec<usize> = (0..ops.len()).filter(|&k| ops[k].0 != ' ').collect();
+    let mut k = 0;
+    while k < changed.len() {
+        let start = changed[k].saturating_sub(CONTEXT);
+        let mut end = changed[k];
+        while k < changed.len() && changed[k] <= end + 2 * CONTEXT {
+            end = changed[k];
+            k += 1;
+        }
+        let end = (end + CONTEXT).min(ops.len() - 1);
+
+        let hunk = &ops[start..=end];
+        let old_len = hunk.iter().filter(|op| op.0 != '+').count();
+        let new_len = hunk.iter().filter(|op| op.0 != '-').count();
+        out.push(DiffLine {
+            kind: DiffLineKind::Hunk,
+            old_line: None,
+            new_line: None,
+            text: format!(
+                "@@ -{},{} +{},{} @@",
+                hunk[0].1 + 1,
+                old_len,
+                hunk[0].2 + 1,
+                new_len
+            ),
+        });
+        for &(tag, i, j, text) in hunk {
+            let (kind, old_line, new_line) = match tag {
+                '+' => (DiffLineKind::Added, None, Some(j + 1)),
+                '-' => (DiffLineKind::Removed, Some(i + 1), None),
+                _ => (DiffLineKind::Context, Some(i + 1), Some(j + 1)),
+            };
+            out.push(DiffLine {
+                kind,
+                old_line,
+                new_line,
+                text: text.to_string(),
+            });
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NGINX_CONF: &str = "user www-data;\n\
+                              http {\n\
+                              \x20   sendfile on;\n\
+                              \x20   gzip on;\n\
+                              \x20   # gzip_types text/plain text/css;\n\
+                              \x20   include /etc/nginx/sites-enabled/*;\n\
+                              }\n";
+
+    #[test]
+    fn test_upsert_directive_replaces_existing() {
+        let updated = upsert_directive(NGINX_CONF, "http", "gzip", "off", |_| true);
+        assert!(updated.contains("    gzip off;\n"));
+        assert!(!updated.contains("gzip on;"));
+        assert_eq!(updated.lines().count(), NGINX_CONF.lines().count());
+    }
+
+    #[test]
+    fn test_upsert_directive_inserts_and_ignores_comments() {
+        let updated = upsert_directive(NGINX_CONF, "http", "gzip_types", "text/css", |_| true);
+        assert!(updated.contains("http {\n    gzip_types text/css;\n"));
+        assert!(updated.contains("# gzip_types text/plain text/css;"));
+    }
+
+    #[test]
+    fn test_upsert_directive_in_every_server_block() {
+        let vhost = "server {\n    listen 80;\n    location / {\n        limit_req zone=api burst=5;\n    }\n}\n\
+                     server {\n    listen 443 ssl;\n    limit_req zone=api burst=5;\n}\n";
+        let updated = upsert_directive(
+            vhost,
+            "server",
+            "limit_req",
+            "zone=api burst=20 nodelay",
+            |args| args.split_whitespace().any(|a| a == "zone=api"),
+        );
+
+        // The location-level directive is untouched, the first server gets a new one
+        assert!(
+            updated.contains("server {\n    limit_req zone=api burst=20 nodelay;\n    listen 80;")
+        );
+        assert!(updated.contains("        limit_req zone=api burst=5;"));
+        // The second server's directive is replaced rather than duplicated
+        assert!(
+            updated.contains("    listen 443 ssl;\n    limit_req zone=api burst=20 nodelay;\n}")
+        );
+        assert_eq!(updated.matches("burst=20").count(), 2);
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let new = NGINX_CONF.replace("gzip on;", "gzip off;");
+        let diff = unified_diff("/etc/nginx/nginx.conf", NGINX_CONF, &new);
+
+        assert!(diff.starts_with(
+            "--- /etc/nginx/nginx.conf\n+++ /etc/nginx/nginx.conf\n@@ -1,7 +1,7 @@\n"
+        ));
+        assert!(diff.contains("-    gzip on;\n+    gzip off;\n"));
+        assert_eq!(unified_diff("x", NGINX_CONF, NGINX_CONF), "");
+    }
+
+    #[test]
+    fn test_parse_structure_tree() {
+        let conf = "upstream backend {\n\
+                    \x20   server 127.0.0.1:8080;\n\
+                    }\n\
+                    server {\n\
+                    \x20   listen 80; # plain http\n\
+                    \x20   server_name example.com\n\
+                    \x20               www.example.com;\n\
+                    \x20   location /api {\n\
+                    \x20       proxy_pass http://backend;\n\
+                    \x20       location ~ \"\\.json{2}$\" {\n\
+                    \x20           add_header X-Kind json;\n\
+                    \x20       }\n\
+                    \x20   }\n\
+                    \x20   if ($http_x_debug = \"on; please\") { return 403; }\n\
+                    \x20   set $path ${uri}x;\n\
+                    }\n";
+        let blocks = parse_structure(conf).unwrap();
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].kind, "upstream");
+        assert_eq!(blocks[0].name, "backend");
+        assert_eq!((blocks[0].start_line, blocks[0].end_line), (1, 3));
+
+        let server = &blocks[1];
+        assert_eq!((server.start_line, server.end_line), (4, 16));
+        let names: Vec<&str> = server.directives.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["listen", "server_name", "set"]);
+        // A directive spanning lines is reported once, where it starts
+        assert_eq!(server.directives[1].args, "example.com www.example.com");
+        assert_eq!(server.directives[1].line, 6);
+        assert_eq!(server.directives[2].args, "$path ${uri}x");
+
+        let location = &server.children[0];
+        assert_eq!(
+            (location.kind.as_str(), location.name.as_str()),
+            ("location", "/api")
+        );
+        assert_eq!((location.start_line, location.end_line), (8, 13));
+        assert_eq!(location.children[0].name, "~ \"\\.json{2}$\"");
+        assert_eq!(location.children[0].directives[0].line, 11);
+
+        let if_block = &server.children[1];
+        assert_eq!(if_block.kind, "if");
+        assert_eq!(if_block.name, "($http_x_debug = \"on; please\")");
+        assert_eq!((if_block.start_line, if_block.end_line), (14, 14));
+        assert_eq!(if_block.directives[0].name, "return");
+    }
+
+    #[test]
+    fn test_parse_structure_errors() {
+        assert!(parse_structure("server {\n    listen 80;\n")
+            .unwrap_err()
+            .starts_with("Line 1:"));
+        assert!(parse_structure("server {\n    listen 80\n}\n")
+            .unwrap_err()
+            .contains("missing ';'"));
+        assert!(parse_structure("}\n").unwrap_err().contains("unexpected"));
+        assert!(parse_structure("server {\n    return 200 \"oops;\n}\n").is_err());
+    }
+
+    #[test]
+    fn test_diff_lines_numbers() {
+        let new = NGINX_CONF.replace("gzip on;", "gzip off;");
+        let lines = diff_lines(NGINX_CONF, &new);
+
+        let removed = lines
+            .iter()
+            .find(|l| l.kind == DiffLineKind::Removed)
+            .unwrap();
+        assert_eq!((removed.old_line, removed.new_line), (Some(4), None));
+        let added = lines
+            .iter()
+            .find(|l| l.kind == DiffLineKind::Added)
+            .unwrap();
+        assert_eq!((added.old_line, added.new_line), (None, Some(4)));
+        assert_eq!(added.text, "    gzip off;");
+        assert!(diff_lines(NGINX_CONF, NGINX_CONF).is_empty());
+    }
+
+    #[test]
+    fn test_diff_lines_large_change() {
+        let old: String = (0..3000).map(|i| format!("old {}\n", i)).collect();
+        let new: String = (0..3000).map(|i| format!("new {}\n", i)).collect();
+        let old = format!("head\n{}tail\n", old);
+        let new = format!("head\n{}tail\n", new);
+        let lines = diff_lines(&old, &new);
+        assert_eq!(lines[0].text, "@@ -1,3002 +1,3002 @@");
+        assert_eq!(lines[1].kind, DiffLineKind::Context);
+        assert_eq!(
+            (lines[2].kind, lines[2].old_line),
+            (DiffLineKind::Removed, Some(2))
+        );
+        assert_eq!(lines[3002].kind, DiffLineKind::Added);
+        assert_eq!(lines[3002].new_line, Some(2));
+        assert_eq!(lines.last().unwrap().text, "tail");
+        assert_eq!(lines.last().unwrap().old_line, Some(3002));
+    }
+
+    #[test]
+    fn test_insert_location_picks_https_server() {
+        let vhost = "server {\n\
+                     \x20   listen 80;\n\
+                     \x20   server_name example.com;\n\
+                     \x20   return 301 https://$host$request_uri;\n\
+                     }\n\
+                     \n\
+                     server {\n\
+                     \x20   listen 443 ssl;\n\
+                     \x20   server_name example.com;\n\
+                     \x20   location / {\n\
+                     \x20       root /var/www/html;\n\
+                     \x20   }\n\
+                     }\n";
+        let body = vec!["proxy_pass http://127.0.0.1:3000;".to_string()];
+        let updated = insert_location(vhost, "/api", &body, None).unwrap();
+        assert!(updated.ends_with(
+            "    }\n\n    location /api {\n        proxy_pass http://127.0.0.1:3000;\n    }\n}\n"
+        ));
+        assert!(updated.starts_with(&vhost[..vhost.find("server {\n    listen 443").unwrap()]));
+
+        assert!(insert_location(&updated, "/api", &body, None)
+            .unwrap_err()
+            .contains("already has"));
+        assert!(insert_location(vhost, "/api", &body, Some("other.com")).is_err());
+    }
+
+    #[test]
+    fn test_insert_location_ambiguous_servers() {
+        let vhost = "server {\n    listen 80;\n    server_name a.com;\n}\n\
+                     server {\n    listen 80;\n    server_name b.com;\n}\n";
+        let body = vec!["root /srv/static;".to_string()];
+        assert!(insert_location(vhost, "/static", &body, None).is_err());
+        let updated = insert_location(vhost, "/static", &body, Some("b.com")).unwrap();
+        assert!(updated.ends_with(
+            "server_name b.com;\n\n    location /static {\n        root /srv/static;\n    }\n}\n"
+        ));
+    }
+
+    #[test]
+    fn test_parse_proxy_target() {
+        let http = parse_proxy_target("http://127.0.0.1:3000/api/;").unwrap();
+        assert_eq!(http.scheme, "http");
+        assert_eq!(http.host.as_deref(), Some("127.0.0.1"));
+        assert_eq!(http.port, Some(3000));
+        assert!(!http.uses_variables);
+
+        let https = parse_proxy_target("https://App-Backend").unwrap();
+        assert_eq!(https.host.as_deref(), Some("app-backend"));
+        assert_eq!(https.port, Some(443));
+        assert_eq!(
+            parse_proxy_target("http://[::1]:8080")
+                .unwrap()
+                .host
+                .as_deref(),
+            Some("::1")
+        );
+        assert_eq!(parse_proxy_target("http://web").unwrap().port, Some(80));
+
+        let unix = parse_proxy_target("http://unix:/run/app/app.sock:/api").unwrap();
+        assert_eq!(unix.unix_socket.as_deref(), Some("/run/app/app.sock"));
+        assert_eq!(unix.host, None);
+
+        let variable = parse_proxy_target("http://$upstream").unwrap();
+        assert!(variable.uses_variables);
+        assert_eq!(variable.host, None);
+        // A variable only in the URI doesn't hide the address
+        let uri_variable = parse_proxy_target("http://api:8000$request_uri").unwrap();
+        assert_eq!(uri_variable.host.as_deref(), Some("api"));
+        assert!(!uri_variable.uses_variables);
+
+        assert!(parse_proxy_target("127.0.0.1:3000").is_none());
+        assert!(parse_proxy_target("http://web:port").is_none());
+    }
+
+    #[test]
+    fn test_vhost_probe() {
+        let vhost = "server {\n\
+                     \x20   listen 80;\n\
+                     \x20   server_name *.example.com example.com;\n\
+                     \x20   return 301 https://$host$request_uri;\n\
+                     }\n\
+                     server {\n\
+                     \x20   listen [::]:8443 ssl http2;\n\
+                     \x20   listen 10.0.0.5:8443 ssl;\n\
+                     \x20   server_name *.example.com example.com;\n\
+                     \x20   location / {\n\
+                     \x20       proxy_pass http://127.0.0.1:3000;\n\
+                     \x20   }\n\
+                     }\n";
+        assert_eq!(
+            vhost_probe(vhost).unwrap(),
+            VhostProbe {
+                server_name: "example.com".to_string(),
+                address: "127.0.0.1".to_string(),
+                port: 8443,
+                https: true,
+                upstream: Some("http://127.0.0.1:3000".to_string()),
+            }
+        );
+
+        let catch_all = vhost_probe(
+            "server {\n    listen 10.0.0.5:8080;\n    server_name _;\n    root /srv;\n}\n",
+        )
+        .unwrap();
+        assert_eq!(catch_all.server_name, "localhost");
+        assert_eq!(
+            (catch_all.address.as_str(), catch_all.port, catch_all.https),
+            ("10.0.0.5", 8080, false)
+        );
+        assert_eq!(catch_all.upstream, None);
+
+        // nginx listens on port 80 when a server has no listen directive
+        let bare = vhost_probe("server {\n    server_name site.test;\n}\n").unwrap();
+        assert_eq!((bare.port, bare.https), (80, false));
+        assert!(vhost_probe("# empty\n").is_err());
+    }
+}
@@ -0,0 +1,225 @@
+//! Rendering `SystemMetrics` in the Prometheus text exposition format.
+//!
+//! Metric names carry a `dpanel_` prefix and are part of the public surface: dashboards
+//! and alerts are built on them, so existing names mustn't change meaning. Add new
+//! metrics instead of renaming old ones.
+
+use crate::types::SystemMetrics;
+use std::fmt::Write;
+
+/// Append one metric family: its `# HELP` and `# TYPE` lines, then a sample per label set
+fn family(
+    out: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    samples: &[(Vec<(&str, &str)>, f64)],
+) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    for (labels, value) in samples {
+        if labels.is_empty() {
+            let _ = writeln!(out, "{} {}", name, value);
+        } else {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(key, value)| format!("{}=\"{}\"", key, escape_label_value(value)))
+                .collect();
+            let _ = writeln!(out, "{}{{{}}} {}", name, labels.join(","), value);
+        }
+    }
+}
+
+/// Escape a label value as the exposition format requires: backslash, quote and newline
+fn escape_label_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub fn render_metrics(metrics: &SystemMetrics) -> String {
+    let mut out = String::from(
+        "# DPanel system metrics. Names and labels are stable; new metrics may be added.\n",
+    );
+    let single = |value: f64| vec![(Vec::new(), value)];
+
+    family(
+        &mut out,
+        "dpanel_cpu_usage_percent",
+        "gauge",
+        "CPU time in use across all cores, 0-100.",
+        &single(metrics.cpu_percent),
+    );
+    family(
+        &mut out,
+        "dpanel_memory_used_bytes",
+        "gauge",
+        "Memory in use, excluding buffers and cache.",
+        &single(metrics.memory_used as f64),
+    );
+    family(
+        &mut out,
+        "dpanel_memory_total_bytes",
+        "gauge",
+        "Total usable memory.",
+        &single(metrics.memory_total as f64),
+    );
+    family(
+        &mut out,
+        "dpanel_swap_used_bytes",
+        "gauge",
+        "Swap space in use.",
+        &single(metrics.swap_used as f64),
+    );
+    family(
+        &mut out,
+        "dpanel_swap_total_bytes",
+        "gauge",
+        "Total swap space.",
+        &single(metrics.swap_total as f64),
+    );
+
+    let disks = |value: fn(&crate::types::DiskUsage) -> f64| -> Vec<(Vec<(&str, &str)>, f64)> {
+        metrics
+            .disk_usage
+            .iter()
+            .map(|disk| (vec![("mountpoint", disk.mount_point.as_str())], value(disk)))
+            .collect()
+    };
+    family(
+        &mut out,
+        "dpanel_filesystem_used_bytes",
+        "gauge",
+        "Space used on the filesystem mounted at mountpoint.",
+        &disks(|d| d.used as f64),
+    );
+    family(
+        &mut out,
+        "dpanel_filesystem_size_bytes",
+        "gauge",
+        "Size of the filesystem mounted at mountpoint.",
+        &disks(|d| d.total as f64),
+    );
+
+    for (period, load) in ["1", "5", "15"].iter().zip(metrics.load_avg) {
+        let name = format!("dpanel_load{}", period);
+        let help = format!("{}-minute load average.", period);
+        family(&mut out, &name, "gauge", &help, &single(load));
+    }
+    family(
+        &mut out,
+        "dpanel_uptime_seconds",
+        "gauge",
+        "Seconds since the server booted.",
+        &single(metrics.uptime as f64),
+    );
+    family(
+        &mut out,
+        "dpanel_processes",
+        "gauge",
+        "Number of running processes.",
+        &single(metrics.process_count as f64),
+    );
+
+    let network = &metrics.network;
+    let interface = |value: u64| {
+        vec![(
+            vec![("interface", network.interface.as_str())],
+            value as f64,
+        )]
+    };
+    family(
+        &mut out,
+        "dpanel_network_receive_bytes_total",
+        "counter",
+        "Bytes received on the default-route interface.",
+        &interface(network.bytes_recv),
+    );
+    family(
+        &mut out,
+        "dpanel_network_transmit_bytes_total",
+        "counter",
+        "Bytes sent on the default-route interface.",
+        &interface(network.bytes_sent),
+    );
+    family(
+        &mut out,
+        "dpanel_network_receive_packets_total",
+        "counter",
+        "Packets received on the default-route interface.",
+        &interface(network.packets_recv),
+    );
+    family(
+        &mut out,
+        "dpanel_network_transmit_packets_total",
+        "counter",
+        "Packets sent on the default-route interface.",
+        &interface(network.packets_sent),
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DiskUsage, NetworkStats};
+
+    #[test]
+    fn test_render_metrics() {
+        let metrics = SystemMetrics {
+            cpu_percent: 12.5,
+            memory_used: 1024,
+            memory_total: 4096,
+            swap_used: 0,
+            swap_total: 2048,
+            disk_usage: vec![DiskUsage {
+                mount_point: "/mnt/odd \"name\"".to_string(),
+                used: 10,
+                total: 100,
+                percent: 10.0,
+            }],
+            load_avg: [0.5, 0.25, 0.1],
+            uptime: 3600,
+            process_count: 120,
+            network: NetworkStats {
+                bytes_sent: 300,
+                bytes_recv: 400,
+                packets_sent: 3,
+                packets_recv: 4,
+                interface: "eth0".to_string(),
+            },
+            cpu_history: Vec::new(),
+            memory_history: Vec::new(),
+            network_history: Vec::new(),
+            collected_at: 0,
+            cached: false,
+        };
+        let text = render_metrics(&metrics);
+
+        assert!(text.contains("# HELP dpanel_cpu_usage_percent "));
+        assert!(
+            text.contains("# TYPE dpanel_cpu_usage_percent gauge\ndpanel_cpu_usage_percent 12.5\n")
+        );
+        assert!(text.contains("dpanel_swap_total_bytes 2048\n"));
+        assert!(text
+            .contains("dpanel_filesystem_size_bytes{mountpoint=\"/mnt/odd \\\"name\\\"\"} 100\n"));
+        assert!(text.contains("dpanel_load15 0.1\n"));
+        assert!(text.contains("# TYPE dpanel_network_receive_bytes_total counter\n"));
+        assert!(text.contains("dpanel_network_receive_bytes_total{interface=\"eth0\"} 400\n"));
+        // One HELP/TYPE header per metric name
+        assert_eq!(text.matches("# TYPE ").count(), 16);
+    }
+
+    #[test]
+    fn test_escape_label_value() {
+        assert_eq!(escape_label_value("a\\b\"c\nd"), "a\\\\b\\\"c\\nd");
+        assert_eq!(escape_label_value("/var/lib"), "/var/lib");
+    }
+}
@@ -0,0 +1,189 @@
+//! Short-lived in-memory cache for read commands that are slow to gather.
+//!
+//! Entries are keyed by server id and command name and expire after a per-command TTL.
+//! Once the cache is full the least recently used entry makes room for the new one.
+//! Commands without a TTL aren't cached at all.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Cached commands and how many seconds their results stay fresh
+const DEFAULT_TTLS: &[(&str, u64)] = &[
+    ("users", 60),
+    ("groups", 60),
+    ("images", 30),
+    ("compose", 30),
+];
+const DEFAULT_CAPACITY: usize = 64;
+
+struct CacheEntry {
+    value: Arc<dyn Any + Send + Sync>,
+    stored_at: Instant,
+    last_used: Instant,
+}
+
+pub struct ResponseCache {
+    entries: Mutex<HashMap<(String, String), CacheEntry>>,
+    ttls: Mutex<HashMap<String, Duration>>,
+    capacity: usize,
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+}
+
+impl ResponseCache {
+    pub fn with_capacity(capacity: usize) -> Self {
+        let ttls = DEFAULT_TTLS
+            .iter()
+            .map(|(command, secs)| (command.to_string(), Duration::from_secs(*secs)))
+            .collect();
+        ResponseCache {
+            entries: Mutex::new(HashMap::new()),
+            ttls: Mutex::new(ttls),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// How long `command` results are kept, `None` when it isn't cached
+    pub async fn ttl(&self, command: &str) -> Option<Duration> {
+        self.ttls
+            .lock()
+            .await
+            .get(command)
+            .copied()
+            .filter(|ttl| !ttl.is_zero())
+    }
+
+    /// Change how long `command` results are kept; zero turns caching off for it
+    pub async fn set_ttl(&self, command: &str, ttl: Duration) {
+        self.ttls.lock().await.insert(command.to_string(), ttl);
+        if ttl.is_zero() {
+            self.invalidate(Some(command)).await;
+        }
+    }
+
+    pub async fn get<T: Clone + 'static>(&self, server_id: &str, command: &str) -> Option<T> {
+        let ttl = self.ttl(command).await?;
+        let key = (server_id.to_string(), command.to_string());
+        let mut entries = self.entries.lock().await;
+        let entry = entries.get_mut(&key)?;
+        if entry.stored_at.elapsed() >= ttl {
+            entries.remove(&key);
+            return None;
+        }
+        entry.last_used = Instant::now();
+        entry.value.downcast_ref::<T>().cloned()
+    }
+
+    pub async fn put<T: Send + Sync + 'static>(&self, server_id: &str, command: &str, value: T) {
+        if self.ttl(command).await.is_none() {
+            return;
+        }
+        let key = (server_id.to_string(), command.to_string());
+        let mut entries = self.entries.lock().await;
+        if !entries.contains_key(&key) && entries.len() >= self.capacity {
+            let least_recent = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(least_recent) = least_recent {
+                entries.remove(&least_recent);
+            }
+        }
+        let now = Instant::now();
+        entries.insert(
+            key,
+            CacheEntry {
+                value: Arc::new(value),
+                stored_at: now,
+                last_used: now,
+            },
+        );
+    }
+
+    /// Drop `command` results on every server, or the whole cache when `None`
+    pub async fn invalidate(&self, command: Option<&str>) {
+        let mut entries = self.entries.lock().await;
+        match command {
+            Some(command) => entries.retain(|(_, cached), _| cached != command),
+            None => entries.clear(),
+        }
+    }
+
+    pub async fn invalidate_entry(&self, server_id: &str, command: &str) {
+        self.entries
+            .lock()
+            .await
+            .remove(&(server_id.to_string(), command.to_string()));
+    }
+
+    pub async fn invalidate_server(&self, server_id: &str) {
+        self.entries
+            .lock()
+            .await
+            .retain(|(cached, _), _| cached != server_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_cache_ttl_and_invalidation() {
+        block_on(async {
+            let cache = ResponseCache::default();
+            cache.put("a", "users", vec!["root".to_string()]).await;
+            cache.put("b", "users", vec!["alice".to_string()]).await;
+            cache.put("a", "uncached", 1u32).await;
+
+            assert_eq!(
+                cache.get::<Vec<String>>("a", "users").await,
+                Some(vec!["root".to_string()])
+            );
+            assert_eq!(cache.get::<u32>("a", "uncached").await, None);
+            // A hit only counts with the type the command stored
+            assert_eq!(cache.get::<u32>("a", "users").await, None);
+
+            cache.invalidate_server("a").await;
+            assert!(cache.get::<Vec<String>>("a", "users").await.is_none());
+            assert!(cache.get::<Vec<String>>("b", "users").await.is_some());
+            cache.invalidate(Some("users")).await;
+            assert!(cache.get::<Vec<String>>("b", "users").await.is_none());
+
+            cache.set_ttl("groups", Duration::from_millis(10)).await;
+            cache.put("a", "groups", 5u32).await;
+            std::thread::sleep(Duration::from_millis(20));
+            assert_eq!(cache.get::<u32>("a", "groups").await, None);
+        });
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        block_on(async {
+            let cache = ResponseCache::with_capacity(2);
+            cache.put("a", "users", 1u32).await;
+            cache.put("b", "users", 2u32).await;
+            std::thread::sleep(Duration::from_millis(2));
+            assert_eq!(cache.get::<u32>("a", "users").await, Some(1));
+
+            cache.put("c", "users", 3u32).await;
+            assert_eq!(cache.get::<u32>("a", "users").await, Some(1));
+            assert_eq!(cache.get::<u32>("b", "users").await, None);
+            assert_eq!(cache.get::<u32>("c", "users").await, Some(3));
+        });
+    }
+}
@@ -1,13 +1,54 @@
 use crate::types::*;
-use ssh2::Session;
-use std::net::TcpStream;
+use base64::engine::general_purpose::STANDARD_NO_PAD;
+use base64::Engine;
+use ssh2::{HashType, MethodType, Session};
+use std::io::ErrorKind;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
-use std::io::Read;
+use std::io::{Read, Write};
 use std::path::Path;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Connect/handshake timeout used when the profile doesn't set `connect_timeout_secs`
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+
+/// libssh2 error codes reported when a blocking call runs past the session/socket timeout
+const LIBSSH2_ERROR_TIMEOUT: i32 = -9;
+const LIBSSH2_ERROR_SOCKET_TIMEOUT: i32 = -30;
+
+/// How often a streaming read wakes up to check whether it was asked to stop
+const STREAM_POLL_MS: u32 = 500;
+
+/// Keepalive interval used when the profile doesn't set `keepalive_secs`
+const DEFAULT_KEEPALIVE_SECS: u64 = 30;
+
+/// Attempts used by retrying callers when the profile doesn't set `retry_attempts`
+const DEFAULT_RETRY_ATTEMPTS: u32 = 3;
+
+/// Output kept per command when the profile doesn't set `max_output_bytes`
+const DEFAULT_MAX_OUTPUT_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Size of each read while collecting command output
+const OUTPUT_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Stages of `connect`, in order, as reported by `diagnose`
+const CONNECT_STAGES: [&str; 3] = ["tcp", "handshake", "auth"];
+
+/// Background thread sending SSH keepalives while a session is open
+struct KeepaliveTask {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
 
 pub struct SshClient {
     config: ServerProfile,
     session: Arc<Mutex<Option<Session>>>,
+    connect_count: AtomicU32,
+    keepalive: Mutex<Option<KeepaliveTask>>,
+    /// Shell prefix that makes `docker` reach the right daemon (see `set_docker_preamble`)
+    docker_preamble: Mutex<String>,
 }
 
 impl SshClient {
@@ -15,6 +56,25 @@ impl SshClient {
         SshClient {
             config,
             session: Arc::new(Mutex::new(None)),
+            connect_count: AtomicU32::new(0),
+            keepalive: Mutex::new(None),
+            docker_preamble: Mutex::new(String::new()),
+        }
+    }
+
+    /// Set a shell snippet run before every command that mentions `docker`, e.g. an
+    /// `export DOCKER_HOST=...;` for rootless daemons or a `docker()` wrapper using sudo.
+    /// Applying it here keeps every docker call, including nested `$(docker ...)`, consistent.
+    pub fn set_docker_preamble(&self, preamble: String) {
+        *self.docker_preamble.lock().unwrap() = preamble;
+    }
+
+    fn with_docker_preamble(&self, command: &str) -> String {
+        let preamble = self.docker_preamble.lock().unwrap();
+        if preamble.is_empty() || !command.contains("docker") {
+            command.to_string()
+        } else {
+            format!("{}{}", preamble, command)
         }
     }
 
@@ -27,12 +87,101 @@ impl SshClient {
         path.to_string()
     }
 
+    fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(
+            self.config
+                .connect_timeout_secs
+                .unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS)
+                .max(1),
+        )
+    }
+
     pub fn connect(&self) -> Result<(), CommandError> {
-        let tcp = TcpStream::connect(format!("{}:{}", self.config.host, self.config.port))
-            .map_err(|e| CommandError {
-                message: format!("Failed to connect to {}:{}: {}", self.config.host, self.config.port, e),
-                code: -1,
-            })?;
+        let timeout = self.connect_timeout();
+        let tcp = self.connect_tcp(timeout)?;
+        let stream_handle = tcp.try_clone().ok();
+        let session = self.handshake(tcp, timeout)?;
+        self.authenticate(&session)?;
+
+        // Commands may legitimately run for a long time, so drop the connect-phase timeouts
+        session.set_timeout(0);
+        if let Some(stream) = stream_handle {
+            let _ = stream.set_read_timeout(None);
+            let _ = stream.set_write_timeout(None);
+        }
+
+        let keepalive_secs = self.config.keepalive_secs.unwrap_or(DEFAULT_KEEPALIVE_SECS);
+        if keepalive_secs > 0 {
+            session.set_keepalive(true, keepalive_secs.min(u32::MAX as u64) as u32);
+        }
+
+        {
+            let mut session_guard = self.session.lock().unwrap();
+            *session_guard = Some(session);
+        }
+        self.connect_count.fetch_add(1, Ordering::SeqCst);
+
+        self.stop_keepalive();
+        if keepalive_secs > 0 {
+            self.start_keepalive(Duration::from_secs(keepalive_secs));
+        }
+
+        Ok(())
+    }
+
+    /// Run the stages of `connect` one by one and report how each went, without keeping
+    /// the session. Stages after the first failure are reported as skipped.
+    pub fn diagnose(&self) -> ConnectionDiagnosis {
+        let timeout = self.connect_timeout();
+        let mut stages = Vec::new();
+
+        let tcp = Self::run_stage(&mut stages, "tcp", || self.connect_tcp(timeout));
+        let session = tcp.and_then(|tcp| {
+            Self::run_stage(&mut stages, "handshake", || self.handshake(tcp, timeout))
+        });
+        if let Some(session) = &session {
+            Self::run_stage(&mut stages, "auth", || self.authenticate(session));
+            let _ = session.disconnect(None, "Diagnosis complete", None);
+        }
+
+        for name in CONNECT_STAGES.iter().skip(stages.len()) {
+            stages.push(ConnectionStage {
+                name: name.to_string(),
+                status: StageStatus::Skipped,
+                duration_ms: None,
+                error: None,
+                error_code: None,
+            });
+        }
+
+        let failed_stage = stages
+            .iter()
+            .find(|stage| stage.status == StageStatus::Failed)
+            .map(|stage| stage.name.clone());
+        ConnectionDiagnosis { success: failed_stage.is_none(), failed_stage, stages }
+    }
+
+    fn run_stage<T>(
+        stages: &mut Vec<ConnectionStage>,
+        name: &str,
+        stage: impl FnOnce() -> Result<T, CommandError>,
+    ) -> Option<T> {
+        let started = Instant::now();
+        let result = stage();
+        let duration_ms = Some(started.elapsed().as_millis() as u64);
+        let (status, error, error_code, value) = match result {
+            Ok(value) => (StageStatus::Ok, None, None, Some(value)),
+            Err(e) => (StageStatus::Failed, Some(e.message), Some(e.code), None),
+        };
+        stages.push(ConnectionStage { name: name.to_string(), status, duration_ms, error, error_code });
+        value
+    }
+
+    /// Start an SSH session over `tcp` and complete the handshake, bounded by `timeout`
+    fn handshake(&self, tcp: TcpStream, timeout: Duration) -> Result<Session, CommandError> {
+        // Bound the handshake and auth too; `connect` clears this once the session is up
+        let _ = tcp.set_read_timeout(Some(timeout));
+        let _ = tcp.set_write_timeout(Some(timeout));
 
         let mut session = Session::new().map_err(|e| CommandError {
             message: format!("Failed to create SSH session: {}", e),
@@ -43,13 +192,28 @@ impl SshClient {
         
         // Use blocking mode for simplicity
         session.set_blocking(true);
+        session.set_timeout(timeout.as_millis().min(u32::MAX as u128) as u32);
 
         // Handshake
-        session.handshake().map_err(|e| CommandError {
-            message: format!("SSH handshake failed: {}", e),
-            code: -1,
+        session.handshake().map_err(|e| {
+            let timed_out = matches!(
+                e.code(),
+                ssh2::ErrorCode::Session(LIBSSH2_ERROR_TIMEOUT | LIBSSH2_ERROR_SOCKET_TIMEOUT)
+            );
+            CommandError {
+                message: if timed_out {
+                    format!("SSH handshake with {}:{} timed out after {}s", self.config.host, self.config.port, timeout.as_secs())
+                } else {
+                    format!("SSH handshake failed: {}", e)
+                },
+                code: if timed_out { CommandError::CONNECTION_TIMED_OUT } else { -1 },
+            }
         })?;
 
+        Ok(session)
+    }
+
+    fn authenticate(&self, session: &Session) -> Result<(), CommandError> {
         // Authenticate based on auth method
         match &self.config.auth_method {
             AuthMethod::Password { password } => {
@@ -87,13 +251,79 @@ impl SshClient {
             });
         }
 
-        let mut session_guard = self.session.lock().unwrap();
-        *session_guard = Some(session);
-
         Ok(())
     }
 
+    /// Try each resolved address in turn, giving up on each after `timeout`
+    fn connect_tcp(&self, timeout: Duration) -> Result<TcpStream, CommandError> {
+        let target = format!("{}:{}", self.config.host, self.config.port);
+        let addrs = target.to_socket_addrs().map_err(|e| CommandError {
+            message: format!("Failed to resolve {}: {}", self.config.host, e),
+            code: -1,
+        })?;
+
+        let mut last_error = None;
+        for addr in addrs {
+            match TcpStream::connect_timeout(&addr, timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        let error = last_error.unwrap_or_else(|| {
+            std::io::Error::new(ErrorKind::NotFound, "no addresses found")
+        });
+        let (message, code) = match error.kind() {
+            ErrorKind::TimedOut | ErrorKind::WouldBlock => (
+                format!("Connection to {} timed out after {}s", target, timeout.as_secs()),
+                CommandError::CONNECTION_TIMED_OUT,
+            ),
+            ErrorKind::ConnectionRefused => (
+                format!("Connection to {} was refused", target),
+                CommandError::CONNECTION_REFUSED,
+            ),
+            _ => (format!("Failed to connect to {}: {}", target, error), -1),
+        };
+        Err(CommandError { message, code })
+    }
+
+    /// Periodically call `keepalive_send` so idle sessions survive the server's `ClientAliveInterval`
+    fn start_keepalive(&self, interval: Duration) {
+        let session = Arc::clone(&self.session);
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+
+        let handle = std::thread::spawn(move || loop {
+            std::thread::park_timeout(interval);
+            if thread_stop.load(Ordering::SeqCst) {
+                break;
+            }
+
+            let session_guard = session.lock().unwrap();
+            match session_guard.as_ref() {
+                Some(session) => {
+                    if let Err(e) = session.keepalive_send() {
+                        log::warn!("SSH keepalive failed: {}", e);
+                    }
+                }
+                None => break,
+            }
+        });
+
+        *self.keepalive.lock().unwrap() = Some(KeepaliveTask { stop, handle });
+    }
+
+    fn stop_keepalive(&self) {
+        if let Some(task) = self.keepalive.lock().unwrap().take() {
+            task.stop.store(true, Ordering::SeqCst);
+            task.handle.thread().unpark();
+            let _ = task.handle.join();
+        }
+    }
+
     pub fn disconnect(&self) {
+        self.stop_keepalive();
+
         let mut session_guard = self.session.lock().unwrap();
         if let Some(_session) = session_guard.take() {
             // Session will be dropped and connection closed automatically
@@ -102,6 +332,41 @@ impl SshClient {
     }
 
     pub fn execute_command(&self, command: &str) -> Result<String, CommandError> {
+        // Return output even if exit status is non-zero (common with fallbacks)
+        let (output, _exit_status) = self.execute_with_status(command)?;
+        Ok(output)
+    }
+
+    /// Run a command and return its stdout together with the remote exit status. Output
+    /// past `max_output_bytes` is dropped; use `execute_bounded` to find out when it was.
+    pub fn execute_with_status(&self, command: &str) -> Result<(String, i32), CommandError> {
+        let result = self.execute_bounded(command)?;
+        Ok((result.output, result.exit_status))
+    }
+
+    /// Like `execute_with_status`, with `input` written to the command's stdin. Data passed
+    /// this way, unlike the command line, can't be seen in the server's process list.
+    pub fn execute_with_input(
+        &self,
+        command: &str,
+        input: &str,
+    ) -> Result<(String, i32), CommandError> {
+        let result = self.run_bounded(command, Some(input))?;
+        Ok((result.output, result.exit_status))
+    }
+
+    /// Run a command, keeping at most `max_output_bytes` of its stdout. Once the limit is
+    /// reached the channel is closed instead of draining the rest, so a runaway command
+    /// can't exhaust memory; the result is then flagged `truncated`.
+    pub fn execute_bounded(&self, command: &str) -> Result<CommandOutput, CommandError> {
+        self.run_bounded(command, None)
+    }
+
+    fn run_bounded(
+        &self,
+        command: &str,
+        input: Option<&str>,
+    ) -> Result<CommandOutput, CommandError> {
         let session_guard = self.session.lock().unwrap();
         let session = session_guard.as_ref().ok_or_else(|| CommandError {
             message: "Not connected".to_string(),
@@ -113,17 +378,73 @@ impl SshClient {
             code: -1,
         })?;
 
-        channel.exec(command).map_err(|e| CommandError {
-            message: format!("Failed to execute command: {}", e),
+        channel
+            .exec(&self.with_docker_preamble(command))
+            .map_err(|e| CommandError {
+                message: format!("Failed to execute command: {}", e),
+                code: -1,
+            })?;
+
+        if let Some(input) = input {
+            channel
+                .write_all(input.as_bytes())
+                .and_then(|_| channel.send_eof().map_err(std::io::Error::from))
+                .map_err(|e| CommandError {
+                    message: format!("Failed to send input: {}", e),
+                    code: -1,
+                })?;
+        }
+
+        let limit = self.max_output_bytes();
+        let result = collect_output(&mut channel, limit, |channel| {
+            channel.wait_close().map_err(|e| CommandError {
+                message: format!("Failed to wait for channel close: {}", e),
+                code: -1,
+            })?;
+            channel.exit_status().map_err(|e| CommandError {
+                message: format!("Failed to get exit status: {}", e),
+                code: -1,
+            })
+        })?;
+
+        if result.truncated {
+            log::warn!(
+                "Output of a command on {} exceeded {} bytes and was truncated",
+                self.config.host,
+                limit
+            );
+            let _ = channel.close();
+        }
+        Ok(result)
+    }
+
+    /// Run a command and copy its stdout into `writer` as it arrives, so large output never
+    /// sits in memory. Returns the number of bytes written and the remote exit status.
+    pub fn execute_to_writer(
+        &self,
+        command: &str,
+        writer: &mut dyn Write,
+    ) -> Result<(u64, i32), CommandError> {
+        let session_guard = self.session.lock().unwrap();
+        let session = session_guard.as_ref().ok_or_else(|| CommandError {
+            message: "Not connected".to_string(),
             code: -1,
         })?;
 
-        let mut output = String::new();
-        let stderr = String::new();
+        let mut channel = session.channel_session().map_err(|e| CommandError {
+            message: format!("Failed to open channel: {}", e),
+            code: -1,
+        })?;
+
+        channel
+            .exec(&self.with_docker_preamble(command))
+            .map_err(|e| CommandError {
+                message: format!("Failed to execute command: {}", e),
+                code: -1,
+            })?;
 
-        // Read stdout
-        channel.read_to_string(&mut output).map_err(|e| CommandError {
-            message: format!("Failed to read output: {}", e),
+        let written = std::io::copy(&mut channel, writer).map_err(|e| CommandError {
+            message: format!("Failed to copy output: {}", e),
             code: -1,
         })?;
 
@@ -137,15 +458,182 @@ impl SshClient {
             code: -1,
         })?;
 
-        // Return output even if exit status is non-zero (common with fallbacks)
-        // Only error if we have stderr and no stdout
-        if output.is_empty() && !stderr.is_empty() {
-            Err(CommandError {
-                message: stderr,
-                code: exit_status,
-            })
-        } else {
-            Ok(output)
+        Ok((written, exit_status))
+    }
+
+    /// Run `command`, retrying up to `attempts` times on connection-level errors. The session
+    /// is re-established before each retry and `backoff` doubles every time. A non-zero exit
+    /// status is a genuine result and is returned without retrying, so only use this for
+    /// commands that are safe to run twice.
+    pub fn execute_with_retry(
+        &self,
+        command: &str,
+        attempts: u32,
+        backoff: Duration,
+    ) -> Result<(String, i32), CommandError> {
+        let attempts = attempts.max(1);
+        let mut delay = backoff;
+        let mut attempt = 1;
+        loop {
+            match self.execute_with_status(command) {
+                Ok(result) => return Ok(result),
+                Err(e) if attempt >= attempts => return Err(e),
+                Err(e) => {
+                    log::warn!(
+                        "Command on {} failed (attempt {}/{}), retrying in {:?}: {}",
+                        self.config.host,
+                        attempt,
+                        attempts,
+                        delay,
+                        e.message
+                    );
+                    std::thread::sleep(delay);
+                    if let Err(e) = self.connect() {
+                        log::warn!("Reconnecting to {} failed: {}", self.config.host, e.message);
+                    }
+                    delay = delay.saturating_mul(2);
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Attempts configured for retrying callers
+    pub fn retry_attempts(&self) -> u32 {
+        self.config.retry_attempts.unwrap_or(DEFAULT_RETRY_ATTEMPTS)
+    }
+
+    pub fn max_output_bytes(&self) -> u64 {
+        self.config.max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT_BYTES).max(1)
+    }
+
+    /// A client for the same server with the same docker preamble, not yet connected
+    pub fn unconnected_copy(&self) -> SshClient {
+        let client = SshClient::new(self.config.clone());
+        client.set_docker_preamble(self.docker_preamble.lock().unwrap().clone());
+        client
+    }
+
+    /// Open a second, independent connection to the same server. Long-running streams
+    /// use it so they don't hold the session lock that regular commands need.
+    pub fn open_dedicated(&self) -> Result<SshClient, CommandError> {
+        let client = self.unconnected_copy();
+        client.connect()?;
+        Ok(client)
+    }
+
+    /// Run `true` to check the session still answers, failing after `timeout`. Returns
+    /// `Ok(false)` without probing while another command holds the session: waiting for
+    /// it would measure that command rather than the connection.
+    pub fn probe(&self, timeout: Duration) -> Result<bool, CommandError> {
+        let Ok(session_guard) = self.session.try_lock() else {
+            return Ok(false);
+        };
+        let session = session_guard.as_ref().ok_or_else(|| CommandError {
+            message: "Not connected".to_string(),
+            code: -1,
+        })?;
+
+        session.set_timeout(timeout.as_millis().min(u32::MAX as u128) as u32);
+        let result = session.channel_session().and_then(|mut channel| {
+            // `true` prints nothing, so there's no output to drain before the close
+            channel.exec("true")?;
+            channel.wait_close()
+        });
+        session.set_timeout(0);
+
+        result.map(|_| true).map_err(|e| {
+            let timed_out = matches!(
+                e.code(),
+                ssh2::ErrorCode::Session(LIBSSH2_ERROR_TIMEOUT | LIBSSH2_ERROR_SOCKET_TIMEOUT)
+            );
+            if timed_out {
+                CommandError {
+                    message: format!("No response within {}s", timeout.as_secs()),
+                    code: CommandError::CONNECTION_TIMED_OUT,
+                }
+            } else {
+                CommandError {
+                    message: format!("Probe failed: {}", e),
+                    code: -1,
+                }
+            }
+        })
+    }
+
+    /// Run a long-lived command, calling `on_line` for each line of stdout until the
+    /// command exits or `stop` is set. Returns the exit status, or `None` if stopped.
+    pub fn stream_lines(
+        &self,
+        command: &str,
+        stop: &AtomicBool,
+        mut on_line: impl FnMut(&str),
+    ) -> Result<Option<i32>, CommandError> {
+        let session_guard = self.session.lock().unwrap();
+        let session = session_guard.as_ref().ok_or_else(|| CommandError {
+            message: "Not connected".to_string(),
+            code: -1,
+        })?;
+
+        let mut channel = session.channel_session().map_err(|e| CommandError {
+            message: format!("Failed to open channel: {}", e),
+            code: -1,
+        })?;
+
+        channel
+            .exec(&self.with_docker_preamble(command))
+            .map_err(|e| CommandError {
+                message: format!("Failed to execute command: {}", e),
+                code: -1,
+            })?;
+
+        // Reads give up after a short timeout so the stop flag is checked regularly
+        session.set_timeout(STREAM_POLL_MS);
+
+        let mut pending: Vec<u8> = Vec::new();
+        let mut buf = [0u8; 8192];
+        let result = loop {
+            if stop.load(Ordering::SeqCst) {
+                break Ok(None);
+            }
+
+            match channel.read(&mut buf) {
+                Ok(0) => break Ok(Some(())),
+                Ok(n) => {
+                    pending.extend_from_slice(&buf[..n]);
+                    while let Some(pos) = pending.iter().position(|&b| b == b'\n') {
+                        let line: Vec<u8> = pending.drain(..=pos).collect();
+                        let line = String::from_utf8_lossy(&line);
+                        on_line(line.trim_end_matches(['\r', '\n']));
+                    }
+                }
+                Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) => {
+                    // Idle stream: nothing else uses this session, so keep it alive here
+                    let _ = session.keepalive_send();
+                }
+                Err(e) => {
+                    break Err(CommandError {
+                        message: format!("Failed to read output: {}", e),
+                        code: -1,
+                    })
+                }
+            }
+        };
+        session.set_timeout(0);
+
+        match result {
+            Ok(Some(())) => {
+                if !pending.is_empty() {
+                    on_line(String::from_utf8_lossy(&pending).trim_end_matches('\r'));
+                }
+                let _ = channel.wait_close();
+                Ok(Some(channel.exit_status().unwrap_or(-1)))
+            }
+            Ok(None) => {
+                let _ = channel.close();
+                Ok(None)
+            }
+            Err(e) => Err(e),
         }
     }
 
@@ -155,7 +643,186 @@ impl SshClient {
         session_guard.as_ref().map_or(false, |s| s.authenticated())
     }
 
+    /// Algorithm negotiated for the current session (kex, ciphers, host key, ...)
+    pub fn negotiated_method(&self, method: MethodType) -> Option<String> {
+        let session_guard = self.session.lock().unwrap();
+        session_guard
+            .as_ref()
+            .and_then(|s| s.methods(method))
+            .map(String::from)
+    }
+
+    /// Fingerprints of the key the server presented during the handshake, in the forms
+    /// `ssh-keygen -lf` prints: `SHA256:<base64>` and colon-separated hex `MD5:`
+    pub fn host_key_fingerprints(&self) -> Result<Vec<HostKeyFingerprint>, CommandError> {
+        let session_guard = self.session.lock().unwrap();
+        let session = session_guard.as_ref().ok_or_else(|| CommandError {
+            message: "Not connected".to_string(),
+            code: -1,
+        })?;
+        let (blob, key_type) = session.host_key().ok_or_else(|| CommandError {
+            message: "The server didn't present a host key".to_string(),
+            code: -1,
+        })?;
+        // The blob starts with the length-prefixed key type name, e.g. `ssh-ed25519`
+        let algorithm = blob
+            .get(..4)
+            .map(|len| u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize)
+            .and_then(|len| blob.get(4..4 + len))
+            .and_then(|name| std::str::from_utf8(name).ok())
+            .map(String::from)
+            .unwrap_or_else(|| format!("{:?}", key_type));
+        let hash = |kind: HashType, name: &str| {
+            session.host_key_hash(kind).ok_or_else(|| CommandError {
+                message: format!("{} host key hashes aren't supported by this libssh2 build", name),
+                code: -1,
+            })
+        };
+        let sha256 = hash(HashType::Sha256, "SHA256")?;
+        let md5 = hash(HashType::Md5, "MD5")?;
+        Ok(vec![HostKeyFingerprint {
+            algorithm,
+            sha256: sha256_fingerprint(sha256),
+            md5: format!("MD5:{}", colon_hex(md5)),
+        }])
+    }
+
+    /// Number of times the session was re-established after the first connect
+    pub fn reconnect_count(&self) -> u32 {
+        self.connect_count.load(Ordering::SeqCst).saturating_sub(1)
+    }
+
     pub fn get_host(&self) -> String {
         self.config.host.clone()
     }
 }
+
+/// Read `reader` to the end or until `limit` bytes, whichever comes first. The flag is
+/// set when more output was waiting beyond the limit.
+fn read_bounded(reader: &mut dyn Read, limit: u64) -> std::io::Result<(Vec<u8>, bool)> {
+    let mut bytes = Vec::new();
+    let mut chunk = vec![0u8; OUTPUT_CHUNK_BYTES];
+    loop {
+        let read = match reader.read(&mut chunk) {
+            Ok(0) => return Ok((bytes, false)),
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        let room = limit.saturating_sub(bytes.len() as u64) as usize;
+        if read > room {
+            bytes.extend_from_slice(&chunk[..room]);
+            return Ok((bytes, true));
+        }
+        bytes.extend_from_slice(&chunk[..read]);
+    }
+}
+
+/// Read a command's output from `reader` up to `limit` bytes. `exit_status` waits for the
+/// command to finish and is only called when all output was read; a truncated result
+/// gets exit status -1 instead, since the command is still running.
+pub fn collect_output<R: Read>(
+    mut reader: R,
+    limit: u64,
+    exit_status: impl FnOnce(&mut R) -> Result<i32, CommandError>,
+) -> Result<CommandOutput, CommandError> {
+    let (bytes, truncated) = read_bounded(&mut reader, limit).map_err(|e| CommandError {
+        message: format!("Failed to read output: {}", e),
+        code: -1,
+    })?;
+    let output = output_to_string(bytes, truncated)?;
+    let exit_status = if truncated { -1 } else { exit_status(&mut reader)? };
+    Ok(CommandOutput {
+        output,
+        exit_status,
+        truncated,
+    })
+}
+
+/// Decode command output. A multi-byte character cut off by truncation is dropped;
+/// invalid UTF-8 anywhere else is an error, as it was for `read_to_string`.
+fn output_to_string(mut bytes: Vec<u8>, truncated: bool) -> Result<String, CommandError> {
+    match std::str::from_utf8(&bytes) {
+        Ok(_) => {}
+        Err(e) if truncated && e.error_len().is_none() => bytes.truncate(e.valid_up_to()),
+        Err(_) => {
+            return Err(CommandError {
+                message: "Failed to read output: stream did not contain valid UTF-8"
+                    .to_string(),
+                code: -1,
+            })
+        }
+    }
+    Ok(String::from_utf8(bytes).unwrap_or_default())
+}
+
+/// `SHA256:` and the hash in standard base64 without the trailing `=`, as OpenSSH prints it
+fn sha256_fingerprint(hash: &[u8]) -> String {
+    format!("SHA256:{}", STANDARD_NO_PAD.encode(hash))
+}
+
+fn colon_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":")
+}
+
+impl Drop for SshClient {
+    fn drop(&mut self) {
+        // The keepalive thread holds its own handle on the session
+        self.stop_keepalive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_encodings() {
+        assert_eq!(sha256_fingerprint(b"foobar"), "SHA256:Zm9vYmFy");
+        assert_eq!(sha256_fingerprint(b"fooba"), "SHA256:Zm9vYmE");
+        assert_eq!(sha256_fingerprint(b"f"), "SHA256:Zg");
+        assert_eq!(colon_hex(&[0x0a, 0xff, 0x00]), "0a:ff:00");
+    }
+
+    #[test]
+    fn test_read_bounded_truncates_endless_output() {
+        // An endless `yes`-style generator
+        let mut generator = std::io::repeat(b'y');
+        let (bytes, truncated) = read_bounded(&mut generator, 100_000).unwrap();
+        assert!(truncated);
+        assert_eq!(bytes.len(), 100_000);
+
+        let mut short = "line one\nline two\n".as_bytes();
+        let (bytes, truncated) = read_bounded(&mut short, 100_000).unwrap();
+        assert!(!truncated);
+        assert_eq!(output_to_string(bytes, truncated).unwrap(), "line one\nline two\n");
+
+        // Exactly at the limit with nothing left over isn't truncation
+        let mut exact = "abcd".as_bytes();
+        assert_eq!(read_bounded(&mut exact, 4).unwrap(), (b"abcd".to_vec(), false));
+    }
+
+    #[test]
+    fn test_collect_output_flags_truncation() {
+        let endless = std::io::repeat(b'y');
+        let result =
+            collect_output(endless, 1024, |_| panic!("a truncated command isn't waited for"))
+                .unwrap();
+        assert!(result.truncated);
+        assert_eq!(result.exit_status, -1);
+        assert_eq!(result.output.len(), 1024);
+
+        let short = "done\n".as_bytes();
+        let result = collect_output(short, 1024, |_| Ok(2)).unwrap();
+        assert!(!result.truncated);
+        assert_eq!((result.output.as_str(), result.exit_status), ("done\n", 2));
+    }
+
+    #[test]
+    fn test_output_to_string_drops_split_character() {
+        let mut bytes = "ok é".as_bytes().to_vec();
+        bytes.pop();
+        assert_eq!(output_to_string(bytes.clone(), true).unwrap(), "ok ");
+        assert!(output_to_string(bytes, false).is_err());
+    }
+}
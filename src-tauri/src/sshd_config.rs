@@ -0,0 +1,243 @@
+//! Reading and editing sshd_config files.
+//!
+//! sshd takes the first value it sees for most keywords, reading `Include`d files at the
+//! point of the `Include`; the keywords in `ACCUMULATING_KEYWORDS` add up over every
+//! line instead. Directives after a `Match` line only apply to matching
+//! connections, up to the next `Match` or the end of the file. Edits are line-based so
+//! comments and layout survive, as in `nginx_config`.
+
+use crate::types::SshdDirective;
+
+/// Keywords where every line counts (`Port 22` and `Port 2222` listen on both) rather
+/// than the first one winning
+pub const ACCUMULATING_KEYWORDS: &[&str] = &[
+    "Port",
+    "ListenAddress",
+    "HostKey",
+    "AllowUsers",
+    "DenyUsers",
+    "AllowGroups",
+    "DenyGroups",
+];
+
+pub fn is_accumulating(key: &str) -> bool {
+    ACCUMULATING_KEYWORDS
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(key))
+}
+
+/// `Key value` or `Key=value` with the key as written and the value trimmed
+fn split_directive(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let end = line
+        .find(|c: char| c.is_whitespace() || c == '=')
+        .unwrap_or(line.len());
+    let (key, rest) = line.split_at(end);
+    let value = rest.trim_start();
+    let value = value.strip_prefix('=').unwrap_or(value).trim();
+    Some((key, value))
+}
+
+/// Every directive of one file in order, `Include` and `Match` lines included
+pub fn parse_sshd_directives(file: &str, content: &str) -> Vec<SshdDirective> {
+    let mut directives = Vec::new();
+    let mut match_block: Option<String> = None;
+    for (idx, line) in content.lines().enumerate() {
+        let Some((key, value)) = split_directive(line) else {
+            continue;
+        };
+        let is_match = key.eq_ignore_ascii_case("Match");
+        directives.push(SshdDirective {
+            key: key.to_string(),
+            value: value.to_string(),
+            file: file.to_string(),
+            line: idx as u32 + 1,
+            match_block: if is_match { None } else { match_block.clone() },
+        });
+        if is_match {
+            // `Match all` returns to global scope
+            match_block = (!value.eq_ignore_ascii_case("all")).then(|| value.to_string());
+        }
+    }
+    directives
+}
+
+/// Shell-style `*`/`?` matching, as used by `Include` patterns
+pub fn glob_matches(pattern: &str, path: &str) -> bool {
+    fn matches(pattern: &[u8], path: &[u8]) -> bool {
+        match (pattern.first(), path.first()) {
+            (None, None) => true,
+            (Some(b'*'), _) => {
+                matches(&pattern[1..], path)
+                    || (!path.is_empty() && path[0] != b'/' && matches(pattern, &path[1..]))
+            }
+            (Some(b'?'), Some(c)) if *c != b'/' => matches(&pattern[1..], &path[1..]),
+            (Some(p), Some(c)) if p == c => matches(&pattern[1..], &path[1..]),
+            _ => false,
+        }
+    }
+    matches(pattern.as_bytes(), path.as_bytes())
+}
+
+/// Whether each line is in global scope, i.e. outside any `Match` block
+fn global_scope(lines: &[String]) -> Vec<bool> {
+    let mut global = true;
+    lines
+        .iter()
+        .map(|line| {
+            if let Some((key, value)) = split_directive(line) {
+                if key.eq_ignore_ascii_case("Match") {
+                    global = value.eq_ignore_ascii_case("all");
+                    return false;
+                }
+            }
+            global
+        })
+        .collect()
+}
+
+/// `content` with `key` set to `value` in global scope. The first global occurrence is
+/// replaced; for `ACCUMULATING_KEYWORDS` the other global occurrences are removed too,
+/// so `value` is the only one left. Without an occurrence the line goes after a
+/// commented-out default (`#Port 22`), or else before the first `Match` block so it
+/// doesn't become conditional.
+pub fn set_sshd_directive(content: &str, key: &str, value: &str) -> String {
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    let new_line = format!("{} {}", key, value);
+    let global = global_scope(&lines);
+    let first_match = global.iter().position(|g| !g).unwrap_or(lines.len());
+    let is_key =
+        |line: &str| split_directive(line).is_some_and(|(k, _)| k.eq_ignore_ascii_case(key));
+
+    let active: Vec<usize> = (0..lines.len())
+        .filter(|&idx| global[idx] && is_key(&lines[idx]))
+        .collect();
+    if let Some(&idx) = active.first() {
+        let indent = &lines[idx][..lines[idx].len() - lines[idx].trim_start().len()];
+        lines[idx] = format!("{}{}", indent, new_line);
+        if is_accumulating(key) {
+            for &extra in active[1..].iter().rev() {
+                lines.remove(extra);
+            }
+        }
+    } else {
+        let commented = lines[..first_match].iter().position(|line| {
+            line.trim_start()
+                .strip_prefix('#')
+                .and_then(split_directive)
+                .is_some_and(|(k, _)| k.eq_ignore_ascii_case(key))
+        });
+        match commented {
+            Some(idx) => lines.insert(idx + 1, new_line),
+            None => lines.insert(first_match, new_line),
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "\
+Include /etc/ssh/sshd_config.d/*.conf
+
+#Port 22
+PermitRootLogin prohibit-password
+  PasswordAuthentication=yes
+
+Match User deploy
+    PasswordAuthentication no
+Match all
+X11Forwarding no
+";
+
+    #[test]
+    fn test_parse_directives_and_match_scope() {
+        let directives = parse_sshd_directives("/etc/ssh/sshd_config", CONFIG);
+        let keys: Vec<&str> = directives.iter().map(|d| d.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "Include",
+                "PermitRootLogin",
+                "PasswordAuthentication",
+                "Match",
+                "PasswordAuthentication",
+                "Match",
+                "X11Forwarding"
+            ]
+        );
+        assert_eq!(directives[2].value, "yes");
+        assert_eq!(directives[2].line, 5);
+        assert_eq!(directives[2].match_block, None);
+        assert_eq!(directives[4].match_block.as_deref(), Some("User deploy"));
+        assert_eq!(directives[6].match_block, None);
+    }
+
+    #[test]
+    fn test_set_directive() {
+        let updated = set_sshd_directive(CONFIG, "passwordauthentication", "no");
+        assert!(updated.contains("\n  passwordauthentication no\n"));
+        // The Match block's own setting is untouched
+        assert!(updated.contains("Match User deploy\n    PasswordAuthentication no\n"));
+
+        let port = set_sshd_directive(CONFIG, "Port", "2222");
+        assert!(port.contains("#Port 22\nPort 2222\nPermitRootLogin"));
+
+        let new_key = set_sshd_directive(CONFIG, "MaxAuthTries", "3");
+        assert!(new_key.contains("MaxAuthTries 3\nMatch User deploy"));
+        assert_eq!(set_sshd_directive("", "Port", "22"), "Port 22\n");
+    }
+
+    #[test]
+    fn test_set_accumulating_directive() {
+        let config = "\
+Port 22
+Port 2200
+PermitRootLogin no
+Match Address 10.0.0.0/8
+    AllowUsers ops
+Match all
+Port 2201
+AllowUsers deploy
+";
+        // Every global Port line is replaced by the one value
+        assert_eq!(
+            set_sshd_directive(config, "Port", "2222"),
+            "Port 2222\nPermitRootLogin no\nMatch Address 10.0.0.0/8\n    AllowUsers ops\nMatch all\nAllowUsers deploy\n"
+        );
+        // The Match block's AllowUsers is left alone
+        let users = set_sshd_directive(config, "AllowUsers", "alice bob");
+        assert!(users.contains("Match Address 10.0.0.0/8\n    AllowUsers ops\n"));
+        assert!(users.contains("AllowUsers alice bob\n"));
+        assert!(!users.contains("deploy"));
+
+        // Other keywords keep their later occurrences, sshd ignores them anyway
+        let repeated = "PermitRootLogin no\nPermitRootLogin yes\n";
+        assert_eq!(
+            set_sshd_directive(repeated, "PermitRootLogin", "prohibit-password"),
+            "PermitRootLogin prohibit-password\nPermitRootLogin yes\n"
+        );
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches(
+            "/etc/ssh/sshd_config.d/*.conf",
+            "/etc/ssh/sshd_config.d/50-cloud-init.conf"
+        ));
+        assert!(!glob_matches(
+            "/etc/ssh/sshd_config.d/*.conf",
+            "/etc/ssh/sshd_config.d/50-cloud-init.conf.bak"
+        ));
+        assert!(!glob_matches(
+            "/etc/ssh/*.conf",
+            "/etc/ssh/sshd_config.d/a.conf"
+        ));
+        assert!(glob_matches("/etc/ssh/extra?.conf", "/etc/ssh/extra1.conf"));
+    }
+}
@@ -0,0 +1,125 @@
+//! Bookkeeping for long-running output streams (docker events, journal tail, ...).
+//!
+//! Each stream runs on its own thread and dedicated SSH connection, polling a stop flag
+//! between reads. The registry keeps those flags keyed by `{server id}/{stream}` so a
+//! stream can be stopped by name, and so every stream of a server can be stopped when
+//! it disconnects instead of leaking the thread and its session.
+//!
+//! A plain `std` mutex guards the map: stream threads deregister themselves from
+//! blocking code, and no lock is held across an await.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+pub struct StreamRegistry {
+    streams: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+pub fn stream_id(server_id: &str, name: &str) -> String {
+    format!("{}/{}", server_id, name)
+}
+
+impl StreamRegistry {
+    /// Track a new stream and return its stop flag. A stream already running under the
+    /// same id is told to stop, so starting a stream twice replaces it.
+    pub fn register_stream(&self, id: &str) -> Arc<AtomicBool> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let previous = self
+            .streams
+            .lock()
+            .unwrap()
+            .insert(id.to_string(), Arc::clone(&stop));
+        if let Some(previous) = previous {
+            previous.store(true, Ordering::SeqCst);
+        }
+        stop
+    }
+
+    /// Forget a stream that ended on its own. Does nothing if `id` has been taken over by
+    /// a newer stream in the meantime.
+    pub fn finish_stream(&self, id: &str, stop: &Arc<AtomicBool>) {
+        let mut streams = self.streams.lock().unwrap();
+        if streams.get(id).is_some_and(|s| Arc::ptr_eq(s, stop)) {
+            streams.remove(id);
+        }
+    }
+
+    /// Signal the stream to stop; returns whether one was running
+    pub fn stop_stream(&self, id: &str) -> bool {
+        match self.streams.lock().unwrap().remove(id) {
+            Some(stop) => {
+                stop.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop every stream of `server_id`, returning how many were running
+    pub fn stop_server_streams(&self, server_id: &str) -> usize {
+        let prefix = stream_id(server_id, "");
+        self.stop_matching(|id| id.starts_with(&prefix))
+    }
+
+    pub fn stop_all_streams(&self) -> usize {
+        self.stop_matching(|_| true)
+    }
+
+    pub fn running_streams(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.streams.lock().unwrap().keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    fn stop_matching(&self, matches: impl Fn(&str) -> bool) -> usize {
+        let mut streams = self.streams.lock().unwrap();
+        let ids: Vec<String> = streams.keys().filter(|id| matches(id)).cloned().collect();
+        for id in &ids {
+            if let Some(stop) = streams.remove(id) {
+                stop.store(true, Ordering::SeqCst);
+            }
+        }
+        ids.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_replaces_and_finish_keeps_newer() {
+        let registry = StreamRegistry::default();
+        let id = stream_id("web", "journal");
+        let first = registry.register_stream(&id);
+        let second = registry.register_stream(&id);
+        assert!(first.load(Ordering::SeqCst));
+        assert!(!second.load(Ordering::SeqCst));
+
+        // The replaced stream's thread exiting mustn't drop its successor
+        registry.finish_stream(&id, &first);
+        assert_eq!(registry.running_streams(), vec![id.clone()]);
+        registry.finish_stream(&id, &second);
+        assert!(registry.running_streams().is_empty());
+        assert!(!registry.stop_stream(&id));
+    }
+
+    #[test]
+    fn test_stop_by_server_and_all() {
+        let registry = StreamRegistry::default();
+        let journal = registry.register_stream(&stream_id("web", "journal"));
+        let events = registry.register_stream(&stream_id("web", "docker-events"));
+        // A server whose id starts like another one's keeps its streams
+        let other = registry.register_stream(&stream_id("web2", "journal"));
+
+        assert_eq!(registry.stop_server_streams("web"), 2);
+        assert!(journal.load(Ordering::SeqCst) && events.load(Ordering::SeqCst));
+        assert!(!other.load(Ordering::SeqCst));
+
+        assert_eq!(registry.stop_all_streams(), 1);
+        assert!(other.load(Ordering::SeqCst));
+        assert!(registry.running_streams().is_empty());
+    }
+}
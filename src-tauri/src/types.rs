@@ -8,6 +8,65 @@ pub struct ServerProfile {
     pub port: u16,
     pub username: String,
     pub auth_method: AuthMethod,
+    /// Seconds between SSH keepalive messages; `None` uses the default, `Some(0)` disables them
+    #[serde(default)]
+    pub keepalive_secs: Option<u64>,
+    /// Seconds to wait for the TCP connect and SSH handshake; `None` uses the default
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+    /// Attempts for read-only dashboard commands hitting connection errors; `None` uses the default
+    #[serde(default)]
+    pub retry_attempts: Option<u32>,
+    /// Seconds between connection health probes; `None` uses the default, `Some(0)` disables them
+    #[serde(default)]
+    pub probe_interval_secs: Option<u64>,
+    /// Bytes of command output kept before the rest is dropped; `None` uses the default
+    #[serde(default)]
+    pub max_output_bytes: Option<u64>,
+}
+
+/// A server with an open connection, as listed by `list_active_connections`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveConnection {
+    pub server_id: String,
+    pub name: String,
+    pub host: String,
+    pub username: String,
+    pub connected: bool,
+    /// Target of commands that don't pass a `server_id`
+    pub is_default: bool,
+    pub permission_mode: PermissionMode,
+}
+
+/// What a connection may do; `ReadOnly` refuses every handler that changes server state
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PermissionMode {
+    ReadOnly,
+    #[default]
+    Full,
+}
+
+/// Dashboard sections gathered by `dashboard_snapshot`; a section is `None` when it
+/// wasn't requested or failed, in which case `errors` holds the reason under its name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardSnapshot {
+    pub server_id: String,
+    pub metrics: Option<SystemMetrics>,
+    pub containers: Option<Vec<DockerContainer>>,
+    pub services: Option<Vec<ServiceInfo>>,
+    pub ufw: Option<UfwOverview>,
+    pub errors: std::collections::HashMap<String, String>,
+}
+
+/// Per-server outcome of `execute_command_multi`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiResult {
+    pub server_id: String,
+    pub output: Option<String>,
+    pub exit_status: Option<i32>,
+    /// The output hit the server's `max_output_bytes` and the rest was dropped
+    pub truncated: bool,
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,11 +77,37 @@ pub struct SavedServerProfile {
     pub port: u16,
     pub username: String,
     pub auth_method: AuthMethod,
+    #[serde(default)]
+    pub keepalive_secs: Option<u64>,
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub retry_attempts: Option<u32>,
+    #[serde(default)]
+    pub probe_interval_secs: Option<u64>,
+    #[serde(default)]
+    pub max_output_bytes: Option<u64>,
     pub created_at: u64,
     pub last_connected: Option<u64>,
     pub connect_on_startup: bool,
 }
 
+/// Payload of the `connection-lost` and `connection-restored` events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionEvent {
+    pub server_id: String,
+    /// Why the probe failed, or how the connection came back
+    pub message: String,
+}
+
+/// Outcome of connecting a `connect_on_startup` profile at launch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartupConnectResult {
+    pub profile: SavedServerProfile,
+    pub success: bool,
+    pub message: String,
+}
+
 impl From<ServerProfile> for SavedServerProfile {
     fn from(profile: ServerProfile) -> Self {
         SavedServerProfile {
@@ -32,6 +117,11 @@ impl From<ServerProfile> for SavedServerProfile {
             port: profile.port,
             username: profile.username,
             auth_method: profile.auth_method,
+            keepalive_secs: profile.keepalive_secs,
+            connect_timeout_secs: profile.connect_timeout_secs,
+            retry_attempts: profile.retry_attempts,
+            probe_interval_secs: profile.probe_interval_secs,
+            max_output_bytes: profile.max_output_bytes,
             created_at: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
@@ -51,6 +141,11 @@ impl From<SavedServerProfile> for ServerProfile {
             port: profile.port,
             username: profile.username,
             auth_method: profile.auth_method,
+            keepalive_secs: profile.keepalive_secs,
+            connect_timeout_secs: profile.connect_timeout_secs,
+            retry_attempts: profile.retry_attempts,
+            probe_interval_secs: profile.probe_interval_secs,
+            max_output_bytes: profile.max_output_bytes,
         }
     }
 }
@@ -75,6 +170,10 @@ pub struct SystemMetrics {
     pub cpu_percent: f64,
     pub memory_used: u64,
     pub memory_total: u64,
+    #[serde(default)]
+    pub swap_used: u64,
+    #[serde(default)]
+    pub swap_total: u64,
     pub disk_usage: Vec<DiskUsage>,
     pub load_avg: [f64; 3],
     pub uptime: u64,
@@ -83,6 +182,26 @@ pub struct SystemMetrics {
     pub cpu_history: Vec<f64>,
     pub memory_history: Vec<f64>,
     pub network_history: Vec<NetworkHistoryPoint>,
+    /// When the numbers were read from the server, in Unix milliseconds
+    #[serde(default)]
+    pub collected_at: u64,
+    /// Served from the last snapshot because the previous poll was too recent
+    #[serde(default)]
+    pub cached: bool,
+}
+
+/// How often metrics are polled. Polls arriving sooner get the previous snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    pub poll_interval_secs: u64,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        MetricsConfig {
+            poll_interval_secs: 5,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -109,6 +228,182 @@ pub struct DiskUsage {
     pub percent: f64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirUsage {
+    pub path: String,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilePermissions {
+    /// Four octal digits including the setuid/setgid/sticky one, e.g. "0644"
+    pub mode_octal: String,
+    /// As `ls -l` shows it, e.g. "-rw-r--r--"
+    pub symbolic: String,
+    pub owner: String,
+    pub group: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LargeFile {
+    pub path: String,
+    /// Size in bytes
+    pub size: u64,
+    /// Unix time of the last modification
+    pub modified: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumStatus {
+    Match,
+    Mismatch,
+    /// The file doesn't exist on the server
+    Missing,
+    /// The expected hash isn't a known digest, or the file couldn't be read
+    Error,
+}
+
+/// One `path -> hash` line of a manifest checked by `verify_checksums`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecksumResult {
+    pub path: String,
+    /// Picked from the length of the expected hash, `None` when it matches no algorithm
+    pub algorithm: Option<String>,
+    pub expected: String,
+    pub actual: Option<String>,
+    pub status: ChecksumStatus,
+    pub message: Option<String>,
+}
+
+/// Pool-level storage for copy-on-write and volume-managed setups that `df` misreports.
+/// Each subsystem is `None` when it isn't in use on the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdvancedStorage {
+    pub zfs: Option<ZfsStorage>,
+    pub btrfs: Option<Vec<BtrfsFilesystem>>,
+    pub lvm: Option<LvmStorage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZfsStorage {
+    pub pools: Vec<ZfsPool>,
+    pub datasets: Vec<ZfsDataset>,
+    pub snapshots: Vec<ZfsSnapshot>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZfsPool {
+    pub name: String,
+    pub size: u64,
+    pub allocated: u64,
+    pub free: u64,
+    pub fragmentation_percent: Option<u32>,
+    pub capacity_percent: u32,
+    pub health: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZfsDataset {
+    pub name: String,
+    pub used: u64,
+    pub available: u64,
+    pub referenced: u64,
+    pub mountpoint: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZfsSnapshot {
+    pub name: String,
+    pub used: u64,
+    pub referenced: u64,
+    /// Seconds since the epoch
+    pub created: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BtrfsFilesystem {
+    pub device: String,
+    pub mount_point: String,
+    pub allocations: Vec<BtrfsAllocation>,
+}
+
+/// One line of `btrfs filesystem df`, e.g. `Data, RAID1: total=..., used=...`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BtrfsAllocation {
+    pub kind: String,
+    pub profile: String,
+    pub total: u64,
+    pub used: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LvmStorage {
+    pub volume_groups: Vec<LvmVolumeGroup>,
+    pub logical_volumes: Vec<LvmLogicalVolume>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LvmVolumeGroup {
+    pub name: String,
+    pub size: u64,
+    pub free: u64,
+    pub lv_count: u32,
+    pub snapshot_count: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LvmLogicalVolume {
+    pub name: String,
+    pub vg_name: String,
+    pub size: u64,
+    /// Fill level of thin pools, thin volumes and snapshots
+    pub data_percent: Option<f64>,
+    /// `lv_attr` string; the first character tells the volume type
+    pub attributes: String,
+    pub origin: Option<String>,
+    pub pool: Option<String>,
+}
+
+/// Ordered by severity, so the worst check decides the overall status
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HealthStatus {
+    Ok,
+    Warn,
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthCheck {
+    pub name: String,
+    pub status: HealthStatus,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub checks: Vec<HealthCheck>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpuInfo {
+    pub index: u32,
+    pub name: String,
+    pub util_percent: f64,
+    pub mem_used_mb: u64,
+    pub mem_total_mb: u64,
+    pub temp_c: f64,
+    pub processes: Vec<GpuProcess>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpuProcess {
+    pub pid: u32,
+    pub process_name: String,
+    pub used_memory_mb: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DockerContainer {
     pub id: String,
@@ -120,6 +415,27 @@ pub struct DockerContainer {
     pub memory_usage: u64,
     pub memory_limit: u64,
     pub ports: Vec<PortMapping>,
+    /// Healthcheck status (`starting`, `healthy`, `unhealthy`); `None` without a HEALTHCHECK
+    pub health: Option<String>,
+    /// Killed by the kernel OOM killer: docker's OOMKilled flag, or an exit with 137
+    /// (SIGKILL), which a timed-out `docker stop` also produces
+    #[serde(default)]
+    pub oom_killed: bool,
+    /// CPU samples from recent container polls, oldest first
+    #[serde(default)]
+    pub cpu_history: Vec<f64>,
+    /// Memory samples as a percentage of the container's limit, oldest first
+    #[serde(default)]
+    pub mem_history: Vec<f64>,
+}
+
+/// A matching line from `search_container_logs`, numbered from the start of the searched tail
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogMatch {
+    pub line_number: u32,
+    pub line: String,
+    pub context_before: Vec<String>,
+    pub context_after: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -130,6 +446,294 @@ pub struct ServiceInfo {
     pub description: String,
 }
 
+/// Outcome of validating and reloading one service, see `reload_services`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReloadResult {
+    pub service: String,
+    /// The config check passed; `false` also when no check is known for the service
+    pub validated: bool,
+    pub reloaded: bool,
+    pub message: String,
+    /// What the check (or the failed reload) printed
+    pub output: String,
+}
+
+/// A unit in `systemctl list-dependencies` output and the units below it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnitDepTree {
+    pub unit: String,
+    /// `loaded`, `not-found`, `masked`, ...
+    pub load_state: String,
+    pub active_state: String,
+    pub sub_state: String,
+    pub children: Vec<UnitDepTree>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalBootRange {
+    pub first_boot_id: String,
+    pub last_boot_id: String,
+}
+
+/// Where `get_service_logs` found a service's logs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "path")]
+pub enum ServiceLogSource {
+    Journal,
+    File(String),
+    NotFound,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceLogResult {
+    pub source: ServiceLogSource,
+    pub lines: Vec<String>,
+    /// Boots covered by the returned entries, for journal sources
+    pub boot_range: Option<JournalBootRange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SocketEndpoint {
+    pub address: String,
+    /// `None` for a wildcard port (`*`)
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkConnection {
+    pub proto: String,
+    /// Socket state as ss names it (ESTAB, LISTEN, TIME-WAIT, UNCONN, ...)
+    pub state: String,
+    pub local: SocketEndpoint,
+    pub remote: SocketEndpoint,
+    pub pid: Option<u32>,
+    pub process: Option<String>,
+}
+
+/// One row of /proc/<pid>/limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceLimit {
+    /// As the kernel names it, e.g. "Max open files"
+    pub name: String,
+    /// A number or "unlimited"
+    pub soft: String,
+    pub hard: String,
+    pub units: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessOpenFiles {
+    pub count: u32,
+    /// The first descriptors as `fd -> target`, e.g. `4 -> socket:[31337]`
+    pub sample: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProblemProcess {
+    pub pid: u32,
+    pub ppid: u32,
+    pub command: String,
+    /// The parent's command, which is what has to reap a zombie
+    pub parent_command: Option<String>,
+    /// `ps` STAT column, e.g. `Z`, `D+`
+    pub state: String,
+    /// Open file descriptors, for `many_open_files` entries
+    pub open_files: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProblemProcesses {
+    pub zombies: Vec<ProblemProcess>,
+    /// Uninterruptible sleep, usually waiting on stuck I/O
+    pub uninterruptible: Vec<ProblemProcess>,
+    /// Most open files first
+    pub many_open_files: Vec<ProblemProcess>,
+    pub fd_threshold: u32,
+    /// Whether every process's descriptors were counted, or only the account's own
+    pub fd_counts_complete: bool,
+}
+
+/// A parsed `proxy_pass` target
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProxyTarget {
+    /// The argument as written
+    pub raw: String,
+    pub scheme: String,
+    /// Lowercased host or IP, without brackets; `None` for unix sockets and variables
+    pub host: Option<String>,
+    /// Explicit port, or the scheme's default
+    pub port: Option<u16>,
+    pub unix_socket: Option<String>,
+    /// The address comes from an nginx variable and can't be resolved statically
+    pub uses_variables: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SysctlEntry {
+    pub key: String,
+    pub value: String,
+}
+
+/// Clock settings as `timedatectl` reports them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeConfig {
+    pub timezone: String,
+    pub local_time: String,
+    pub utc_time: String,
+    pub ntp_enabled: bool,
+    pub ntp_synced: bool,
+    /// Active time sync daemon (`systemd-timesyncd`, `chrony`, `ntp`, ...), if any
+    pub ntp_service: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebootStatus {
+    /// The package manager asked for a reboot, or the running kernel isn't the newest one
+    pub required: bool,
+    /// Packages that asked for it, from /var/run/reboot-required.pkgs (Debian/Ubuntu)
+    pub packages: Vec<String>,
+    /// `uname -r`
+    pub running_kernel: String,
+    /// Newest kernel in /boot, `None` when their names carry no version
+    pub installed_kernel: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SecurityModuleKind {
+    AppArmor,
+    SELinux,
+    None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnforcementMode {
+    Enforcing,
+    /// AppArmor with every profile in complain mode
+    Complaining,
+    /// SELinux logging denials without enforcing them
+    Permissive,
+    Disabled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityModule {
+    pub kind: SecurityModuleKind,
+    pub mode: EnforcementMode,
+    /// AppArmor profiles or SELinux policy modules; 0 when they can't be read without root
+    pub profiles_loaded: u32,
+    /// Loaded AppArmor profiles, empty for SELinux
+    pub profiles: Vec<AppArmorProfile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppArmorProfile {
+    pub name: String,
+    /// `enforce`, `complain`, `kill` or `unconfined`
+    pub mode: String,
+}
+
+/// A kernel ring buffer message from `dmesg`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DmesgEntry {
+    /// ISO 8601, or seconds since boot (`1234.567890`) where dmesg can't print ISO times
+    pub timestamp: String,
+    /// `emerg`, `alert`, `crit`, `err`, `warn`, `notice`, `info` or `debug`
+    pub level: String,
+    pub message: String,
+}
+
+/// One mount line of `/etc/fstab`. Fields keep fstab's escaping (`\040` for a space).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FstabEntry {
+    /// `/dev/sdb1`, `UUID=...`, `LABEL=...`, `server:/export`, ...
+    pub device: String,
+    pub mountpoint: String,
+    pub fstype: String,
+    pub options: String,
+    #[serde(default)]
+    pub dump: u32,
+    #[serde(default)]
+    pub pass: u32,
+}
+
+/// An active swap area from `/proc/swaps`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwapDevice {
+    pub name: String,
+    /// `file` or `partition`
+    #[serde(rename = "type")]
+    pub swap_type: String,
+    pub size_bytes: u64,
+    pub used_bytes: u64,
+    pub priority: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FstabSaveResult {
+    pub message: String,
+    pub entries: Vec<FstabEntry>,
+    /// Missing devices or mount points found before writing; fstab is left as it was when any are
+    pub problems: Vec<String>,
+    /// Whether `mount -a` ran and succeeded, `None` when it wasn't requested or was skipped
+    pub mounted: Option<bool>,
+    pub mount_output: Option<String>,
+    /// `mount -a` failed and the previous fstab was put back
+    pub rolled_back: bool,
+}
+
+/// What `stat` reports about a remote path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteStat {
+    pub size: u64,
+    /// Permission bits in octal, as `stat` prints them ("644")
+    pub mode: String,
+    pub uid: u32,
+    pub gid: u32,
+    pub is_dir: bool,
+    /// Last modification, in Unix seconds
+    pub modified: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PingResult {
+    pub target: String,
+    pub packets_sent: u32,
+    pub packets_received: u32,
+    /// Average round trip, absent when nothing came back
+    pub avg_ms: Option<f64>,
+    pub loss_percent: f64,
+    /// Had to be run through sudo because unprivileged ping isn't allowed
+    pub used_sudo: bool,
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalDiskUsage {
+    /// Archived and active journal files combined
+    pub bytes: u64,
+    /// Timestamp of the oldest entry still in the journal, ISO 8601
+    pub oldest_entry: Option<String>,
+    /// Journal files `journalctl --verify` reported as corrupt
+    pub corrupt_files: Vec<String>,
+}
+
+/// What `vacuum_journal` keeps: the newest entries up to a total size, or entries
+/// younger than a number of days
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum VacuumSpec {
+    Size { max_bytes: u64 },
+    Time { max_age_days: u32 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalVacuumResult {
+    pub freed_bytes: u64,
+    pub deleted_files: Vec<String>,
+    pub output: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct LogEntry {
@@ -155,10 +759,61 @@ pub struct LoginAttempt {
     pub success: bool,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum StageStatus {
+    Ok,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionStage {
+    /// `tcp`, `handshake` or `auth`
+    pub name: String,
+    pub status: StageStatus,
+    pub duration_ms: Option<u64>,
+    pub error: Option<String>,
+    pub error_code: Option<i32>,
+}
+
+/// A fingerprint of the connected server's host key, to compare against one obtained
+/// out-of-band
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostKeyFingerprint {
+    /// Key type as named in the key itself, e.g. `ssh-ed25519`
+    pub algorithm: String,
+    /// `SHA256:<base64>`
+    pub sha256: String,
+    /// `MD5:aa:bb:...`
+    pub md5: String,
+}
+
+/// Per-stage outcome of a connection attempt, see `diagnose_connection`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionDiagnosis {
+    pub success: bool,
+    pub failed_stage: Option<String>,
+    pub stages: Vec<ConnectionStage>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectionResult {
     pub success: bool,
     pub message: String,
+    /// `CommandError::code` of the failure, so the UI can tell timeouts from refusals
+    pub error_code: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionHealth {
+    pub connected: bool,
+    /// True only if a round-trip command actually succeeded on the session
+    pub authenticated: bool,
+    pub latency_ms: Option<u64>,
+    pub cipher: Option<String>,
+    pub kex: Option<String>,
+    pub reconnect_count: u32,
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -167,12 +822,28 @@ pub struct CommandError {
     pub code: i32,
 }
 
+impl CommandError {
+    /// The host didn't answer the TCP connect or SSH handshake in time
+    pub const CONNECTION_TIMED_OUT: i32 = -2;
+    /// The host actively refused the TCP connection
+    pub const CONNECTION_REFUSED: i32 = -3;
+}
+
 impl From<String> for CommandError {
     fn from(message: String) -> Self {
         CommandError { message, code: -1 }
     }
 }
 
+/// Stdout of a command read under the profile's `max_output_bytes` limit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandOutput {
+    pub output: String,
+    /// -1 when the output was truncated, since the command is abandoned mid-run
+    pub exit_status: i32,
+    pub truncated: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UfwStatus {
     pub active: bool,
@@ -183,11 +854,77 @@ pub struct UfwStatus {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UfwRule {
+    /// The destination column without its interface and `(v6)` markers
     pub rule: String,
+    /// Destination address, `Anywhere` when the rule only names a port or application
     pub to: String,
+    /// `ALLOW`, `DENY`, `REJECT` or `LIMIT`
     pub action: String,
+    pub direction: UfwDirection,
     pub from: String,
+    /// Port, port range or application profile, without the protocol
     pub port: Option<String>,
+    pub interface_in: Option<String>,
+    pub interface_out: Option<String>,
+    pub ip_version: IpVersion,
+    /// `ufw status numbered` positions of the lines merged into this rule
+    pub rule_numbers: Vec<u32>,
+}
+
+/// Traffic a UFW rule matches; `Fwd` is a `ufw route` rule for forwarded packets
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum UfwDirection {
+    In,
+    Out,
+    Fwd,
+}
+
+impl UfwDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UfwDirection::In => "IN",
+            UfwDirection::Out => "OUT",
+            UfwDirection::Fwd => "FWD",
+        }
+    }
+}
+
+/// Address family a UFW rule applies to; `Both` when an IPv4 rule has an identical `(v6)` twin.
+/// Also used for listening sockets, where `Both` is a dual-stack `*` bind.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum IpVersion {
+    V4,
+    V6,
+    Both,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Ipv6Coverage {
+    /// `IPV6=` in /etc/default/ufw, `None` when it couldn't be read
+    pub ipv6_enabled: Option<bool>,
+    pub v4_only_rules: u32,
+    pub v6_only_rules: u32,
+    pub dual_stack_rules: u32,
+    /// Rules open to anyone over IPv4 without an IPv6 counterpart
+    pub v4_only_targets: Vec<String>,
+    pub warning: Option<String>,
+}
+
+/// Outcome of a state-changing command. `success` comes from the exit status, so a
+/// command that printed warnings but worked still counts as a success.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionResult {
+    pub success: bool,
+    pub message: String,
+    pub raw_output: String,
+}
+
+/// A firewall rule as it would be applied: the exact command and a readable summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UfwRulePreview {
+    pub command: String,
+    pub description: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -205,6 +942,23 @@ pub struct PortInfo {
     pub action: String,
     pub source: String,
     pub service_name: Option<String>,
+    /// The rest are only filled in for listening sockets
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub ip_version: Option<IpVersion>,
+    #[serde(default)]
+    pub pid: Option<u32>,
+    /// Every process holding the socket: nginx workers, or docker-proxy for a published
+    /// container port. Empty when ss couldn't see the owner without root.
+    #[serde(default)]
+    pub processes: Vec<SocketProcess>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SocketProcess {
+    pub name: String,
+    pub pid: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -214,6 +968,76 @@ pub struct UfwOverview {
     pub blocked_ports: Vec<PortInfo>,
     pub all_rules: Vec<UfwRule>,
     pub stats: UfwStats,
+    /// Published container ports, which Docker exposes past UFW unless it is patched
+    #[serde(default)]
+    pub docker_exposures: Vec<DockerExposure>,
+    #[serde(default)]
+    pub ipv6: Ipv6Coverage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DockerExposure {
+    pub container: String,
+    pub host_ip: String,
+    pub host_port: u16,
+    pub container_port: u16,
+    pub protocol: String,
+    /// Action of the UFW rule covering this port, if any (e.g. "DENY")
+    pub ufw_action: Option<String>,
+    /// Reachable from outside regardless of what UFW says
+    pub bypasses_ufw: bool,
+    pub warning: Option<String>,
+    pub suggestion: Option<String>,
+}
+
+/// Which netfilter frontend `get_firewall_raw` read the rules through
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FirewallBackend {
+    Iptables,
+    Nftables,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FirewallDump {
+    pub backend: FirewallBackend,
+    pub chains: Vec<FirewallChain>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FirewallChain {
+    /// `filter`, `nat`, ... for iptables; family and name (`inet filter`) for nftables
+    pub table: String,
+    pub name: String,
+    /// Default verdict of a built-in or base chain; `None` for chains that are only jumped to
+    pub policy: Option<String>,
+    /// Created by Docker (DOCKER, DOCKER-USER, DOCKER-ISOLATION-STAGE-1, ...)
+    pub docker: bool,
+    pub rules: Vec<FirewallRule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FirewallRule {
+    /// As printed, without iptables' leading `-A <chain>`
+    pub rule: String,
+    /// Verdict or the chain jumped to (ACCEPT, DOCKER-USER, drop, ...)
+    pub target: Option<String>,
+}
+
+/// Hardening advice for a container port published on every interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuggestedRule {
+    pub container: String,
+    pub host_port: u16,
+    pub container_port: u16,
+    pub protocol: String,
+    /// `allow_from_source` when a UFW rule can limit who reaches the port, `world_exposed`
+    /// when UFW can't filter it at all
+    pub kind: String,
+    /// Address the suggested rule admits
+    pub source: Option<String>,
+    pub message: String,
+    /// The exact command `apply_suggested_rule` runs; `None` for warnings
+    pub command: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -238,6 +1062,33 @@ pub struct ContainerDetails {
     pub cpu_limit: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ContainerSource {
+    /// Created by `docker compose`, per its `com.docker.compose.*` labels
+    Compose,
+    /// Started by hand with `docker run` or through the API
+    Standalone,
+}
+
+/// How a container was created, linking compose containers back to their project files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerProvenance {
+    pub source: ContainerSource,
+    pub project: Option<String>,
+    pub service: Option<String>,
+    /// Compose files the project was started from, as absolute paths on the server
+    pub config_files: Vec<String>,
+    /// Entries of `config_files` that no longer exist on disk
+    pub missing_files: Vec<String>,
+    /// Entries of `config_files` that couldn't be checked: the connecting user can't see
+    /// them and sudo needs a password
+    pub unchecked_files: Vec<String>,
+    pub working_dir: Option<String>,
+    /// Started by `docker compose run` rather than `up`
+    pub one_off: bool,
+    pub compose_version: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PortMapping {
     pub host_ip: String,
@@ -268,6 +1119,44 @@ pub struct DockerVolume {
     pub labels: Vec<Label>,
 }
 
+/// A Swarm secret; its value can't be read back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DockerSecret {
+    pub id: String,
+    pub name: String,
+    pub created: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DockerConfig {
+    pub id: String,
+    pub name: String,
+    pub created: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerLogs {
+    pub logs: String,
+    /// The `docker logs` flags used, e.g. `--tail=100`, `--since=2026-10-16T09:00:00Z`
+    pub flags: Vec<String>,
+}
+
+/// A `docker exec` session in a container, running or recently finished
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecSession {
+    pub id: String,
+    /// Entrypoint and arguments, joined with spaces
+    pub command: String,
+    pub running: bool,
+    /// Host PID of the exec'd process while it runs
+    pub pid: Option<u32>,
+    pub exit_code: Option<i64>,
+    /// `--user` if one was given; otherwise the container's default user
+    pub user: Option<String>,
+    pub tty: bool,
+    pub privileged: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DockerNetwork {
     pub id: String,
@@ -289,6 +1178,84 @@ pub struct DockerImage {
     pub architecture: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FsChangeKind {
+    Added,
+    Changed,
+    Deleted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FsChange {
+    pub kind: FsChangeKind,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerDiff {
+    pub changes: Vec<FsChange>,
+    /// Number of changes reported by `docker diff` before capping
+    pub total: usize,
+    pub truncated: bool,
+}
+
+/// What the connected account may do through sudo, from `sudo -n -l`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SudoAccess {
+    /// Whether the `sudo` binary exists at all
+    pub installed: bool,
+    /// The account is root and needs no sudo
+    pub is_root: bool,
+    /// Listed in sudoers, or in a sudo/wheel/admin group when the listing needs a password
+    pub can_sudo: bool,
+    /// Can run any command without a password, which DPanel's actions rely on
+    pub passwordless: bool,
+    /// Rules from `sudo -l`, e.g. `(ALL : ALL) NOPASSWD: ALL`
+    pub allowed_commands: Vec<String>,
+    /// Explanation for the UI when `passwordless` is false
+    pub warning: Option<String>,
+}
+
+/// How DPanel reaches the Docker daemon on a server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DockerContextInfo {
+    pub available: bool,
+    /// `direct`, `env` (a `DOCKER_HOST` override), `sudo` or `unavailable`
+    pub invocation: String,
+    pub socket: Option<String>,
+    pub rootless: bool,
+    pub version: Option<String>,
+    pub context_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DockerEvent {
+    pub action: String,
+    pub actor_id: String,
+    pub actor_name: Option<String>,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub time: i64,
+}
+
+/// One entry from `journalctl -o json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Microseconds since the epoch
+    pub timestamp: i64,
+    pub unit: Option<String>,
+    pub priority: Option<u8>,
+    pub message: String,
+}
+
+/// Emitted when a background stream ends without being stopped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamEnded {
+    pub stream: String,
+    pub server_id: String,
+    pub reason: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComposeProject {
     pub name: String,
@@ -297,6 +1264,47 @@ pub struct ComposeProject {
     pub content: String,
 }
 
+/// The on-disk compose discovery cache for one server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheInfo {
+    /// Unix time of the last scan, `None` when nothing is cached
+    pub last_scan: Option<u64>,
+    pub age_secs: Option<u64>,
+    /// Older than the cache TTL, so the next lookup rescans
+    pub expired: bool,
+    pub project_count: usize,
+    pub scan_paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposeConfigError {
+    pub message: String,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+}
+
+/// Result of `docker compose config` on a saved file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposeValidation {
+    pub ok: bool,
+    pub errors: Vec<ComposeConfigError>,
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposeSaveResult {
+    pub message: String,
+    pub validation: ComposeValidation,
+    /// The previous file was restored because the new content failed validation
+    pub rolled_back: bool,
+    pub redeployed: bool,
+    /// Output of `docker compose up -d`, when a redeploy was requested
+    pub redeploy_output: Option<String>,
+    /// What the save changed compared to the `.bak` backup; empty after a rollback
+    #[serde(default)]
+    pub diff: Vec<DiffLine>,
+}
+
 // ==================== USER MANAGEMENT TYPES ====================
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -321,12 +1329,16 @@ pub struct SystemGroup {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
 pub struct SSHKey {
+    /// Position among the non-empty lines of authorized_keys, as `delete_ssh_key` counts them
+    pub index: usize,
     pub key_type: String,
     pub key_data: String,
     pub comment: String,
+    /// `SHA256:...`, empty when `ssh-keygen` couldn't read the key
     pub fingerprint: String,
+    /// Restrictions in front of the key, e.g. `from="10.0.0.0/8"` or `no-pty`
+    pub options: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -346,6 +1358,29 @@ pub struct UserDetail {
     pub ssh_keys: Vec<SSHKey>,
 }
 
+/// What a user consumes on the host; each probe is `None` when it couldn't run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserResourceUsage {
+    pub username: String,
+    pub process_count: Option<u32>,
+    pub home_bytes: Option<u64>,
+    /// `None` when quota tools aren't installed or quotas aren't enabled
+    pub quotas: Option<Vec<UserQuota>>,
+}
+
+/// One filesystem line of `quota -u`; limits of 0 mean unlimited
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserQuota {
+    pub filesystem: String,
+    pub used_bytes: u64,
+    pub soft_limit_bytes: u64,
+    pub hard_limit_bytes: u64,
+    pub files: u64,
+    pub file_soft_limit: u64,
+    pub file_hard_limit: u64,
+    pub over_limit: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateUserRequest {
     pub username: String,
@@ -356,6 +1391,13 @@ pub struct CreateUserRequest {
     pub create_home: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkUserResult {
+    pub username: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct ModifyUserRequest {
@@ -375,6 +1417,157 @@ pub struct NginxStatus {
     pub config_test: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigMessage {
+    pub level: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigTestResult {
+    pub ok: bool,
+    pub errors: Vec<ConfigMessage>,
+    pub warnings: Vec<ConfigMessage>,
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NginxSaveResult {
+    pub message: String,
+    pub test: ConfigTestResult,
+    /// The previous file was restored because the new content failed validation
+    pub rolled_back: bool,
+    /// Re-test run after restoring, confirming the server is back in a good state
+    pub rollback_test: Option<ConfigTestResult>,
+    /// What the save changed compared to the `.bak` backup; empty after a rollback
+    #[serde(default)]
+    pub diff: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DiffLineKind {
+    /// `@@ -a,b +c,d @@` header starting a hunk
+    Hunk,
+    Context,
+    Added,
+    Removed,
+}
+
+/// One block of a file in /etc/logrotate.d
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogrotateConfig {
+    pub file: String,
+    /// Log paths or globs the block applies to
+    pub paths: Vec<String>,
+    pub rotate_count: Option<u32>,
+    /// daily, weekly, ...; `None` inherits the global default
+    pub frequency: Option<String>,
+    pub compress: bool,
+    /// Rotate once the log reaches this size, as written (e.g. "100M")
+    pub size: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogrotateSaveResult {
+    /// False when `logrotate -d` rejected the content and the file was left unchanged
+    pub saved: bool,
+    pub message: String,
+    /// The dry run, in the same shape as an `nginx -t` result
+    pub test: ConfigTestResult,
+    pub diff: Vec<DiffLine>,
+}
+
+/// One directive of sshd_config or a file it includes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SshdDirective {
+    /// As written; sshd itself matches keywords case-insensitively
+    pub key: String,
+    pub value: String,
+    pub file: String,
+    pub line: u32,
+    /// Criteria of the `Match` block the directive sits in; `None` applies to every connection
+    pub match_block: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SshdSaveResult {
+    /// Nothing was changed: the directive can lock users out and the change wasn't confirmed
+    pub requires_confirmation: bool,
+    /// Lockout risk of the directive, set whenever the change needs confirmation
+    pub warning: Option<String>,
+    /// The file holding the effective setting, which is the one edited
+    pub file: Option<String>,
+    /// Backup, `sshd -t` and rollback outcome; `None` when nothing was written
+    pub save: Option<NginxSaveResult>,
+    pub reloaded: bool,
+    pub message: String,
+}
+
+/// One line of a unified diff, numbered on the side(s) it appears on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub old_line: Option<usize>,
+    pub new_line: Option<usize>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NginxEditResult {
+    pub message: String,
+    /// Unified diff of every file that was changed
+    pub diff: String,
+    /// Outcome of the last save, `None` when nothing needed to change
+    pub save: Option<NginxSaveResult>,
+    pub reloaded: bool,
+}
+
+/// A tarball of /etc/nginx kept on the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NginxSnapshot {
+    /// Archive name without `.tar.gz`, which is `<created_at>-<label>`
+    pub id: String,
+    /// The label given when it was taken, reduced to letters, digits, `-` and `_`
+    pub label: String,
+    /// Unix time on the server
+    pub created_at: u64,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NginxRestoreResult {
+    pub message: String,
+    /// `nginx -t` of the restored config
+    pub test: ConfigTestResult,
+    /// The restored config failed its test and the previous one was put back
+    pub rolled_back: bool,
+    pub rollback_test: Option<ConfigTestResult>,
+    pub reloaded: bool,
+}
+
+/// A `{ ... }` block of an nginx config, e.g. `server`, `location /api` or `if ($x)`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NginxBlock {
+    pub kind: String,
+    /// Arguments after the block keyword, such as a location's path or an upstream's name
+    pub name: String,
+    /// 1-based lines of the opening statement and of the closing `}`
+    pub start_line: usize,
+    pub end_line: usize,
+    pub directives: Vec<NginxDirective>,
+    pub children: Vec<NginxBlock>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NginxDirective {
+    pub name: String,
+    pub args: String,
+    /// 1-based line the directive starts on
+    pub line: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NginxVhost {
     pub name: String,
@@ -385,10 +1578,50 @@ pub struct NginxVhost {
     pub root_path: String,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VhostEnableOutcome {
+    /// The link was already there and pointed at sites-available; nothing was reloaded
+    AlreadyEnabled,
+    /// Linked, tested and reloaded
+    Enabled,
+    /// sites-enabled holds a symlink to some other file, which was left alone
+    TargetMismatch,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VhostEnableResult {
+    pub outcome: VhostEnableOutcome,
+    pub message: String,
+    /// What the sites-enabled symlink points to, as `readlink` prints it
+    pub link_target: Option<String>,
+}
+
+/// Outcome of a test request to a vhost from the server itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VhostTestResult {
+    pub vhost: String,
+    pub url: String,
+    /// `Host` header (or TLS server name) the request was sent with
+    pub server_name: String,
+    /// `None` when no HTTP response came back
+    pub status_code: Option<u16>,
+    pub time_total_secs: Option<f64>,
+    /// The `proxy_pass` target of `location /`, when the site proxies
+    pub expected_upstream: Option<String>,
+    /// `false` when nginx answered 502/503/504 for a proxied site; `None` when the site
+    /// doesn't proxy or nginx didn't answer
+    pub upstream_ok: Option<bool>,
+    /// What curl printed on failure
+    pub error: Option<String>,
+}
+
 // Cron types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CronJob {
     pub id: usize,
+    /// 1-based line in the source crontab, used to address the job for edits
+    pub line_number: usize,
     pub schedule: String,
     pub command: String,
     pub user: String,
@@ -403,6 +1636,31 @@ pub struct CronFolder {
     pub scripts: Vec<String>,
 }
 
+/// Contents of /etc/cron.allow and /etc/cron.deny, `None` for a file that doesn't exist.
+/// When cron.allow exists only the users in it may use `crontab` and cron.deny is ignored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CronAccess {
+    pub allow: Option<Vec<String>>,
+    pub deny: Option<Vec<String>>,
+}
+
+/// A one-shot job queued with `at`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AtJob {
+    pub id: u32,
+    /// As `atq` prints it, in the server's local time
+    pub scheduled_for: String,
+    /// Queue letter; `=` while the job is running
+    pub queue: String,
+    pub user: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AtJobScheduled {
+    pub job_id: u32,
+    pub scheduled_for: String,
+}
+
 // ==================== INFRASTRUCTURE GRAPH TYPES ====================
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -422,6 +1680,7 @@ pub enum InfraGraphNodeType {
     Vhost,
     HostPort,
     Container,
+    Database,
     DockerNetwork,
     HostNetwork,
 }
@@ -433,9 +1692,30 @@ pub enum NodeStatus {
     Stopped,
     Healthy,
     Unhealthy,
+    /// Running, but over a `GraphThresholds` limit or failing its healthcheck
+    Warning,
+    /// Restarting or killed for running out of memory
+    Critical,
     Unknown,
 }
 
+/// Container usage above which the infrastructure graph marks a node as a warning
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphThresholds {
+    pub cpu_percent: f64,
+    /// Memory usage as a percentage of the container's limit
+    pub memory_percent: f64,
+}
+
+impl Default for GraphThresholds {
+    fn default() -> Self {
+        GraphThresholds {
+            cpu_percent: 80.0,
+            memory_percent: 90.0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InfraGraphEdge {
     pub source: String,
@@ -462,3 +1742,120 @@ pub struct InfraSummary {
     pub total_volumes: usize,
     pub total_networks: usize,
 }
+
+// ==================== CONFIG GRAPH TYPES ====================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigFileType {
+    Json,
+    Toml,
+    Yaml,
+    Yml,
+    Ts,
+    Js,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigFile {
+    pub path: String,
+    pub file_type: ConfigFileType,
+    pub size: u64,
+    pub modified: u64,
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphNodeType {
+    File,
+    Module,
+    Environment,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub id: String,
+    pub label: String,
+    pub node_type: GraphNodeType,
+    pub metadata: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    pub edge_type: String,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphData {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageLocation {
+    pub file: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub context: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigSearchResult {
+    pub key: String,
+    pub file: String,
+    pub value: Option<String>,
+    pub usages: Vec<UsageLocation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigChangeKind {
+    Created,
+    Modified,
+    Removed,
+    Renamed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigFileChange {
+    pub path: String,
+    pub kind: ConfigChangeKind,
+}
+
+/// A package pinned by a manifest (`package.json` or `Cargo.toml`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestDependency {
+    pub name: String,
+    /// Section the dependency came from, e.g. `dependencies`, `devDependencies`, `dev-dependencies`
+    pub section: String,
+    pub version: VersionSpec,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionKind {
+    Exact,
+    Caret,
+    Tilde,
+    Range,
+    Any,
+    Tag,
+    Git,
+    Path,
+    Workspace,
+    Url,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionSpec {
+    /// The requirement exactly as written in the manifest
+    pub raw: String,
+    pub kind: VersionKind,
+    /// Bare version number when one can be extracted (`^1.2.3` -> `1.2.3`)
+    pub version: Option<String>,
+}
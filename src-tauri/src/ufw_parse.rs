@@ -0,0 +1,309 @@
+//! Parsing the rule table of `ufw status [verbose|numbered]`.
+//!
+//! Each line holds a destination, an action with its direction and a source. UFW pads
+//! the destination to 26 columns but separates it from the action by a single space
+//! once it's longer, so lines are split on the action keyword rather than on runs of
+//! spaces. Either address can carry an `on <iface>` suffix and a `(v6)` marker, and the
+//! destination may combine an address with a port (`10.0.0.5 443/tcp`) or name an
+//! application profile (`Nginx Full`).
+
+use crate::types::{IpVersion, UfwDirection, UfwRule};
+use std::net::IpAddr;
+
+const ACTIONS: &[&str] = &["ALLOW", "DENY", "REJECT", "LIMIT"];
+const ANYWHERE: &str = "Anywhere";
+
+/// Parse every rule line below the `--` header separator.
+///
+/// UFW lists every IPv6 rule a second time with a `(v6)` marker; an IPv4 rule whose
+/// twin is present is reported once as `IpVersion::Both`, carrying both rule numbers.
+pub fn parse_ufw_rules(status_output: &str) -> Vec<UfwRule> {
+    let mut rules: Vec<UfwRule> = Vec::new();
+    let rule_lines = status_output
+        .lines()
+        .skip_while(|l| !l.trim_start().starts_with("--"))
+        .skip(1)
+        .filter(|l| !l.trim().is_empty());
+
+    for (idx, line) in rule_lines.enumerate() {
+        let (number, line) = strip_rule_number(line);
+        let Some(parsed) = parse_rule_line(line) else {
+            continue;
+        };
+        let number = number.unwrap_or(idx as u32 + 1);
+
+        if parsed.v6 {
+            let twin = rules
+                .iter_mut()
+                .find(|r| r.ip_version == IpVersion::V4 && same_rule(r, &parsed.rule));
+            if let Some(twin) = twin {
+                twin.ip_version = IpVersion::Both;
+                twin.rule_numbers.push(number);
+                continue;
+            }
+        }
+
+        let mut rule = parsed.rule;
+        rule.ip_version = if parsed.v6 {
+            IpVersion::V6
+        } else {
+            IpVersion::V4
+        };
+        rule.rule_numbers = vec![number];
+        rules.push(rule);
+    }
+    rules
+}
+
+struct ParsedLine {
+    rule: UfwRule,
+    v6: bool,
+}
+
+/// One address column split into its parts
+struct Endpoint {
+    text: String,
+    address: String,
+    port: Option<String>,
+    interface: Option<String>,
+    v6: bool,
+}
+
+/// `[ 3] 22/tcp ...` from `ufw status numbered`
+fn strip_rule_number(line: &str) -> (Option<u32>, &str) {
+    let trimmed = line.trim_start();
+    if let Some(rest) = trimmed.strip_prefix('[') {
+        if let Some((number, rest)) = rest.split_once(']') {
+            if let Ok(number) = number.trim().parse() {
+                return (Some(number), rest);
+            }
+        }
+    }
+    (None, line)
+}
+
+fn parse_rule_line(line: &str) -> Option<ParsedLine> {
+    let line = match line.find(" # ") {
+        Some(pos) => &line[..pos],
+        None => line,
+    };
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    // The destination is never empty, so the action can't be the first token
+    let action_idx = tokens.iter().skip(1).position(|t| ACTIONS.contains(t))? + 1;
+
+    let mut rest = &tokens[action_idx + 1..];
+    let direction = match rest.first() {
+        Some(&"OUT") => UfwDirection::Out,
+        Some(&"FWD") => UfwDirection::Fwd,
+        _ => UfwDirection::In,
+    };
+    if matches!(rest.first(), Some(&"IN" | &"OUT" | &"FWD")) {
+        rest = &rest[1..];
+    }
+    let source: Vec<&str> = rest
+        .iter()
+        .filter(|t| !matches!(**t, "(log)" | "(log-all)"))
+        .copied()
+        .collect();
+
+    let destination = parse_endpoint(&tokens[..action_idx]);
+    let source = parse_endpoint(&source);
+
+    // Route rules show the outgoing interface with the destination; other rules show
+    // the incoming one there and the outgoing one with the source
+    let (interface_in, interface_out) = if direction == UfwDirection::Fwd {
+        (source.interface, destination.interface)
+    } else {
+        (destination.interface, source.interface)
+    };
+
+    Some(ParsedLine {
+        v6: destination.v6 || source.v6,
+        rule: UfwRule {
+            rule: destination.text,
+            to: destination.address,
+            action: tokens[action_idx].to_string(),
+            direction,
+            from: source.text,
+            port: destination.port,
+            interface_in,
+            interface_out,
+            ip_version: IpVersion::V4,
+            rule_numbers: Vec::new(),
+        },
+    })
+}
+
+fn parse_endpoint(tokens: &[&str]) -> Endpoint {
+    let mut v6 = false;
+    let mut interface = None;
+    let mut words: Vec<&str> = Vec::new();
+    let mut iter = tokens.iter().copied().peekable();
+    while let Some(token) = iter.next() {
+        if token == "(v6)" {
+            v6 = true;
+        } else if token == "on" && !words.is_empty() && iter.peek().is_some() {
+            interface = iter.next().map(String::from);
+        } else {
+            words.push(token);
+        }
+    }
+
+    let text = if words.is_empty() {
+        ANYWHERE.to_string()
+    } else {
+        words.join(" ")
+    };
+    let (address, port_spec) = match words.first() {
+        Some(first) if is_address(first) => (first.to_string(), words[1..].join(" ")),
+        _ => (ANYWHERE.to_string(), words.join(" ")),
+    };
+    let port = port_spec
+        .split('/')
+        .next()
+        .filter(|p| !p.is_empty())
+        .map(String::from);
+
+    Endpoint {
+        text,
+        address,
+        port,
+        interface,
+        v6,
+    }
+}
+
+fn is_address(token: &str) -> bool {
+    if token == ANYWHERE {
+        return true;
+    }
+    let address = token.split_once('/').map_or(token, |(address, _)| address);
+    address.parse::<IpAddr>().is_ok()
+}
+
+/// Whether a `(v6)` line describes the same rule as an IPv4 one
+fn same_rule(v4: &UfwRule, v6: &UfwRule) -> bool {
+    v4.rule == v6.rule
+        && v4.to == v6.to
+        && v4.action == v6.action
+        && v4.direction == v6.direction
+        && v4.from == v6.from
+        && v4.interface_in == v6.interface_in
+        && v4.interface_out == v6.interface_out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERBOSE_FIXTURE: &str = "Status: active\n\
+        Logging: on (low)\n\
+        Default: deny (incoming), allow (outgoing), deny (routed)\n\
+        New profiles: skip\n\
+        \n\
+        To                         Action      From\n\
+        --                         ------      ----\n\
+        22/tcp                     LIMIT IN    Anywhere\n\
+        10.0.0.5 443/tcp           ALLOW IN    192.168.1.0/24             # admin\n\
+        Nginx Full                 ALLOW IN    Anywhere\n\
+        3306 on eth1               ALLOW IN    10.0.0.0/8\n\
+        Anywhere on wg0            ALLOW IN    Anywhere\n\
+        53                         ALLOW OUT   Anywhere on eth0\n\
+        192.168.100.200 8080:8090/tcp ALLOW IN    Anywhere\n\
+        172.17.0.2 5432/tcp        ALLOW FWD   10.8.0.0/24\n\
+        Anywhere on eth1           ALLOW FWD   Anywhere on eth0\n\
+        22/tcp (v6)                LIMIT IN    Anywhere (v6)\n\
+        Nginx Full (v6)            ALLOW IN    Anywhere (v6)\n\
+        Anywhere (v6) on eth1      ALLOW FWD   Anywhere (v6) on eth0\n";
+
+    #[test]
+    fn test_parse_rule_variants() {
+        let rules = parse_ufw_rules(VERBOSE_FIXTURE);
+        assert_eq!(rules.len(), 9);
+
+        assert_eq!(rules[0].action, "LIMIT");
+        assert_eq!(rules[0].direction, UfwDirection::In);
+        assert_eq!(rules[0].port.as_deref(), Some("22"));
+        assert_eq!(rules[0].ip_version, IpVersion::Both);
+        assert_eq!(rules[0].rule_numbers, vec![1, 10]);
+
+        assert_eq!(rules[1].rule, "10.0.0.5 443/tcp");
+        assert_eq!(rules[1].to, "10.0.0.5");
+        assert_eq!(rules[1].port.as_deref(), Some("443"));
+        assert_eq!(rules[1].from, "192.168.1.0/24");
+
+        assert_eq!(rules[2].to, ANYWHERE);
+        assert_eq!(rules[2].port.as_deref(), Some("Nginx Full"));
+        assert_eq!(rules[2].ip_version, IpVersion::Both);
+
+        // Only a single space separates the long destination from the action
+        assert_eq!(rules[6].to, "192.168.100.200");
+        assert_eq!(rules[6].port.as_deref(), Some("8080:8090"));
+        assert_eq!(rules[6].action, "ALLOW");
+    }
+
+    #[test]
+    fn test_parse_interface_scoped_rules() {
+        let rules = parse_ufw_rules(VERBOSE_FIXTURE);
+
+        assert_eq!(rules[3].rule, "3306");
+        assert_eq!(rules[3].interface_in.as_deref(), Some("eth1"));
+        assert_eq!(rules[3].interface_out, None);
+
+        assert_eq!(rules[4].rule, ANYWHERE);
+        assert_eq!(rules[4].port, None);
+        assert_eq!(rules[4].interface_in.as_deref(), Some("wg0"));
+
+        assert_eq!(rules[5].direction, UfwDirection::Out);
+        assert_eq!(rules[5].from, ANYWHERE);
+        assert_eq!(rules[5].interface_in, None);
+        assert_eq!(rules[5].interface_out.as_deref(), Some("eth0"));
+    }
+
+    #[test]
+    fn test_parse_routed_rules() {
+        let rules = parse_ufw_rules(VERBOSE_FIXTURE);
+
+        let container = &rules[7];
+        assert_eq!(container.direction, UfwDirection::Fwd);
+        assert_eq!(container.to, "172.17.0.2");
+        assert_eq!(container.port.as_deref(), Some("5432"));
+        assert_eq!(container.from, "10.8.0.0/24");
+        assert_eq!(container.ip_version, IpVersion::V4);
+
+        let bridge = &rules[8];
+        assert_eq!(bridge.direction, UfwDirection::Fwd);
+        assert_eq!(bridge.interface_in.as_deref(), Some("eth0"));
+        assert_eq!(bridge.interface_out.as_deref(), Some("eth1"));
+        assert_eq!(bridge.ip_version, IpVersion::Both);
+        assert_eq!(bridge.rule_numbers, vec![9, 12]);
+    }
+
+    #[test]
+    fn test_parse_numbered_and_plain_status() {
+        let numbered = "Status: active\n\
+            \n\
+                 To                         Action      From\n\
+                 --                         ------      ----\n\
+            [ 1] 22/tcp                     ALLOW IN    Anywhere\n\
+            [ 2] 80/tcp                     DENY IN     Anywhere\n\
+            [ 3] 22/tcp (v6)                ALLOW IN    Anywhere (v6)\n";
+        let rules = parse_ufw_rules(numbered);
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].rule_numbers, vec![1, 3]);
+        assert_eq!(rules[1].action, "DENY");
+
+        // Plain `ufw status` leaves out the IN of incoming rules
+        let plain = "Status: active\n\
+            \n\
+            To                         Action      From\n\
+            --                         ------      ----\n\
+            OpenSSH                    ALLOW       Anywhere\n\
+            25                         REJECT OUT  Anywhere\n";
+        let rules = parse_ufw_rules(plain);
+        assert_eq!(rules[0].direction, UfwDirection::In);
+        assert_eq!(rules[0].port.as_deref(), Some("OpenSSH"));
+        assert_eq!(rules[1].action, "REJECT");
+        assert_eq!(rules[1].direction, UfwDirection::Out);
+    }
+}
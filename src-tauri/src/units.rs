@@ -0,0 +1,95 @@
+//! Parsing and formatting of human-readable byte sizes.
+//!
+//! Docker and coreutils print sizes in both decimal (`kB`, `MB`, `GB`) and binary
+//! (`KiB`, `MiB`, `GiB`) units. Decimal units are powers of 1000 and binary units
+//! powers of 1024, matching what the tools themselves mean.
+
+const BINARY_UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+
+/// Parse sizes like `"1.5GiB"`, `"100 MB"`, `"0B"` or a bare `"2048"` into bytes
+pub fn parse_bytes(input: &str) -> Option<u64> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+
+    let split = input
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+    let value: f64 = number.parse().ok()?;
+
+    let multiplier: f64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "k" | "kb" => 1e3,
+        "m" | "mb" => 1e6,
+        "g" | "gb" => 1e9,
+        "t" | "tb" => 1e12,
+        "p" | "pb" => 1e15,
+        "kib" => 1024.0,
+        "mib" => 1024.0_f64.powi(2),
+        "gib" => 1024.0_f64.powi(3),
+        "tib" => 1024.0_f64.powi(4),
+        "pib" => 1024.0_f64.powi(5),
+        _ => return None,
+    };
+
+    Some((value * multiplier).round() as u64)
+}
+
+/// Format a byte count using binary units, e.g. `1.50 GiB`
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < BINARY_UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{:.2} {}", value, BINARY_UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bytes_binary_units() {
+        assert_eq!(parse_bytes("1.5GiB"), Some(1_610_612_736));
+        assert_eq!(parse_bytes("512MiB"), Some(536_870_912));
+        assert_eq!(parse_bytes("4KiB"), Some(4096));
+    }
+
+    #[test]
+    fn test_parse_bytes_decimal_units_and_spaces() {
+        assert_eq!(parse_bytes("100 MB"), Some(100_000_000));
+        assert_eq!(parse_bytes("1.2GB"), Some(1_200_000_000));
+        assert_eq!(parse_bytes("13.3kB"), Some(13_300));
+    }
+
+    #[test]
+    fn test_parse_bytes_zero_and_bare_numbers() {
+        assert_eq!(parse_bytes("0B"), Some(0));
+        assert_eq!(parse_bytes("2048"), Some(2048));
+        assert_eq!(parse_bytes(" 7 "), Some(7));
+    }
+
+    #[test]
+    fn test_parse_bytes_rejects_garbage() {
+        assert_eq!(parse_bytes(""), None);
+        assert_eq!(parse_bytes("--"), None);
+        assert_eq!(parse_bytes("12 parsecs"), None);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.50 KiB");
+        assert_eq!(format_bytes(1_610_612_736), "1.50 GiB");
+    }
+}
@@ -2,7 +2,7 @@ import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { useServer } from '../context/ServerContext';
 import { useToast } from '../context/ToastContext';
-import { ServerProfile, AuthMethod, ConnectionResult, SavedServerProfile } from '../types';
+import { ServerProfile, AuthMethod, ConnectionResult, SavedServerProfile, CONNECTION_TIMED_OUT, CONNECTION_REFUSED } from '../types';
 import {
   Box,
   Paper,
@@ -134,6 +134,10 @@ export default function ConnectionManager() {
       });
       if (result.success) {
         addToast('Connection test successful!', 'success');
+      } else if (result.error_code === CONNECTION_TIMED_OUT) {
+        addToast(`${result.message}. Check the host address and that the server is reachable.`, 'error');
+      } else if (result.error_code === CONNECTION_REFUSED) {
+        addToast(`${result.message}. Check that SSH is running on port ${formData.port}.`, 'error');
       } else {
         addToast(result.message, 'error');
       }
@@ -156,6 +160,11 @@ export default function ConnectionManager() {
         port: profile.port,
         username: profile.username,
         auth_method: profile.auth_method,
+        keepalive_secs: profile.keepalive_secs,
+        connect_timeout_secs: profile.connect_timeout_secs,
+        retry_attempts: profile.retry_attempts,
+        probe_interval_secs: profile.probe_interval_secs,
+        max_output_bytes: profile.max_output_bytes,
       };
       const result: ConnectionResult = await invoke('connect_to_server', { profile: serverProfile });
       if (result.success) {
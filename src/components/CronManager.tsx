@@ -11,6 +11,7 @@ import {
 
 interface CronJob {
   id: number;
+  line_number: number;
   schedule: string;
   command: string;
   user: string;
@@ -43,6 +44,7 @@ const CronManager = memo(function CronManager() {
   const [activeTab, setActiveTab] = useState<string>('user');
   const [userCrontab, setUserCrontab] = useState('');
   const [systemCrontab, setSystemCrontab] = useState('');
+  const [userJobs, setUserJobs] = useState<CronJob[]>([]);
   const [cronDJobs, setCronDJobs] = useState<CronJob[]>([]);
   const [cronFolders, setCronFolders] = useState<CronFolder[]>([]);
   const [showAddModal, setShowAddModal] = useState(false);
@@ -61,6 +63,16 @@ const CronManager = memo(function CronManager() {
     }
   }, [isConnected]);
 
+  const fetchUserJobs = useCallback(async () => {
+    if (!isConnected) return;
+    try {
+      const jobs = await invoke<CronJob[]>('get_user_cron_jobs');
+      setUserJobs(jobs);
+    } catch (err: any) {
+      console.log('User cron jobs error:', err.message);
+    }
+  }, [isConnected]);
+
   const fetchSystemCrontab = useCallback(async () => {
     if (!isConnected) return;
     try {
@@ -94,17 +106,19 @@ const CronManager = memo(function CronManager() {
   useEffect(() => {
     if (isConnected) {
       fetchUserCrontab();
+      fetchUserJobs();
       fetchSystemCrontab();
       fetchCronDJobs();
       fetchCronFolders();
     }
-  }, [isConnected, fetchUserCrontab, fetchSystemCrontab, fetchCronDJobs, fetchCronFolders]);
+  }, [isConnected, fetchUserCrontab, fetchUserJobs, fetchSystemCrontab, fetchCronDJobs, fetchCronFolders]);
 
   const handleSaveUserCrontab = async () => {
     setLoading(true);
     try {
       await invoke('save_user_crontab', { content: userCrontab });
       addToast('Crontab saved', 'success');
+      fetchUserJobs();
     } catch (err: any) {
       addToast(`Failed to save crontab: ${err.message}`, 'error');
     } finally {
@@ -125,6 +139,7 @@ const CronManager = memo(function CronManager() {
       setNewSchedule('');
       setNewCommand('');
       fetchUserCrontab();
+      fetchUserJobs();
     } catch (err: any) {
       addToast(`Failed to add job: ${err.message}`, 'error');
     } finally {
@@ -139,6 +154,7 @@ const CronManager = memo(function CronManager() {
       await invoke('delete_cron_job', { lineNumber });
       addToast('Cron job deleted', 'success');
       fetchUserCrontab();
+      fetchUserJobs();
     } catch (err: any) {
       addToast(`Failed to delete job: ${err.message}`, 'error');
     } finally {
@@ -152,6 +168,7 @@ const CronManager = memo(function CronManager() {
       await invoke('toggle_cron_job', { lineNumber, enabled });
       addToast(`Job ${enabled ? 'enabled' : 'disabled'}`, 'success');
       fetchUserCrontab();
+      fetchUserJobs();
     } catch (err: any) {
       addToast(`Failed to toggle job: ${err.message}`, 'error');
     } finally {
@@ -159,6 +176,22 @@ const CronManager = memo(function CronManager() {
     }
   };
 
+  // Disabling renames the whole drop-in to `<name>.disabled`, which cron skips
+  const handleToggleCronDFile = async (source: string) => {
+    const filename = source.split('/').pop() ?? '';
+    const enabled = filename.endsWith('.disabled');
+    setLoading(true);
+    try {
+      await invoke('toggle_cron_d_file', { filename, enabled });
+      addToast(`${filename.replace(/\.disabled$/, '')} ${enabled ? 'enabled' : 'disabled'}`, 'success');
+      fetchCronDJobs();
+    } catch (err: any) {
+      addToast(`Failed to toggle file: ${err.message}`, 'error');
+    } finally {
+      setLoading(false);
+    }
+  };
+
   const viewLogs = async () => {
     setLoading(true);
     try {
@@ -172,24 +205,6 @@ const CronManager = memo(function CronManager() {
     }
   };
 
-  const parseCrontabLines = (crontab: string) => {
-    const lines = crontab.split('\n');
-    const parsed: { line: string; isComment: boolean; isVariable: boolean; isValid: boolean; index: number }[] = [];
-
-    lines.forEach((line, index) => {
-      const trimmed = line.trim();
-      parsed.push({
-        line,
-        isComment: trimmed.startsWith('#') || trimmed === '',
-        isVariable: trimmed.startsWith('SHELL=') || trimmed.startsWith('PATH=') || trimmed.startsWith('MAILTO='),
-        isValid: !trimmed.startsWith('#') && !trimmed.startsWith('SHELL=') && !trimmed.startsWith('PATH=') && !trimmed.startsWith('MAILTO=') && trimmed !== '',
-        index: index + 1,
-      });
-    });
-
-    return parsed;
-  };
-
   if (!isConnected) {
     return (
       <div className="page-container animate-fade-in-up">
@@ -219,9 +234,6 @@ const CronManager = memo(function CronManager() {
     );
   }
 
-  const userLines = parseCrontabLines(userCrontab);
-  const systemLines = parseCrontabLines(systemCrontab);
-
   return (
     <div className="page-container animate-fade-in-up">
       {/* Header */}
@@ -350,9 +362,9 @@ const CronManager = memo(function CronManager() {
                 <Text fw={600} size="sm" style={{ color: 'hsl(var(--text-primary))', marginBottom: 'var(--space-4)' }}>Current Jobs</Text>
                 <ScrollArea.Autosize mah={400}>
                   <Stack gap="xs">
-                    {userLines.filter(l => l.isValid).map((job, idx) => (
+                    {userJobs.map((job) => (
                       <Paper
-                        key={idx}
+                        key={job.line_number}
                         withBorder
                         p="sm"
                         radius="md"
@@ -364,22 +376,22 @@ const CronManager = memo(function CronManager() {
                         <Group justify="space-between">
                           <Stack gap={0} style={{ flex: 1 }}>
                             <Text size="xs" style={{ fontFamily: 'var(--font-mono)', color: 'hsl(var(--primary))' }}>
-                              {job.line.split(' ').slice(0, 5).join(' ')}
+                              {job.schedule}
                             </Text>
                             <Text size="xs" c="var(--text-tertiary)" style={{ fontFamily: 'var(--font-mono)' }}>
-                              {job.line.split(' ').slice(5).join(' ')}
+                              {job.command}
                             </Text>
                           </Stack>
                           <Group gap="xs">
                             <ActionIcon
                               size="sm"
                               style={{
-                                background: job.line.trim().startsWith('#') ? 'hsl(var(--bg-tertiary))' : 'hsl(var(--success-subtle))',
-                                color: job.line.trim().startsWith('#') ? 'hsl(var(--text-tertiary))' : 'hsl(var(--success))',
+                                background: !job.enabled ? 'hsl(var(--bg-tertiary))' : 'hsl(var(--success-subtle))',
+                                color: !job.enabled ? 'hsl(var(--text-tertiary))' : 'hsl(var(--success))',
                               }}
-                              onClick={() => handleToggleJob(job.index, job.line.trim().startsWith('#'))}
+                              onClick={() => handleToggleJob(job.line_number, !job.enabled)}
                             >
-                              {job.line.trim().startsWith('#') ? <IconToggleRight size={18} /> : <IconToggleLeft size={18} />}
+                              {!job.enabled ? <IconToggleRight size={18} /> : <IconToggleLeft size={18} />}
                             </ActionIcon>
                             <ActionIcon
                               size="sm"
@@ -387,7 +399,7 @@ const CronManager = memo(function CronManager() {
                                 background: 'hsl(var(--error-subtle))',
                                 color: 'hsl(var(--error))',
                               }}
-                              onClick={() => handleDeleteJob(job.index)}
+                              onClick={() => handleDeleteJob(job.line_number)}
                             >
                               <IconTrash size={18} />
                             </ActionIcon>
@@ -395,7 +407,7 @@ const CronManager = memo(function CronManager() {
                         </Group>
                       </Paper>
                     ))}
-                    {userLines.filter(l => l.isValid).length === 0 && (
+                    {userJobs.length === 0 && (
                       <Text c="var(--text-tertiary)" ta="center" size="sm">No cron jobs configured</Text>
                     )}
                   </Stack>
@@ -460,7 +472,18 @@ const CronManager = memo(function CronManager() {
                       <Table.Td>
                         <Text size="sm" style={{ fontFamily: 'var(--font-mono)', color: 'hsl(var(--text-primary))' }}>{job.command}</Text>
                       </Table.Td>
-                      <Table.Td c="var(--text-tertiary)">{job.source}</Table.Td>
+                      <Table.Td c="var(--text-tertiary)">
+                        <Group gap="xs" wrap="nowrap">
+                          <Text size="sm">{job.source}</Text>
+                          <Button
+                            size="compact-xs"
+                            variant="subtle"
+                            onClick={() => handleToggleCronDFile(job.source)}
+                          >
+                            {job.source.endsWith('.disabled') ? 'Enable file' : 'Disable file'}
+                          </Button>
+                        </Group>
+                      </Table.Td>
                       <Table.Td>
                         <Badge
                           size="sm"
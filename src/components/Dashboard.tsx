@@ -1,7 +1,7 @@
 import { useState, useEffect, useMemo, memo, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { useServer } from '../context/ServerContext';
-import { SystemMetrics } from '../types';
+import { MetricsConfig, RebootStatus, SystemMetrics } from '../types';
 import {
   Paper, Text, Group, SimpleGrid, Progress, Badge, Title, Stack, Grid,
   Divider, Tooltip, ActionIcon, Box, Skeleton, Card,
@@ -14,12 +14,14 @@ const Dashboard = memo(function Dashboard() {
   const [error, setError] = useState<string | null>(null);
   const [lastUpdate, setLastUpdate] = useState<Date>(new Date());
   const [cpuCores, setCpuCores] = useState<number>(1);
+  const [pollMs, setPollMs] = useState<number>(5000);
+  const [rebootStatus, setRebootStatus] = useState<RebootStatus | null>(null);
 
   const fetchMetrics = useCallback(async () => {
     try {
       const result = await invoke('get_system_metrics') as SystemMetrics;
       setCachedMetrics(result);
-      setLastUpdate(new Date());
+      setLastUpdate(result.collected_at ? new Date(result.collected_at) : new Date());
       setError(null);
     } catch (err: any) {
       setError(err.message || 'Failed to fetch metrics');
@@ -35,13 +37,19 @@ const Dashboard = memo(function Dashboard() {
       } catch (err) { console.error('Failed to get CPU cores:', err); }
     };
     fetchCpuCores();
+    invoke<MetricsConfig>('get_metrics_config')
+      .then((config) => setPollMs(config.poll_interval_secs * 1000))
+      .catch(() => {});
+    invoke<RebootStatus>('get_reboot_status')
+      .then(setRebootStatus)
+      .catch(() => {});
   }, []);
 
   useEffect(() => {
     fetchMetrics();
     
     // Start polling interval
-    let interval = setInterval(fetchMetrics, 5000);
+    let interval = setInterval(fetchMetrics, pollMs);
     
     // Visibility API - pause fetching when tab is hidden
     const handleVisibilityChange = () => {
@@ -50,7 +58,7 @@ const Dashboard = memo(function Dashboard() {
       } else {
         // Tab is visible again - fetch immediately and restart interval
         fetchMetrics();
-        interval = setInterval(fetchMetrics, 5000);
+        interval = setInterval(fetchMetrics, pollMs);
       }
     };
 
@@ -60,7 +68,7 @@ const Dashboard = memo(function Dashboard() {
       clearInterval(interval);
       document.removeEventListener('visibilitychange', handleVisibilityChange);
     };
-  }, [fetchMetrics]);
+  }, [fetchMetrics, pollMs]);
 
   const formatBytes = (bytes: number, decimals = 1) => {
     if (bytes === 0) return '0 B';
@@ -214,6 +222,34 @@ const Dashboard = memo(function Dashboard() {
         </Group>
       </Group>
 
+      {rebootStatus?.required && (
+        <Paper
+          p="sm"
+          style={{
+            marginBottom: 'var(--space-4)',
+            background: 'hsl(var(--warning-subtle))',
+            border: '1px solid hsl(var(--warning-border))',
+          }}
+        >
+          <Group gap="sm" wrap="nowrap">
+            <Icons.AlertTriangle size={18} style={{ color: 'hsl(var(--warning))', flexShrink: 0 }} />
+            <Stack gap={0}>
+              <Text size="sm" fw={600} style={{ color: 'hsl(var(--warning))' }}>
+                Reboot required
+              </Text>
+              <Text size="xs" c="var(--text-secondary)">
+                {rebootStatus.installed_kernel && rebootStatus.installed_kernel !== rebootStatus.running_kernel
+                  ? `Running kernel ${rebootStatus.running_kernel}, newest installed is ${rebootStatus.installed_kernel}. `
+                  : ''}
+                {rebootStatus.packages.length > 0
+                  ? `Updated packages waiting on a reboot: ${rebootStatus.packages.join(', ')}`
+                  : 'Updates are installed but not active until the server restarts.'}
+              </Text>
+            </Stack>
+          </Group>
+        </Paper>
+      )}
+
       {!metrics ? (
         <SimpleGrid cols={{ base: 1, sm: 2, lg: 4 }}>
           {[...Array(4)].map((_, i) => (
@@ -1,8 +1,9 @@
 import { useState, useEffect, useCallback, useMemo, memo } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { invokeAction } from '../lib/utils';
 import { useServer } from '../context/ServerContext';
 import { useToast } from '../context/ToastContext';
-import { DockerContainer, ContainerDetails, DockerVolume, DockerNetwork, DockerImage, ComposeProject } from '../types';
+import { DockerContainer, ContainerDetails, ContainerProvenance, DockerVolume, DockerNetwork, DockerImage, ComposeProject, ContainerLogs } from '../types';
 import {
   Paper, Text, Group, Title, Button, Modal, Stack, ScrollArea, Grid, Card,
   Progress, Divider, Tabs, Badge, ActionIcon, Table, Code, Alert, CopyButton,
@@ -17,6 +18,7 @@ const DockerEnhanced = memo(function DockerEnhanced() {
   const [containers, setContainers] = useState<DockerContainer[]>([]);
   const [selectedContainer, setSelectedContainer] = useState<ContainerDetails | null>(null);
   const [showDetailsModal, setShowDetailsModal] = useState(false);
+  const [provenance, setProvenance] = useState<ContainerProvenance | null>(null);
   const [showSecrets, setShowSecrets] = useState(false);
   const [volumes, setVolumes] = useState<DockerVolume[]>([]);
   const [networks, setNetworks] = useState<DockerNetwork[]>([]);
@@ -104,7 +106,11 @@ const DockerEnhanced = memo(function DockerEnhanced() {
     try {
       const details = await invoke<ContainerDetails>('get_container_details', { containerName });
       setSelectedContainer(details);
+      setProvenance(null);
       setShowDetailsModal(true);
+      invoke<ContainerProvenance>('get_container_provenance', { containerName })
+        .then(setProvenance)
+        .catch(() => setProvenance(null));
     } catch (err: any) {
       addToast(`Failed to fetch details: ${err.message}`, 'error');
     }
@@ -112,7 +118,7 @@ const DockerEnhanced = memo(function DockerEnhanced() {
 
   const handleContainerAction = async (action: string, containerName: string) => {
     try {
-      await invoke('docker_container_action', { action, containerName });
+      await invokeAction('docker_container_action', { action, containerName });
       addToast(`Container ${containerName} ${action}ed`, 'success');
       setTimeout(fetchContainers, 500);
     } catch (err: any) {
@@ -367,21 +373,49 @@ const DockerEnhanced = memo(function DockerEnhanced() {
                         </Box>
                         <Text fw={600} style={{ color: 'hsl(var(--text-primary))' }}>{container.name}</Text>
                       </Group>
-                      <Badge
-                        size="sm"
-                        variant="light"
-                        style={{
-                          background: container.state.toLowerCase().includes('running')
-                            ? 'hsl(var(--success-subtle))'
-                            : 'hsl(var(--error-subtle))',
-                          color: container.state.toLowerCase().includes('running')
-                            ? 'hsl(var(--success))'
-                            : 'hsl(var(--error))',
-                          border: `1px solid hsl(var(--${container.state.toLowerCase().includes('running') ? 'success' : 'error'}-border))`,
-                        }}
-                      >
-                        {container.state}
-                      </Badge>
+                      <Group gap="xs">
+                        {container.oom_killed && (
+                          <Tooltip label="Killed for running out of memory">
+                            <Badge
+                              size="sm"
+                              variant="light"
+                              style={{
+                                background: 'hsl(var(--error-subtle))',
+                                color: 'hsl(var(--error))',
+                              }}
+                            >
+                              OOM
+                            </Badge>
+                          </Tooltip>
+                        )}
+                        {container.health && (
+                          <Badge
+                            size="sm"
+                            variant="light"
+                            style={{
+                              background: `hsl(var(--${container.health === 'healthy' ? 'success' : container.health === 'unhealthy' ? 'error' : 'warning'}-subtle))`,
+                              color: `hsl(var(--${container.health === 'healthy' ? 'success' : container.health === 'unhealthy' ? 'error' : 'warning'}))`,
+                            }}
+                          >
+                            {container.health}
+                          </Badge>
+                        )}
+                        <Badge
+                          size="sm"
+                          variant="light"
+                          style={{
+                            background: container.state.toLowerCase().includes('running')
+                              ? 'hsl(var(--success-subtle))'
+                              : 'hsl(var(--error-subtle))',
+                            color: container.state.toLowerCase().includes('running')
+                              ? 'hsl(var(--success))'
+                              : 'hsl(var(--error))',
+                            border: `1px solid hsl(var(--${container.state.toLowerCase().includes('running') ? 'success' : 'error'}-border))`,
+                          }}
+                        >
+                          {container.state}
+                        </Badge>
+                      </Group>
                     </Group>
 
                     <Text size="xs" c="var(--text-tertiary)" style={{ fontFamily: 'var(--font-mono)' }}>
@@ -484,9 +518,9 @@ const DockerEnhanced = memo(function DockerEnhanced() {
                         size="compact-xs"
                         onClick={async () => {
                           try {
-                            const logs = await invoke('get_container_logs', { containerName: container.name, lines: 100 });
+                            const result = await invoke<ContainerLogs>('get_container_logs', { containerName: container.name, lines: 100 });
                             addToast('Logs copied to clipboard', 'success');
-                            navigator.clipboard.writeText(String(logs));
+                            navigator.clipboard.writeText(result.logs);
                           } catch (err: any) {
                             addToast(`Failed: ${err.message}`, 'error');
                           }
@@ -975,6 +1009,37 @@ const DockerEnhanced = memo(function DockerEnhanced() {
               </Paper>
             </SimpleGrid>
 
+            {provenance && (
+              <Paper
+                withBorder
+                p="md"
+                radius="md"
+                style={{
+                  background: 'hsl(var(--bg-tertiary))',
+                  borderColor: 'hsl(var(--border-subtle))',
+                }}
+              >
+                <Text size="xs" c="var(--text-tertiary)">Created by</Text>
+                {provenance.source === 'Compose' ? (
+                  <>
+                    <Text fw={600} size="sm" style={{ color: 'hsl(var(--text-primary))' }}>
+                      Compose project {provenance.project}, service {provenance.service}
+                    </Text>
+                    {provenance.config_files.map((file) => (
+                      <Text key={file} size="xs" ff="monospace" c={provenance.missing_files.includes(file) ? 'red' : 'dimmed'}>
+                        {file}{provenance.missing_files.includes(file) ? ' (missing)' : ''}
+                        {provenance.unchecked_files.includes(file) ? ' (not checked: permission denied)' : ''}
+                      </Text>
+                    ))}
+                  </>
+                ) : (
+                  <Text fw={600} size="sm" style={{ color: 'hsl(var(--text-primary))' }}>
+                    Standalone container (docker run)
+                  </Text>
+                )}
+              </Paper>
+            )}
+
             <Tabs defaultValue="env">
               <Tabs.List style={{ background: 'hsl(var(--bg-tertiary))', borderColor: 'hsl(var(--border-subtle))' }}>
                 <Tabs.Tab value="env" leftSection={<Icons.Settings size={16} />}>
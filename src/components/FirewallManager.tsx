@@ -1,8 +1,9 @@
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { invokeAction } from '../lib/utils';
 import { useServer } from '../context/ServerContext';
 import { useToast } from '../context/ToastContext';
-import { UfwOverview, PortInfo } from '../types';
+import { UfwOverview, UfwRulePreview, PortInfo, SuggestedRule } from '../types';
 import {
   Paper,
   Text,
@@ -27,6 +28,7 @@ import {
   Grid,
   Box,
   Loader,
+  Code,
 } from '@mantine/core';
 import {
   IconShield,
@@ -78,10 +80,11 @@ export default function FirewallManager() {
   const [loading, setLoading] = useState(false);
   const [overview, setOverview] = useState<UfwOverview | null>(null);
   const [listeningPorts, setListeningPorts] = useState<PortInfo[]>([]);
+  const [suggestedRules, setSuggestedRules] = useState<SuggestedRule[]>([]);
   const [activeTab, setActiveTab] = useState<string>('overview');
   const [showAddRuleModal, setShowAddRuleModal] = useState(false);
   const [showSettingsModal, setShowSettingsModal] = useState(false);
-  const [deleteConfirm, setDeleteConfirm] = useState<number | null>(null);
+  const [deleteConfirm, setDeleteConfirm] = useState<number[] | null>(null);
 
   const [newRule, setNewRule] = useState({
     action: 'allow',
@@ -89,6 +92,26 @@ export default function FirewallManager() {
     protocol: 'tcp',
     fromIp: 'any',
   });
+  const [rulePreview, setRulePreview] = useState<UfwRulePreview | null>(null);
+  const [rulePreviewError, setRulePreviewError] = useState<string | null>(null);
+
+  useEffect(() => {
+    if (!showAddRuleModal) return;
+    invoke<UfwRulePreview>('preview_ufw_rule', {
+      ruleType: newRule.action,
+      port: newRule.port || undefined,
+      fromIp: newRule.fromIp === 'any' ? undefined : newRule.fromIp,
+      protocol: newRule.protocol,
+    })
+      .then((preview) => {
+        setRulePreview(preview);
+        setRulePreviewError(null);
+      })
+      .catch((error) => {
+        setRulePreview(null);
+        setRulePreviewError(String(error));
+      });
+  }, [newRule, showAddRuleModal]);
 
   const [settings, setSettings] = useState({
     defaultIncoming: 'deny',
@@ -106,6 +129,10 @@ export default function FirewallManager() {
       ]);
       setOverview(overviewData);
       setListeningPorts(listeningData);
+      // Optional: servers without Docker simply have nothing to suggest
+      invoke<SuggestedRule[]>('suggest_ufw_rules_for_containers')
+        .then(setSuggestedRules)
+        .catch(() => setSuggestedRules([]));
     } catch (error: any) {
       addToast(`Failed to load firewall data: ${error.message}`, 'error');
     } finally {
@@ -121,7 +148,7 @@ export default function FirewallManager() {
 
   const handleToggleFirewall = async (enable: boolean) => {
     try {
-      await invoke('ufw_action', { action: enable ? 'enable' : 'disable' });
+      await invokeAction('ufw_action', { action: enable ? 'enable' : 'disable' });
       addToast(`Firewall ${enable ? 'enabled' : 'disabled'}`, 'success');
       fetchUfwData();
     } catch (error: any) {
@@ -131,11 +158,11 @@ export default function FirewallManager() {
 
   const handleAddRule = async () => {
     try {
-      await invoke('ufw_add_rule', {
+      await invokeAction('ufw_add_rule', {
         ruleType: newRule.action,
         port: newRule.port || undefined,
         fromIp: newRule.fromIp === 'any' ? undefined : newRule.fromIp,
-        protocol: newRule.protocol === 'tcp' ? undefined : newRule.protocol,
+        protocol: newRule.protocol,
       });
       addToast('Rule added successfully', 'success');
       setShowAddRuleModal(false);
@@ -146,9 +173,12 @@ export default function FirewallManager() {
     }
   };
 
-  const handleDeleteRule = async (ruleNumber: number) => {
+  const handleDeleteRule = async (ruleNumbers: number[]) => {
     try {
-      await invoke('ufw_delete_rule', { ruleNumber });
+      // Highest first, so deleting one doesn't renumber the next
+      for (const ruleNumber of [...ruleNumbers].sort((a, b) => b - a)) {
+        await invokeAction('ufw_delete_rule', { ruleNumber });
+      }
       addToast('Rule deleted', 'success');
       setDeleteConfirm(null);
       fetchUfwData();
@@ -315,6 +345,81 @@ export default function FirewallManager() {
             </Group>
           </Card>
 
+          {/* Docker-published ports that bypass UFW */}
+          {overview.docker_exposures.some(e => e.warning) && (
+            <Alert
+              icon={<IconAlertTriangle size={18} />}
+              color="orange"
+              title="Docker ports bypass the firewall"
+              style={{
+                marginBottom: 'var(--space-4)',
+                background: 'hsl(var(--warning-subtle))',
+                border: '1px solid hsl(var(--warning-border))',
+                color: 'hsl(var(--text-primary))',
+              }}
+            >
+              <Stack gap="xs">
+                {overview.docker_exposures.filter(e => e.warning).map((exposure, idx) => (
+                  <Stack key={idx} gap={0}>
+                    <Text size="sm" fw={600}>{exposure.warning}</Text>
+                    {exposure.suggestion && (
+                      <Text size="xs" c="var(--text-tertiary)">{exposure.suggestion}</Text>
+                    )}
+                  </Stack>
+                ))}
+              </Stack>
+            </Alert>
+          )}
+
+          {/* One-click rules limiting published container ports to this machine's address */}
+          {suggestedRules.some(r => r.command) && (
+            <Card className="card" style={{ marginBottom: 'var(--space-4)' }}>
+              <Text size="sm" fw={600} mb="xs">Suggested container rules</Text>
+              <Stack gap="xs">
+                {suggestedRules.filter(r => r.command).map((rule, idx) => (
+                  <Group key={idx} justify="space-between" wrap="nowrap">
+                    <Stack gap={2}>
+                      <Text size="sm">{rule.message}</Text>
+                      <Code>{rule.command}</Code>
+                    </Stack>
+                    <Button
+                      size="xs"
+                      variant="light"
+                      onClick={async () => {
+                        try {
+                          await invokeAction('apply_suggested_rule', { rule });
+                          addToast('Rule added successfully', 'success');
+                          fetchUfwData();
+                        } catch (error: any) {
+                          addToast(`Failed: ${error.message}`, 'error');
+                        }
+                      }}
+                    >
+                      Apply
+                    </Button>
+                  </Group>
+                ))}
+              </Stack>
+            </Card>
+          )}
+
+          {/* Rules that leave IPv6 unfiltered */}
+          {overview.ipv6.warning && (
+            <Alert
+              icon={<IconAlertTriangle size={18} />}
+              color="orange"
+              title="IPv6 is not fully covered"
+              style={{
+                marginBottom: 'var(--space-4)',
+                background: 'hsl(var(--warning-subtle))',
+                border: '1px solid hsl(var(--warning-border))',
+                color: 'hsl(var(--text-primary))',
+              }}
+            >
+              <Text size="sm">{overview.ipv6.warning}</Text>
+            </Alert>
+          )}
+
           {/* Stats Grid */}
           <SimpleGrid cols={{ base: 1, sm: 2, lg: 4 }} style={{ marginBottom: 'var(--space-4)' }}>
             {/* Open Ports */}
@@ -794,7 +899,7 @@ export default function FirewallManager() {
                 </Group>
                 {listeningPorts.length === 0 ? (
                   <Center p="xl">
-                    <Text c="var(--text-tertiary)">No listening TCP ports detected</Text>
+                    <Text c="var(--text-tertiary)">No listening ports detected</Text>
                   </Center>
                 ) : (
                   <Stack gap="xs">
@@ -848,7 +953,11 @@ export default function FirewallManager() {
                                     </Badge>
                                   )}
                                 </Group>
-                                <Text size="sm" c="var(--text-tertiary)">{port.service_name || 'Unknown service'}</Text>
+                                <Text size="sm" c="var(--text-tertiary)">
+                                  {port.service_name || 'Unknown service'}
+                                  {port.pid != null && ` (pid ${port.pid}${port.processes && port.processes.length > 1 ? ` +${port.processes.length - 1}` : ''})`}
+                                  {port.address && ` on ${port.address}`}
+                                </Text>
                               </Stack>
                             </Group>
                             {!isOpenInFirewall && (
@@ -858,7 +967,7 @@ export default function FirewallManager() {
                                 color="green"
                                 onClick={async () => {
                                   try {
-                                    await invoke('ufw_add_rule', {
+                                    await invokeAction('ufw_add_rule', {
                                       ruleType: 'allow',
                                       port: port.port,
                                       protocol: 'tcp',
@@ -989,18 +1098,26 @@ export default function FirewallManager() {
                                       color: 'white',
                                     }}
                                   >
-                                    {rule.action.toUpperCase()}
+                                    {rule.action.toUpperCase()} {rule.direction}
                                   </Badge>
                                   {rule.port && (
                                     <Text size="sm" fw={600} style={{ color: 'hsl(var(--text-primary))' }}>Port {rule.port}</Text>
                                   )}
+                                  <Badge variant="light" size="xs" color={rule.ip_version === 'V4' ? 'orange' : 'gray'}>
+                                    {rule.ip_version === 'Both' ? 'IPv4 + IPv6' : rule.ip_version === 'V4' ? 'IPv4 only' : 'IPv6 only'}
+                                  </Badge>
                                 </Group>
-                                <Text size="xs" c="var(--text-tertiary)">From: {rule.from}</Text>
+                                <Text size="xs" c="var(--text-tertiary)">
+                                  From: {rule.from}
+                                  {rule.interface_in && ` on ${rule.interface_in}`}
+                                  {rule.to !== 'Anywhere' && ` · To: ${rule.to}`}
+                                  {rule.interface_out && ` · Out on ${rule.interface_out}`}
+                                </Text>
                               </Stack>
                             </Group>
                             <ActionIcon
                               variant="subtle"
-                              onClick={() => setDeleteConfirm(idx + 1)}
+                              onClick={() => setDeleteConfirm(rule.rule_numbers)}
                               style={{
                                 background: 'hsl(var(--error-subtle))',
                                 color: 'hsl(var(--error))',
@@ -1051,7 +1168,7 @@ export default function FirewallManager() {
                     disabled={isAlreadyOpen}
                     onClick={async () => {
                       try {
-                        await invoke('ufw_add_rule', {
+                        await invokeAction('ufw_add_rule', {
                           ruleType: 'allow',
                           port,
                           protocol: proto,
@@ -1159,6 +1276,15 @@ export default function FirewallManager() {
               },
             }}
           />
+          {rulePreview && (
+            <Stack gap={4}>
+              <Text size="sm" style={{ color: 'hsl(var(--text-primary))' }}>{rulePreview.description}</Text>
+              <Code>{rulePreview.command}</Code>
+            </Stack>
+          )}
+          {rulePreviewError && (
+            <Text size="sm" c="red">{rulePreviewError}</Text>
+          )}
           <Group justify="flex-end" mt="md">
             <Button
               variant="subtle"
@@ -1175,6 +1301,7 @@ export default function FirewallManager() {
               variant="filled"
               color="green"
               onClick={handleAddRule}
+              disabled={rulePreviewError !== null}
               style={{
                 background: 'hsl(var(--success))',
                 color: 'white',
@@ -1234,7 +1361,7 @@ export default function FirewallManager() {
               color: 'hsl(var(--text-primary))',
             }}
           >
-            <Text size="sm">Delete rule #{deleteConfirm}? This cannot be undone.</Text>
+            <Text size="sm">Delete rule #{deleteConfirm?.join(' and #')}? This cannot be undone.</Text>
           </Alert>
           <Group justify="flex-end">
             <Button
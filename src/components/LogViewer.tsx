@@ -2,6 +2,7 @@ import { useState, useRef, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { useServer } from '../context/ServerContext';
 import { useToast } from '../context/ToastContext';
+import { ServiceLogResult } from '../types';
 import {
   Paper, Text, Group, TextInput, Button, Title, Stack, ScrollArea,
   Badge, Grid, ActionIcon, Divider, Loader, Center, Box, Card, SimpleGrid,
@@ -166,7 +167,11 @@ export default function LogViewer() {
       if (type === 'system') {
         result = await invoke('execute_command', { command: 'journalctl -n 300 --no-pager 2>&1' });
       } else if (type === 'service') {
-        result = await invoke('get_service_logs', { serviceName: value, lines: 300 });
+        const serviceLogs = await invoke<ServiceLogResult>('get_service_logs', { serviceName: value, lines: 300 });
+        if (serviceLogs.source.type === 'NotFound') {
+          throw new Error(`No logs found for service '${value}'`);
+        }
+        result = serviceLogs.lines.join('\n');
       } else if (type === 'file') {
         result = await invoke('execute_command', { command: `tail -n 300 ${value} 2>&1` });
       } else {
@@ -2,8 +2,9 @@ import { useState, useEffect, useCallback, memo } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { useServer } from '../context/ServerContext';
 import { useToast } from '../context/ToastContext';
+import { DiffLine, ConfigMessage, NginxSaveResult, ConfigTestResult, VhostEnableResult } from '../types';
 import {
-  Paper, Text, Group, Title, Button, Stack, Grid, Card, ThemeIcon, Badge, ActionIcon, Modal, Box, Loader, Center, Divider, Tabs, Code, ScrollArea, Textarea,
+  Paper, Text, Group, Title, Button, Stack, Grid, Card, ThemeIcon, Badge, ActionIcon, Modal, Box, Loader, Center, Divider, Tabs, Code, ScrollArea, Textarea, Switch,
 } from '@mantine/core';
 import {
   IconServer, IconRefresh, IconPlayerPlay, IconPlayerStop, IconReload, IconFileCode, IconCheck, IconX, IconTrash, IconWorld, IconLock,
@@ -16,6 +17,12 @@ interface NginxStatus {
   config_test: string;
 }
 
+const countChangedLines = (diff: DiffLine[]) =>
+  diff.filter((line) => line.kind === 'Added' || line.kind === 'Removed').length;
+
+const formatConfigError = (error: ConfigMessage) =>
+  error.file ? `${error.message} (${error.file}:${error.line})` : error.message;
+
 interface NginxVhost {
   name: string;
   enabled: boolean;
@@ -31,6 +38,7 @@ const NginxManager = memo(function NginxManager() {
   const [loading, setLoading] = useState(false);
   const [status, setStatus] = useState<NginxStatus | null>(null);
   const [vhosts, setVhosts] = useState<NginxVhost[]>([]);
+  const [showDefaultSite, setShowDefaultSite] = useState(false);
   const [activeTab, setActiveTab] = useState<string>('overview');
   const [selectedVhost, setSelectedVhost] = useState<NginxVhost | null>(null);
   const [vhostConfig, setVhostConfig] = useState('');
@@ -54,12 +62,12 @@ const NginxManager = memo(function NginxManager() {
   const fetchVhosts = useCallback(async () => {
     if (!isConnected) return;
     try {
-      const data = await invoke<NginxVhost[]>('get_nginx_vhosts');
+      const data = await invoke<NginxVhost[]>('get_nginx_vhosts', { includeDefault: showDefaultSite });
       setVhosts(data);
     } catch (err: any) {
       console.log('Vhosts error:', err.message);
     }
-  }, [isConnected]);
+  }, [isConnected, showDefaultSite]);
 
   useEffect(() => {
     if (isConnected) {
@@ -84,11 +92,11 @@ const NginxManager = memo(function NginxManager() {
   const handleTestConfig = async () => {
     setLoading(true);
     try {
-      const result = await invoke<string>('nginx_test_config');
-      if (result.includes('syntax is ok')) {
+      const result = await invoke<ConfigTestResult>('nginx_test_config');
+      if (result.ok) {
         addToast('Configuration is valid', 'success');
       } else {
-        addToast(result, 'warning');
+        addToast(result.errors.map(formatConfigError).join('\n') || result.output, 'warning');
       }
     } catch (err: any) {
       addToast(`Config test failed: ${err.message}`, 'error');
@@ -114,9 +122,13 @@ const NginxManager = memo(function NginxManager() {
   const saveMainConfig = async () => {
     setLoading(true);
     try {
-      await invoke('save_nginx_config', { content: mainConfig });
-      addToast('Main config saved and validated', 'success');
-      setShowConfigModal(false);
+      const result = await invoke<NginxSaveResult>('save_nginx_config', { content: mainConfig });
+      if (result.test.ok) {
+        addToast(`Main config saved and validated (${countChangedLines(result.diff)} lines changed)`, 'success');
+        setShowConfigModal(false);
+      } else {
+        addToast(`${result.message} ${result.test.errors.map(formatConfigError).join('\n')}`, 'warning');
+      }
       fetchStatus();
     } catch (err: any) {
       addToast(`Failed to save config: ${err.message}`, 'error');
@@ -144,9 +156,13 @@ const NginxManager = memo(function NginxManager() {
     if (!selectedVhost) return;
     setLoading(true);
     try {
-      await invoke('save_vhost_config', { name: selectedVhost.name, content: vhostConfig });
-      addToast(`Vhost '${selectedVhost.name}' saved`, 'success');
-      setShowConfigModal(false);
+      const result = await invoke<NginxSaveResult>('save_vhost_config', { name: selectedVhost.name, content: vhostConfig });
+      if (result.test.ok) {
+        addToast(`Vhost '${selectedVhost.name}' saved (${countChangedLines(result.diff)} lines changed)`, 'success');
+        setShowConfigModal(false);
+      } else {
+        addToast(`${result.message} ${result.test.errors.map(formatConfigError).join('\n')}`, 'warning');
+      }
       fetchVhosts();
     } catch (err: any) {
       addToast(`Failed to save vhost: ${err.message}`, 'error');
@@ -157,8 +173,8 @@ const NginxManager = memo(function NginxManager() {
 
   const handleEnableVhost = async (name: string) => {
     try {
-      await invoke('enable_vhost', { name });
-      addToast(`Vhost '${name}' enabled`, 'success');
+      const result = await invoke<VhostEnableResult>('enable_vhost', { name });
+      addToast(result.message, result.outcome === 'target_mismatch' ? 'warning' : 'success');
       fetchVhosts();
     } catch (err: any) {
       addToast(`Failed to enable vhost: ${err.message}`, 'error');
@@ -507,6 +523,13 @@ const NginxManager = memo(function NginxManager() {
 
         {/* Virtual Hosts Tab */}
         <Tabs.Panel value="vhosts" pt="md">
+          <Group justify="flex-end" mb="sm">
+            <Switch
+              label="Show default site"
+              checked={showDefaultSite}
+              onChange={(e) => setShowDefaultSite(e.currentTarget.checked)}
+            />
+          </Group>
           {vhosts.length === 0 ? (
             <Card className="card card-elevated">
               <Center>
@@ -1,7 +1,9 @@
 import { useState, useEffect, useCallback, memo } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { invokeAction } from '../lib/utils';
 import { useServer } from '../context/ServerContext';
 import { useToast } from '../context/ToastContext';
+import { ServiceLogResult } from '../types';
 import {
   Paper, Text, Group, Title, Button, Stack, ScrollArea, Table, Badge, ActionIcon, Modal, Box, Loader, Center, Divider, TextInput, Card, SimpleGrid,
 } from '@mantine/core';
@@ -23,7 +25,7 @@ const ServicesManager = memo(function ServicesManager() {
   const [loading, setLoading] = useState(false);
   const [selectedService, setSelectedService] = useState<ServiceInfo | null>(null);
   const [showLogsModal, setShowLogsModal] = useState(false);
-  const [serviceLogs, setServiceLogs] = useState<string>('');
+  const [serviceLogs, setServiceLogs] = useState<ServiceLogResult | null>(null);
   const [logsLoading, setLogsLoading] = useState(false);
   const [searchTerm, setSearchTerm] = useState('');
   const [filterState, setFilterState] = useState<string>('all');
@@ -49,7 +51,7 @@ const ServicesManager = memo(function ServicesManager() {
 
   const handleServiceAction = async (action: string, serviceName: string) => {
     try {
-      await invoke('service_action', { action, serviceName });
+      await invokeAction('service_action', { action, serviceName });
       addToast(`Service ${serviceName} ${action}ed`, 'success');
       fetchServices();
     } catch (err: any) {
@@ -62,11 +64,11 @@ const ServicesManager = memo(function ServicesManager() {
     setShowLogsModal(true);
     setLogsLoading(true);
     try {
-      const logs = await invoke<string>('get_service_logs', { serviceName: service.name, lines: 200 });
+      const logs = await invoke<ServiceLogResult>('get_service_logs', { serviceName: service.name, lines: 200 });
       setServiceLogs(logs);
     } catch (err: any) {
       addToast(`Failed to fetch logs: ${err.message}`, 'error');
-      setServiceLogs('');
+      setServiceLogs(null);
     } finally {
       setLogsLoading(false);
     }
@@ -501,10 +503,20 @@ const ServicesManager = memo(function ServicesManager() {
               <Center py="xl">
                 <Loader size="sm" color="hsl(var(--primary))" />
               </Center>
-            ) : serviceLogs ? (
-              <pre style={{ margin: 0, whiteSpace: 'pre-wrap', wordBreak: 'break-all', color: 'hsl(var(--text-secondary))' }}>
-                {serviceLogs}
-              </pre>
+            ) : serviceLogs && serviceLogs.lines.length > 0 ? (
+              <>
+                <Text size="xs" c="var(--text-tertiary)" mb="xs">
+                  {serviceLogs.source.type === 'File' ? `From file: ${serviceLogs.source.path}` : 'From systemd journal'}
+                </Text>
+                <pre style={{ margin: 0, whiteSpace: 'pre-wrap', wordBreak: 'break-all', color: 'hsl(var(--text-secondary))' }}>
+                  {serviceLogs.lines.map((line, idx) => (
+                    <div key={idx}>
+                      <span style={{ color: 'hsl(var(--text-tertiary))', userSelect: 'none' }}>{String(idx + 1).padStart(4, ' ')}  </span>
+                      {line}
+                    </div>
+                  ))}
+                </pre>
+              </>
             ) : (
               <Text c="var(--text-tertiary)" ta="center">No logs available</Text>
             )}
@@ -1,8 +1,9 @@
 import { useState, useEffect, useCallback, memo } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { invokeAction } from '../lib/utils';
 import { useServer } from '../context/ServerContext';
 import { useToast } from '../context/ToastContext';
-import { SystemUser, CreateUserRequest } from '../types';
+import { SystemUser, CreateUserRequest, UserResourceUsage } from '../types';
 import {
   Paper, Text, Group, Title, Button, Stack, Table, Badge, ActionIcon, Modal, Box, Loader, Center, Divider, TextInput, Card, SimpleGrid, Switch, Tooltip, ScrollArea, Select,
 } from '@mantine/core';
@@ -20,6 +21,8 @@ const UserManager = memo(function UserManager() {
   const [groups, setGroups] = useState<SystemGroup[]>([]);
   const [loading, setLoading] = useState(false);
   const [showCreateModal, setShowCreateModal] = useState(false);
+  const [usageUser, setUsageUser] = useState<string | null>(null);
+  const [usage, setUsage] = useState<UserResourceUsage | null>(null);
 
   const [newUser, setNewUser] = useState<CreateUserRequest>({
     username: '',
@@ -67,7 +70,7 @@ const UserManager = memo(function UserManager() {
     }
     setLoading(true);
     try {
-      await invoke('create_user', { request: newUser });
+      await invokeAction('create_user', { request: newUser });
       addToast(`User '${newUser.username}' created`, 'success');
       setShowCreateModal(false);
       setNewUser({ username: '', password: '', home: '', shell: '/bin/bash', groups: [], create_home: true });
@@ -83,7 +86,7 @@ const UserManager = memo(function UserManager() {
     if (!confirm(`Delete user '${username}'? This action cannot be undone.`)) return;
     setLoading(true);
     try {
-      await invoke('delete_user', { username, removeHome: false });
+      await invokeAction('delete_user', { username, removeHome: false });
       addToast(`User '${username}' deleted`, 'success');
       fetchUsers();
     } catch (err: any) {
@@ -106,6 +109,24 @@ const UserManager = memo(function UserManager() {
     }
   };
 
+  const handleShowUsage = async (username: string) => {
+    setUsageUser(username);
+    setUsage(null);
+    try {
+      setUsage(await invoke<UserResourceUsage>('get_user_resource_usage', { username }));
+    } catch (err: any) {
+      addToast(`Failed to get resource usage: ${err.message}`, 'error');
+      setUsageUser(null);
+    }
+  };
+
+  const formatBytes = (bytes: number) => {
+    if (bytes === 0) return '0 B';
+    const sizes = ['B', 'KiB', 'MiB', 'GiB', 'TiB'];
+    const i = Math.min(Math.floor(Math.log(bytes) / Math.log(1024)), sizes.length - 1);
+    return `${parseFloat((bytes / Math.pow(1024, i)).toFixed(1))} ${sizes[i]}`;
+  };
+
   const availableGroups = groups.map(g => g.name).filter(g => g !== newUser.username);
 
   const stats = {
@@ -389,6 +410,19 @@ const UserManager = memo(function UserManager() {
                     </Table.Td>
                     <Table.Td>
                       <Group gap="xs">
+                        <Tooltip label="Resource usage">
+                          <ActionIcon
+                            size="sm"
+                            style={{
+                              background: 'hsl(var(--primary-subtle))',
+                              color: 'hsl(var(--primary))',
+                            }}
+                            onClick={() => handleShowUsage(user.username)}
+                            title="Resource usage"
+                          >
+                            <Icons.Gauge size={16} />
+                          </ActionIcon>
+                        </Tooltip>
                         <Tooltip label={user.locked ? 'Unlock' : 'Lock'}>
                           <ActionIcon
                             size="sm"
@@ -427,6 +461,79 @@ const UserManager = memo(function UserManager() {
         </ScrollArea.Autosize>
       </Card>
 
+      {/* Resource Usage Modal */}
+      <Modal
+        opened={usageUser !== null}
+        onClose={() => setUsageUser(null)}
+        title={<Text fw={600} style={{ color: 'hsl(var(--text-primary))' }}>Resource usage: {usageUser}</Text>}
+        size="md"
+        centered
+        styles={{
+          content: {
+            backgroundColor: 'hsl(var(--bg-primary))',
+            border: '1px solid hsl(var(--border-default))',
+          },
+          header: {
+            borderBottom: '1px solid hsl(var(--border-subtle))',
+          },
+          body: {
+            backgroundColor: 'hsl(var(--bg-primary))',
+          },
+        }}
+      >
+        {!usage ? (
+          <Center py="xl">
+            <Loader size="md" color="hsl(var(--primary))" />
+          </Center>
+        ) : (
+          <Stack gap="md">
+            <SimpleGrid cols={2}>
+              <Stack gap={0}>
+                <Text size="sm" c="var(--text-tertiary)">Processes</Text>
+                <Text fw={600} c="var(--text-primary)">{usage.process_count ?? 'Unknown'}</Text>
+              </Stack>
+              <Stack gap={0}>
+                <Text size="sm" c="var(--text-tertiary)">Home directory</Text>
+                <Text fw={600} c="var(--text-primary)">
+                  {usage.home_bytes !== null ? formatBytes(usage.home_bytes) : 'Unknown'}
+                </Text>
+              </Stack>
+            </SimpleGrid>
+            <Divider />
+            {usage.quotas === null ? (
+              <Text size="sm" c="var(--text-tertiary)">Disk quotas are not enabled on this server</Text>
+            ) : usage.quotas.length === 0 ? (
+              <Text size="sm" c="var(--text-tertiary)">No quota limits for this user</Text>
+            ) : (
+              <Table verticalSpacing="xs">
+                <Table.Thead>
+                  <Table.Tr>
+                    <Table.Th>Filesystem</Table.Th>
+                    <Table.Th>Used</Table.Th>
+                    <Table.Th>Soft / hard limit</Table.Th>
+                    <Table.Th>Files</Table.Th>
+                  </Table.Tr>
+                </Table.Thead>
+                <Table.Tbody>
+                  {usage.quotas.map((quota) => (
+                    <Table.Tr key={quota.filesystem}>
+                      <Table.Td style={{ fontFamily: 'var(--font-mono)' }}>{quota.filesystem}</Table.Td>
+                      <Table.Td style={{ color: quota.over_limit ? 'hsl(var(--error))' : undefined }}>
+                        {formatBytes(quota.used_bytes)}
+                      </Table.Td>
+                      <Table.Td>
+                        {quota.soft_limit_bytes ? formatBytes(quota.soft_limit_bytes) : '—'} / {quota.hard_limit_bytes ? formatBytes(quota.hard_limit_bytes) : '—'}
+                      </Table.Td>
+                      <Table.Td>{quota.files}</Table.Td>
+                    </Table.Tr>
+                  ))}
+                </Table.Tbody>
+              </Table>
+            )}
+          </Stack>
+        )}
+      </Modal>
+
       {/* Create User Modal */}
       <Modal
         opened={showCreateModal}
@@ -9,6 +9,7 @@ import {
   IconRouter,
   IconPlug,
   IconCloud,
+  IconDatabase,
 } from '@tabler/icons-react';
 import { InfraGraphNodeType, NodeStatus } from '../../types/infrastructure-graph';
 
@@ -33,6 +34,8 @@ const getNodeIcon = (nodeType: InfraGraphNodeType) => {
       return <IconPlug size={20} />;
     case 'container':
       return <IconBrandDocker size={20} />;
+    case 'database':
+      return <IconDatabase size={20} />;
     case 'dockernetwork':
       return <IconNetwork size={20} />;
     case 'hostnetwork':
@@ -76,6 +79,12 @@ const getNodeColors = (nodeType: InfraGraphNodeType, status: NodeStatus) => {
         border: isStopped ? '#4b5563' : '#3b82f6',
         text: '#ffffff',
       };
+    case 'database':
+      return {
+        bg: isStopped ? '#4b5563' : 'linear-gradient(135deg, #ec4899 0%, #db2777 100%)',
+        border: isStopped ? '#4b5563' : '#ec4899',
+        text: '#ffffff',
+      };
     case 'dockernetwork':
       return {
         bg: isStopped ? '#4b5563' : 'linear-gradient(135deg, #06b6d4 0%, #0891b2 100%)',
@@ -106,6 +115,10 @@ const getStatusBadge = (status: NodeStatus) => {
       return { color: 'gray', label: 'Stopped' };
     case 'unhealthy':
       return { color: 'red', label: 'Unhealthy' };
+    case 'warning':
+      return { color: 'yellow', label: 'Warning' };
+    case 'critical':
+      return { color: 'red', label: 'Critical' };
     default:
       return { color: 'gray', label: 'Unknown' };
   }
@@ -31,6 +31,7 @@ const LEGEND_ITEMS: LegendItem[] = [
   { color: '#f97316', label: 'Host Port', type: 'hostport' },
   { color: '#8b5cf6', label: 'Vhost', type: 'vhost' },
   { color: '#3b82f6', label: 'Container', type: 'container' },
+  { color: '#ec4899', label: 'Database', type: 'database' },
   { color: '#06b6d4', label: 'Docker Network', type: 'dockernetwork' },
   { color: '#6b7280', label: 'Host Network', type: 'hostnetwork' },
 ];
@@ -66,6 +67,7 @@ export function InfrastructureGraphView() {
       vhost: [],
       hostport: [],
       container: [],
+      database: [],
       dockernetwork: [],
     };
 
@@ -94,7 +96,8 @@ export function InfrastructureGraphView() {
       vhost: { x: -300, y: 300 },
       hostport: { x: 300, y: 300 },
       container: { x: 0, y: 450 },
-      dockernetwork: { x: 0, y: 600 },
+      database: { x: 0, y: 600 },
+      dockernetwork: { x: 0, y: 750 },
     };
     
     const positionedNodes: Node<any>[] = [];
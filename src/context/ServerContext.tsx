@@ -1,5 +1,7 @@
-import { createContext, useContext, useState, ReactNode } from 'react';
-import { ServerProfile, SystemMetrics, DockerContainer } from '../types';
+import { createContext, useContext, useEffect, useState, ReactNode } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { ServerProfile, SystemMetrics, DockerContainer, StartupConnectResult, ConnectionEvent } from '../types';
 
 interface ServerContextType {
   activeServer: ServerProfile | null;
@@ -26,6 +28,43 @@ export function ServerProvider({ children }: { children: ReactNode }) {
   const [metricsTimestamp, setMetricsTimestamp] = useState<number | null>(null);
   const [containersTimestamp, setContainersTimestamp] = useState<number | null>(null);
 
+  // Profiles marked connect-on-startup are connected by the backend at launch. Results
+  // that arrived before this listener existed are fetched once on mount.
+  useEffect(() => {
+    const apply = (result: StartupConnectResult) => {
+      if (result.success) {
+        setActiveServer(result.profile);
+        setIsConnected(true);
+      } else {
+        console.warn(`Auto-connect to ${result.profile.name} failed: ${result.message}`);
+      }
+    };
+    invoke<StartupConnectResult[]>('get_startup_connect_results')
+      .then((results) => results.forEach(apply))
+      .catch(() => {});
+    const unlisten = listen<StartupConnectResult>('startup-connect-result', (event) => apply(event.payload));
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // The backend probes each connection and reports drops and successful reconnects
+  useEffect(() => {
+    if (!activeServer) return;
+    const lost = listen<ConnectionEvent>('connection-lost', (event) => {
+      if (event.payload.server_id !== activeServer.id) return;
+      console.warn(`Connection to ${activeServer.name} lost: ${event.payload.message}`);
+      setIsConnected(false);
+    });
+    const restored = listen<ConnectionEvent>('connection-restored', (event) => {
+      if (event.payload.server_id === activeServer.id) setIsConnected(true);
+    });
+    return () => {
+      lost.then((fn) => fn());
+      restored.then((fn) => fn());
+    };
+  }, [activeServer]);
+
   const invalidateCache = () => {
     setCachedMetrics(null);
     setCachedContainers(null);
@@ -1,6 +1,17 @@
 import { type ClassValue, clsx } from "clsx"
 import { twMerge } from "tailwind-merge"
+import { invoke } from "@tauri-apps/api/core"
+import type { ActionResult } from "../types"
 
 export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs))
 }
+
+// Invoke a command returning an ActionResult, throwing its message when it failed
+export async function invokeAction(command: string, args?: Record<string, unknown>): Promise<ActionResult> {
+  const result = await invoke<ActionResult>(command, args)
+  if (!result.success) {
+    throw new Error(result.message)
+  }
+  return result
+}
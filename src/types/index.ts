@@ -5,6 +5,13 @@ export interface ServerProfile {
   port: number;
   username: string;
   auth_method: AuthMethod;
+  keepalive_secs?: number | null;
+  connect_timeout_secs?: number | null;
+  retry_attempts?: number | null;
+  // Seconds between connection health probes; 0 disables them
+  probe_interval_secs?: number | null;
+  // Bytes of command output kept before the rest is dropped
+  max_output_bytes?: number | null;
 }
 
 export interface SavedServerProfile extends ServerProfile {
@@ -13,6 +20,19 @@ export interface SavedServerProfile extends ServerProfile {
   connect_on_startup: boolean;
 }
 
+// Payload of the `connection-lost` and `connection-restored` events
+export interface ConnectionEvent {
+  server_id: string;
+  message: string;
+}
+
+// Emitted as `startup-connect-result` for each connect_on_startup profile at launch
+export interface StartupConnectResult {
+  profile: SavedServerProfile;
+  success: boolean;
+  message: string;
+}
+
 export type AuthMethod =
   | { type: "Password"; password: string }
   | { type: "PrivateKey"; key_path: string; passphrase?: string };
@@ -21,6 +41,8 @@ export interface SystemMetrics {
   cpu_percent: number;
   memory_used: number;
   memory_total: number;
+  swap_used: number;
+  swap_total: number;
   disk_usage: DiskUsage[];
   load_avg: [number, number, number];
   uptime: number;
@@ -29,6 +51,25 @@ export interface SystemMetrics {
   cpu_history: number[];
   memory_history: number[];
   network_history: NetworkHistoryPoint[];
+  // Unix ms when the numbers were read; cached is set when a poll came sooner than
+  // MetricsConfig.poll_interval_secs and got the previous snapshot
+  collected_at: number;
+  cached: boolean;
+}
+
+export interface MetricsConfig {
+  poll_interval_secs: number;
+}
+
+// Returned by dashboard_snapshot; unrequested or failed sections are null, failures
+// are reported in `errors` under the section name
+export interface DashboardSnapshot {
+  server_id: string;
+  metrics: SystemMetrics | null;
+  containers: DockerContainer[] | null;
+  services: ServiceInfo[] | null;
+  ufw: UfwOverview | null;
+  errors: Record<string, string>;
 }
 
 export interface NetworkStats {
@@ -61,9 +102,24 @@ export interface DockerContainer {
   cpu_percent: number;
   memory_usage: number;
   memory_limit: number;
+  ports: PortMapping[];
+  health: 'starting' | 'healthy' | 'unhealthy' | null;
+  // OOMKilled flag or an exit with 137
+  oom_killed: boolean;
+  // Recent samples, oldest first; memory is a percentage of the limit
+  cpu_history: number[];
+  mem_history: number[];
   volumes?: string[];
 }
 
+// search_container_logs result; line_number counts from the start of the searched tail
+export interface LogMatch {
+  line_number: number;
+  line: string;
+  context_before: string[];
+  context_after: string[];
+}
+
 export interface ServiceInfo {
   name: string;
   state: string;
@@ -71,11 +127,269 @@ export interface ServiceInfo {
   description: string;
 }
 
+// Outcome of validating and reloading one service with reload_services
+export interface ReloadResult {
+  service: string;
+  validated: boolean;
+  reloaded: boolean;
+  message: string;
+  output: string;
+}
+
+// A unit in `systemctl list-dependencies` output and the units below it
+export interface UnitDepTree {
+  unit: string;
+  load_state: string;
+  active_state: string;
+  sub_state: string;
+  children: UnitDepTree[];
+}
+
+export interface JournalBootRange {
+  first_boot_id: string;
+  last_boot_id: string;
+}
+
+export type ServiceLogSource =
+  | { type: 'Journal' }
+  | { type: 'File'; path: string }
+  | { type: 'NotFound' };
+
+export interface ServiceLogResult {
+  source: ServiceLogSource;
+  lines: string[];
+  boot_range: JournalBootRange | null;
+}
+
+// avg_ms is null when no reply came back
+export interface SocketEndpoint {
+  address: string;
+  port: number | null; // null for a wildcard port
+}
+
+export interface NetworkConnection {
+  proto: string;
+  state: string; // ss state name: ESTAB, LISTEN, TIME-WAIT, UNCONN, ...
+  local: SocketEndpoint;
+  remote: SocketEndpoint;
+  pid: number | null;
+  process: string | null;
+}
+
+// A missing file is null; when allow exists, deny is ignored
+export interface CronAccess {
+  allow: string[] | null;
+  deny: string[] | null;
+}
+
+export interface AtJob {
+  id: number;
+  scheduled_for: string; // as atq prints it, server local time
+  queue: string; // "=" while running
+  user: string;
+}
+
+export interface AtJobScheduled {
+  job_id: number;
+  scheduled_for: string;
+}
+
+export interface ResourceLimit {
+  name: string; // e.g. "Max open files"
+  soft: string; // a number or "unlimited"
+  hard: string;
+  units: string | null;
+}
+
+export interface ProcessOpenFiles {
+  count: number;
+  sample: string[]; // "fd -> target"
+}
+
+export interface ProblemProcess {
+  pid: number;
+  ppid: number;
+  command: string;
+  parent_command: string | null;
+  state: string; // ps STAT column
+  open_files: number | null;
+}
+
+export interface ProblemProcesses {
+  zombies: ProblemProcess[];
+  uninterruptible: ProblemProcess[];
+  many_open_files: ProblemProcess[];
+  fd_threshold: number;
+  // false when only the connected account's own processes could be counted
+  fd_counts_complete: boolean;
+}
+
+export interface SysctlEntry {
+  key: string;
+  value: string;
+}
+
+export interface RebootStatus {
+  required: boolean;
+  packages: string[];
+  running_kernel: string;
+  installed_kernel: string | null;
+}
+
+export type SecurityModuleKind = 'AppArmor' | 'SELinux' | 'None';
+
+export type EnforcementMode = 'enforcing' | 'complaining' | 'permissive' | 'disabled';
+
+export interface SecurityModule {
+  kind: SecurityModuleKind;
+  mode: EnforcementMode;
+  profiles_loaded: number; // 0 when unreadable without root
+  profiles: AppArmorProfile[];
+}
+
+export interface AppArmorProfile {
+  name: string;
+  mode: string; // enforce, complain, kill, unconfined
+}
+
+export interface TimeConfig {
+  timezone: string;
+  local_time: string;
+  utc_time: string;
+  ntp_enabled: boolean;
+  ntp_synced: boolean;
+  // Active time sync daemon, e.g. systemd-timesyncd or chrony
+  ntp_service: string | null;
+}
+
+// A kernel ring buffer message; timestamp is ISO 8601 or seconds since boot
+export interface DmesgEntry {
+  timestamp: string;
+  level: 'emerg' | 'alert' | 'crit' | 'err' | 'warn' | 'notice' | 'info' | 'debug';
+  message: string;
+}
+
+// One mount line of /etc/fstab; fields keep fstab's escaping (\040 for a space)
+export interface FstabEntry {
+  device: string;
+  mountpoint: string;
+  fstype: string;
+  options: string;
+  dump: number;
+  pass: number;
+}
+
+// An active swap area from /proc/swaps
+export interface SwapDevice {
+  name: string;
+  type: 'file' | 'partition' | string;
+  size_bytes: number;
+  used_bytes: number;
+  priority: number;
+}
+
+export interface FstabSaveResult {
+  message: string;
+  entries: FstabEntry[];
+  // Missing devices or mount points; nothing is written when there are any
+  problems: string[];
+  mounted: boolean | null;
+  mount_output: string | null;
+  rolled_back: boolean;
+}
+
+export interface RemoteStat {
+  size: number;
+  mode: string; // octal permission bits, e.g. "644"
+  uid: number;
+  gid: number;
+  is_dir: boolean;
+  modified: number; // unix seconds
+}
+
+export interface PingResult {
+  target: string;
+  packets_sent: number;
+  packets_received: number;
+  avg_ms: number | null;
+  loss_percent: number;
+  used_sudo: boolean;
+  output: string;
+}
+
+export interface JournalDiskUsage {
+  bytes: number;
+  oldest_entry: string | null;
+  corrupt_files: string[];
+}
+
+// vacuum_journal keeps the newest entries up to max_bytes, or those younger than max_age_days
+export type VacuumSpec =
+  | { type: 'Size'; max_bytes: number }
+  | { type: 'Time'; max_age_days: number };
+
+export interface JournalVacuumResult {
+  freed_bytes: number;
+  deleted_files: string[];
+  output: string;
+}
+
 export interface ConnectionResult {
   success: boolean;
   message: string;
+  error_code: number | null;
+}
+
+// Open connection as returned by list_active_connections; commands accept an
+// optional serverId and otherwise target the connection flagged is_default
+export interface ActiveConnection {
+  server_id: string;
+  name: string;
+  host: string;
+  username: string;
+  connected: boolean;
+  is_default: boolean;
+  permission_mode: PermissionMode;
 }
 
+// ReadOnly connections reject every command that changes server state
+export type PermissionMode = 'ReadOnly' | 'Full';
+
+// check_sudo_access result; warning explains why root actions would fail
+export interface SudoAccess {
+  installed: boolean;
+  is_root: boolean;
+  can_sudo: boolean;
+  passwordless: boolean;
+  allowed_commands: string[];
+  warning: string | null;
+}
+
+export interface ConnectionStage {
+  name: 'tcp' | 'handshake' | 'auth';
+  status: 'Ok' | 'Failed' | 'Skipped';
+  duration_ms: number | null;
+  error: string | null;
+  error_code: number | null;
+}
+
+// Fingerprint of the connected server's host key, as printed by `ssh-keygen -lf`
+export interface HostKeyFingerprint {
+  algorithm: string;
+  sha256: string;
+  md5: string;
+}
+
+export interface ConnectionDiagnosis {
+  success: boolean;
+  failed_stage: string | null;
+  stages: ConnectionStage[];
+}
+
+// CommandError codes surfaced in ConnectionResult.error_code
+export const CONNECTION_TIMED_OUT = -2;
+export const CONNECTION_REFUSED = -3;
+
 export interface UfwStatus {
   active: boolean;
   logging: string;
@@ -87,8 +401,47 @@ export interface UfwRule {
   rule: string;
   to: string;
   action: string;
+  direction: 'IN' | 'OUT' | 'FWD';
   from: string;
   port: string | null;
+  interface_in: string | null;
+  interface_out: string | null;
+  ip_version: 'V4' | 'V6' | 'Both';
+  // `ufw status numbered` positions; a dual-stack rule has one per address family
+  rule_numbers: number[];
+}
+
+export interface Ipv6Coverage {
+  ipv6_enabled: boolean | null;
+  v4_only_rules: number;
+  v6_only_rules: number;
+  dual_stack_rules: number;
+  v4_only_targets: string[];
+  warning: string | null;
+}
+
+// Outcome of a state-changing command; success reflects the exit status
+export interface ActionResult {
+  success: boolean;
+  message: string;
+  raw_output: string;
+}
+
+export interface UfwRulePreview {
+  command: string;
+  description: string;
+}
+
+// Hardening advice for a container port published on every interface
+export interface SuggestedRule {
+  container: string;
+  host_port: number;
+  container_port: number;
+  protocol: string;
+  kind: 'allow_from_source' | 'world_exposed';
+  source: string | null;
+  message: string;
+  command: string | null;
 }
 
 export interface UfwStats {
@@ -104,6 +457,16 @@ export interface PortInfo {
   action: string;
   source: string;
   service_name: string | null;
+  // Only set for listening sockets
+  address?: string | null;
+  ip_version?: IpVersion | null;
+  pid?: number | null;
+  processes?: SocketProcess[];
+}
+
+export interface SocketProcess {
+  name: string;
+  pid: number;
 }
 
 export interface UfwOverview {
@@ -112,9 +475,53 @@ export interface UfwOverview {
   blocked_ports: PortInfo[];
   all_rules: UfwRule[];
   stats: UfwStats;
+  docker_exposures: DockerExposure[];
+  ipv6: Ipv6Coverage;
+}
+
+export interface DockerExposure {
+  container: string;
+  host_ip: string;
+  host_port: number;
+  container_port: number;
+  protocol: string;
+  ufw_action: string | null;
+  bypasses_ufw: boolean;
+  warning: string | null;
+  suggestion: string | null;
+}
+
+// Raw netfilter rules from iptables -S or nft list ruleset
+export type FirewallBackend = 'Iptables' | 'Nftables';
+
+export interface FirewallRule {
+  rule: string;
+  target: string | null; // verdict or jumped-to chain
+}
+
+export interface FirewallChain {
+  table: string; // "filter" for iptables, "inet filter" for nftables
+  name: string;
+  policy: string | null;
+  docker: boolean;
+  rules: FirewallRule[];
+}
+
+export interface FirewallDump {
+  backend: FirewallBackend;
+  chains: FirewallChain[];
 }
 
 // Docker extended types
+export interface DockerContextInfo {
+  available: boolean;
+  invocation: 'direct' | 'env' | 'sudo' | 'unavailable';
+  socket: string | null;
+  rootless: boolean;
+  version: string | null;
+  context_name: string | null;
+}
+
 export interface PortMapping {
   host_ip: string;
   host_port: string;
@@ -154,6 +561,19 @@ export interface ContainerDetails {
   cpu_limit: string;
 }
 
+// get_container_provenance; config_files are absolute paths on the server
+export interface ContainerProvenance {
+  source: 'Compose' | 'Standalone';
+  project: string | null;
+  service: string | null;
+  config_files: string[];
+  missing_files: string[];
+  unchecked_files: string[]; // not visible to the user and sudo needs a password
+  working_dir: string | null;
+  one_off: boolean;
+  compose_version: string | null;
+}
+
 export interface DockerVolume {
   name: string;
   driver: string;
@@ -181,6 +601,15 @@ export interface DockerImage {
   architecture: string;
 }
 
+// One line of diff_against_backup / a save's `diff`; line numbers are 1-based and
+// null on the side the line doesn't appear on
+export interface DiffLine {
+  kind: 'Hunk' | 'Context' | 'Added' | 'Removed';
+  old_line: number | null;
+  new_line: number | null;
+  text: string;
+}
+
 export interface ComposeProject {
   name: string;
   path: string;
@@ -188,6 +617,14 @@ export interface ComposeProject {
   content: string;
 }
 
+export interface CacheInfo {
+  last_scan: number | null;
+  age_secs: number | null;
+  expired: boolean;
+  project_count: number;
+  scan_paths: string[];
+}
+
 // ==================== USER MANAGEMENT TYPES ====================
 
 export interface SystemUser {
@@ -210,10 +647,12 @@ export interface SystemGroup {
 }
 
 export interface SSHKey {
+  index: number;
   key_type: string;
   key_data: string;
   comment: string;
   fingerprint: string;
+  options: string[];
 }
 
 export interface UserDetail {
@@ -231,6 +670,25 @@ export interface UserDetail {
   ssh_keys: SSHKey[];
 }
 
+export interface UserQuota {
+  filesystem: string;
+  used_bytes: number;
+  soft_limit_bytes: number;
+  hard_limit_bytes: number;
+  files: number;
+  file_soft_limit: number;
+  file_hard_limit: number;
+  over_limit: boolean;
+}
+
+// Each probe is null when it couldn't run; quotas is null when quotas aren't set up
+export interface UserResourceUsage {
+  username: string;
+  process_count: number | null;
+  home_bytes: number | null;
+  quotas: UserQuota[] | null;
+}
+
 export interface CreateUserRequest {
   username: string;
   password?: string;
@@ -239,3 +697,151 @@ export interface CreateUserRequest {
   groups: string[];
   create_home: boolean;
 }
+
+export interface BulkUserResult {
+  username: string;
+  success: boolean;
+  error: string | null;
+}
+
+export type ChecksumStatus = 'match' | 'mismatch' | 'missing' | 'error';
+
+export interface ChecksumResult {
+  path: string;
+  algorithm: string | null;
+  expected: string;
+  actual: string | null;
+  status: ChecksumStatus;
+  message: string | null;
+}
+
+export interface FilePermissions {
+  mode_octal: string; // e.g. "0644"
+  symbolic: string; // e.g. "-rw-r--r--"
+  owner: string;
+  group: string;
+}
+
+export interface LargeFile {
+  path: string;
+  size: number;
+  modified: number;
+}
+
+export interface DockerSecret {
+  id: string;
+  name: string;
+  created: string;
+}
+
+export interface DockerConfig {
+  id: string;
+  name: string;
+  created: string;
+}
+
+export interface ContainerLogs {
+  logs: string;
+  flags: string[]; // e.g. "--tail=100", "--timestamps"
+}
+
+export interface ExecSession {
+  id: string;
+  command: string;
+  running: boolean;
+  pid: number | null; // host PID while running
+  exit_code: number | null;
+  user: string | null;
+  tty: boolean;
+  privileged: boolean;
+}
+
+export interface ConfigMessage {
+  level: string;
+  file: string | null;
+  line: number | null;
+  message: string;
+}
+
+// Result of a config check: nginx -t, logrotate -d or sshd -t
+export interface ConfigTestResult {
+  ok: boolean;
+  errors: ConfigMessage[];
+  warnings: ConfigMessage[];
+  output: string;
+}
+
+export interface LogrotateConfig {
+  file: string;
+  paths: string[];
+  rotate_count: number | null;
+  frequency: string | null; // null inherits the global default
+  compress: boolean;
+  size: string | null; // as written, e.g. "100M"
+}
+
+export interface LogrotateSaveResult {
+  saved: boolean;
+  message: string;
+  test: ConfigTestResult; // logrotate -d dry run
+  diff: DiffLine[];
+}
+
+export interface NginxSaveResult {
+  message: string;
+  test: ConfigTestResult;
+  rolled_back: boolean;
+  rollback_test: ConfigTestResult | null;
+  diff: DiffLine[];
+}
+
+export interface NginxSnapshot {
+  id: string; // archive name without .tar.gz
+  label: string;
+  created_at: number; // unix seconds, server clock
+  size: number;
+}
+
+export interface NginxRestoreResult {
+  message: string;
+  test: ConfigTestResult;
+  rolled_back: boolean; // restored config failed nginx -t; previous one put back
+  rollback_test: ConfigTestResult | null;
+  reloaded: boolean;
+}
+
+export interface SshdDirective {
+  key: string;
+  value: string;
+  file: string;
+  line: number;
+  match_block: string | null; // null applies to every connection
+}
+
+export interface SshdSaveResult {
+  requires_confirmation: boolean; // nothing changed; resend with confirm_lockout_risk
+  warning: string | null;
+  file: string | null;
+  save: NginxSaveResult | null; // sshd -t outcome, same shape as an nginx save
+  reloaded: boolean;
+  message: string;
+}
+
+export type VhostEnableOutcome = 'already_enabled' | 'enabled' | 'target_mismatch';
+
+export interface VhostEnableResult {
+  outcome: VhostEnableOutcome;
+  message: string;
+  link_target: string | null;
+}
+
+export interface VhostTestResult {
+  vhost: string;
+  url: string;
+  server_name: string;
+  status_code: number | null;
+  time_total_secs: number | null;
+  expected_upstream: string | null;
+  upstream_ok: boolean | null;
+  error: string | null;
+}
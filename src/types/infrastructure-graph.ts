@@ -1,8 +1,14 @@
 // Infrastructure Graph TypeScript Types
 
-export type InfraGraphNodeType = 'internet' | 'nginx' | 'vhost' | 'hostport' | 'container' | 'dockernetwork' | 'hostnetwork';
+export type InfraGraphNodeType = 'internet' | 'nginx' | 'vhost' | 'hostport' | 'container' | 'database' | 'dockernetwork' | 'hostnetwork';
 
-export type NodeStatus = 'running' | 'stopped' | 'healthy' | 'unhealthy' | 'unknown';
+export type NodeStatus = 'running' | 'stopped' | 'healthy' | 'unhealthy' | 'warning' | 'critical' | 'unknown';
+
+// Container usage above which a node is shown as 'warning'
+export interface GraphThresholds {
+  cpu_percent: number;
+  memory_percent: number;
+}
 
 export interface InfraGraphNode {
   id: string;
@@ -37,10 +43,24 @@ export interface InfraGraphNode {
     container_port?: string;
     protocol?: string;
     mode?: string;
+    database?: boolean;
+    engine?: string | null;
+    // Why a container is 'warning' or 'critical', e.g. { metric: 'cpu', value: 93.1, threshold: 80 }
+    status_reason?: { metric: string; value: number | string | boolean; threshold?: number } | null;
     [key: string]: unknown;
   };
 }
 
+// A parsed proxy_pass argument; host and port are null when built from variables
+export interface ProxyTarget {
+  raw: string;
+  scheme: string;
+  host: string | null;
+  port: number | null;
+  unix_socket: string | null;
+  uses_variables: boolean;
+}
+
 export interface InfraGraphEdge {
   source: string;
   target: string;
@@ -50,6 +70,10 @@ export interface InfraGraphEdge {
     ports?: number[];
     backend?: string;
     mode?: string;
+    // proxy_pass edges carry the ProxyTarget they were resolved from
+    scheme?: ProxyTarget['scheme'];
+    host?: ProxyTarget['host'];
+    port?: ProxyTarget['port'];
     [key: string]: unknown;
   };
 }